@@ -22,10 +22,26 @@ pub struct Args {
     #[arg(help=get_datatype_help(), num_args(0..))]
     pub datatype: Vec<String>,
 
-    /// Block numbers, see syntax below
+    /// Block numbers, see syntax below, or use --start-block and --end-block
     #[arg(short, long, allow_negative_numbers = true, help_heading = "Content Options", num_args(1..))]
     pub blocks: Option<Vec<String>>,
 
+    /// Text file of block numbers or ranges, one per line
+    #[arg(long, help_heading = "Content Options", value_name = "PATH")]
+    pub blocks_from_file: Option<String>,
+
+    /// First block of range, alternative to --blocks start:end
+    #[arg(long, help_heading = "Content Options", value_name = "BLOCK")]
+    pub start_block: Option<String>,
+
+    /// Range starts after this block, alternative to --start-block
+    #[arg(long, help_heading = "Content Options", value_name = "BLOCK")]
+    pub start_block_exclusive: Option<String>,
+
+    /// Last block of range, inclusive [default: latest]
+    #[arg(long, help_heading = "Content Options", value_name = "BLOCK")]
+    pub end_block: Option<String>,
+
     /// Timestamps in unix, see syntax below
     #[arg(long, allow_negative_numbers = true, help_heading = "Content Options", num_args(0..))]
     pub timestamps: Option<Vec<String>>,
@@ -39,13 +55,14 @@ pub struct Args {
     )]
     pub txs: Option<Vec<String>>,
 
-    /// Align chunk boundaries to regular intervals,
-    /// e.g. (1000 2000 3000), not (1106 2106 3106)
+    /// Align chunk boundaries to multiples of chunk size,
+    /// e.g. (1000 2000 3000), not (1106 2106 3106),
+    /// first and last chunks may be partial
     #[arg(short, long, help_heading = "Content Options", verbatim_doc_comment)]
     pub align: bool,
 
-    /// Reorg buffer, save blocks only when this old,
-    /// can be a number of blocks
+    /// Reorg buffer, save blocks only when this many blocks
+    /// behind latest, e.g. 64 to stay clear of most reorgs
     #[arg(
         long,
         default_value_t = 0,
@@ -55,13 +72,31 @@ pub struct Args {
     )]
     pub reorg_buffer: u64,
 
+    /// Keep collecting chunks of new blocks after the range,
+    /// writing each chunk once all of its blocks are past
+    /// the reorg buffer, exit with ctrl-c
+    #[arg(long, help_heading = "Content Options", verbatim_doc_comment)]
+    pub follow: bool,
+
+    /// Seconds between polls for new blocks with --follow
+    /// [default: 12]
+    #[arg(long, value_name = "SECONDS", help_heading = "Content Options", verbatim_doc_comment)]
+    pub poll_interval: Option<f64>,
+
+    /// Skip chunks before --contract was deployed,
+    /// found by binary search over eth_getCode
+    #[arg(long, help_heading = "Content Options", verbatim_doc_comment)]
+    pub skip_before_deployment: bool,
+
     /// Columns to include alongside the defaults,
-    /// use `all` to include all available columns
+    /// use `all` to include all available columns,
+    /// prefix with datatype for one datatype, e.g. blocks.author
     #[arg(short, long, value_name="COLS", num_args(0..), verbatim_doc_comment, help_heading="Content Options")]
     pub include_columns: Option<Vec<String>>,
 
-    /// Columns to exclude from the defaults
-    #[arg(short, long, value_name="COLS", num_args(0..), help_heading="Content Options")]
+    /// Columns to exclude from the defaults,
+    /// prefix with datatype for one datatype, e.g. txs.value
+    #[arg(short, long, value_name="COLS", num_args(0..), verbatim_doc_comment, help_heading="Content Options")]
     pub exclude_columns: Option<Vec<String>>,
 
     /// Columns to use instead of the defaults,
@@ -69,11 +104,40 @@ pub struct Args {
     #[arg(long, value_name="COLS", num_args(0..), verbatim_doc_comment, help_heading="Content Options")]
     pub columns: Option<Vec<String>>,
 
-    /// Set output datatype(s) of U256 integers
-    /// [default: binary, string, f64]
-    #[arg(long, num_args(1..), help_heading = "Content Options", verbatim_doc_comment)]
+    /// Add block_timestamp column to transactions and logs
+    #[arg(long, help_heading = "Content Options")]
+    pub include_block_timestamp: bool,
+
+    /// Set output datatype(s) of U256 integers: binary, hex, decimal (alias
+    /// string), f64, f32, u64, u32, each written as a column with its suffix,
+    /// e.g. value_binary, value_hex, value_string [default: binary, string, f64]
+    #[arg(
+        long,
+        visible_alias = "u256-format",
+        num_args(1..),
+        help_heading = "Content Options",
+        verbatim_doc_comment
+    )]
     pub u256_types: Option<Vec<String>>,
 
+    /// How to handle u256 values that overflow u32, u64, or f32
+    /// columns: error, saturate, or null [default: error]
+    #[arg(long, value_name = "POLICY", help_heading = "Content Options", verbatim_doc_comment)]
+    pub overflow_policy: Option<String>,
+
+    /// Cast columns to types before writing, as column=type,
+    /// e.g. block_number=int32,gas_price=float64, types are bool,
+    /// uint32, uint64, int32, int64, float32, float64, string
+    #[arg(
+        long,
+        value_name = "COL=TYPE",
+        num_args(1..),
+        value_delimiter = ',',
+        help_heading = "Content Options",
+        verbatim_doc_comment
+    )]
+    pub column_type_override: Option<Vec<String>>,
+
     /// Use hex string encoding for binary columns
     #[arg(long, help_heading = "Content Options")]
     pub hex: bool,
@@ -82,14 +146,55 @@ pub struct Args {
     #[arg(short, long, num_args(0..), help_heading="Content Options")]
     pub sort: Option<Vec<String>>,
 
-    /// Exclude items from failed transactions
+    /// Sort rows in descending order
+    #[arg(long, help_heading = "Content Options")]
+    pub sort_desc: bool,
+
+    /// Skip sorting rows, same as `--sort none`
     #[arg(long, help_heading = "Content Options")]
+    pub no_sort: bool,
+
+    /// Drop duplicate rows before writing, comparing
+    /// block_number, log_index, and transaction_hash of logs,
+    /// transaction_hash of transactions, block_number of blocks,
+    /// and entire rows of other datatypes
+    #[arg(long, help_heading = "Content Options", verbatim_doc_comment)]
+    pub dedup: bool,
+
+    /// Exclude items from failed transactions, fetching receipts
+    /// of transactions if needed
+    #[arg(
+        long,
+        visible_alias = "exclude-failed-transactions",
+        help_heading = "Content Options",
+        verbatim_doc_comment
+    )]
     pub exclude_failed: bool,
 
+    /// Use max fee as gas price when base fee is unavailable
+    #[arg(long, help_heading = "Content Options")]
+    pub estimate_gas_price: bool,
+
+    /// Skip blocks that the node has not produced or has pruned,
+    /// instead of failing their chunks
+    #[arg(long, help_heading = "Content Options", verbatim_doc_comment)]
+    pub allow_missing_blocks: bool,
+
     /// RPC url [default: 1. MESC 2. ETH_RPC_URL]
-    #[arg(short, long, help_heading = "Source Options")]
+    /// comma separated urls spread requests across endpoints
+    #[arg(short, long, help_heading = "Source Options", verbatim_doc_comment)]
     pub rpc: Option<String>,
 
+    /// Read RPC url from ETH_RPC_URL, keeping it out of shell
+    /// history and process listings
+    #[arg(long, help_heading = "Source Options", verbatim_doc_comment)]
+    pub env_rpc: bool,
+
+    /// Firehose grpc endpoint, streams blocks and transactions
+    /// instead of requesting them over RPC
+    #[arg(long, value_name = "URL", help_heading = "Source Options", verbatim_doc_comment)]
+    pub firehose_endpoint: Option<String>,
+
     /// Network name [default: name of eth_getChainId]
     #[arg(long, help_heading = "Source Options")]
     pub network_name: Option<String>,
@@ -98,12 +203,29 @@ pub struct Args {
     #[arg(short('l'), long, value_name = "limit", help_heading = "Acquisition Options")]
     pub requests_per_second: Option<u32>,
 
-    /// Max retries for provider errors
-    #[arg(long, default_value_t = 5, value_name = "R", help_heading = "Acquisition Options")]
+    /// Use N/M of ratelimit, for M processes sharing a node
+    #[arg(long, value_name = "N/M", help_heading = "Acquisition Options")]
+    pub rps_share: Option<String>,
+
+    /// Max retries for provider errors,
+    /// and reconnects of dropped websocket connections
+    #[arg(
+        long,
+        default_value_t = 5,
+        value_name = "R",
+        help_heading = "Acquisition Options",
+        verbatim_doc_comment
+    )]
     pub max_retries: u32,
 
-    /// Initial retry backoff time (ms)
-    #[arg(long, default_value_t = 500, value_name = "B", help_heading = "Acquisition Options")]
+    /// Initial retry backoff time (ms), doubled for each further retry
+    #[arg(
+        long,
+        visible_alias = "retry-backoff-ms",
+        default_value_t = 200,
+        value_name = "B",
+        help_heading = "Acquisition Options"
+    )]
     pub initial_backoff: u64,
 
     /// Global number of concurrent requests
@@ -114,26 +236,78 @@ pub struct Args {
     #[arg(long, value_name = "M", help_heading = "Acquisition Options")]
     pub max_concurrent_chunks: Option<u64>,
 
+    /// Timeout of each http rpc request (seconds), 0 for no timeout
+    #[arg(long, value_name = "SECONDS", help_heading = "Acquisition Options")]
+    pub request_timeout_seconds: Option<u64>,
+
+    /// Timeout of connecting to http rpc endpoints (seconds), 0 for no timeout
+    #[arg(long, value_name = "SECONDS", help_heading = "Acquisition Options")]
+    pub connect_timeout_seconds: Option<u64>,
+
+    /// Keep idle http connections open for reuse (seconds), 0 to not reuse connections
+    #[arg(long, value_name = "SECONDS", help_heading = "Acquisition Options")]
+    pub http_keepalive: Option<u64>,
+
+    /// Requests per json-rpc batch over http, 0 or 1 disables batching
+    #[arg(long, default_value_t = 1, value_name = "N", help_heading = "Acquisition Options")]
+    pub rpc_batch_size: u64,
+
+    /// Number of collected chunks that can wait to be written [default: 4]
+    #[arg(long, value_name = "N", help_heading = "Acquisition Options")]
+    pub writer_queue_size: Option<usize>,
+
+    /// Cache raw rpc responses in directory, reusing them in later runs
+    #[arg(long, value_name = "DIR", help_heading = "Acquisition Options")]
+    pub cache_dir: Option<String>,
+
     /// Chunk collection order (normal, reverse, random)
     #[arg(long, help_heading = "Acquisition Options")]
     pub chunk_order: Option<String>,
 
+    /// Random sleep before starting, e.g. 0-120s
+    #[arg(long, value_name = "DURATION", help_heading = "Acquisition Options")]
+    pub startup_jitter: Option<String>,
+
     /// Dry run, collect no data
     #[arg(short, long, help_heading = "Acquisition Options")]
     pub dry: bool,
 
+    /// Estimate output size by writing one sample chunk per datatype
+    #[arg(long, help_heading = "Acquisition Options")]
+    pub dry_run_estimate_size: bool,
+
+    /// Check output files for missing blocks and rows,
+    /// with --dry check existing files without collecting
+    #[arg(long, help_heading = "Acquisition Options", verbatim_doc_comment)]
+    pub verify: bool,
+
     /// Remember current command for future use
     #[arg(long)]
     pub remember: bool,
 
+    /// Print column lineage of parquet files, use as
+    /// cryo schema --lineage FILE...
+    #[arg(long, verbatim_doc_comment)]
+    pub lineage: bool,
+
     /// Extra verbosity
     #[arg(short, long)]
     pub verbose: bool,
 
     /// Run quietly without printing information to stdout
-    #[arg(long)]
+    #[arg(long, visible_alias = "quiet")]
     pub no_verbose: bool,
 
+    /// Format of summaries and chunk progress, text or json
+    /// json prints one event per line [default: text]
+    #[arg(long, value_name = "FORMAT", verbatim_doc_comment)]
+    pub log_format: Option<String>,
+
+    /// Config file of default options
+    /// [default: ~/.config/cryo/config.toml]
+    #[arg(long, value_name = "PATH", verbatim_doc_comment)]
+    pub config: Option<PathBuf>,
+
     /// Number of blocks per file
     #[arg(short, long, default_value_t = 1000, help_heading = "Output Options")]
     pub chunk_size: u64,
@@ -151,7 +325,8 @@ pub struct Args {
     pub output_dir: String,
 
     /// Subdirectories for output files
-    /// can be `datatype`, `network`, or custom string
+    /// can be `datatype`, `network`, or custom string,
+    /// e.g. `datatype` writes each datatype to its own directory
     #[arg(long, help_heading = "Output Options", verbatim_doc_comment, num_args(1..))]
     pub subdirs: Vec<String>,
 
@@ -164,13 +339,84 @@ pub struct Args {
     pub overwrite: bool,
 
     /// Save as csv instead of parquet
-    #[arg(long, help_heading = "Output Options")]
+    #[arg(long, help_heading = "Output Options", conflicts_with_all = ["json", "jsonl"])]
     pub csv: bool,
 
     /// Save as json instead of parquet
-    #[arg(long, help_heading = "Output Options")]
+    #[arg(long, help_heading = "Output Options", conflicts_with = "jsonl")]
     pub json: bool,
 
+    /// Save as newline-delimited json instead of parquet
+    #[arg(long, help_heading = "Output Options")]
+    pub jsonl: bool,
+
+    /// Output format: parquet, csv, json, jsonl, or delta,
+    /// delta appends to a Delta Lake table per datatype
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        help_heading = "Output Options",
+        conflicts_with_all = ["csv", "json", "jsonl"],
+        verbatim_doc_comment
+    )]
+    pub output_format: Option<String>,
+
+    /// Compress csv output while writing: gzip, zstd, or none
+    #[arg(long, value_name = "NAME", help_heading = "Output Options")]
+    pub csv_compression: Option<String>,
+
+    /// Save each datatype as a parquet dataset directory,
+    /// with _metadata and _common_metadata summary files
+    #[arg(
+        long,
+        help_heading = "Output Options",
+        verbatim_doc_comment,
+        conflicts_with_all = ["csv", "json", "jsonl"]
+    )]
+    pub dataset: bool,
+
+    /// Approximate size of output files, chunks of block ranges are split
+    /// into files of narrower block ranges to reach it
+    #[arg(long, value_name = "MB", help_heading = "Output Options", verbatim_doc_comment)]
+    pub target_file_mb: Option<u64>,
+
+    /// Write files to hive partitioned directories,
+    /// {datatype}/block_number={bucket}/, of N_BLOCKS blocks each
+    #[arg(long, value_name = "N_BLOCKS", help_heading = "Output Options", verbatim_doc_comment)]
+    pub partition_width: Option<u64>,
+
+    /// Write files to hive partitioned directories nested in order of KEYS,
+    /// network, datatype, and block_<N_BLOCKS>, e.g. network=ethereum/
+    /// datatype=blocks/block_1000000=17/ [default: network datatype block_1000000]
+    #[arg(
+        long,
+        value_name = "KEYS",
+        num_args(0..),
+        help_heading = "Output Options",
+        verbatim_doc_comment
+    )]
+    pub hive_partitions: Option<Vec<String>>,
+
+    /// Whether to keep hive partition keys as columns of files, keep or drop
+    /// [default: drop]
+    #[arg(long, value_name = "MODE", help_heading = "Output Options", verbatim_doc_comment)]
+    pub partition_columns: Option<String>,
+
+    /// Template of file names, placeholders: {network}, {datatype}, {chunk},
+    /// {start_block}, {end_block}, {chunk_index}, {ext}, / creates subdirectories
+    /// [default: {network}__{datatype}__{chunk}.{ext}]
+    #[arg(long, value_name = "FMT", help_heading = "Output Options", verbatim_doc_comment)]
+    pub file_name_template: Option<String>,
+
+    /// Prefix of every output file name, e.g. a run id, to keep the files
+    /// of separate runs in one directory apart
+    #[arg(long, value_name = "PREFIX", help_heading = "Output Options", verbatim_doc_comment)]
+    pub output_prefix: Option<String>,
+
+    /// Fail instead of warning when output format works poorly for datatype
+    #[arg(long, help_heading = "Output Options")]
+    pub strict_formats: bool,
+
     /// Number of rows per row group in parquet file
     #[arg(long, value_name = "GROUP_SIZE", help_heading = "Output Options")]
     pub row_group_size: Option<usize>,
@@ -179,12 +425,38 @@ pub struct Args {
     #[arg(long, help_heading = "Output Options")]
     pub n_row_groups: Option<usize>,
 
+    /// Approximate uncompressed size of row groups in parquet file
+    /// (alternative to --row-group-size and --n-row-groups)
+    #[arg(long, value_name = "MB", help_heading = "Output Options", verbatim_doc_comment)]
+    pub row_group_mb: Option<u64>,
+
     /// Do not write statistics to parquet files
     #[arg(long, help_heading = "Output Options")]
     pub no_stats: bool,
 
-    /// Compression algorithm and level
-    #[arg(long, help_heading="Output Options", value_name="NAME [#]", num_args(1..=2), default_value = "lz4")]
+    /// Do not dictionary encode columns of parquet files
+    #[arg(long, help_heading = "Output Options")]
+    pub no_dictionary: bool,
+
+    /// Dictionary encode only these string columns of parquet files,
+    /// e.g. address topic0 (binary columns require --hex)
+    #[arg(
+        long,
+        value_name = "COLUMNS",
+        num_args(1..),
+        help_heading = "Output Options",
+        verbatim_doc_comment
+    )]
+    pub dictionary_columns: Option<Vec<String>>,
+
+    /// Do not record cryo version, rpc url hash, block range, datatype,
+    /// and chain id in parquet footer metadata
+    #[arg(long, help_heading = "Output Options", verbatim_doc_comment)]
+    pub no_metadata: bool,
+
+    /// Compression algorithm and level of parquet output,
+    /// as NAME, NAME LEVEL, or NAME:LEVEL
+    #[arg(long, help_heading="Output Options", value_name="NAME [#]", num_args(1..=2), default_value = "lz4", verbatim_doc_comment)]
     pub compression: Vec<String>,
 
     /// Directory to save summary report
@@ -192,16 +464,41 @@ pub struct Args {
     #[arg(long, help_heading = "Output Options", verbatim_doc_comment)]
     pub report_dir: Option<PathBuf>,
 
-    /// Avoid saving a summary report
-    #[arg(long, help_heading = "Output Options")]
+    /// Avoid saving a summary report, or a copy of it in output dir
+    /// named {prefix}__{range}__summary.json
+    #[arg(long, help_heading = "Output Options", verbatim_doc_comment)]
     pub no_report: bool,
 
+    /// Report time spent in each phase of chunk pipeline
+    #[arg(long, help_heading = "Output Options")]
+    pub timing_report: bool,
+
+    /// Print rpc request counts, latencies, and retries of each method,
+    /// and time spent and rows produced per chunk
+    #[arg(long, help_heading = "Output Options", verbatim_doc_comment)]
+    pub stats: bool,
+
     /// Address(es)
-    #[arg(long, help_heading = "Dataset-specific Options", num_args(1..))]
+    #[arg(
+        long,
+        help_heading = "Dataset-specific Options",
+        visible_alias = "addresses",
+        num_args(1..)
+    )]
     pub address: Option<Vec<String>>,
 
+    /// Text file of addresses, one per line, added to --address
+    #[arg(long, help_heading = "Dataset-specific Options", value_name = "PATH")]
+    pub addresses_file: Option<String>,
+
     /// To Address(es)
-    #[arg(long, help_heading = "Dataset-specific Options", value_name="address", num_args(1..))]
+    #[arg(
+        long,
+        help_heading = "Dataset-specific Options",
+        value_name = "address",
+        num_args(1..),
+        visible_alias = "to"
+    )]
     pub to_address: Option<Vec<String>>,
 
     /// From Address(es)
@@ -212,24 +509,49 @@ pub struct Args {
     #[arg(long, help_heading = "Dataset-specific Options", num_args(1..))]
     pub call_data: Option<Vec<String>>,
 
-    /// Function(s) to use for eth_calls
-    #[arg(long, help_heading = "Dataset-specific Options", num_args(1..))]
+    /// Function(s) to use for eth_calls, as 4 byte selectors or as
+    /// signatures like balanceOf(address)(uint256), whose outputs
+    /// are decoded into columns named after the function
+    #[arg(long, help_heading = "Dataset-specific Options", num_args(1..), verbatim_doc_comment)]
     pub function: Option<Vec<String>>,
 
-    /// Input(s) to use for eth_calls
-    #[arg(long, help_heading = "Dataset-specific Options", num_args(1..))]
+    /// Input(s) to use for eth_calls, as hex appended to selectors,
+    /// or as comma-separated args of function signatures
+    #[arg(
+        long,
+        help_heading = "Dataset-specific Options",
+        num_args(1..),
+        visible_alias = "args",
+        verbatim_doc_comment
+    )]
     pub inputs: Option<Vec<String>>,
 
     /// Slot(s)
     #[arg(long, help_heading = "Dataset-specific Options", num_args(1..))]
     pub slot: Option<Vec<String>>,
 
-    /// Contract address(es)
-    #[arg(long, help_heading = "Dataset-specific Options", num_args(1..))]
+    /// CSV file of address,slot pairs
+    #[arg(long, help_heading = "Dataset-specific Options", value_name = "PATH")]
+    pub slots_file: Option<String>,
+
+    /// Contract address(es), space or comma separated
+    #[arg(
+        long,
+        visible_alias = "contract-address",
+        help_heading = "Dataset-specific Options",
+        num_args(1..),
+        value_delimiter = ','
+    )]
     pub contract: Option<Vec<String>>,
 
-    /// Topic0(s)
-    #[arg(long, visible_alias = "event", help_heading = "Dataset-specific Options", num_args(1..))]
+    /// Topic0(s), space or comma separated
+    #[arg(
+        long,
+        visible_aliases = ["event", "event-topic"],
+        help_heading = "Dataset-specific Options",
+        num_args(1..),
+        value_delimiter = ','
+    )]
     pub topic0: Option<Vec<String>>,
 
     /// Topic1(s)
@@ -244,22 +566,73 @@ pub struct Args {
     #[arg(long, help_heading = "Dataset-specific Options", num_args(1..))]
     pub topic3: Option<Vec<String>>,
 
-    /// Event signature for log decoding
-    #[arg(long, value_name = "SIG", help_heading = "Dataset-specific Options", num_args(1..))]
+    /// Event signature for log decoding,
+    /// also used as topic0 filter when --topic0 is not given
+    #[arg(
+        long,
+        value_name = "SIG",
+        help_heading = "Dataset-specific Options",
+        num_args(1..),
+        verbatim_doc_comment
+    )]
     pub event_signature: Option<String>,
 
-    /// Blocks per request (eth_getLogs)
+    /// Blocks per request (eth_getLogs, eth_feeHistory)
     #[arg(
         long,
         value_name = "BLOCKS",
         default_value_t = 1,
-        help_heading = "Dataset-specific Options"
+        help_heading = "Dataset-specific Options",
+        visible_alias = "log-request-size"
     )]
     pub inner_request_size: u64,
 
+    /// Adapt blocks per eth_getLogs request to response sizes,
+    /// halving them when responses are too large and growing
+    /// them back up to this many blocks
+    #[arg(
+        long,
+        value_name = "BLOCKS",
+        help_heading = "Dataset-specific Options",
+        verbatim_doc_comment
+    )]
+    pub max_log_request_size: Option<u64>,
+
+    /// Split eth_getLogs requests whose responses have more
+    /// than this many logs into smaller requests
+    #[arg(
+        long,
+        value_name = "LOGS",
+        help_heading = "Dataset-specific Options",
+        verbatim_doc_comment
+    )]
+    pub max_response_rows: Option<u64>,
+
+    /// Receipts per request group when eth_getBlockReceipts
+    /// is unavailable (eth_getTransactionReceipt)
+    #[arg(
+        long,
+        value_name = "RECEIPTS",
+        help_heading = "Dataset-specific Options",
+        verbatim_doc_comment
+    )]
+    pub receipt_request_size: Option<u64>,
+
     /// Event signature for log decoding
     #[arg(long, value_name = "tracer", help_heading = "Dataset-specific Options")]
     pub js_tracer: Option<String>,
+
+    /// Reward percentiles of fee_history, space or comma
+    /// separated [default: 25, 50, 75]
+    #[arg(
+        long,
+        value_name = "P",
+        help_heading = "Dataset-specific Options",
+        num_args(1..),
+        value_delimiter = ',',
+        verbatim_doc_comment
+    )]
+    pub fee_percentiles: Option<Vec<f64>>,
 }
 
 impl Args {
@@ -315,6 +688,8 @@ fn get_after_str() -> String {
       <white><bold>cryo help</bold></white>                      display help message
       <white><bold>cryo help syntax</bold></white>               display block + tx specification syntax
       <white><bold>cryo help datasets</bold></white>             display list of all datasets
+      <white><bold>cryo datasets</bold></white>                  display schema and sort of every dataset
+      <white><bold>cryo print-config</bold></white> ARGS...      print resolved options as config toml
       <white><bold>cryo help</bold></white>"#
     );
     let post_subcommands = " <DATASET(S)>         display info about a dataset";
@@ -323,6 +698,6 @@ fn get_after_str() -> String {
 
 fn get_datatype_help() -> &'static str {
     cstr!(
-        r#"datatype(s) to collect, use <white><bold>cryo datasets</bold></white> to see all available"#
+        r#"datatype(s) to collect, or all, use <white><bold>cryo datasets</bold></white> to see all available"#
     )
 }
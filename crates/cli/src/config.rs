@@ -0,0 +1,417 @@
+// a config file provides default values for cli options
+// - read from ~/.config/cryo/config.toml, or from the path given by --config
+// - keys are the snake_case names of cli options, e.g. `rpc`, `output_dir`, `max_concurrent_chunks`
+// - options given explicitly on the command line take precedence over the config file, and an
+//   option of an exclusive group, e.g. --json, also overrides the other options of its group
+// - `cryo print-config ...` prints the options of a run, resolved against the rpc, as config toml
+
+use crate::args::Args;
+use clap_cryo::{parser::ValueSource, ArgMatches, Parser};
+use cryo_freeze::{BlockChunk, FileOutput, ParseError, Query, Source};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// default options read from a toml config file, mirroring the fields of [`Args`]
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CryoConfig {
+    pub(crate) datatype: Option<Vec<String>>,
+    pub(crate) blocks: Option<Vec<String>>,
+    pub(crate) blocks_from_file: Option<String>,
+    pub(crate) start_block: Option<String>,
+    pub(crate) start_block_exclusive: Option<String>,
+    pub(crate) end_block: Option<String>,
+    pub(crate) timestamps: Option<Vec<String>>,
+    pub(crate) txs: Option<Vec<String>>,
+    pub(crate) align: Option<bool>,
+    pub(crate) reorg_buffer: Option<u64>,
+    pub(crate) follow: Option<bool>,
+    pub(crate) poll_interval: Option<f64>,
+    pub(crate) skip_before_deployment: Option<bool>,
+    pub(crate) include_columns: Option<Vec<String>>,
+    pub(crate) exclude_columns: Option<Vec<String>>,
+    pub(crate) columns: Option<Vec<String>>,
+    pub(crate) include_block_timestamp: Option<bool>,
+    pub(crate) u256_types: Option<Vec<String>>,
+    pub(crate) overflow_policy: Option<String>,
+    pub(crate) column_type_override: Option<Vec<String>>,
+    pub(crate) hex: Option<bool>,
+    pub(crate) sort: Option<Vec<String>>,
+    pub(crate) sort_desc: Option<bool>,
+    pub(crate) no_sort: Option<bool>,
+    pub(crate) dedup: Option<bool>,
+    pub(crate) exclude_failed: Option<bool>,
+    pub(crate) estimate_gas_price: Option<bool>,
+    pub(crate) allow_missing_blocks: Option<bool>,
+    pub(crate) rpc: Option<String>,
+    pub(crate) env_rpc: Option<bool>,
+    pub(crate) firehose_endpoint: Option<String>,
+    pub(crate) network_name: Option<String>,
+    pub(crate) requests_per_second: Option<u32>,
+    pub(crate) rps_share: Option<String>,
+    pub(crate) max_retries: Option<u32>,
+    pub(crate) initial_backoff: Option<u64>,
+    pub(crate) max_concurrent_requests: Option<u64>,
+    pub(crate) max_concurrent_chunks: Option<u64>,
+    pub(crate) request_timeout_seconds: Option<u64>,
+    pub(crate) connect_timeout_seconds: Option<u64>,
+    pub(crate) http_keepalive: Option<u64>,
+    pub(crate) rpc_batch_size: Option<u64>,
+    pub(crate) writer_queue_size: Option<usize>,
+    pub(crate) cache_dir: Option<String>,
+    pub(crate) chunk_order: Option<String>,
+    pub(crate) startup_jitter: Option<String>,
+    pub(crate) dry: Option<bool>,
+    pub(crate) dry_run_estimate_size: Option<bool>,
+    pub(crate) verify: Option<bool>,
+    pub(crate) verbose: Option<bool>,
+    pub(crate) no_verbose: Option<bool>,
+    pub(crate) log_format: Option<String>,
+    pub(crate) chunk_size: Option<u64>,
+    pub(crate) n_chunks: Option<u64>,
+    pub(crate) partition_by: Option<Vec<String>>,
+    pub(crate) output_dir: Option<String>,
+    pub(crate) subdirs: Option<Vec<String>>,
+    pub(crate) label: Option<String>,
+    pub(crate) overwrite: Option<bool>,
+    pub(crate) csv: Option<bool>,
+    pub(crate) json: Option<bool>,
+    pub(crate) jsonl: Option<bool>,
+    pub(crate) output_format: Option<String>,
+    pub(crate) csv_compression: Option<String>,
+    pub(crate) dataset: Option<bool>,
+    pub(crate) target_file_mb: Option<u64>,
+    pub(crate) partition_width: Option<u64>,
+    pub(crate) hive_partitions: Option<Vec<String>>,
+    pub(crate) partition_columns: Option<String>,
+    pub(crate) file_name_template: Option<String>,
+    pub(crate) output_prefix: Option<String>,
+    pub(crate) strict_formats: Option<bool>,
+    pub(crate) row_group_size: Option<usize>,
+    pub(crate) n_row_groups: Option<usize>,
+    pub(crate) row_group_mb: Option<u64>,
+    pub(crate) no_stats: Option<bool>,
+    pub(crate) no_dictionary: Option<bool>,
+    pub(crate) dictionary_columns: Option<Vec<String>>,
+    pub(crate) no_metadata: Option<bool>,
+    pub(crate) compression: Option<Vec<String>>,
+    pub(crate) report_dir: Option<PathBuf>,
+    pub(crate) no_report: Option<bool>,
+    pub(crate) timing_report: Option<bool>,
+    pub(crate) stats: Option<bool>,
+    pub(crate) address: Option<Vec<String>>,
+    pub(crate) addresses_file: Option<String>,
+    pub(crate) to_address: Option<Vec<String>>,
+    pub(crate) from_address: Option<Vec<String>>,
+    pub(crate) call_data: Option<Vec<String>>,
+    pub(crate) function: Option<Vec<String>>,
+    pub(crate) inputs: Option<Vec<String>>,
+    pub(crate) slot: Option<Vec<String>>,
+    pub(crate) slots_file: Option<String>,
+    pub(crate) contract: Option<Vec<String>>,
+    pub(crate) topic0: Option<Vec<String>>,
+    pub(crate) topic1: Option<Vec<String>>,
+    pub(crate) topic2: Option<Vec<String>>,
+    pub(crate) topic3: Option<Vec<String>>,
+    pub(crate) event_signature: Option<String>,
+    pub(crate) inner_request_size: Option<u64>,
+    pub(crate) max_log_request_size: Option<u64>,
+    pub(crate) max_response_rows: Option<u64>,
+    pub(crate) receipt_request_size: Option<u64>,
+    pub(crate) js_tracer: Option<String>,
+    pub(crate) fee_percentiles: Option<Vec<f64>>,
+}
+
+/// load config from --config path, or from the default config path if it exists
+pub(crate) fn load_config(path: Option<&PathBuf>) -> Result<CryoConfig, ParseError> {
+    let path = match (path, get_default_config_path()) {
+        (Some(path), _) => path.clone(),
+        (None, Some(path)) if path.exists() => path,
+        (None, _) => return Ok(CryoConfig::default()),
+    };
+    let contents = std::fs::read_to_string(&path).map_err(|_| {
+        ParseError::ParseError(format!("could not read config file {}", path.display()))
+    })?;
+    toml::from_str(&contents).map_err(|e| {
+        ParseError::ParseError(format!("could not parse config file {}: {}", path.display(), e))
+    })
+}
+
+/// groups of options that select the same thing in different ways, such as output format
+///
+/// if any option of a group is given on the command line, the config values of the whole group
+/// are ignored, e.g. `csv = true` in config does not conflict with `--json`
+const EXCLUSIVE_OPTIONS: &[&[&str]] = &[
+    &[
+        "blocks",
+        "blocks_from_file",
+        "start_block",
+        "start_block_exclusive",
+        "end_block",
+        "timestamps",
+    ],
+    &["csv", "json", "jsonl", "output_format", "dataset"],
+    &["rpc", "env_rpc"],
+    &["row_group_size", "n_row_groups", "row_group_mb"],
+    &["no_dictionary", "dictionary_columns"],
+];
+
+/// fill options that were not given on the command line with values from config
+///
+/// an option is given if its value came from the command line, even if it equals the default.
+/// args that were not parsed from a command line have no matches, and then an option is given
+/// if it differs from its default
+pub(crate) fn merge_config_into_args(
+    config: CryoConfig,
+    args: Args,
+    matches: Option<&ArgMatches>,
+) -> Result<Args, ParseError> {
+    let to_value = |value: Result<Value, serde_json::Error>| {
+        value.map_err(|_| ParseError::ParseError("could not serialize config".to_string()))
+    };
+    let defaults = Args::try_parse_from(["cryo"]).unwrap_or_default();
+    let mut args_value = to_value(serde_json::to_value(args))?;
+    let config_value = to_value(serde_json::to_value(config))?;
+    let default_value = to_value(serde_json::to_value(defaults))?;
+
+    if let (Value::Object(args_map), Value::Object(config_map), Value::Object(default_map)) =
+        (&mut args_value, &config_value, &default_value)
+    {
+        let given = |key: &str| match matches {
+            Some(matches) => matches.value_source(key) == Some(ValueSource::CommandLine),
+            None => args_map.get(key) != default_map.get(key),
+        };
+        let overridden: Vec<&str> = EXCLUSIVE_OPTIONS
+            .iter()
+            .filter(|group| group.iter().any(|key| given(key)))
+            .flat_map(|group| group.iter().copied())
+            .collect();
+        let values: Vec<_> = config_map
+            .iter()
+            .filter(|(key, value)| {
+                !value.is_null() && !given(key) && !overridden.contains(&key.as_str())
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        args_map.extend(values);
+    }
+
+    serde_json::from_value(args_value)
+        .map_err(|_| ParseError::ParseError("config has invalid option values".to_string()))
+}
+
+/// options of a run resolved against the rpc, as config toml that reproduces the run
+///
+/// block tags, dates, and default block ranges are replaced by the block ranges they resolved
+/// to, network name by the network that was used, and rpc options as in [`resolved_rpc`]
+pub(crate) fn resolved_config_toml(
+    args: &Args,
+    query: &Query,
+    source: &Source,
+    sink: &FileOutput,
+) -> Result<String, ParseError> {
+    let invalid = |e: serde_json::Error| {
+        ParseError::ParseError(format!("could not convert options to config: {}", e))
+    };
+    let mut args_value = serde_json::to_value(args).map_err(invalid)?;
+    if let Value::Object(args_map) = &mut args_value {
+        for key in ["remember", "lineage", "config"] {
+            args_map.remove(key);
+        }
+    }
+    let mut config: CryoConfig = serde_json::from_value(args_value).map_err(invalid)?;
+
+    if args.timestamps.is_none() {
+        if let Some(blocks) = resolved_blocks(query) {
+            config.blocks = Some(blocks);
+            config.blocks_from_file = None;
+            config.start_block = None;
+            config.start_block_exclusive = None;
+            config.end_block = None;
+        }
+    }
+    config.network_name = Some(sink.prefix.clone());
+    (config.rpc, config.env_rpc) =
+        resolved_rpc(args, &source.rpc_url, std::env::var("ETH_RPC_URL").ok());
+
+    let toml = toml::to_string(&config)
+        .map_err(|e| ParseError::ParseError(format!("could not serialize config: {}", e)))?;
+    Ok(format!(
+        "# resolved by cryo {} for chain_id {}\n{}",
+        cryo_freeze::CRYO_VERSION,
+        source.chain_id,
+        toml
+    ))
+}
+
+/// rpc options that select the endpoint of a run, without writing its url verbatim since urls
+/// often contain api keys
+///
+/// MESC endpoint names given to --rpc are kept as given, a url that fell back to ETH_RPC_URL
+/// becomes `env_rpc = true`, and any other url, given to --rpc or from MESC, is masked
+fn resolved_rpc(
+    args: &Args,
+    rpc_url: &str,
+    env_rpc_url: Option<String>,
+) -> (Option<String>, Option<bool>) {
+    if args.env_rpc {
+        return (None, Some(true))
+    }
+    if let Some(rpc) = &args.rpc {
+        let masked: Vec<_> = rpc.split(',').map(str::trim).map(mask_rpc_arg).collect();
+        return (Some(masked.join(",")), None)
+    }
+    match env_rpc_url {
+        Some(url) if crate::parse::add_url_scheme(url.trim()) == rpc_url => (None, Some(true)),
+        _ => {
+            let masked: Vec<_> = rpc_url.split(',').map(cryo_freeze::mask_rpc_url).collect();
+            (Some(masked.join(",")), None)
+        }
+    }
+}
+
+/// mask a value of --rpc unless it is a MESC endpoint name, which cannot contain an api key
+fn mask_rpc_arg(rpc: &str) -> String {
+    let is_name = !rpc.is_empty() && !rpc.contains(['.', ':', '/', '@', '?']);
+    if is_name {
+        rpc.to_string()
+    } else {
+        cryo_freeze::mask_rpc_url(&crate::parse::add_url_scheme(rpc))
+    }
+}
+
+/// block ranges and numbers of the chunks of query, with adjacent ranges merged
+fn resolved_blocks(query: &Query) -> Option<Vec<String>> {
+    let mut ranges = Vec::new();
+    let mut numbers = Vec::new();
+    for partition in query.partitions.iter() {
+        for chunk in partition.block_numbers.iter().flatten() {
+            match chunk {
+                BlockChunk::Range(start, end) => ranges.push((*start, *end)),
+                BlockChunk::Numbers(values) => numbers.extend(values.iter().copied()),
+            }
+        }
+    }
+    if ranges.is_empty() && numbers.is_empty() {
+        return None
+    }
+
+    ranges.sort();
+    let mut merged: Vec<(u64, u64)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some((_, last_end)) if start <= *last_end + 1 => *last_end = (*last_end).max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    numbers.sort();
+    numbers.dedup();
+
+    // range ends are exclusive in block syntax
+    let ranges = merged.into_iter().map(|(start, end)| format!("{}:{}", start, end + 1));
+    Some(ranges.chain(numbers.into_iter().map(|number| number.to_string())).collect())
+}
+
+fn get_default_config_path() -> Option<PathBuf> {
+    let config_dir = match std::env::var("XDG_CONFIG_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var("HOME").ok()?).join(".config"),
+    };
+    Some(config_dir.join("cryo").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap_cryo::{CommandFactory, FromArgMatches};
+
+    fn merge(config: &str, command: &[&str]) -> Args {
+        let config: CryoConfig = toml::from_str(config).unwrap();
+        let matches = Args::command().get_matches_from(command);
+        let args = Args::from_arg_matches(&matches).unwrap();
+        merge_config_into_args(config, args, Some(&matches)).unwrap()
+    }
+
+    #[test]
+    fn test_cli_options_take_precedence() {
+        let config = r#"
+            rpc = "http://localhost:8545"
+            chunk_size = 500
+            max_concurrent_chunks = 8
+            "#;
+        let args = merge(config, &["cryo", "blocks", "--chunk-size", "10"]);
+        assert_eq!(args.rpc, Some("http://localhost:8545".to_string()));
+        assert_eq!(args.max_concurrent_chunks, Some(8));
+        assert_eq!(args.chunk_size, 10);
+        assert_eq!(args.datatype, vec!["blocks".to_string()]);
+    }
+
+    #[test]
+    fn test_cli_default_values_take_precedence() {
+        let config = r#"
+            chunk_size = 500
+            csv = true
+            "#;
+        let args = merge(config, &["cryo", "blocks", "--chunk-size", "1000"]);
+        assert_eq!(args.chunk_size, 1000);
+        assert!(args.csv);
+        let args = merge(config, &["cryo", "blocks"]);
+        assert_eq!(args.chunk_size, 500);
+
+        // without matches, only options that differ from their defaults count as given
+        let config: CryoConfig = toml::from_str(config).unwrap();
+        let args = Args::parse_from(["cryo", "blocks", "--chunk-size", "1000"]);
+        let args = merge_config_into_args(config, args, None).unwrap();
+        assert_eq!(args.chunk_size, 500);
+    }
+
+    #[test]
+    fn test_cli_options_override_exclusive_group() {
+        let config = r#"
+            datatype = ["logs"]
+            csv = true
+            blocks = ["1:100"]
+            "#;
+        let args = merge(config, &["cryo", "--json", "--start-block", "5"]);
+        assert_eq!(args.datatype, vec!["logs".to_string()]);
+        assert!(args.json && !args.csv);
+        assert_eq!(args.blocks, None);
+        assert_eq!(args.start_block, Some("5".to_string()));
+    }
+
+    #[test]
+    fn test_resolved_rpc_hides_urls() {
+        let url = "https://eth.example.com/v2/secret";
+        let resolve = |command: &str, env_url: Option<&str>| {
+            let args = Args::parse_from(command.split_whitespace());
+            resolved_rpc(&args, url, env_url.map(|env_url| env_url.to_string()))
+        };
+        assert_eq!(resolve("cryo blocks --rpc eth", None), (Some("eth".to_string()), None));
+        assert_eq!(
+            resolve("cryo blocks --rpc eth.example.com/v2/secret", None),
+            (Some("http://eth.example.com/***".to_string()), None)
+        );
+        assert_eq!(
+            resolve(&format!("cryo blocks --rpc {},eth", url), None),
+            (Some("https://eth.example.com/***,eth".to_string()), None)
+        );
+        assert_eq!(resolve("cryo blocks --env-rpc", Some(url)), (None, Some(true)));
+        assert_eq!(resolve("cryo blocks", Some(url)), (None, Some(true)));
+        assert_eq!(
+            resolve("cryo blocks", Some("https://other.example.com")),
+            (Some("https://eth.example.com/***".to_string()), None)
+        );
+        assert_eq!(
+            resolve("cryo blocks", None),
+            (Some("https://eth.example.com/***".to_string()), None)
+        );
+    }
+
+    #[test]
+    fn test_unknown_config_key() {
+        let error = toml::from_str::<CryoConfig>("not_an_option = 1").unwrap_err().to_string();
+        assert!(error.contains("not_an_option"));
+        assert!(error.contains("expected one of") && error.contains("`chunk_size`"));
+    }
+}
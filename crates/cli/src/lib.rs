@@ -1,4 +1,17 @@
 //! utilties for parsing user inputs to run cryo
+//!
+//! parsed inputs can be passed to `cryo_freeze` to run cryo from another program
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let args = cryo_cli::parse_str("cryo blocks -b 18M:+1000").await?;
+//! let (query, source, sink, env) = cryo_cli::parse_args(&args).await?;
+//! if let Some(summary) = cryo_freeze::freeze(&query, &source, &sink, &env).await? {
+//!     println!("wrote {} rows", summary.n_rows);
+//! }
+//! # Ok(())
+//! # }
+//! ```
 
 #![warn(missing_docs, unreachable_pub, unused_crate_dependencies)]
 #![deny(unused_must_use, rust_2018_idioms)]
@@ -8,14 +21,14 @@
 ))]
 
 mod args;
+mod config;
 mod parse;
 mod remember;
 mod run;
 
 // used in main.rs but not lib.rs
 use eyre as _;
-use tokio as _;
 
 pub use args::Args;
 pub use parse::{parse_args, parse_query, parse_str};
-pub use run::run;
+pub use run::{run, run_interruptible};
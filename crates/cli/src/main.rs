@@ -1,8 +1,9 @@
 //! cryo_cli is a cli for cryo_freeze
 
-use clap_cryo::Parser;
+use clap_cryo::{CommandFactory, FromArgMatches};
 
 mod args;
+mod config;
 mod parse;
 mod remember;
 mod run;
@@ -14,9 +15,15 @@ use eyre::Result;
 #[allow(unreachable_code)]
 #[allow(clippy::needless_return)]
 async fn main() -> Result<()> {
-    let args = Args::parse();
-    match run::run(args).await {
-        Ok(Some(freeze_summary)) if freeze_summary.errored.is_empty() => Ok(()),
+    let matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    match run::run_interruptible(args, Some(matches)).await {
+        Ok(Some(freeze_summary)) if freeze_summary.interrupted => std::process::exit(130),
+        Ok(Some(freeze_summary))
+            if freeze_summary.errored.is_empty() && freeze_summary.discrepancies.is_empty() =>
+        {
+            Ok(())
+        }
         Ok(Some(_freeze_summary)) => std::process::exit(1),
         Ok(None) => Ok(()),
         Err(e) => {
@@ -11,9 +11,13 @@ use super::{execution, file_output, query, source};
 pub async fn parse_args(
     args: &Args,
 ) -> Result<(Query, Source, FileOutput, ExecutionEnv), ParseError> {
+    source::startup_jitter(args).await?;
     let source = source::parse_source(args).await?;
     let query = query::parse_query(args, Arc::new(source.clone())).await?;
     let sink = file_output::parse_file_output(args, &source)?;
+    file_output::check_file_name_template(args, &query, &sink)?;
+    file_output::check_hive_partitions(&query, &sink)?;
+    file_output::check_dictionary_columns(&query, &sink)?;
     let env = execution::parse_execution_env(args, query.n_tasks() as u64)?;
     Ok((query, source, sink, env))
 }
@@ -2,15 +2,148 @@ use polars::prelude::*;
 use std::collections::HashMap;
 
 use cryo_freeze::{BlockChunk, ChunkData, Datatype, ParseError, Source, Subchunk, Table};
+use ethers::types::BlockNumber;
 
+use super::timestamps::Rounding;
 use crate::args::Args;
 
+/// chain head of a query, fetched from the provider at most once so that every relative block
+/// reference (`latest`, open-ended ranges, negative offsets, reorg buffer) resolves against the
+/// same block
+pub(crate) struct ChainHead {
+    pub(crate) source: Arc<Source>,
+    latest: tokio::sync::OnceCell<u64>,
+    safe: tokio::sync::OnceCell<u64>,
+    finalized: tokio::sync::OnceCell<u64>,
+    block_timestamps: std::sync::Mutex<HashMap<u64, u64>>,
+    resolved_times: std::sync::Mutex<Vec<(String, u64)>>,
+}
+
+impl ChainHead {
+    pub(crate) fn new(source: Arc<Source>) -> ChainHead {
+        ChainHead {
+            source,
+            latest: tokio::sync::OnceCell::new(),
+            safe: tokio::sync::OnceCell::new(),
+            finalized: tokio::sync::OnceCell::new(),
+            block_timestamps: std::sync::Mutex::new(HashMap::new()),
+            resolved_times: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// latest block number, fetched on first use
+    pub(crate) async fn latest(&self) -> Result<u64, ParseError> {
+        self.latest.get_or_try_init(|| get_latest_block_number(self.source.clone())).await.copied()
+    }
+
+    /// safe block number, fetched on first use
+    pub(crate) async fn safe(&self) -> Result<u64, ParseError> {
+        self.safe
+            .get_or_try_init(|| get_tagged_block_number(self.source.clone(), BlockNumber::Safe))
+            .await
+            .copied()
+    }
+
+    /// finalized block number, fetched on first use
+    pub(crate) async fn finalized(&self) -> Result<u64, ParseError> {
+        self.finalized
+            .get_or_try_init(|| {
+                get_tagged_block_number(self.source.clone(), BlockNumber::Finalized)
+            })
+            .await
+            .copied()
+    }
+
+    /// block tags, dates, and timestamps resolved while parsing, with the block number of each
+    pub(crate) fn resolved_tags(&self) -> Vec<(String, u64)> {
+        let mut resolved: Vec<(String, u64)> =
+            [("latest", &self.latest), ("safe", &self.safe), ("finalized", &self.finalized)]
+                .into_iter()
+                .filter_map(|(tag, block)| block.get().map(|block| (tag.to_string(), *block)))
+                .collect();
+        if let Ok(resolved_times) = self.resolved_times.lock() {
+            resolved.extend(resolved_times.iter().cloned());
+        }
+        resolved
+    }
+
+    /// timestamp of block, cached so that searches for several times share lookups
+    async fn block_timestamp(&self, block_number: u64) -> Result<u64, ParseError> {
+        let cached =
+            self.block_timestamps.lock().ok().and_then(|cache| cache.get(&block_number).copied());
+        if let Some(timestamp) = cached {
+            return Ok(timestamp)
+        }
+        let timestamp =
+            super::timestamps::get_block_timestamp(block_number, self.source.clone()).await?;
+        if let Ok(mut cache) = self.block_timestamps.lock() {
+            cache.insert(block_number, timestamp);
+        }
+        Ok(timestamp)
+    }
+
+    /// block at a date or timestamp, found by binary search over block timestamps
+    ///
+    /// `Ceil` gives the first block at or after the timestamp, `Floor` the last block at or before
+    /// it. times before the genesis block or after the latest block are errors
+    pub(crate) async fn timestamp_block(
+        &self,
+        time_ref: &str,
+        timestamp: u64,
+        rounding: Rounding,
+    ) -> Result<u64, ParseError> {
+        let latest = self.latest().await?;
+        if timestamp < self.block_timestamp(0).await? {
+            return Err(ParseError::ParseError(format!("{} is before the genesis block", time_ref)))
+        }
+        if timestamp > self.block_timestamp(latest).await? {
+            return Err(ParseError::ParseError(format!(
+                "{} is after the latest block {}",
+                time_ref, latest
+            )))
+        }
+
+        // find first block past the timestamp, >= for Ceil and > for Floor
+        let (mut l, mut r) = (0, latest + 1);
+        while l < r {
+            let mid = (l + r) / 2;
+            let block_timestamp = self.block_timestamp(mid).await?;
+            let past_timestamp = match rounding {
+                Rounding::Floor => block_timestamp > timestamp,
+                Rounding::Ceil => block_timestamp >= timestamp,
+            };
+            if past_timestamp {
+                r = mid;
+            } else {
+                l = mid + 1;
+            }
+        }
+        let block = match rounding {
+            Rounding::Floor => l.saturating_sub(1),
+            Rounding::Ceil => l,
+        };
+
+        if let Ok(mut resolved_times) = self.resolved_times.lock() {
+            resolved_times.push((time_ref.to_string(), block));
+        }
+        Ok(block)
+    }
+}
+
+/// block tags accepted in place of block numbers, a range ending at a tag includes the tag block
+const BLOCK_TAGS: [&str; 4] = ["latest", "safe", "finalized", "earliest"];
+
 pub(crate) async fn parse_blocks(
     args: &Args,
-    source: Arc<Source>,
+    head: &ChainHead,
 ) -> Result<(Option<Vec<Option<String>>>, Option<Vec<BlockChunk>>), ParseError> {
+    if let Some(block_range) = parse_block_range_flags(args, head).await? {
+        return Ok((None, Some(postprocess_block_chunks(vec![block_range], args, head).await?)))
+    }
+
     let (files, explicit_numbers): (Vec<&String>, Vec<&String>) = match &args.blocks {
         Some(blocks) => blocks.iter().partition(|tx| std::path::Path::new(tx).exists()),
+        None if args.blocks_from_file.is_some() => (Vec::new(), Vec::new()),
         None => return Ok((None, None)),
     };
 
@@ -40,14 +173,17 @@ pub(crate) async fn parse_blocks(
         (None, None)
     };
 
-    let explicit_chunks = if !explicit_numbers.is_empty() {
+    let explicit_chunks = if !explicit_numbers.is_empty() || args.blocks_from_file.is_some() {
         // parse inputs into BlockChunks
         let mut block_chunks = Vec::new();
         for explicit_number in explicit_numbers {
-            let outputs = parse_block_inputs(explicit_number, source.clone()).await?;
+            let outputs = parse_block_inputs(explicit_number, head).await?;
             block_chunks.extend(outputs);
         }
-        postprocess_block_chunks(block_chunks, args, source).await?
+        if let Some(path) = &args.blocks_from_file {
+            block_chunks.extend(read_block_list_file(path, head).await?);
+        }
+        postprocess_block_chunks(block_chunks, args, head).await?
     } else {
         Vec::new()
     };
@@ -67,6 +203,67 @@ pub(crate) async fn parse_blocks(
     Ok((labels, Some(block_chunks)))
 }
 
+/// parse --start-block, --start-block-exclusive, and --end-block into a single range
+async fn parse_block_range_flags(
+    args: &Args,
+    head: &ChainHead,
+) -> Result<Option<BlockChunk>, ParseError> {
+    let start = match (&args.start_block, &args.start_block_exclusive) {
+        (None, None) if args.end_block.is_none() => return Ok(None),
+        (Some(_), Some(_)) => {
+            return Err(ParseError::ParseError(
+                "cannot use both --start-block and --start-block-exclusive".to_string(),
+            ))
+        }
+        _ if args.blocks.is_some() || args.blocks_from_file.is_some() => {
+            return Err(ParseError::ParseError(
+                "cannot use --blocks or --blocks-from-file with --start-block or --end-block"
+                    .to_string(),
+            ))
+        }
+        (Some(start), None) => parse_block_number(start, RangePosition::First, head).await?,
+        (None, Some(start)) => parse_block_number(start, RangePosition::First, head)
+            .await?
+            .checked_add(1)
+            .ok_or(ParseError::ParseError("--start-block-exclusive is too large".to_string()))?,
+        (None, None) => 0,
+    };
+    let end_block = args.end_block.as_deref().unwrap_or("latest");
+    let end = parse_block_number(end_block, RangePosition::Last, head).await?;
+    if end < start {
+        return Err(ParseError::ParseError(format!(
+            "--end-block {} is before start of range {}",
+            end, start
+        )))
+    }
+    Ok(Some(BlockChunk::Range(start, end)))
+}
+
+/// read one block number or block range per line, skipping blank lines and # comments
+async fn read_block_list_file(path: &str, head: &ChainHead) -> Result<Vec<BlockChunk>, ParseError> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        ParseError::ParseError(format!("could not read --blocks-from-file {}: {}", path, e))
+    })?;
+    let mut block_chunks = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue
+        }
+        let chunk = parse_block_token(line, true, head).await.map_err(|e| {
+            ParseError::ParseError(format!(
+                "could not parse line {} of {} ({}): {}",
+                index + 1,
+                path,
+                line,
+                e
+            ))
+        })?;
+        block_chunks.push(chunk);
+    }
+    Ok(block_chunks)
+}
+
 fn read_integer_column(path: &str, column: &str) -> Result<Vec<u64>, ParseError> {
     let file = std::fs::File::open(path)
         .map_err(|_e| ParseError::ParseError("could not open file path".to_string()))?;
@@ -107,30 +304,29 @@ fn read_integer_column(path: &str, column: &str) -> Result<Vec<u64>, ParseError>
 pub(crate) async fn postprocess_block_chunks(
     block_chunks: Vec<BlockChunk>,
     args: &Args,
-    source: Arc<Source>,
+    head: &ChainHead,
 ) -> Result<Vec<BlockChunk>, ParseError> {
-    // align
-    let block_chunks = if args.align {
-        block_chunks.into_iter().filter_map(|x| x.align(args.chunk_size)).collect()
-    } else {
-        block_chunks
-    };
-
-    // split block range into chunks
-    let block_chunks = match args.n_chunks {
-        Some(n_chunks) => block_chunks.subchunk_by_count(&n_chunks),
-        None => block_chunks.subchunk_by_size(&args.chunk_size),
+    // split block range into chunks, aligned to multiples of chunk size if specified
+    let block_chunks = match (args.n_chunks, args.align) {
+        (Some(_), true) => {
+            return Err(ParseError::ParseError(
+                "--align cannot be used with --n-chunks".to_string(),
+            ))
+        }
+        (Some(n_chunks), false) => block_chunks.subchunk_by_count(&n_chunks),
+        (None, true) => block_chunks.subchunk_by_aligned_size(&args.chunk_size),
+        (None, false) => block_chunks.subchunk_by_size(&args.chunk_size),
     };
 
     // apply reorg buffer
-    let block_chunks = apply_reorg_buffer(block_chunks, args.reorg_buffer, source).await?;
+    let block_chunks = apply_reorg_buffer(block_chunks, args.reorg_buffer, head).await?;
 
     Ok(block_chunks)
 }
 
 pub(crate) async fn get_default_block_chunks(
     args: &Args,
-    source: Arc<Source>,
+    head: &ChainHead,
     schemas: &HashMap<Datatype, Table>,
 ) -> Result<Vec<BlockChunk>, ParseError> {
     let default_blocks = match schemas
@@ -141,27 +337,24 @@ pub(crate) async fn get_default_block_chunks(
         Some(Some(blocks)) => blocks,
         _ => "0:latest".to_string(),
     };
-    let block_chunks = parse_block_inputs(&default_blocks, source.clone()).await?;
-    postprocess_block_chunks(block_chunks, args, source).await
+    let block_chunks = parse_block_inputs(&default_blocks, head).await?;
+    postprocess_block_chunks(block_chunks, args, head).await
 }
 
 /// parse block numbers to freeze
-async fn parse_block_inputs(
-    inputs: &str,
-    source: Arc<Source>,
-) -> Result<Vec<BlockChunk>, ParseError> {
+async fn parse_block_inputs(inputs: &str, head: &ChainHead) -> Result<Vec<BlockChunk>, ParseError> {
     let parts: Vec<&str> = inputs.split(' ').collect();
     match parts.len() {
         1 => {
             let first_input = parts.first().ok_or_else(|| {
                 ParseError::ParseError("Failed to get the first input".to_string())
             })?;
-            parse_block_token(first_input, true, source).await.map(|x| vec![x])
+            parse_block_token(first_input, true, head).await.map(|x| vec![x])
         }
         _ => {
             let mut chunks = Vec::new();
             for part in parts {
-                chunks.push(parse_block_token(part, false, source.clone()).await?);
+                chunks.push(parse_block_token(part, false, head).await?);
             }
             Ok(chunks)
         }
@@ -177,14 +370,24 @@ enum RangePosition {
 async fn parse_block_token(
     s: &str,
     as_range: bool,
-    source: Arc<Source>,
+    head: &ChainHead,
 ) -> Result<BlockChunk, ParseError> {
     let s = s.replace('_', "");
 
+    // a trailing t means the token specifies timestamps rather than block numbers
+    if s.ends_with('t') && !BLOCK_TAGS.iter().any(|tag| s.ends_with(tag)) {
+        let s: Vec<&str> = s
+            .split(':')
+            .map(|part| if part == "latest" { part } else { part.trim_end_matches('t') })
+            .collect();
+        let source = head.source.clone();
+        return super::timestamps::parse_timestamp_token(&s.join(":"), as_range, source).await
+    }
+
     let parts: Vec<&str> = s.split(':').collect();
     match parts.as_slice() {
         [block_ref] => {
-            let block = parse_block_number(block_ref, RangePosition::None, source).await?;
+            let block = parse_block_number(block_ref, RangePosition::None, head).await?;
             Ok(BlockChunk::Numbers(vec![block]))
         }
         [first_ref, second_ref] => {
@@ -198,11 +401,11 @@ async fn parse_block_token(
                 (*second_ref, None)
             };
 
-            let (start_block, end_block) = parse_block_range(first_ref, second_ref, source).await?;
+            let (start_block, end_block) = parse_block_range(first_ref, second_ref, head).await?;
             block_range_to_block_chunk(start_block, end_block, as_range, None, n_keep)
         }
         [first_ref, second_ref, third_ref] => {
-            let (start_block, end_block) = parse_block_range(first_ref, second_ref, source).await?;
+            let (start_block, end_block) = parse_block_range(first_ref, second_ref, head).await?;
             let range_size = third_ref
                 .parse::<u32>()
                 .map_err(|_e| ParseError::ParseError("start_block parse error".to_string()))?;
@@ -222,7 +425,10 @@ pub(crate) fn block_range_to_block_chunk(
     n_blocks: Option<u32>,
 ) -> Result<BlockChunk, ParseError> {
     if end_block < start_block {
-        Err(ParseError::ParseError("end_block should not be less than start_block".to_string()))
+        Err(ParseError::ParseError(format!(
+            "block range is empty: end_block {} is less than start_block {}",
+            end_block, start_block
+        )))
     } else if let Some(n_blocks) = n_blocks {
         let blocks = evenly_spaced_subset((start_block..=end_block).collect(), n_blocks as usize);
         Ok(BlockChunk::Numbers(blocks))
@@ -248,11 +454,11 @@ pub(crate) fn block_range_to_block_chunk(
 
 fn evenly_spaced_subset<T: Clone>(items: Vec<T>, subset_length: usize) -> Vec<T> {
     if subset_length == 0 || items.is_empty() {
-        return Vec::new();
+        return Vec::new()
     }
 
     if subset_length >= items.len() {
-        return items.to_vec();
+        return items.to_vec()
     }
 
     let original_length = items.len();
@@ -273,21 +479,24 @@ fn evenly_spaced_subset<T: Clone>(items: Vec<T>, subset_length: usize) -> Vec<T>
 async fn parse_block_range(
     first_ref: &str,
     second_ref: &str,
-    source: Arc<Source>,
+    head: &ChainHead,
 ) -> Result<(u64, u64), ParseError> {
     let (start_block, end_block) = match (first_ref, second_ref) {
         _ if first_ref.starts_with('-') => {
-            let end_block = parse_block_number(second_ref, RangePosition::Last, source).await?;
-            let start_block =
-                end_block
-                    .checked_sub(first_ref[1..].parse::<u64>().map_err(|_e| {
-                        ParseError::ParseError("start_block parse error".to_string())
-                    })?)
-                    .ok_or_else(|| ParseError::ParseError("start_block underflow".to_string()))?;
+            let end_block = parse_block_number(second_ref, RangePosition::Last, head).await?;
+            let n_blocks = first_ref[1..]
+                .parse::<u64>()
+                .map_err(|_e| ParseError::ParseError("start_block parse error".to_string()))?;
+            let start_block = end_block.checked_sub(n_blocks).ok_or_else(|| {
+                ParseError::ParseError(format!(
+                    "cannot start {} blocks before block {}",
+                    n_blocks, end_block
+                ))
+            })?;
             (start_block, end_block)
         }
         _ if second_ref.starts_with('+') => {
-            let start_block = parse_block_number(first_ref, RangePosition::First, source).await?;
+            let start_block = parse_block_number(first_ref, RangePosition::First, head).await?;
             let end_block =
                 start_block
                     .checked_add(second_ref[1..].parse::<u64>().map_err(|_e| {
@@ -297,19 +506,22 @@ async fn parse_block_range(
             (start_block, end_block)
         }
         _ => {
-            let start_block =
-                parse_block_number(first_ref, RangePosition::First, source.clone()).await?;
-            let end_block = parse_block_number(second_ref, RangePosition::Last, source).await?;
+            let start_block = parse_block_number(first_ref, RangePosition::First, head).await?;
+            let end_block = parse_block_number(second_ref, RangePosition::Last, head).await?;
             (start_block, end_block)
         }
     };
 
-    let end_block =
-        if second_ref != "latest" && !second_ref.is_empty() && !first_ref.starts_with('-') {
-            end_block - 1
-        } else {
-            end_block
-        };
+    let end_block = if !BLOCK_TAGS.contains(&second_ref) &&
+        !second_ref.is_empty() &&
+        !first_ref.starts_with('-')
+    {
+        end_block.checked_sub(1).ok_or_else(|| {
+            ParseError::ParseError(format!("block range {}:{} is empty", first_ref, second_ref))
+        })?
+    } else {
+        end_block
+    };
 
     let start_block = if first_ref.starts_with('-') { start_block + 1 } else { start_block };
 
@@ -319,18 +531,23 @@ async fn parse_block_range(
 async fn parse_block_number(
     block_ref: &str,
     range_position: RangePosition,
-    source: Arc<Source>,
+    head: &ChainHead,
 ) -> Result<u64, ParseError> {
+    if let Some(timestamp) = parse_time_ref(block_ref)? {
+        // range ends are exclusive, so both ends resolve to the first block at or after the time
+        let rounding = match range_position {
+            RangePosition::None => Rounding::Floor,
+            RangePosition::First | RangePosition::Last => Rounding::Ceil,
+        };
+        return head.timestamp_block(block_ref, timestamp, rounding).await
+    }
     match (block_ref, range_position) {
-        ("latest", _) => source.get_block_number().await.map(|n| n.as_u64()).map_err(|_e| {
-            ParseError::ParseError("Error retrieving latest block number".to_string())
-        }),
+        ("latest", _) => head.latest().await,
+        ("safe", _) => head.safe().await,
+        ("finalized", _) => head.finalized().await,
+        ("earliest", _) => Ok(0),
         ("", RangePosition::First) => Ok(0),
-        ("", RangePosition::Last) => {
-            source.get_block_number().await.map(|n| n.as_u64()).map_err(|_e| {
-                ParseError::ParseError("Error retrieving last block number".to_string())
-            })
-        }
+        ("", RangePosition::Last) => head.latest().await,
         ("", RangePosition::None) => Err(ParseError::ParseError("invalid input".to_string())),
         _ if block_ref.ends_with('B') | block_ref.ends_with('b') => {
             let s = &block_ref[..block_ref.len() - 1];
@@ -357,28 +574,77 @@ async fn parse_block_number(
     }
 }
 
+/// unix timestamp of a `YYYY-MM-DD` date (at 00:00 utc) or of a `t`-prefixed or `t`-suffixed
+/// unix timestamp, or None if block reference is neither
+fn parse_time_ref(block_ref: &str) -> Result<Option<u64>, ParseError> {
+    if let Some(timestamp) = block_ref.strip_prefix('t') {
+        return timestamp
+            .parse::<u64>()
+            .map(Some)
+            .map_err(|_| ParseError::ParseError(format!("invalid timestamp: {}", block_ref)));
+    }
+    if let Some(timestamp) = block_ref.strip_suffix('t') {
+        if !timestamp.is_empty() && timestamp.chars().all(|c| c.is_ascii_digit()) {
+            return timestamp
+                .parse::<u64>()
+                .map(Some)
+                .map_err(|_| ParseError::ParseError(format!("invalid timestamp: {}", block_ref)));
+        }
+    }
+    let is_date = block_ref.len() == 10 &&
+        block_ref.char_indices().all(|(i, c)| {
+            if i == 4 || i == 7 {
+                c == '-'
+            } else {
+                c.is_ascii_digit()
+            }
+        });
+    if !is_date {
+        return Ok(None)
+    }
+    let date = chrono::NaiveDate::parse_from_str(block_ref, "%Y-%m-%d")
+        .map_err(|_| ParseError::ParseError(format!("invalid date: {}", block_ref)))?;
+    let epoch = chrono::NaiveDate::from_ymd_opt(1970, 1, 1)
+        .ok_or_else(|| ParseError::ParseError("invalid epoch".to_string()))?;
+    let days = (date - epoch).num_days();
+    u64::try_from(days * 86400)
+        .map(Some)
+        .map_err(|_| ParseError::ParseError(format!("date is before 1970: {}", block_ref)))
+}
+
 async fn apply_reorg_buffer(
     block_chunks: Vec<BlockChunk>,
     reorg_filter: u64,
-    source: Arc<Source>,
+    head: &ChainHead,
 ) -> Result<Vec<BlockChunk>, ParseError> {
     match reorg_filter {
         0 => Ok(block_chunks),
         reorg_filter => {
-            let latest_block = match source.get_block_number().await {
-                Ok(result) => result.as_u64(),
-                Err(_e) => {
-                    return Err(ParseError::ParseError("reorg buffer parse error".to_string()))
-                }
-            };
-            let max_allowed = latest_block - reorg_filter;
-            Ok(block_chunks
+            let latest_block = head.latest().await?;
+            let max_allowed = latest_block.checked_sub(reorg_filter);
+            let n_chunks = block_chunks.len();
+            // trim chunks that extend past the buffer, dropping chunks entirely within it
+            let block_chunks: Vec<_> = block_chunks
                 .into_iter()
-                .filter_map(|x| match x.max_value() {
-                    Some(max_block) if max_block <= max_allowed => Some(x),
+                .filter_map(|x| match (x, max_allowed) {
+                    (BlockChunk::Range(start, end), Some(max_allowed)) if start <= max_allowed => {
+                        Some(BlockChunk::Range(start, end.min(max_allowed)))
+                    }
+                    (BlockChunk::Numbers(numbers), Some(max_allowed)) => {
+                        let numbers: Vec<_> =
+                            numbers.into_iter().filter(|n| *n <= max_allowed).collect();
+                        (!numbers.is_empty()).then_some(BlockChunk::Numbers(numbers))
+                    }
                     _ => None,
                 })
-                .collect())
+                .collect();
+            if n_chunks > 0 && block_chunks.is_empty() {
+                return Err(ParseError::ParseError(format!(
+                    "all blocks are within the reorg buffer of {} blocks from latest block {}",
+                    reorg_filter, latest_block
+                )))
+            }
+            Ok(block_chunks)
         }
     }
 }
@@ -391,9 +657,22 @@ pub(crate) async fn get_latest_block_number(source: Arc<Source>) -> Result<u64,
         .map_err(|_e| ParseError::ParseError("Error retrieving latest block number".to_string()))
 }
 
+/// number of the block that a block tag refers to, for tags other than `latest`
+async fn get_tagged_block_number(source: Arc<Source>, tag: BlockNumber) -> Result<u64, ParseError> {
+    source
+        .get_tagged_block_number(tag)
+        .await
+        .map_err(|e| {
+            ParseError::ParseError(format!("Error retrieving {} block number ({})", tag, e))
+        })?
+        .map(|n| n.as_u64())
+        .ok_or_else(|| ParseError::ParseError(format!("provider has no {} block", tag)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use clap_cryo::Parser;
     use ethers::prelude::*;
 
     enum BlockTokenTest<'a> {
@@ -402,17 +681,7 @@ mod tests {
     }
 
     async fn block_token_test_helper(tests: Vec<(BlockTokenTest<'_>, bool)>) {
-        let (provider, mock) = Provider::mocked();
-        let source = Source {
-            provider: provider.into(),
-            semaphore: Arc::new(None),
-            rate_limiter: Arc::new(None),
-            chain_id: 1,
-            inner_request_size: 1,
-            max_concurrent_chunks: None,
-            rpc_url: "".to_string(),
-            labels: cryo_freeze::SourceLabels::default(),
-        };
+        let (source, mock) = Source::mocked();
         let source = Arc::new(source);
         for (test, res) in tests {
             match test {
@@ -440,7 +709,8 @@ mod tests {
     ) -> bool {
         match expected {
             BlockChunk::Numbers(expected_block_numbers) => {
-                let block_chunks = parse_block_token(token, false, source).await.unwrap();
+                let head = ChainHead::new(source);
+                let block_chunks = parse_block_token(token, false, &head).await.unwrap();
                 assert!(matches!(block_chunks, BlockChunk::Numbers { .. }));
                 let BlockChunk::Numbers(block_numbers) = block_chunks else {
                     panic!("Unexpected shape")
@@ -448,7 +718,8 @@ mod tests {
                 block_numbers == expected_block_numbers
             }
             BlockChunk::Range(expected_range_start, expected_range_end) => {
-                let block_chunks = parse_block_token(token, true, source).await.unwrap();
+                let head = ChainHead::new(source);
+                let block_chunks = parse_block_token(token, true, &head).await.unwrap();
                 assert!(matches!(block_chunks, BlockChunk::Range { .. }));
                 let BlockChunk::Range(range_start, range_end) = block_chunks else {
                     panic!("Unexpected shape")
@@ -464,17 +735,8 @@ mod tests {
     }
 
     async fn block_input_test_helper(tests: Vec<(BlockInputTest<'_>, bool)>) {
-        let (provider, mock) = Provider::mocked();
-        let source = Arc::new(Source {
-            provider: provider.into(),
-            chain_id: 1,
-            rpc_url: "".to_string(),
-            inner_request_size: 1,
-            semaphore: Arc::new(None),
-            max_concurrent_chunks: Some(1),
-            rate_limiter: Arc::new(None),
-            labels: cryo_freeze::SourceLabels::default(),
-        });
+        let (source, mock) = Source::mocked();
+        let source = Arc::new(Source { max_concurrent_chunks: Some(1), ..source });
         for (test, res) in tests {
             match test {
                 BlockInputTest::WithMock((inputs, expected, latest)) => {
@@ -501,7 +763,7 @@ mod tests {
         expected: Vec<BlockChunk>,
         source: Arc<Source>,
     ) -> bool {
-        let block_chunks = parse_block_inputs(inputs, source).await.unwrap();
+        let block_chunks = parse_block_inputs(inputs, &ChainHead::new(source)).await.unwrap();
         assert_eq!(block_chunks.len(), expected.len());
         for (i, block_chunk) in block_chunks.iter().enumerate() {
             let expected_chunk = &expected[i];
@@ -514,7 +776,7 @@ mod tests {
                         panic!("Unexpected shape")
                     };
                     if expected_block_numbers != block_numbers {
-                        return false;
+                        return false
                     }
                 }
                 BlockChunk::Range(expected_range_start, expected_range_end) => {
@@ -523,7 +785,7 @@ mod tests {
                         panic!("Unexpected shape")
                     };
                     if expected_range_start != range_start || expected_range_end != range_end {
-                        return false;
+                        return false
                     }
                 }
             }
@@ -537,17 +799,8 @@ mod tests {
     }
 
     async fn block_number_test_helper(tests: Vec<(BlockNumberTest<'_>, bool)>) {
-        let (provider, mock) = Provider::mocked();
-        let source = Source {
-            provider: provider.into(),
-            semaphore: Arc::new(None),
-            rate_limiter: Arc::new(None),
-            chain_id: 1,
-            inner_request_size: 1,
-            max_concurrent_chunks: Some(1),
-            rpc_url: "".to_string(),
-            labels: cryo_freeze::SourceLabels::default(),
-        };
+        let (source, mock) = Source::mocked();
+        let source = Source { max_concurrent_chunks: Some(1), ..source };
         let source = Arc::new(source);
         for (test, res) in tests {
             match test {
@@ -586,7 +839,8 @@ mod tests {
         expected: u64,
         source: Arc<Source>,
     ) -> bool {
-        let block_number = parse_block_number(block_ref, range_position, source).await.unwrap();
+        let head = ChainHead::new(source);
+        let block_number = parse_block_number(block_ref, range_position, &head).await.unwrap();
         block_number == expected
     }
 
@@ -601,6 +855,7 @@ mod tests {
             (BlockTokenTest::WithoutMock((r"10:+100", BlockChunk::Range(10, 109))), true), /* Relative positive */
             (BlockTokenTest::WithMock((r"1:latest", BlockChunk::Range(1, 12), 12)), true), /* Explicit latest */
             (BlockTokenTest::WithMock((r"1:", BlockChunk::Range(1, 12), 12)), true), /* Implicit latest */
+            (BlockTokenTest::WithMock((r"-5:", BlockChunk::Range(8, 12), 12)), true), /* Last n blocks */
             // Number type
             (BlockTokenTest::WithoutMock((r"1", BlockChunk::Numbers(vec![1]))), true), /* Single block */
         ];
@@ -614,6 +869,7 @@ mod tests {
         let block_inputs_multiple = String::from(r"1 2");
         let block_inputs_latest = String::from(r"1:latest");
         let block_inputs_multiple_complex = String::from(r"15M:+1 1000:1002 -3:1b 2000");
+        let block_inputs_multiple_latest = String::from(r"-3: 9:latest");
         let tests: Vec<(BlockInputTest<'_>, bool)> = vec![
             // Range Type
             (
@@ -647,10 +903,95 @@ mod tests {
                 )),
                 true,
             ), // Multi input complex
+            (
+                BlockInputTest::WithMock((
+                    &block_inputs_multiple_latest,
+                    vec![
+                        BlockChunk::Numbers(vec![10, 11, 12]),
+                        BlockChunk::Numbers(vec![9, 10, 11, 12]),
+                    ],
+                    12,
+                )),
+                true,
+            ), // Multi input latest, fetched once
         ];
         block_input_test_helper(tests).await;
     }
 
+    #[tokio::test]
+    async fn empty_block_ranges() {
+        let (source, _mock) = Source::mocked();
+        let source = Arc::new(Source { max_concurrent_chunks: Some(1), ..source });
+        let head = ChainHead::new(source);
+        for token in ["5:5", "0:0", "10:5", "-20:10", "-0:10"] {
+            assert!(parse_block_token(token, true, &head).await.is_err(), "{}", token);
+        }
+    }
+
+    #[tokio::test]
+    async fn block_tag_parsing() {
+        let (source, mock) = Source::mocked();
+        let source = Arc::new(Source { max_concurrent_chunks: Some(1), ..source });
+        let head = ChainHead::new(source);
+        mock.push(Block::<TxHash> { number: Some(U64::from(10)), ..Default::default() }).unwrap();
+        let chunk = parse_block_token("5:finalized", true, &head).await.unwrap();
+        assert!(matches!(chunk, BlockChunk::Range(5, 10)));
+        let chunk = parse_block_token("earliest:finalized", true, &head).await.unwrap();
+        assert!(matches!(chunk, BlockChunk::Range(0, 10)));
+        assert_eq!(head.resolved_tags(), vec![("finalized".to_string(), 10)]);
+    }
+
+    #[tokio::test]
+    async fn reorg_buffer_trims_range() {
+        let (source, mock) = Source::mocked();
+        let source = Arc::new(Source { max_concurrent_chunks: Some(1), ..source });
+        let head = ChainHead::new(source);
+        mock.push(U64::from(100)).unwrap();
+        let chunks = vec![
+            BlockChunk::Range(0, 49),
+            BlockChunk::Range(50, 99),
+            BlockChunk::Numbers(vec![79, 80, 81]),
+            BlockChunk::Range(90, 100),
+        ];
+        let chunks = apply_reorg_buffer(chunks, 20, &head).await.unwrap();
+        assert_eq!(chunks.len(), 3);
+        assert!(matches!(chunks[0], BlockChunk::Range(0, 49)));
+        assert!(matches!(chunks[1], BlockChunk::Range(50, 80)));
+        assert!(matches!(&chunks[2], BlockChunk::Numbers(numbers) if numbers == &vec![79, 80]));
+    }
+
+    #[tokio::test]
+    async fn block_range_flag_parsing() {
+        let (source, mock) = Source::mocked();
+        let source = Arc::new(Source { max_concurrent_chunks: Some(1), ..source });
+        let head = ChainHead::new(source);
+        let parse = |command: &str| Args::parse_from(command.split_whitespace());
+
+        let args = parse("cryo blocks --start-block 100 --end-block 2500");
+        let (_, chunks) = parse_blocks(&args, &head).await.unwrap();
+        let chunks = chunks.unwrap();
+        assert_eq!(chunks.len(), 3);
+        assert!(matches!(chunks[0], BlockChunk::Range(100, 1099)));
+        assert!(matches!(chunks[2], BlockChunk::Range(2100, 2500)));
+
+        let args = parse("cryo blocks --start-block-exclusive 99 --end-block 100");
+        let (_, chunks) = parse_blocks(&args, &head).await.unwrap();
+        assert!(matches!(chunks.unwrap()[..], [BlockChunk::Range(100, 100)]));
+
+        mock.push(U64::from(150)).unwrap();
+        let args = parse("cryo blocks --start-block 100");
+        let (_, chunks) = parse_blocks(&args, &head).await.unwrap();
+        assert!(matches!(chunks.unwrap()[..], [BlockChunk::Range(100, 150)]));
+
+        for command in [
+            "cryo blocks --blocks 1:2 --start-block 1",
+            "cryo blocks --start-block 1 --start-block-exclusive 1",
+            "cryo blocks --start-block 10 --end-block 9",
+        ] {
+            assert!(parse_blocks(&parse(command), &head).await.is_err());
+        }
+    }
+
     #[tokio::test]
     async fn block_number_parsing() {
         // Ranges
@@ -668,4 +1009,70 @@ mod tests {
         ];
         block_number_test_helper(tests).await;
     }
+
+    #[tokio::test]
+    async fn block_list_file_parsing() {
+        let (source, _mock) = Source::mocked();
+        let source = Arc::new(Source { max_concurrent_chunks: Some(1), ..source });
+        let head = ChainHead::new(source);
+        let dir = std::env::temp_dir().join(format!("cryo_block_list_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("blocks.txt");
+        std::fs::write(&path, "# blocks to collect\n100\n\n  200:300  \n# trailing comment\n")
+            .unwrap();
+        let chunks = read_block_list_file(path.to_str().unwrap(), &head).await.unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert!(matches!(&chunks[0], BlockChunk::Numbers(numbers) if numbers == &vec![100]));
+        assert!(matches!(chunks[1], BlockChunk::Range(200, 299)));
+
+        let path = dir.join("invalid.txt");
+        std::fs::write(&path, "100\n# comment\nabc\n").unwrap();
+        let path = path.to_str().unwrap();
+        let Err(ParseError::ParseError(message)) = read_block_list_file(path, &head).await else {
+            panic!("invalid line should fail")
+        };
+        assert!(message.contains(&format!("line 3 of {} (abc)", path)), "{}", message);
+
+        assert!(read_block_list_file(dir.join("missing.txt").to_str().unwrap(), &head)
+            .await
+            .is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn time_ref_parsing() {
+        let (source, mock) = Source::mocked();
+        let source = Arc::new(Source { max_concurrent_chunks: Some(1), ..source });
+        let head = ChainHead::new(source);
+        mock.push(U64::from(10)).unwrap();
+        // blocks every 12 seconds starting at timestamp 100
+        head.block_timestamps.lock().unwrap().extend((0..=10).map(|i| (i, 100 + 12 * i)));
+
+        let chunk = parse_block_token("t124:t160", true, &head).await.unwrap();
+        assert!(matches!(chunk, BlockChunk::Range(2, 4)));
+        let chunk = parse_block_token("3:t161", true, &head).await.unwrap();
+        assert!(matches!(chunk, BlockChunk::Range(3, 5)));
+        let chunk = parse_block_token("t130", false, &head).await.unwrap();
+        assert!(matches!(&chunk, BlockChunk::Numbers(numbers) if numbers == &vec![2]));
+        assert!(parse_block_token("t50:5", true, &head).await.is_err());
+        assert!(parse_block_token("5:t300", true, &head).await.is_err());
+        assert!(head.resolved_tags().contains(&("t124".to_string(), 2)));
+
+        // timestamp start with a block tag end
+        let chunk = parse_block_token("124t:latest", true, &head).await.unwrap();
+        assert!(matches!(chunk, BlockChunk::Range(2, 10)));
+        mock.push(Block::<TxHash> { number: Some(U64::from(8)), ..Default::default() }).unwrap();
+        let chunk = parse_block_token("124t:finalized", true, &head).await.unwrap();
+        assert!(matches!(chunk, BlockChunk::Range(2, 8)));
+        mock.push(Block::<TxHash> { number: Some(U64::from(9)), ..Default::default() }).unwrap();
+        let chunk = parse_block_token("136t:safe", true, &head).await.unwrap();
+        assert!(matches!(chunk, BlockChunk::Range(3, 9)));
+
+        assert_eq!(parse_time_ref("2023-01-01").unwrap(), Some(1672531200));
+        assert_eq!(parse_time_ref("17000000").unwrap(), None);
+        assert_eq!(parse_time_ref("1680000000t").unwrap(), Some(1680000000));
+        assert_eq!(parse_time_ref("latest").unwrap(), None);
+        assert!(parse_time_ref("2023-13-01").is_err());
+    }
 }
@@ -0,0 +1,204 @@
+use crate::args::Args;
+use cryo_freeze::{ChunkData, Datatype, ParseError, Partition, Source};
+use ethers::prelude::*;
+use std::collections::HashMap;
+
+/// datatypes whose rows all come from logs emitted by the `--contract` filter
+const CONTRACT_EVENT_DATATYPES: [Datatype; 6] = [
+    Datatype::Logs,
+    Datatype::Erc20Transfers,
+    Datatype::Erc20Approvals,
+    Datatype::Erc721Transfers,
+    Datatype::Erc1155Transfers,
+    Datatype::NftTransfers,
+];
+
+/// drop partitions whose blocks all precede the deployment of their contracts
+///
+/// returns the remaining partitions and the number of partitions dropped
+pub(crate) async fn skip_before_deployment(
+    args: &Args,
+    partitions: Vec<Partition>,
+    datatypes: &[Datatype],
+    source: &Source,
+) -> Result<(Vec<Partition>, u64), ParseError> {
+    if !args.skip_before_deployment {
+        return Ok((partitions, 0))
+    }
+    if args.contract.is_none() {
+        return Err(ParseError::ParseError(
+            "--skip-before-deployment requires --contract".to_string(),
+        ))
+    }
+    if let Some(datatype) = datatypes.iter().find(|dt| !CONTRACT_EVENT_DATATYPES.contains(dt)) {
+        return Err(ParseError::ParseError(format!(
+            "--skip-before-deployment does not support {}",
+            datatype.name()
+        )))
+    }
+
+    let block_ranges: Vec<_> = partitions
+        .iter()
+        .filter_map(|partition| partition.block_numbers.as_ref())
+        .map(|block_numbers| (block_numbers.min_value(), block_numbers.max_value()))
+        .collect();
+    let start_block = block_ranges.iter().filter_map(|(min, _)| *min).min();
+    let end_block = block_ranges.iter().filter_map(|(_, max)| *max).max();
+    let (start_block, end_block) = match (start_block, end_block) {
+        (Some(start_block), Some(end_block)) => (start_block, end_block),
+        _ => return Ok((partitions, 0)),
+    };
+
+    let mut deployments: HashMap<Vec<u8>, Option<u64>> = HashMap::new();
+    let mut kept = Vec::new();
+    let mut n_skipped = 0;
+    for partition in partitions.into_iter() {
+        let contracts = partition.contracts.as_ref().map(|x| x.values()).unwrap_or_default();
+        let mut first_deployment = None;
+        for contract in contracts.iter() {
+            let deployment = match deployments.get(contract) {
+                Some(deployment) => *deployment,
+                None => {
+                    let deployment =
+                        find_deployment_block(contract, start_block, end_block, source).await?;
+                    deployments.insert(contract.clone(), deployment);
+                    deployment
+                }
+            };
+            first_deployment = match (first_deployment, deployment) {
+                (Some(first), Some(deployment)) => Some(std::cmp::min(first, deployment)),
+                (first, deployment) => first.or(deployment),
+            };
+        }
+        let partition_end = partition.block_numbers.as_ref().and_then(|x| x.max_value());
+        let skip = match (first_deployment, partition_end) {
+            _ if contracts.is_empty() => false,
+            (None, _) => true,
+            (Some(deployment), Some(partition_end)) => partition_end < deployment,
+            (Some(_), None) => false,
+        };
+        if skip {
+            n_skipped += 1;
+        } else {
+            kept.push(partition);
+        }
+    }
+    Ok((kept, n_skipped))
+}
+
+/// binary search the first block within range at which contract has code
+///
+/// returns None if contract has no code at the end of the range. eth_getCode only reflects
+/// current code, so a contract that self-destructed before the end of the range looks undeployed
+async fn find_deployment_block(
+    contract: &[u8],
+    start_block: u64,
+    end_block: u64,
+    source: &Source,
+) -> Result<Option<u64>, ParseError> {
+    if contract.len() != 20 {
+        return Err(ParseError::ParseError("invalid contract address".to_string()))
+    }
+    let address = H160::from_slice(contract);
+    if has_code(address, start_block, source).await? {
+        return Ok(Some(start_block))
+    }
+    if !has_code(address, end_block, source).await? {
+        return Ok(None)
+    }
+
+    // no code at low, code at high
+    let (mut low, mut high) = (start_block, end_block);
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        if has_code(address, mid, source).await? {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    Ok(Some(high))
+}
+
+async fn has_code(address: H160, block_number: u64, source: &Source) -> Result<bool, ParseError> {
+    let code = source
+        .get_code(address, block_number.into())
+        .await
+        .map_err(|e| ParseError::ParseError(format!("could not get code of contract ({})", e)))?;
+    Ok(!code.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cryo_freeze::{AddressChunk, BlockChunk};
+    use std::sync::Arc;
+
+    const CONTRACT: [u8; 20] = [1u8; 20];
+
+    /// push eth_getCode responses, given in the order they are requested
+    fn push_code_responses(mock: &MockProvider, has_code: &[bool]) {
+        // mock provider returns responses last in, first out
+        for has_code in has_code.iter().rev() {
+            let code = if *has_code { Bytes::from(vec![0x60]) } else { Bytes::default() };
+            mock.push(code).unwrap();
+        }
+    }
+
+    fn block_partitions() -> Vec<Partition> {
+        [(0, 99), (100, 199), (200, 299)]
+            .into_iter()
+            .map(|(start, end)| Partition {
+                block_numbers: Some(vec![BlockChunk::Range(start, end)]),
+                contracts: Some(vec![AddressChunk::Values(vec![CONTRACT.to_vec()])]),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn args() -> Args {
+        Args {
+            skip_before_deployment: true,
+            contract: Some(vec![format!("0x{}", hex::encode(CONTRACT))]),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deployed_mid_range() {
+        let (source, mock) = Source::mocked();
+        // probes: start, end, then bisection of (0, 299] toward deployment at block 150
+        let probes = [false, true, false, true, true, true, true, true, true, true];
+        push_code_responses(&mock, &probes);
+        let (partitions, n_skipped) =
+            skip_before_deployment(&args(), block_partitions(), &[Datatype::Logs], &source)
+                .await
+                .unwrap();
+        assert_eq!(n_skipped, 1);
+        let starts: Vec<_> =
+            partitions.iter().map(|p| p.block_numbers.as_ref().unwrap().min_value()).collect();
+        assert_eq!(starts, vec![Some(100), Some(200)]);
+    }
+
+    #[tokio::test]
+    async fn test_never_deployed() {
+        let (source, mock) = Source::mocked();
+        push_code_responses(&mock, &[false, false]);
+        let datatypes = [Datatype::Erc20Transfers];
+        let (partitions, n_skipped) =
+            skip_before_deployment(&args(), block_partitions(), &datatypes, &source).await.unwrap();
+        assert!(partitions.is_empty());
+        assert_eq!(n_skipped, 3);
+    }
+
+    #[tokio::test]
+    async fn test_deployed_before_range() {
+        let (source, mock) = Source::mocked();
+        push_code_responses(&mock, &[true]);
+        let (partitions, n_skipped) =
+            skip_before_deployment(&args(), block_partitions(), &[Datatype::Logs], &source)
+                .await
+                .unwrap();
+        assert_eq!((partitions.len(), n_skipped), (3, 0));
+    }
+}
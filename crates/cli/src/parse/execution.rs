@@ -1,5 +1,9 @@
 use crate::args::Args;
-use cryo_freeze::{ExecutionEnv, ExecutionEnvBuilder, ParseError};
+use cryo_freeze::{ExecutionEnv, ExecutionEnvBuilder, FollowConfig, LogFormat, ParseError};
+use std::time::Duration;
+
+/// seconds between polls for new blocks when following the chain, about one mainnet block
+const DEFAULT_POLL_INTERVAL: f64 = 12.0;
 
 pub(crate) fn parse_execution_env(args: &Args, n_tasks: u64) -> Result<ExecutionEnv, ParseError> {
     let args_str =
@@ -12,14 +16,49 @@ pub(crate) fn parse_execution_env(args: &Args, n_tasks: u64) -> Result<Execution
         (false, false) => 1,
     };
 
+    let log_format = match &args.log_format {
+        Some(log_format) => log_format.parse::<LogFormat>()?,
+        None => LogFormat::Text,
+    };
+
+    let follow = match (args.follow, args.poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL)) {
+        (false, _) => None,
+        (true, poll_interval) if poll_interval.is_finite() && poll_interval > 0.0 => {
+            let poll_interval = Duration::from_secs_f64(poll_interval);
+            Some(FollowConfig { poll_interval, chunk_size: args.chunk_size })
+        }
+        (true, _) => {
+            return Err(ParseError::ParseError("--poll-interval must be positive".to_string()))
+        }
+    };
+
     let builder = ExecutionEnvBuilder::new()
         .dry(args.dry)
+        .estimate_size(args.dry_run_estimate_size)
+        .verify(args.verify)
         .verbose(verbose)
+        .log_format(log_format)
+        .follow(follow)
         .report(!args.no_report)
         .report_dir(args.report_dir.clone())
-        .args(args_str);
+        .args(args_str)
+        .stats(args.stats)
+        .timing_report(args.timing_report)
+        .map_err(|e| ParseError::ParseError(e.to_string()))?;
+
+    let builder = match args.writer_queue_size {
+        Some(0) => {
+            return Err(ParseError::ParseError(
+                "--writer-queue-size must be at least 1".to_string(),
+            ))
+        }
+        Some(writer_queue_size) => builder.writer_queue_size(writer_queue_size),
+        None => builder,
+    };
 
-    let builder = if !args.no_verbose {
+    // dry runs do not collect, so they have no progress to show, and json logs progress as events
+    let show_bar = log_format == LogFormat::Text;
+    let builder = if show_bar && !args.no_verbose && !args.dry && !args.dry_run_estimate_size {
         builder
             .bar(n_tasks)
             .map_err(|_| ParseError::ParseError("could not create progress bar".to_string()))?
@@ -1,5 +1,8 @@
 use crate::args::Args;
-use cryo_freeze::{FileFormat, FileOutput, ParseError, Source, SubDir};
+use cryo_freeze::{
+    ChunkData, CollectError, ColumnType, CsvCompression, Dim, FileFormat, FileOutput, HiveKey,
+    ParseError, Query, Source, SubDir,
+};
 use polars::prelude::*;
 use std::fs;
 
@@ -24,28 +27,258 @@ pub(crate) fn parse_file_output(args: &Args, source: &Source) -> Result<FileOutp
         args.n_row_groups,
         Some(args.chunk_size as usize),
     );
+    let row_group_bytes = parse_row_group_bytes(args)?;
+    if args.no_dictionary && args.dictionary_columns.is_some() {
+        return Err(ParseError::ParseError(
+            "--no-dictionary cannot be combined with --dictionary-columns".to_string(),
+        ))
+    }
 
     let format = parse_output_format(args)?;
+    if format != FileFormat::Parquet && args.compression != ["lz4"] {
+        eprintln!("warning: --compression only applies to parquet output, ignoring it");
+    }
+    let csv_compression = parse_csv_compression(args, &format)?;
+    let verifiable = match format {
+        FileFormat::Parquet => true,
+        FileFormat::Csv => matches!(csv_compression, CsvCompression::None),
+        FileFormat::Json | FileFormat::Jsonl | FileFormat::Delta => false,
+    };
+    if args.verify && !verifiable {
+        return Err(ParseError::ParseError(
+            "--verify only supports parquet and uncompressed csv output".to_string(),
+        ))
+    }
     let file_prefix = parse_network_name(args, source.chain_id);
 
-    let subdirs = parse_subdirs(args);
+    let (hive_partitions, hive_partition_columns) = parse_hive_partitions(args)?;
+    let subdirs = parse_subdirs(args, &hive_partitions);
+
+    let target_file_size = match args.target_file_mb {
+        Some(0) => {
+            return Err(ParseError::ParseError("--target-file-mb must be at least 1".to_string()))
+        }
+        target_file_mb => target_file_mb.map(|mb| mb * 1_000_000),
+    };
+    if args.partition_width == Some(0) {
+        return Err(ParseError::ParseError("--partition-width must be at least 1".to_string()))
+    }
+    if format == FileFormat::Delta &&
+        (target_file_size.is_some() ||
+            args.partition_width.is_some() ||
+            !hive_partitions.is_empty() ||
+            args.file_name_template.is_some() ||
+            args.output_prefix.is_some())
+    {
+        return Err(ParseError::ParseError(
+            "--target-file-mb, --partition-width, --hive-partitions, --file-name-template, and \
+             --output-prefix do not apply to delta output"
+                .to_string(),
+        ))
+    }
+    if let Some(output_prefix) = &args.output_prefix {
+        if output_prefix.is_empty() || output_prefix.contains(['/', '\\']) {
+            return Err(ParseError::ParseError(
+                "--output-prefix must be a non-empty file name prefix without /".to_string(),
+            ))
+        }
+    }
 
     let output = FileOutput {
         output_dir,
         subdirs,
         parquet_statistics: !args.no_stats,
+        parquet_dictionary: !args.no_dictionary,
+        dictionary_columns: args.dictionary_columns.clone(),
         overwrite: args.overwrite,
         prefix: file_prefix,
         format,
         suffix: label.clone(),
         parquet_compression,
         row_group_size,
+        row_group_bytes,
+        dataset: args.dataset,
+        target_file_size,
+        partition_width: args.partition_width,
+        hive_partitions,
+        hive_partition_columns,
+        file_name_template: args.file_name_template.clone(),
+        output_prefix: args.output_prefix.clone(),
+        csv_compression,
+        metadata: !args.no_metadata,
     };
 
     Ok(output)
 }
 
-pub(crate) fn parse_subdirs(args: &Args) -> Vec<SubDir> {
+fn parse_csv_compression(args: &Args, format: &FileFormat) -> Result<CsvCompression, ParseError> {
+    let raw_compression = args.csv_compression.as_deref().map(|raw| raw.to_lowercase());
+    let compression = match raw_compression.as_deref() {
+        None | Some("none") => CsvCompression::None,
+        Some("gzip") | Some("gz") => CsvCompression::Gzip,
+        Some("zstd") | Some("zst") => CsvCompression::Zstd,
+        Some(raw) => {
+            return Err(ParseError::ParseError(format!(
+                "invalid --csv-compression, use gzip, zstd, or none: {}",
+                raw
+            )))
+        }
+    };
+    if compression != CsvCompression::None && format != &FileFormat::Csv {
+        return Err(ParseError::ParseError("--csv-compression requires csv output".to_string()))
+    }
+    Ok(compression)
+}
+
+/// check that file name template gives each chunk its own file, named so that completed chunks
+/// can be recognized and skipped when cryo is rerun
+pub(crate) fn check_file_name_template(
+    args: &Args,
+    query: &Query,
+    sink: &FileOutput,
+) -> Result<(), ParseError> {
+    let template = match &sink.file_name_template {
+        Some(template) => template,
+        None => return Ok(()),
+    };
+    let error =
+        |message: &str| Err(ParseError::ParseError(format!("--file-name-template {}", message)));
+    if template.starts_with('/') || template.split('/').any(|piece| piece == "..") {
+        return error("must stay within the output directory")
+    }
+    if let Some(partition) = query.partitions.first() {
+        for datatype in query.schemas.keys() {
+            match sink.render_file_name(template, query, partition, *datatype) {
+                Ok(_) => {}
+                Err(CollectError::CollectError(message)) => return error(&message),
+                Err(e) => return error(&e.to_string()),
+            }
+        }
+    }
+
+    let has_block_range = template.contains("{start_block}") && template.contains("{end_block}");
+    if !template.contains("{chunk}") {
+        if query.partitioned_by != [Dim::BlockNumber] {
+            return error("must contain {chunk} when chunks are not block ranges")
+        } else if !has_block_range {
+            return error("must contain {start_block} and {end_block}, or {chunk}")
+        }
+    }
+    if args.target_file_mb.is_some() &&
+        !template.contains("{chunk}") &&
+        !template.contains("{start_block}_to_{end_block}")
+    {
+        return error(
+            "must contain {chunk} or {start_block}_to_{end_block} to use --target-file-mb",
+        )
+    }
+    if args.dataset {
+        return error("cannot be used with --dataset")
+    }
+    if sink.csv_compression != CsvCompression::None && !template.ends_with(".{ext}") {
+        return error("must end with .{ext} to use --csv-compression")
+    }
+    Ok(())
+}
+
+/// parse keys of hive partitioned subdirectories, and whether to keep the keys as columns
+fn parse_hive_partitions(args: &Args) -> Result<(Vec<HiveKey>, bool), ParseError> {
+    let raw_keys = match &args.hive_partitions {
+        Some(raw_keys) if raw_keys.is_empty() => DEFAULT_HIVE_PARTITIONS.to_vec(),
+        Some(raw_keys) => raw_keys.iter().map(|raw_key| raw_key.as_str()).collect(),
+        None => {
+            if args.partition_columns.is_some() {
+                return Err(ParseError::ParseError(
+                    "--partition-columns requires --hive-partitions".to_string(),
+                ))
+            }
+            return Ok((Vec::new(), false))
+        }
+    };
+    if args.partition_width.is_some() {
+        return Err(ParseError::ParseError(
+            "--hive-partitions cannot be used with --partition-width, use a block_<N_BLOCKS> key"
+                .to_string(),
+        ))
+    }
+    let mut keys = Vec::new();
+    for raw_key in raw_keys.into_iter() {
+        let key = match raw_key {
+            "network" => HiveKey::Network,
+            "datatype" => HiveKey::Datatype,
+            _ => match raw_key.strip_prefix("block_").map(|width| width.parse::<u64>()) {
+                Some(Ok(0)) => {
+                    return Err(ParseError::ParseError(
+                        "--hive-partitions block width must be at least 1".to_string(),
+                    ))
+                }
+                Some(Ok(width)) => HiveKey::BlockBucket(width),
+                _ => {
+                    return Err(ParseError::ParseError(format!(
+                        "invalid --hive-partitions key: {}, valid keys are network, datatype, and \
+                         block_<N_BLOCKS>",
+                        raw_key
+                    )))
+                }
+            },
+        };
+        if keys.contains(&key) {
+            return Err(ParseError::ParseError(format!(
+                "--hive-partitions key is given twice: {}",
+                raw_key
+            )))
+        }
+        if keys.iter().any(|key| matches!(key, HiveKey::BlockBucket(_))) {
+            return Err(ParseError::ParseError(
+                "--hive-partitions block key must be the last key".to_string(),
+            ))
+        }
+        keys.push(key);
+    }
+    let columns = match args.partition_columns.as_deref() {
+        None | Some("drop") => false,
+        Some("keep") => true,
+        Some(raw) => {
+            return Err(ParseError::ParseError(format!(
+                "invalid --partition-columns, use keep or drop: {}",
+                raw
+            )))
+        }
+    };
+    Ok((keys, columns))
+}
+
+/// keys of hive partitions when --hive-partitions is given without keys
+const DEFAULT_HIVE_PARTITIONS: [&str; 3] = ["network", "datatype", "block_1000000"];
+
+/// check that each chunk of blocks falls within a single block bucket of hive partitions
+pub(crate) fn check_hive_partitions(query: &Query, sink: &FileOutput) -> Result<(), ParseError> {
+    let width = match sink.hive_partitions.iter().find_map(|key| match key {
+        HiveKey::BlockBucket(width) => Some(*width),
+        _ => None,
+    }) {
+        Some(width) => width,
+        None => return Ok(()),
+    };
+    for partition in query.partitions.iter() {
+        let block_numbers = match &partition.block_numbers {
+            Some(block_numbers) => block_numbers,
+            None => continue,
+        };
+        if let (Some(first), Some(last)) = (block_numbers.min_value(), block_numbers.max_value()) {
+            if first / width != last / width {
+                return Err(ParseError::ParseError(format!(
+                    "chunk of blocks {} to {} spans two block_{} hive partitions, use --align with \
+                     a chunk size that divides {}",
+                    first, last, width, width
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn parse_subdirs(args: &Args, hive_partitions: &[HiveKey]) -> Vec<SubDir> {
     let mut subdirs = Vec::new();
     for arg in args.subdirs.iter() {
         if arg == "datatype" {
@@ -56,6 +289,13 @@ pub(crate) fn parse_subdirs(args: &Args) -> Vec<SubDir> {
             subdirs.push(SubDir::Custom(arg.clone()))
         }
     }
+    // datasets and hive partitions are read as one table per directory
+    if (args.dataset || args.partition_width.is_some()) &&
+        !subdirs.iter().any(|subdir| matches!(subdir, SubDir::Datatype)) &&
+        !hive_partitions.contains(&HiveKey::Datatype)
+    {
+        subdirs.push(SubDir::Datatype)
+    }
     subdirs
 }
 
@@ -89,49 +329,90 @@ pub(crate) fn parse_network_name(args: &Args, chain_id: u64) -> String {
 }
 
 pub(crate) fn parse_output_format(args: &Args) -> Result<FileFormat, ParseError> {
-    match (args.csv, args.json) {
-        (true, true) => {
-            Err(ParseError::ParseError("choose one of parquet, csv, or json".to_string()))
+    if let Some(output_format) = &args.output_format {
+        let format = match output_format.to_lowercase().as_str() {
+            "parquet" => FileFormat::Parquet,
+            "csv" => FileFormat::Csv,
+            "json" => FileFormat::Json,
+            "jsonl" | "ndjson" => FileFormat::Jsonl,
+            "delta" if cfg!(feature = "delta") => FileFormat::Delta,
+            "delta" => {
+                return Err(ParseError::ParseError(
+                    "delta output requires cryo to be built with the delta feature".to_string(),
+                ))
+            }
+            _ => {
+                return Err(ParseError::ParseError(format!(
+                    "invalid output format: {}",
+                    output_format
+                )))
+            }
+        };
+        if args.dataset && format != FileFormat::Parquet {
+            return Err(ParseError::ParseError("--dataset requires parquet output".to_string()))
         }
-        (true, _) => Ok(FileFormat::Csv),
-        (_, true) => Ok(FileFormat::Json),
-        (false, false) => Ok(FileFormat::Parquet),
+        return Ok(format)
+    }
+    match (args.csv, args.json, args.jsonl) {
+        (true, false, false) => Ok(FileFormat::Csv),
+        (false, true, false) => Ok(FileFormat::Json),
+        (false, false, true) => Ok(FileFormat::Jsonl),
+        (false, false, false) => Ok(FileFormat::Parquet),
+        _ => Err(ParseError::ParseError("choose one of parquet, csv, json, or jsonl".to_string())),
     }
 }
 
-fn parse_compression(input: &Vec<String>) -> Result<ParquetCompression, ParseError> {
-    match input.as_slice() {
-        [algorithm] if algorithm.as_str() == "uncompressed" => Ok(ParquetCompression::Uncompressed),
-        [algorithm] if algorithm.as_str() == "snappy" => Ok(ParquetCompression::Snappy),
-        [algorithm] if algorithm.as_str() == "lzo" => Ok(ParquetCompression::Lzo),
-        [algorithm] if algorithm.as_str() == "lz4" => Ok(ParquetCompression::Lz4Raw),
-        [algorithm, level_str] if algorithm.as_str() == "gzip" => match level_str.parse::<u8>() {
-            Ok(level) => match GzipLevel::try_new(level) {
-                Ok(gzip_level) => Ok(ParquetCompression::Gzip(Some(gzip_level))),
-                Err(_) => Err(ParseError::ParseError("Invalid compression level".to_string())),
-            },
-            Err(_) => Err(ParseError::ParseError("Invalid compression level".to_string())),
+/// compression algorithms of parquet output, with the levels that each accepts
+const COMPRESSION_OPTIONS: &str =
+    "lz4, zstd[:1-22], snappy, gzip[:0-10], brotli[:0-11], lzo, or uncompressed";
+
+fn parse_compression(input: &[String]) -> Result<ParquetCompression, ParseError> {
+    // level can be given as a separate value or after a colon, as in zstd:6
+    let (algorithm, level) = match input {
+        [algorithm] => match algorithm.split_once(':') {
+            Some((algorithm, level)) => (algorithm, Some(level)),
+            None => (algorithm.as_str(), None),
         },
-        [algorithm, level_str] if algorithm.as_str() == "brotli" => {
-            match level_str.parse::<u32>() {
-                Ok(level) => match BrotliLevel::try_new(level) {
-                    Ok(brotli_level) => Ok(ParquetCompression::Brotli(Some(brotli_level))),
-                    Err(_) => Err(ParseError::ParseError("Invalid compression level".to_string())),
-                },
-                Err(_) => Err(ParseError::ParseError("Invalid compression level".to_string())),
-            }
+        [algorithm, level] => (algorithm.as_str(), Some(level.as_str())),
+        _ => {
+            return Err(ParseError::ParseError(format!(
+                "invalid compression {}, choose one of {}",
+                input.join(" "),
+                COMPRESSION_OPTIONS
+            )))
         }
-        [algorithm, level_str] if algorithm.as_str() == "zstd" => match level_str.parse::<i32>() {
-            Ok(level) => match ZstdLevel::try_new(level) {
-                Ok(zstd_level) => Ok(ParquetCompression::Zstd(Some(zstd_level))),
-                Err(_) => Err(ParseError::ParseError("Invalid compression level".to_string())),
-            },
-            Err(_) => Err(ParseError::ParseError("Invalid compression level".to_string())),
+    };
+    let invalid_level = || {
+        ParseError::ParseError(format!(
+            "invalid {} compression level, choose one of {}",
+            algorithm, COMPRESSION_OPTIONS
+        ))
+    };
+    match (algorithm, level) {
+        ("uncompressed" | "none", None) => Ok(ParquetCompression::Uncompressed),
+        ("snappy", None) => Ok(ParquetCompression::Snappy),
+        ("lzo", None) => Ok(ParquetCompression::Lzo),
+        ("lz4", None) => Ok(ParquetCompression::Lz4Raw),
+        ("gzip", None) => Ok(ParquetCompression::Gzip(None)),
+        ("gzip", Some(level)) => match level.parse::<u8>().map(GzipLevel::try_new) {
+            Ok(Ok(level)) => Ok(ParquetCompression::Gzip(Some(level))),
+            _ => Err(invalid_level()),
         },
-        [algorithm] if ["gzip", "brotli", "zstd"].contains(&algorithm.as_str()) => {
-            Err(ParseError::ParseError("Missing compression level".to_string()))
-        }
-        _ => Err(ParseError::ParseError("Invalid compression algorithm".to_string())),
+        ("brotli", None) => Ok(ParquetCompression::Brotli(None)),
+        ("brotli", Some(level)) => match level.parse::<u32>().map(BrotliLevel::try_new) {
+            Ok(Ok(level)) => Ok(ParquetCompression::Brotli(Some(level))),
+            _ => Err(invalid_level()),
+        },
+        ("zstd", None) => Ok(ParquetCompression::Zstd(None)),
+        ("zstd", Some(level)) => match level.parse::<i32>().map(ZstdLevel::try_new) {
+            Ok(Ok(level)) => Ok(ParquetCompression::Zstd(Some(level))),
+            _ => Err(invalid_level()),
+        },
+        ("uncompressed" | "none" | "snappy" | "lzo" | "lz4", Some(_)) => Err(invalid_level()),
+        _ => Err(ParseError::ParseError(format!(
+            "invalid compression algorithm {}, choose one of {}",
+            algorithm, COMPRESSION_OPTIONS
+        ))),
     }
 }
 
@@ -146,3 +427,121 @@ fn parse_row_group_size(
         _ => None,
     }
 }
+
+fn parse_row_group_bytes(args: &Args) -> Result<Option<u64>, ParseError> {
+    match args.row_group_mb {
+        Some(_) if args.row_group_size.is_some() || args.n_row_groups.is_some() => {
+            Err(ParseError::ParseError(
+                "--row-group-mb cannot be combined with --row-group-size or --n-row-groups"
+                    .to_string(),
+            ))
+        }
+        Some(0) => Err(ParseError::ParseError("--row-group-mb must be at least 1".to_string())),
+        row_group_mb => Ok(row_group_mb.map(|mb| mb * 1_000_000)),
+    }
+}
+
+/// check that dictionary columns are string columns of the collected datatypes
+///
+/// polars can only dictionary encode string columns, so binary columns must be stored as hex
+pub(crate) fn check_dictionary_columns(query: &Query, sink: &FileOutput) -> Result<(), ParseError> {
+    let columns = match &sink.dictionary_columns {
+        Some(columns) => columns,
+        None => return Ok(()),
+    };
+    for column in columns.iter() {
+        let column_types: Vec<ColumnType> =
+            query.schemas.values().filter_map(|schema| schema.column_type(column)).collect();
+        if column_types.is_empty() {
+            return Err(ParseError::ParseError(format!(
+                "dictionary column {} is not a column of the collected datatypes",
+                column
+            )))
+        }
+        for column_type in column_types.into_iter() {
+            match column_type {
+                ColumnType::String | ColumnType::Hex => {}
+                ColumnType::Binary => {
+                    return Err(ParseError::ParseError(format!(
+                        "binary column {} cannot be dictionary encoded, use --hex to store it as \
+                         strings",
+                        column
+                    )))
+                }
+                _ => {
+                    return Err(ParseError::ParseError(format!(
+                        "{} column {} cannot be dictionary encoded, only string columns can",
+                        column_type.as_str(),
+                        column
+                    )))
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap_cryo::Parser;
+
+    fn compression(input: &[&str]) -> Result<ParquetCompression, ParseError> {
+        parse_compression(&input.iter().map(|x| x.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_parse_compression() {
+        assert!(matches!(compression(&["zstd:6"]), Ok(ParquetCompression::Zstd(Some(_)))));
+        assert!(matches!(compression(&["zstd", "6"]), Ok(ParquetCompression::Zstd(Some(_)))));
+        assert!(matches!(compression(&["zstd"]), Ok(ParquetCompression::Zstd(None))));
+        assert!(matches!(compression(&["lz4"]), Ok(ParquetCompression::Lz4Raw)));
+        for invalid in [&["zstd:23"][..], &["gzip", "x"], &["lz4:3"], &["bzip2"]] {
+            let message = compression(invalid).unwrap_err().to_string();
+            assert!(message.contains(COMPRESSION_OPTIONS), "{}", message);
+        }
+    }
+
+    #[test]
+    fn test_parse_hive_partitions() {
+        let hive = |command: &str| {
+            parse_hive_partitions(&Args::parse_from(command.split_whitespace()))
+                .map_err(|e| e.to_string())
+        };
+        let default_keys = vec![HiveKey::Network, HiveKey::Datatype, HiveKey::BlockBucket(1000000)];
+        assert_eq!(hive("cryo blocks --hive-partitions"), Ok((default_keys, false)));
+        assert_eq!(
+            hive("cryo blocks --hive-partitions datatype block_1000 --partition-columns keep"),
+            Ok((vec![HiveKey::Datatype, HiveKey::BlockBucket(1000)], true))
+        );
+        assert_eq!(hive("cryo blocks"), Ok((vec![], false)));
+        for invalid in [
+            "cryo blocks --hive-partitions block_1000 network",
+            "cryo blocks --hive-partitions block_0",
+            "cryo blocks --hive-partitions chain",
+            "cryo blocks --hive-partitions network network",
+            "cryo blocks --hive-partitions --partition-width 1000",
+            "cryo blocks --hive-partitions --partition-columns maybe",
+            "cryo blocks --partition-columns keep",
+        ] {
+            assert!(hive(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_parse_row_group_bytes() {
+        let row_group_bytes = |command: &str| {
+            parse_row_group_bytes(&Args::parse_from(command.split_whitespace()))
+                .map_err(|e| e.to_string())
+        };
+        assert_eq!(row_group_bytes("cryo blocks --row-group-mb 64"), Ok(Some(64_000_000)));
+        assert_eq!(row_group_bytes("cryo blocks --row-group-size 1000"), Ok(None));
+        for invalid in [
+            "cryo blocks --row-group-mb 0",
+            "cryo blocks --row-group-mb 64 --row-group-size 1000",
+            "cryo blocks --row-group-mb 64 --n-row-groups 4",
+        ] {
+            assert!(row_group_bytes(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 mod args;
 mod blocks;
+mod deployments;
 mod execution;
 mod file_output;
 mod parse_utils;
@@ -13,3 +14,4 @@ pub use args::*;
 #[allow(unused_imports)]
 pub use query::*;
 use schemas::*;
+pub(crate) use source::add_url_scheme;
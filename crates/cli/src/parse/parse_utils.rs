@@ -39,7 +39,7 @@ impl BinaryInputList {
     }
 }
 
-type ParsedBinaryArg = HashMap<BinaryInputList, Vec<Vec<u8>>>;
+pub(crate) type ParsedBinaryArg = HashMap<BinaryInputList, Vec<Vec<u8>>>;
 
 /// parse binary argument list
 /// each argument can be a hex string or a parquet column reference
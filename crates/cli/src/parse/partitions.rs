@@ -1,31 +1,34 @@
 use super::{
-    blocks,
-    parse_utils::{hex_string_to_binary, hex_strings_to_binary, parse_binary_arg},
+    blocks::{self, ChainHead},
+    parse_utils::{hex_string_to_binary, hex_strings_to_binary, parse_binary_arg, ParsedBinaryArg},
     timestamps,
 };
 use crate::args::Args;
 use cryo_freeze::{
-    AddressChunk, CallDataChunk, Datatype, Dim, ParseError, Partition, PartitionLabels, SlotChunk,
-    Source, Table, TimeDimension, TopicChunk, TransactionChunk,
+    AddressChunk, CallDataChunk, CallDecoder, Datatype, Dim, ParseError, Partition,
+    PartitionLabels, SlotChunk, Table, TimeDimension, TopicChunk, TransactionChunk,
 };
 use ethers::prelude::*;
 use rand::{seq::SliceRandom, thread_rng};
-use std::{collections::HashMap, str::FromStr, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
 type ChunkLabels = Vec<Option<String>>;
 
 pub(crate) async fn parse_partitions(
     args: &Args,
-    source: Arc<Source>,
+    head: &ChainHead,
     schemas: &HashMap<Datatype, Table>,
 ) -> Result<(Vec<Partition>, Vec<Dim>, TimeDimension), ParseError> {
     // TODO: if wanting to chunk these non-block dimensions, do it in parse_binary_arg()
     // TODO: map from args to dim is not exhaustive
 
     // parse chunk data
-    let (block_number_labels, block_numbers) = blocks::parse_blocks(args, source.clone()).await?;
+    let (block_number_labels, block_numbers) = blocks::parse_blocks(args, head).await?;
     let (block_number_labels, block_numbers) = if block_numbers.is_none() {
-        timestamps::parse_timestamps(args, source.clone()).await?
+        timestamps::parse_timestamps(args, head.source.clone(), head).await?
     } else {
         (block_number_labels, block_numbers)
     };
@@ -38,15 +41,26 @@ pub(crate) async fn parse_partitions(
     let (from_address_labels, from_addresses) =
         parse_address_chunks(&args.from_address, "from_address")?;
     let (to_address_labels, to_addresses) = parse_address_chunks(&args.to_address, "to_address")?;
-    let (slot_labels, slots) = parse_slot_chunks(&args.slot, "slot")?;
-    let (topic0_labels, topic0s) = parse_topic(&args.topic0, "topic0")?;
+    let slot_pairs = parse_slot_pairs(args)?;
+    let (address_labels, addresses, slot_labels, slots) = match slot_pairs {
+        Some(_) => (None, None, None, None),
+        None => {
+            let (slot_labels, slots) = parse_slot_chunks(&args.slot, "slot")?;
+            (address_labels, addresses, slot_labels, slots)
+        }
+    };
+    let topic0 = match &args.topic0 {
+        Some(topic0) => Some(topic0.clone()),
+        None => get_event_signature_topic0(schemas),
+    };
+    let (topic0_labels, topic0s) = parse_topic(&topic0, "topic0")?;
     let (topic1_labels, topic1s) = parse_topic(&args.topic1, "topic1")?;
     let (topic2_labels, topic2s) = parse_topic(&args.topic2, "topic2")?;
     let (topic3_labels, topic3s) = parse_topic(&args.topic3, "topic3")?;
 
     // set default blocks
     let block_numbers = if block_numbers.is_none() && transactions.is_none() {
-        Some(blocks::get_default_block_chunks(args, source, schemas).await?)
+        Some(blocks::get_default_block_chunks(args, head, schemas).await?)
     } else {
         block_numbers
     };
@@ -83,11 +97,17 @@ pub(crate) async fn parse_partitions(
     };
     let time_dimension = parse_time_dimension(&chunk);
 
-    let partition_by = match args.partition_by.clone() {
+    let mut partition_by = match args.partition_by.clone() {
         Some(dim_names) => {
             let dims: Result<Vec<_>, _> =
                 dim_names.into_iter().map(|x| Dim::from_str(&x)).collect();
-            dims?
+            let dims = dims?;
+            if slot_pairs.is_some() && !dims.contains(&Dim::Address) {
+                return Err(ParseError::ParseError(
+                    "--partition-by must include address when using --slots-file".to_string(),
+                ))
+            }
+            dims
         }
         None => {
             let multichunk_dims: Vec<Dim> = Dim::all_dims()
@@ -104,9 +124,18 @@ pub(crate) async fn parse_partitions(
             }
         }
     };
-    let mut partitions = chunk
-        .partition_with_labels(labels, partition_by.clone())
-        .map_err(|e| ParseError::ParseError(format!("could not partition labels ({})", e)))?;
+    let mut partitions = match slot_pairs {
+        Some(slot_pairs) => {
+            // each address gets its own files, so that slots are only paired with their address
+            if !partition_by.contains(&Dim::Address) {
+                partition_by.push(Dim::Address);
+            }
+            partition_slot_pairs(&chunk, labels, slot_pairs, &partition_by)?
+        }
+        None => chunk
+            .partition_with_labels(labels, partition_by.clone())
+            .map_err(|e| ParseError::ParseError(format!("could not partition labels ({})", e)))?,
+    };
 
     match args.chunk_order.as_deref() {
         None => {}
@@ -126,6 +155,12 @@ pub(crate) async fn parse_partitions(
     Ok((partitions, partition_by, time_dimension))
 }
 
+/// topic0 of --event-signature, used to filter logs when topic0 is not given explicitly
+fn get_event_signature_topic0(schemas: &HashMap<Datatype, Table>) -> Option<Vec<String>> {
+    let decoder = schemas.get(&Datatype::Logs)?.log_decoder.as_ref()?;
+    Some(vec![format!("0x{}", hex::encode(decoder.event.signature().as_bytes()))])
+}
+
 fn parse_time_dimension(partition: &Partition) -> TimeDimension {
     if partition.transactions.is_some() {
         TimeDimension::Transactions
@@ -142,15 +177,10 @@ fn parse_call_datas(
     let call_datas = match (call_datas, function, inputs) {
         (None, None, None) => return Ok(None),
         (Some(call_data), None, None) => hex_strings_to_binary(call_data)?,
-        (None, Some(function), None) => hex_strings_to_binary(function)?,
-        (None, Some(function), Some(inputs)) => {
+        (None, Some(function), inputs) => {
             let mut call_datas = Vec::new();
             for f in function.iter() {
-                for i in inputs.iter() {
-                    let mut call_data = hex_string_to_binary(f)?.clone();
-                    call_data.extend(hex_string_to_binary(i)?);
-                    call_datas.push(call_data);
-                }
+                call_datas.extend(parse_function_call_datas(f, inputs)?);
             }
             call_datas
         }
@@ -174,6 +204,45 @@ fn parse_call_datas(
     Ok(Some(vec![CallDataChunk::Values(call_datas)]))
 }
 
+/// call datas of a function, which is either a hex selector that each hex input is appended to,
+/// or a signature whose args are abi encoded from each comma-separated input
+fn parse_function_call_datas(
+    function: &String,
+    inputs: &Option<Vec<String>>,
+) -> Result<Vec<Vec<u8>>, ParseError> {
+    if !function.contains('(') {
+        let selector = hex_string_to_binary(function)?;
+        let inputs = match inputs {
+            Some(inputs) => inputs,
+            None => return Ok(vec![selector]),
+        };
+        return inputs
+            .iter()
+            .map(|input| {
+                let mut call_data = selector.clone();
+                call_data.extend(hex_string_to_binary(input)?);
+                Ok(call_data)
+            })
+            .collect();
+    }
+
+    let decoder = CallDecoder::new(function.clone()).map_err(ParseError::ParseError)?;
+    match (decoder.function.inputs.is_empty(), inputs) {
+        (true, _) => Ok(vec![decoder.encode_call_data(&[]).map_err(ParseError::ParseError)?]),
+        (false, Some(inputs)) => inputs
+            .iter()
+            .map(|input| {
+                let args: Vec<&str> = input.split(',').collect();
+                decoder.encode_call_data(&args).map_err(ParseError::ParseError)
+            })
+            .collect(),
+        (false, None) => Err(ParseError::ParseError(format!(
+            "function {} requires its args to be given with --inputs",
+            decoder.function.name
+        ))),
+    }
+}
+
 pub(crate) fn parse_transaction_chunks(
     input: &Option<Vec<String>>,
     default_column: &str,
@@ -194,6 +263,7 @@ pub(crate) fn parse_address_chunks(
 ) -> Result<(Option<ChunkLabels>, Option<Vec<AddressChunk>>), ParseError> {
     if let Some(input) = input {
         let parsed = parse_binary_arg(input, default_column)?;
+        validate_value_lengths(&parsed, 20, default_column)?;
         let labels: Vec<Option<String>> = parsed.keys().map(|x| x.clone().to_label()).collect();
         let chunks = parsed.values().map(|a| AddressChunk::Values(a.clone())).collect();
         Ok((Some(labels), Some(chunks)))
@@ -209,19 +279,219 @@ pub(crate) fn parse_slot_chunks(
     if let Some(input) = input {
         let parsed = parse_binary_arg(input, default_column)?;
         let labels: Vec<Option<String>> = parsed.keys().map(|x| x.clone().to_label()).collect();
-        let chunks = parsed.values().map(|a| SlotChunk::Values(a.clone())).collect();
+        let mut chunks = Vec::new();
+        for values in parsed.into_values() {
+            let n_values = values.len();
+            let mut seen = HashSet::new();
+            let slots: Vec<_> = values
+                .into_iter()
+                .map(pad_slot)
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .filter(|slot| seen.insert(slot.clone()))
+                .collect();
+            if slots.len() < n_values {
+                eprintln!("warning: skipping {} duplicate slots", n_values - slots.len());
+            }
+            chunks.push(SlotChunk::Values(slots));
+        }
         Ok((Some(labels), Some(chunks)))
     } else {
         Ok((None, None))
     }
 }
 
+/// address and slot pairs of --slots-file and of --address with --slot, without duplicates
+///
+/// returns None when --slots-file is not used, in which case addresses and slots are crossed
+fn parse_slot_pairs(args: &Args) -> Result<Option<Vec<(Vec<u8>, Vec<u8>)>>, ParseError> {
+    let path = match &args.slots_file {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+    let mut pairs = Vec::new();
+    match (&args.address, &args.slot) {
+        (Some(addresses), Some(slots)) => {
+            let addresses = parse_binary_arg(addresses, "address")?;
+            validate_value_lengths(&addresses, 20, "address")?;
+            let slots = parse_binary_arg(slots, "slot")?;
+            let slots =
+                slots.into_values().flatten().map(pad_slot).collect::<Result<Vec<_>, _>>()?;
+            for address in addresses.into_values().flatten() {
+                for slot in slots.iter() {
+                    pairs.push((address.clone(), slot.clone()));
+                }
+            }
+        }
+        (None, None) => {}
+        _ => {
+            let message = "--address and --slot must be used together when using --slots-file";
+            return Err(ParseError::ParseError(message.to_string()))
+        }
+    }
+    pairs.extend(read_slot_pairs_file(path)?);
+
+    let n_pairs = pairs.len();
+    let mut seen = HashSet::new();
+    let pairs: Vec<_> = pairs.into_iter().filter(|pair| seen.insert(pair.clone())).collect();
+    if pairs.len() < n_pairs {
+        eprintln!("warning: skipping {} duplicate address/slot pairs", n_pairs - pairs.len());
+    }
+    Ok(Some(pairs))
+}
+
+/// read one address,slot pair per line, skipping blank lines, # comments, and a header line
+fn read_slot_pairs_file(path: &str) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ParseError> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        ParseError::ParseError(format!("could not read --slots-file {}: {}", path, e))
+    })?;
+    let mut pairs = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.replace(' ', "") == "address,slot" {
+            continue
+        }
+        let pair = parse_slot_pair(line).map_err(|e| {
+            ParseError::ParseError(format!(
+                "could not parse line {} of {} ({}): {}",
+                index + 1,
+                path,
+                line,
+                e
+            ))
+        })?;
+        pairs.push(pair);
+    }
+    Ok(pairs)
+}
+
+/// args with the addresses of --addresses-file appended to --address, without duplicates
+///
+/// returns None when --addresses-file is not used
+pub(crate) fn add_addresses_file(args: &Args) -> Result<Option<Args>, ParseError> {
+    let path = match &args.addresses_file {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+    let file_addresses = read_addresses_file(path)?;
+    if file_addresses.is_empty() {
+        let message = format!("--addresses-file {} contains no addresses", path);
+        return Err(ParseError::ParseError(message))
+    }
+    let mut addresses = args.address.clone().unwrap_or_default();
+    for address in file_addresses.into_iter() {
+        if !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+    Ok(Some(Args { address: Some(addresses), addresses_file: None, ..args.clone() }))
+}
+
+/// read one address per line, skipping blank lines, # comments, and a header line
+fn read_addresses_file(path: &str) -> Result<Vec<String>, ParseError> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        ParseError::ParseError(format!("could not read --addresses-file {}: {}", path, e))
+    })?;
+    let mut addresses = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line == "address" {
+            continue
+        }
+        let address = line.strip_prefix("0x").unwrap_or(line);
+        match hex::decode(address) {
+            Ok(bytes) if bytes.len() == 20 => addresses.push(format!("0x{}", address)),
+            _ => {
+                return Err(ParseError::ParseError(format!(
+                    "could not parse line {} of {} ({}): expected 20 byte hex address",
+                    index + 1,
+                    path,
+                    line
+                )))
+            }
+        }
+    }
+    Ok(addresses)
+}
+
+fn parse_slot_pair(line: &str) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let fields: Vec<&str> = line.split(',').map(|field| field.trim()).collect();
+    if fields.len() != 2 {
+        return Err(format!("expected address,slot but got {} fields", fields.len()))
+    }
+    let address = hex::decode(fields[0].strip_prefix("0x").unwrap_or(fields[0]))
+        .map_err(|_| "could not parse address as hex".to_string())?;
+    if address.len() != 20 {
+        return Err(format!("expected 20 byte address but got {} bytes", address.len()))
+    }
+    let slot = fields[1].strip_prefix("0x").unwrap_or(fields[1]);
+    let slot = if slot.len() % 2 == 1 { format!("0{}", slot) } else { slot.to_string() };
+    let slot = hex::decode(slot).map_err(|_| "could not parse slot as hex".to_string())?;
+    let slot = pad_slot(slot).map_err(|e| e.to_string())?;
+    Ok((address, slot))
+}
+
+/// left pad slot to 32 bytes, so that short slots like 0x00 can be used
+fn pad_slot(slot: Vec<u8>) -> Result<Vec<u8>, ParseError> {
+    if slot.len() > 32 {
+        return Err(ParseError::ParseError(format!(
+            "invalid slot, expected at most 32 bytes but got {}: 0x{}",
+            slot.len(),
+            hex::encode(&slot)
+        )))
+    }
+    let mut padded = vec![0u8; 32 - slot.len()];
+    padded.extend(slot);
+    Ok(padded)
+}
+
+/// partition each address separately with only the slots paired with that address
+fn partition_slot_pairs(
+    chunk: &Partition,
+    labels: PartitionLabels,
+    slot_pairs: Vec<(Vec<u8>, Vec<u8>)>,
+    partition_by: &[Dim],
+) -> Result<Vec<Partition>, ParseError> {
+    let mut address_slots: Vec<(Vec<u8>, Vec<Vec<u8>>)> = Vec::new();
+    let mut address_indices: HashMap<Vec<u8>, usize> = HashMap::new();
+    for (address, slot) in slot_pairs.into_iter() {
+        match address_indices.get(&address) {
+            Some(index) => address_slots[*index].1.push(slot),
+            None => {
+                address_indices.insert(address.clone(), address_slots.len());
+                address_slots.push((address, vec![slot]));
+            }
+        }
+    }
+
+    let mut partitions = Vec::new();
+    for (address, slots) in address_slots.into_iter() {
+        let address_label = Some(format!("0x{}", hex::encode(&address)));
+        let address_chunk = Partition {
+            addresses: Some(vec![AddressChunk::Values(vec![address])]),
+            slots: Some(vec![SlotChunk::Values(slots)]),
+            ..chunk.clone()
+        };
+        let address_labels = PartitionLabels {
+            address_labels: Some(vec![address_label]),
+            slot_labels: None,
+            ..labels.clone()
+        };
+        let address_partitions = address_chunk
+            .partition_with_labels(address_labels, partition_by.to_vec())
+            .map_err(|e| ParseError::ParseError(format!("could not partition labels ({})", e)))?;
+        partitions.extend(address_partitions);
+    }
+    Ok(partitions)
+}
+
 fn parse_topic(
     input: &Option<Vec<String>>,
     default_column: &str,
 ) -> Result<(Option<ChunkLabels>, Option<Vec<TopicChunk>>), ParseError> {
     if let Some(input) = input {
         let parsed = parse_binary_arg(input, default_column)?;
+        validate_value_lengths(&parsed, 32, default_column)?;
         let labels: Vec<Option<String>> = parsed.keys().map(|x| x.clone().to_label()).collect();
         let chunks = parsed.values().map(|a| TopicChunk::Values(a.clone())).collect();
         Ok((Some(labels), Some(chunks)))
@@ -229,3 +499,115 @@ fn parse_topic(
         Ok((None, None))
     }
 }
+
+/// check that every parsed value has the expected number of bytes
+fn validate_value_lengths(
+    parsed: &ParsedBinaryArg,
+    n_bytes: usize,
+    name: &str,
+) -> Result<(), ParseError> {
+    for value in parsed.values().flatten() {
+        if value.len() != n_bytes {
+            return Err(ParseError::ParseError(format!(
+                "invalid {}, expected {} bytes but got {}: 0x{}",
+                name,
+                n_bytes,
+                value.len(),
+                hex::encode(value)
+            )))
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_slot_pairs_file() {
+        let path = std::env::temp_dir().join(format!("cryo_slots_{}.csv", std::process::id()));
+        let address = format!("0x{}", "ab".repeat(20));
+        let contents = format!("address,slot\n{},0x0\n\n# comment\n{},0x1\n", address, address);
+        std::fs::write(&path, contents).unwrap();
+        let pairs = read_slot_pairs_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0].0, vec![0xab; 20]);
+        assert_eq!(pairs[1].1, [vec![0; 31], vec![1]].concat());
+
+        std::fs::write(&path, format!("{},0x0\n{}\n", address, address)).unwrap();
+        let message = read_slot_pairs_file(path.to_str().unwrap()).unwrap_err().to_string();
+        assert!(message.contains("could not parse line 2"), "{}", message);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_validate_value_lengths() {
+        let inputs = |values: &[String]| Some(values.to_vec());
+        let address = "ab".repeat(20);
+        for valid in [address.clone(), format!("0x{}", address)] {
+            assert!(
+                parse_address_chunks(&inputs(&[valid.clone()]), "address").is_ok(),
+                "{}",
+                valid
+            );
+        }
+        for invalid in ["ab".repeat(19), format!("0x{}", "ab".repeat(21)), "0x".to_string()] {
+            let message =
+                parse_address_chunks(&inputs(&[address.clone(), invalid.clone()]), "address")
+                    .map(|_| ())
+                    .unwrap_err()
+                    .to_string();
+            assert!(message.contains("invalid address, expected 20 bytes"), "{}", message);
+        }
+
+        let topic = "cd".repeat(32);
+        for valid in [topic.clone(), format!("0x{}", topic)] {
+            assert!(parse_topic(&inputs(&[valid.clone()]), "topic0").is_ok(), "{}", valid);
+        }
+        for invalid in [format!("0x{}", "cd".repeat(31)), address.clone(), "cd".repeat(33)] {
+            let message = parse_topic(&inputs(&[invalid.clone()]), "topic0")
+                .map(|_| ())
+                .unwrap_err()
+                .to_string();
+            assert!(message.contains("invalid topic0, expected 32 bytes"), "{}", message);
+        }
+    }
+
+    #[test]
+    fn test_read_addresses_file() {
+        let path = std::env::temp_dir().join(format!("cryo_addresses_{}.txt", std::process::id()));
+        let (a, b) = ("ab".repeat(20), "CD".repeat(20));
+        std::fs::write(&path, format!("address\n0x{}\n\n# comment\n  {}  \n", a, b)).unwrap();
+        let addresses = read_addresses_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(addresses, vec![format!("0x{}", a), format!("0x{}", b)]);
+
+        std::fs::write(&path, format!("0x{}\n0x1234\n", a)).unwrap();
+        let message = read_addresses_file(path.to_str().unwrap()).unwrap_err().to_string();
+        assert!(message.contains("could not parse line 2"), "{}", message);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_function_call_datas() {
+        let inputs = Some(vec!["0x01".to_string(), "0x02".to_string()]);
+        let call_datas = parse_function_call_datas(&"0x18160ddd".to_string(), &inputs).unwrap();
+        assert_eq!(
+            call_datas,
+            vec![vec![0x18, 0x16, 0x0d, 0xdd, 1], vec![0x18, 0x16, 0x0d, 0xdd, 2]]
+        );
+
+        let function = "totalSupply()(uint256)".to_string();
+        let call_datas = parse_function_call_datas(&function, &inputs).unwrap();
+        assert_eq!(call_datas, vec![vec![0x18, 0x16, 0x0d, 0xdd]]);
+
+        let function = "allowance(address,address)(uint256)".to_string();
+        let owner = "0x00000000000000000000000000000000000000aa";
+        let inputs = Some(vec![format!("{},{}", owner, owner)]);
+        let call_datas = parse_function_call_datas(&function, &inputs).unwrap();
+        assert_eq!(call_datas[0].len(), 68);
+        assert!(parse_function_call_datas(&function, &None).is_err());
+        let inputs = Some(vec![owner.to_string()]);
+        assert!(parse_function_call_datas(&function, &inputs).is_err());
+    }
+}
@@ -1,21 +1,38 @@
-use super::{parse_schemas, partitions};
+use super::{blocks::ChainHead, deployments, parse_schemas, partitions};
 use crate::args::Args;
-use cryo_freeze::{Dim, ParseError, Query, QueryLabels, Schemas, Source};
+use cryo_freeze::{Datatype, Dim, ParseError, Query, QueryLabels, Schemas, Source};
 use std::sync::Arc;
 
+/// datatypes whose rows of failed transactions are dropped by --exclude-failed
+const EXCLUDE_FAILED_DATATYPES: [Datatype; 4] =
+    [Datatype::Transactions, Datatype::Traces, Datatype::Contracts, Datatype::NativeTransfers];
+
 /// parse Query struct from cli Args
 pub async fn parse_query(args: &Args, source: Arc<Source>) -> Result<Query, ParseError> {
     let (datatypes, schemas) = parse_schemas(args)?;
 
+    let file_args = partitions::add_addresses_file(args)?;
+    let args = file_args.as_ref().unwrap_or(args);
     let arg_aliases = find_arg_aliases(args, &schemas);
     let new_args =
         if !arg_aliases.is_empty() { Some(apply_arg_aliases(args, arg_aliases)?) } else { None };
     let args = new_args.as_ref().unwrap_or(args);
+    ensure_required_args(args, &schemas)?;
+    ensure_exclude_failed_applies(args, &schemas)?;
 
+    let head = ChainHead::new(source.clone());
     let (partitions, partitioned_by, time_dimension) =
-        partitions::parse_partitions(args, source, &schemas).await?;
+        partitions::parse_partitions(args, &head, &schemas).await?;
+    let schema_datatypes: Vec<_> = schemas.keys().cloned().collect();
+    let (partitions, chunks_before_deployment) =
+        deployments::skip_before_deployment(args, partitions, &schema_datatypes, &source).await?;
     let datatypes = cryo_freeze::cluster_datatypes(datatypes);
-    let labels = QueryLabels { align: args.align, reorg_buffer: args.reorg_buffer };
+    let labels = QueryLabels {
+        align: args.align,
+        reorg_buffer: args.reorg_buffer,
+        chunks_before_deployment,
+        block_tags: head.resolved_tags(),
+    };
     Ok(Query {
         datatypes,
         schemas,
@@ -23,11 +40,76 @@ pub async fn parse_query(args: &Args, source: Arc<Source>) -> Result<Query, Pars
         partitions,
         partitioned_by,
         exclude_failed: args.exclude_failed,
+        estimate_gas_price: args.estimate_gas_price,
         js_tracer: args.js_tracer.clone(),
         labels,
+        skipped_rows: Default::default(),
+        duplicate_rows: Default::default(),
+        allow_missing_blocks: args.allow_missing_blocks,
+        missing_blocks: Default::default(),
+        degraded_datatypes: Default::default(),
+        request_progress: Default::default(),
+        block_transaction_counts: Default::default(),
+        block_timestamps: Default::default(),
     })
 }
 
+/// check that each datatype's required arguments are given, before any rpc requests are made
+fn ensure_required_args(args: &Args, schemas: &Schemas) -> Result<(), ParseError> {
+    let mut datatypes: Vec<_> = schemas.keys().collect();
+    datatypes.sort_by_key(|datatype| datatype.name());
+    for datatype in datatypes.into_iter() {
+        let missing: Vec<_> = datatype
+            .required_parameters()
+            .iter()
+            .filter(|dim| match dim {
+                Dim::CallData => args.call_data.is_none() && args.function.is_none(),
+                dim => args.dim_is_none(dim),
+            })
+            .map(dim_flag)
+            .collect();
+        if !missing.is_empty() {
+            return Err(ParseError::ParseError(format!(
+                "{} requires {}",
+                datatype.name(),
+                missing.join(" and ")
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// check that --exclude-failed is only used with datatypes whose rows it can drop
+fn ensure_exclude_failed_applies(args: &Args, schemas: &Schemas) -> Result<(), ParseError> {
+    if !args.exclude_failed {
+        return Ok(())
+    }
+    let mut unsupported: Vec<_> = schemas
+        .keys()
+        .filter(|datatype| !EXCLUDE_FAILED_DATATYPES.contains(*datatype))
+        .map(|datatype| datatype.name())
+        .collect();
+    unsupported.sort();
+    match unsupported.is_empty() {
+        true => Ok(()),
+        false => Err(ParseError::ParseError(format!(
+            "--exclude-failed-transactions only applies to {}, and cannot be used with {}",
+            EXCLUDE_FAILED_DATATYPES.iter().map(|x| x.name()).collect::<Vec<_>>().join(", "),
+            unsupported.join(", ")
+        ))),
+    }
+}
+
+/// cli flag that specifies values of dimension
+fn dim_flag(dim: &Dim) -> String {
+    match dim {
+        Dim::BlockNumber => "--blocks".to_string(),
+        Dim::TransactionHash => "--txs".to_string(),
+        Dim::CallData => "--call-data or --function".to_string(),
+        dim => format!("--{}", dim.to_string().replace('_', "-")),
+    }
+}
+
 fn find_arg_aliases(args: &Args, schemas: &Schemas) -> Vec<(Dim, Dim)> {
     // does not currently handle optional args, just required args
     let mut swaps = Vec::new();
@@ -59,14 +141,20 @@ trait DimIsNone {
 impl DimIsNone for Args {
     fn dim_is_some(&self, dim: &Dim) -> bool {
         match dim {
-            Dim::BlockNumber => self.blocks.is_some(),
+            Dim::BlockNumber => {
+                self.blocks.is_some() ||
+                    self.blocks_from_file.is_some() ||
+                    self.start_block.is_some() ||
+                    self.start_block_exclusive.is_some() ||
+                    self.end_block.is_some()
+            }
             Dim::TransactionHash => self.txs.is_some(),
-            Dim::Address => self.address.is_some(),
+            Dim::Address => self.address.is_some() || self.slots_file.is_some(),
             Dim::FromAddress => self.from_address.is_some(),
             Dim::ToAddress => self.to_address.is_some(),
             Dim::Contract => self.contract.is_some(),
             Dim::CallData => self.call_data.is_some(),
-            Dim::Slot => self.slot.is_some(),
+            Dim::Slot => self.slot.is_some() || self.slots_file.is_some(),
             Dim::Topic0 => self.topic0.is_some(),
             Dim::Topic1 => self.topic1.is_some(),
             Dim::Topic2 => self.topic2.is_some(),
@@ -106,3 +194,40 @@ fn apply_arg_aliases(args: &Args, arg_aliases: Vec<(Dim, Dim)>) -> Result<Args,
     }
     Ok(args)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap_cryo::Parser;
+
+    fn required_args_error(command: &str) -> Option<String> {
+        let args = Args::parse_from(command.split_whitespace());
+        let (_, schemas) = parse_schemas(&args).unwrap();
+        ensure_required_args(&args, &schemas).err().map(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_ensure_required_args() {
+        let message = required_args_error("cryo balances -b 100:200").unwrap();
+        assert!(message.contains("balances requires --address"), "{}", message);
+        assert!(required_args_error("cryo balances -b 100:200:10 --address 0x00").is_none());
+        let message = required_args_error("cryo eth_calls --contract 0x00").unwrap();
+        assert!(message.contains("eth_calls requires --call-data or --function"), "{}", message);
+        assert!(required_args_error("cryo eth_calls --contract 0x00 --function 0x01").is_none());
+        assert!(required_args_error("cryo blocks").is_none());
+    }
+
+    #[test]
+    fn test_ensure_exclude_failed_applies() {
+        let error = |command: &str| {
+            let args = Args::parse_from(command.split_whitespace());
+            let (_, schemas) = parse_schemas(&args).unwrap();
+            ensure_exclude_failed_applies(&args, &schemas).err().map(|e| e.to_string())
+        };
+        assert!(error("cryo txs --exclude-failed-transactions").is_none());
+        assert!(error("cryo txs traces --exclude-failed").is_none());
+        assert!(error("cryo blocks logs").is_none());
+        let message = error("cryo blocks logs txs --exclude-failed-transactions").unwrap();
+        assert!(message.contains("cannot be used with blocks, logs"), "{}", message);
+    }
+}
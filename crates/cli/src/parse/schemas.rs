@@ -1,7 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use cryo_freeze::{
-    ColumnEncoding, Datatype, FileFormat, LogDecoder, MultiDatatype, ParseError, Table,
+    CallDecoder, ColumnEncoding, ColumnType, Datatype, FileFormat, LogDecoder, MultiDatatype,
+    OverflowPolicy, ParseError, Table,
 };
 
 use super::file_output;
@@ -13,15 +14,31 @@ fn parse_datatypes(raw_inputs: &Vec<String>) -> Result<Vec<Datatype>, ParseError
     let mut datatypes = Vec::new();
 
     'outer: for raw_input in raw_inputs {
+        if raw_input == "all" {
+            for datatype in Datatype::all_without_parameters() {
+                if !datatypes.contains(&datatype) {
+                    datatypes.push(datatype)
+                }
+            }
+            continue
+        }
         for multi_datatype in MultiDatatype::variants().iter() {
             if raw_input.as_str() == multi_datatype.name() {
                 for datatype in multi_datatype.datatypes() {
                     datatypes.push(datatype)
                 }
-                continue 'outer
+                continue 'outer;
             }
         }
-        datatypes.push(Datatype::from_str(raw_input)?);
+        let datatype = Datatype::from_str(raw_input).map_err(|e| {
+            let groups: Vec<_> = MultiDatatype::variants().iter().map(|x| x.name()).collect();
+            ParseError::ParseError(format!(
+                "{}, valid dataset groups are: all, {}",
+                e.to_string().trim_start_matches("Parsing error: "),
+                groups.join(", ")
+            ))
+        })?;
+        datatypes.push(datatype);
     }
     Ok(datatypes)
 }
@@ -31,12 +48,50 @@ pub(crate) fn parse_schemas(
 ) -> Result<(Vec<Datatype>, HashMap<Datatype, Table>), ParseError> {
     // parse inputs
     let datatypes = parse_datatypes(&args.datatype)?;
-    let sort = parse_sort_columns(&args.sort, &datatypes)?;
+    let sort = match (args.no_sort, &args.sort) {
+        (true, Some(_)) => {
+            return Err(ParseError::ParseError("--no-sort cannot be used with --sort".to_string()))
+        }
+        (true, None) => datatypes.iter().map(|datatype| (*datatype, None)).collect(),
+        (false, raw_sort) => parse_sort_columns(raw_sort, &datatypes)?,
+    };
+    if args.sort_desc && sort.values().all(|columns| columns.is_none()) {
+        return Err(ParseError::ParseError(
+            "--sort-desc requires sorting to be enabled".to_string(),
+        ))
+    }
     let u256_types = parse_u256_types(args)?;
+
+    // columns of u256 representations, e.g. value_f64, select or exclude only that representation
+    let (columns, selected_u256_columns) =
+        split_u256_column_names(&args.columns, &datatypes, &u256_types, true)?;
+    let (include_columns, _) =
+        split_u256_column_names(&args.include_columns, &datatypes, &u256_types, true)?;
+    let (exclude_columns, excluded_u256_columns) =
+        split_u256_column_names(&args.exclude_columns, &datatypes, &u256_types, false)?;
+    let overflow_policy = parse_overflow_policy(args)?;
+    let fee_percentiles = parse_fee_percentiles(args, &datatypes)?;
     let output_format = file_output::parse_output_format(args)?;
-    let binary_column_format = match args.hex | (output_format != FileFormat::Parquet) {
-        true => ColumnEncoding::Hex,
-        false => ColumnEncoding::Binary,
+    let binary_column_format =
+        match args.hex | !matches!(output_format, FileFormat::Parquet | FileFormat::Delta) {
+            true => ColumnEncoding::Hex,
+            false => ColumnEncoding::Binary,
+        };
+    if matches!(output_format, FileFormat::Json | FileFormat::Jsonl) && !args.hex {
+        eprintln!("warning: raw bytes are not valid json, encoding binary columns as hex");
+    }
+    for datatype in datatypes.iter() {
+        if let Some(warning) = datatype.format_warning(&output_format) {
+            if args.strict_formats {
+                return Err(ParseError::ParseError(warning))
+            }
+            eprintln!("warning: {}", warning);
+        }
+    }
+
+    // make sure that selected columns exist, since each datatype must have all of them
+    if let Some(columns) = &columns {
+        ensure_selected_columns(columns, &datatypes)?
     };
 
     let log_decoder = match args.event_signature {
@@ -47,6 +102,18 @@ pub(crate) fn parse_schemas(
         None => None,
     };
 
+    let call_decoders = parse_call_decoders(args, &datatypes)?;
+
+    // split columns prefixed by a datatype from columns that apply to every datatype
+    let (include_columns, shared_include_columns) =
+        split_columns_by_datatype(&include_columns, &datatypes, true)?;
+    let (exclude_columns, shared_exclude_columns) =
+        split_columns_by_datatype(&exclude_columns, &datatypes, false)?;
+    let include_columns = match args.include_block_timestamp {
+        true => include_block_timestamp(include_columns, &datatypes)?,
+        false => include_columns,
+    };
+
     // create schemas
     let schemas: Result<HashMap<Datatype, Table>, ParseError> = datatypes
         .iter()
@@ -55,13 +122,25 @@ pub(crate) fn parse_schemas(
                 .table_schema(
                     &u256_types,
                     &binary_column_format,
-                    &args.include_columns,
-                    &args.exclude_columns,
-                    &args.columns,
+                    &include_columns[datatype],
+                    &exclude_columns[datatype],
+                    &columns,
                     sort[datatype].clone(),
                     log_decoder.clone(),
                 )
-                .map(|schema| (*datatype, schema))
+                .map(|mut schema| {
+                    schema.overflow_policy = overflow_policy;
+                    schema.sort_descending = args.sort_desc;
+                    if let (Datatype::FeeHistory, Some(fee_percentiles)) =
+                        (datatype, &fee_percentiles)
+                    {
+                        schema.set_fee_percentiles(fee_percentiles);
+                    }
+                    if *datatype == Datatype::EthCalls {
+                        schema.set_call_decoders(&call_decoders);
+                    }
+                    (*datatype, schema)
+                })
                 .map_err(|e| {
                     ParseError::ParseError(format!(
                         "Failed to get schema for datatype: {:?}, {:?}",
@@ -72,16 +151,36 @@ pub(crate) fn parse_schemas(
         .collect();
 
     // make sure all included columns ended up in at least one schema
-    if let (Ok(schemas), Some(include_columns)) = (&schemas, &args.include_columns) {
-        ensure_included_columns(include_columns, schemas)?
+    if let Ok(schemas) = &schemas {
+        ensure_included_columns(&shared_include_columns, schemas)?
     };
 
     // make sure all excluded columns are excluded from at least one schema
-    if let (Ok(schemas), Some(exclude_columns)) = (&schemas, &args.exclude_columns) {
-        ensure_excluded_columns(exclude_columns, schemas)?
+    if let Ok(schemas) = &schemas {
+        ensure_excluded_columns(&shared_exclude_columns, schemas)?
     };
 
-    Ok((datatypes, schemas?))
+    // sort by columns that are in the schema
+    let mut schemas = schemas?;
+    for schema in schemas.values_mut() {
+        schema.excluded_u256_columns =
+            get_excluded_u256_columns(schema, &excluded_u256_columns, &selected_u256_columns);
+    }
+    for schema in schemas.values_mut() {
+        fit_sort_columns(schema, args.sort.is_some())?
+    }
+
+    if args.dedup {
+        for schema in schemas.values_mut() {
+            enable_dedup(schema)?
+        }
+    }
+
+    if let Some(overrides) = &args.column_type_override {
+        apply_column_type_overrides(overrides, &mut schemas)?
+    }
+
+    Ok((datatypes, schemas))
 }
 
 fn parse_u256_types(args: &Args) -> Result<Vec<U256Type>, ParseError> {
@@ -94,7 +193,8 @@ fn parse_u256_types(args: &Args) -> Result<Vec<U256Type>, ParseError> {
                     let lower_case = raw.to_lowercase();
                     match lower_case.as_str() {
                         "binary" => Ok(U256Type::Binary),
-                        "string" | "str" => Ok(U256Type::String),
+                        "string" | "str" | "decimal" => Ok(U256Type::String),
+                        "hex" => Ok(U256Type::Hex),
                         "f32" | "float32" => Ok(U256Type::F32),
                         "f64" | "float64" | "float" => Ok(U256Type::F64),
                         "u32" | "uint32" => Ok(U256Type::U32),
@@ -108,6 +208,258 @@ fn parse_u256_types(args: &Args) -> Result<Vec<U256Type>, ParseError> {
     )
 }
 
+fn parse_overflow_policy(args: &Args) -> Result<OverflowPolicy, ParseError> {
+    match args.overflow_policy.as_deref().map(|raw| raw.to_lowercase()).as_deref() {
+        None | Some("error") => Ok(OverflowPolicy::Error),
+        Some("saturate") => Ok(OverflowPolicy::Saturate),
+        Some("null") => Ok(OverflowPolicy::Null),
+        Some(raw) => Err(ParseError::ParseError(format!("invalid overflow policy: {}", raw))),
+    }
+}
+
+/// parse reward percentiles of fee_history, which nodes require to be increasing and within 0-100
+fn parse_fee_percentiles(
+    args: &Args,
+    datatypes: &[Datatype],
+) -> Result<Option<Vec<f64>>, ParseError> {
+    let percentiles = match &args.fee_percentiles {
+        Some(percentiles) => percentiles,
+        None => return Ok(None),
+    };
+    if !datatypes.contains(&Datatype::FeeHistory) {
+        return Err(ParseError::ParseError("--fee-percentiles requires fee_history".to_string()))
+    }
+    if percentiles.iter().any(|percentile| !(0.0..=100.0).contains(percentile)) {
+        return Err(ParseError::ParseError("fee percentiles must be between 0 and 100".to_string()))
+    }
+    if percentiles.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(ParseError::ParseError("fee percentiles must be increasing".to_string()))
+    }
+    Ok(Some(percentiles.clone()))
+}
+
+/// parse decoders of the --function signatures of eth_calls, functions given as hex selectors are
+/// not decoded
+fn parse_call_decoders(
+    args: &Args,
+    datatypes: &[Datatype],
+) -> Result<Vec<CallDecoder>, ParseError> {
+    let functions = match &args.function {
+        Some(functions) if datatypes.contains(&Datatype::EthCalls) => functions,
+        _ => return Ok(Vec::new()),
+    };
+    let base_columns = Datatype::EthCalls.column_types();
+    let mut decoders: Vec<CallDecoder> = Vec::new();
+    let mut output_columns = HashSet::new();
+    for function in functions.iter().filter(|function| function.contains('(')) {
+        let decoder = CallDecoder::new(function.clone()).map_err(ParseError::ParseError)?;
+        if decoders.iter().any(|other| other.selector() == decoder.selector()) {
+            continue
+        }
+        for (column, _) in decoder.output_columns() {
+            if base_columns.contains_key(column.as_str()) || !output_columns.insert(column.clone())
+            {
+                return Err(ParseError::ParseError(format!(
+                    "output column {} of function {} is used by another column",
+                    column, decoder.raw
+                )))
+            }
+        }
+        decoders.push(decoder);
+    }
+    Ok(decoders)
+}
+
+type ColumnsByDatatype = HashMap<Datatype, Option<Vec<String>>>;
+
+/// representations that u256 columns can be written as
+const U256_TYPES: [U256Type; 8] = [
+    U256Type::Binary,
+    U256Type::String,
+    U256Type::Hex,
+    U256Type::F32,
+    U256Type::F64,
+    U256Type::U32,
+    U256Type::U64,
+    U256Type::Decimal128,
+];
+
+/// output column of a u256 representation given in column arguments, e.g. value_f64
+struct U256ColumnName {
+    /// datatype that the name is prefixed with, if any
+    datatype: Option<Datatype>,
+    /// name of u256 column, e.g. value
+    column: String,
+    /// name of output column, e.g. value_f64
+    output_column: String,
+}
+
+/// split names of u256 representations from the other column names
+///
+/// with `keep_column`, each representation is replaced by its u256 column so that the column is
+/// selected, otherwise representations are only returned separately
+fn split_u256_column_names(
+    raw_columns: &Option<Vec<String>>,
+    datatypes: &[Datatype],
+    u256_types: &[U256Type],
+    keep_column: bool,
+) -> Result<(Option<Vec<String>>, Vec<U256ColumnName>), ParseError> {
+    let raw_columns = match raw_columns {
+        Some(raw_columns) => raw_columns,
+        None => return Ok((None, Vec::new())),
+    };
+    let mut columns = Vec::new();
+    let mut names = Vec::new();
+    for raw_column in raw_columns.iter() {
+        let (prefix, output_column) = match raw_column.split_once('.') {
+            Some((prefix, column)) => (Some(prefix), column),
+            None => (None, raw_column.as_str()),
+        };
+        let datatype = prefix.and_then(|prefix| Datatype::from_str(prefix).ok());
+        let is_u256_column = |column: &str| {
+            datatypes
+                .iter()
+                .filter(|x| prefix.is_none() || Some(**x) == datatype)
+                .any(|x| x.column_types().get(column).copied() == Some(ColumnType::UInt256))
+        };
+        let u256_column = U256_TYPES.iter().find_map(|u256_type| {
+            let column = output_column.strip_suffix(u256_type.suffix().as_str())?;
+            is_u256_column(column).then_some((u256_type, column))
+        });
+        let (u256_type, column) = match u256_column {
+            Some(u256_column) => u256_column,
+            None => {
+                columns.push(raw_column.clone());
+                continue
+            }
+        };
+        if !u256_types.contains(u256_type) {
+            return Err(ParseError::ParseError(format!(
+                "column {} requires {} in --u256-types",
+                raw_column,
+                &u256_type.suffix()[1..]
+            )))
+        }
+        if keep_column {
+            match prefix {
+                Some(prefix) => columns.push(format!("{}.{}", prefix, column)),
+                None => columns.push(column.to_string()),
+            }
+        }
+        let output_column = output_column.to_string();
+        names.push(U256ColumnName { datatype, column: column.to_string(), output_column });
+    }
+    Ok((Some(columns), names))
+}
+
+/// output columns of u256 representations to leave out of schema
+///
+/// excluded representations are left out, and selecting representations of a column with
+/// --columns leaves out the other representations of the column
+fn get_excluded_u256_columns(
+    schema: &Table,
+    excluded: &[U256ColumnName],
+    selected: &[U256ColumnName],
+) -> Vec<String> {
+    let applies = |name: &&U256ColumnName| {
+        (name.datatype.is_none() || name.datatype == Some(schema.datatype)) &&
+            schema.column_type(&name.column) == Some(ColumnType::UInt256)
+    };
+    let mut columns: Vec<String> =
+        excluded.iter().filter(applies).map(|name| name.output_column.clone()).collect();
+    let selected: Vec<_> = selected.iter().filter(applies).collect();
+    for name in selected.iter() {
+        for u256_type in schema.u256_types.iter() {
+            let output_column = name.column.clone() + u256_type.suffix().as_str();
+            if !selected.iter().any(|other| other.output_column == output_column) &&
+                !columns.contains(&output_column)
+            {
+                columns.push(output_column)
+            }
+        }
+    }
+    columns
+}
+
+/// datatypes that can include the timestamp of the block of each row
+const BLOCK_TIMESTAMP_DATATYPES: [Datatype; 2] = [Datatype::Transactions, Datatype::Logs];
+
+/// add block_timestamp to the included columns of each datatype that supports it
+fn include_block_timestamp(
+    mut include_columns: ColumnsByDatatype,
+    datatypes: &[Datatype],
+) -> Result<ColumnsByDatatype, ParseError> {
+    if !datatypes.iter().any(|datatype| BLOCK_TIMESTAMP_DATATYPES.contains(datatype)) {
+        return Err(ParseError::ParseError(
+            "--include-block-timestamp requires transactions or logs".to_string(),
+        ))
+    }
+    for datatype in BLOCK_TIMESTAMP_DATATYPES.iter() {
+        if let Some(columns) = include_columns.get_mut(datatype) {
+            let columns = columns.get_or_insert_with(Vec::new);
+            if !columns.iter().any(|column| column == "all") {
+                columns.push("block_timestamp".to_string());
+            }
+        }
+    }
+    Ok(include_columns)
+}
+
+/// split columns given as `datatype.column` into the columns of each datatype
+///
+/// unprefixed columns apply to every datatype and are also returned separately, so that they can
+/// be checked against all schemas. prefixed columns must name a datatype of the run and one of its
+/// columns
+fn split_columns_by_datatype(
+    raw_columns: &Option<Vec<String>>,
+    datatypes: &[Datatype],
+    allow_all: bool,
+) -> Result<(ColumnsByDatatype, Vec<String>), ParseError> {
+    let mut columns: HashMap<Datatype, Vec<String>> = HashMap::new();
+    let mut shared = Vec::new();
+    for raw_column in raw_columns.iter().flatten() {
+        let (raw_datatype, column) = match raw_column.split_once('.') {
+            Some(pieces) => pieces,
+            None => {
+                shared.push(raw_column.clone());
+                continue
+            }
+        };
+        let datatype = match Datatype::from_str(raw_datatype) {
+            Ok(datatype) if datatypes.contains(&datatype) => datatype,
+            _ => {
+                let mut names: Vec<_> = datatypes.iter().map(|datatype| datatype.name()).collect();
+                names.sort();
+                return Err(ParseError::ParseError(format!(
+                    "invalid datatype prefix of column {}, datatypes of this run are: {}",
+                    raw_column,
+                    names.join(", ")
+                )))
+            }
+        };
+        let is_all = allow_all && column == "all";
+        if !is_all && !datatype.column_types().contains_key(column) {
+            return Err(ParseError::ParseError(format!(
+                "{} does not support column: {}\n{}",
+                datatype.name(),
+                column,
+                format_valid_columns([datatype].iter())
+            )))
+        }
+        columns.entry(datatype).or_default().push(column.to_string());
+    }
+
+    let by_datatype = datatypes
+        .iter()
+        .map(|datatype| {
+            let mut datatype_columns = shared.clone();
+            datatype_columns.extend(columns.remove(datatype).unwrap_or_default());
+            (*datatype, raw_columns.as_ref().map(|_| datatype_columns))
+        })
+        .collect();
+    Ok((by_datatype, shared))
+}
+
 fn ensure_included_columns(
     include_columns: &[String],
     schemas: &cryo_freeze::Schemas,
@@ -129,8 +481,9 @@ fn ensure_included_columns(
     }
     if !unknown_columns.is_empty() {
         return Err(ParseError::ParseError(format!(
-            "datatypes do not support these columns: {:?}",
-            unknown_columns
+            "datatypes do not support these columns: {:?}\n{}",
+            unknown_columns,
+            format_valid_columns(schemas.keys())
         )))
     }
     Ok(())
@@ -157,13 +510,166 @@ fn ensure_excluded_columns(
     }
     if !unknown_columns.is_empty() {
         return Err(ParseError::ParseError(format!(
-            "datatypes do not support these columns: {:?}",
-            unknown_columns
+            "datatypes do not support these columns: {:?}\n{}",
+            unknown_columns,
+            format_valid_columns(schemas.keys())
         )))
     }
     Ok(())
 }
 
+fn ensure_selected_columns(columns: &[String], datatypes: &[Datatype]) -> Result<(), ParseError> {
+    if columns.len() == 1 && columns[0] == "all" {
+        return Ok(())
+    }
+    for datatype in datatypes.iter() {
+        let column_types = datatype.column_types();
+        let unknown_columns: Vec<_> =
+            columns.iter().filter(|column| !column_types.contains_key(column.as_str())).collect();
+        if !unknown_columns.is_empty() {
+            return Err(ParseError::ParseError(format!(
+                "{} does not support these columns: {:?}\n{}",
+                datatype.name(),
+                unknown_columns,
+                format_valid_columns([datatype].into_iter())
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// list columns of each datatype, ordered by datatype name
+fn format_valid_columns<'a>(datatypes: impl Iterator<Item = &'a Datatype>) -> String {
+    let mut datatypes: Vec<_> = datatypes.collect();
+    datatypes.sort_by_key(|datatype| datatype.name());
+    datatypes
+        .iter()
+        .map(|datatype| {
+            let columns: Vec<_> = datatype.column_types().keys().copied().collect();
+            format!("valid columns of {}: {}", datatype.name(), columns.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// sort by the leading default sort columns that are in schema, since excluded columns cannot be
+/// sorted by
+///
+/// custom sort columns that are not in schema are an error instead
+fn fit_sort_columns(schema: &mut Table, custom_sort: bool) -> Result<(), ParseError> {
+    let sort_columns = match &schema.sort_columns {
+        Some(sort_columns) => sort_columns.clone(),
+        None => return Ok(()),
+    };
+    let missing: Vec<_> =
+        sort_columns.iter().filter(|column| !schema.has_column(column)).cloned().collect();
+    if missing.is_empty() {
+        return Ok(())
+    }
+    if custom_sort {
+        return Err(ParseError::ParseError(format!(
+            "cannot sort {} by columns that are not selected: {:?}",
+            schema.datatype.name(),
+            missing
+        )))
+    }
+    let present: Vec<_> =
+        sort_columns.into_iter().take_while(|column| schema.has_column(column)).collect();
+    schema.sort_columns = if present.is_empty() { None } else { Some(present) };
+    Ok(())
+}
+
+/// drop duplicate rows of schema before writing, which requires the columns of its natural key
+fn enable_dedup(schema: &mut Table) -> Result<(), ParseError> {
+    let missing: Vec<_> = schema
+        .datatype
+        .dedup_key()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|column| !schema.has_column(column))
+        .collect();
+    if !missing.is_empty() {
+        return Err(ParseError::ParseError(format!(
+            "cannot dedup {} without columns that are not selected: {:?}",
+            schema.datatype.name(),
+            missing
+        )))
+    }
+    schema.dedup = true;
+    Ok(())
+}
+
+/// cast columns given as column=type to type, in every schema that outputs the column
+fn apply_column_type_overrides(
+    raw_overrides: &[String],
+    schemas: &mut HashMap<Datatype, Table>,
+) -> Result<(), ParseError> {
+    for raw in raw_overrides.iter() {
+        let (column, raw_type) = raw.split_once('=').ok_or_else(|| {
+            ParseError::ParseError(format!(
+                "invalid column type override, use column=type: {}",
+                raw
+            ))
+        })?;
+        let column_type = parse_override_type(raw_type)?;
+        let mut in_a_schema = false;
+        for schema in schemas.values_mut() {
+            if output_columns(schema).iter().any(|output_column| output_column == column) {
+                schema.column_type_overrides.insert(column.to_string(), column_type);
+                in_a_schema = true;
+            }
+        }
+        if !in_a_schema {
+            let mut valid: Vec<_> = schemas
+                .values()
+                .map(|schema| {
+                    let columns = output_columns(schema).join(", ");
+                    format!("output columns of {}: {}", schema.datatype.name(), columns)
+                })
+                .collect();
+            valid.sort();
+            return Err(ParseError::ParseError(format!(
+                "cannot override type of column that is not output: {}\n{}",
+                column,
+                valid.join("\n")
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// names of the columns written for schema, where u256 columns have a column per u256 type
+fn output_columns(schema: &Table) -> Vec<String> {
+    schema
+        .columns()
+        .into_iter()
+        .flat_map(|column| match schema.column_type(column) {
+            Some(ColumnType::UInt256) => {
+                schema.u256_output_columns(column).into_iter().map(|(name, _)| name).collect()
+            }
+            _ => vec![column.to_string()],
+        })
+        .collect()
+}
+
+fn parse_override_type(raw: &str) -> Result<ColumnType, ParseError> {
+    match raw.to_lowercase().as_str() {
+        "bool" | "boolean" => Ok(ColumnType::Boolean),
+        "uint32" | "u32" => Ok(ColumnType::UInt32),
+        "uint64" | "u64" => Ok(ColumnType::UInt64),
+        "int32" | "i32" => Ok(ColumnType::Int32),
+        "int64" | "i64" => Ok(ColumnType::Int64),
+        "float32" | "f32" => Ok(ColumnType::Float32),
+        "float64" | "f64" => Ok(ColumnType::Float64),
+        "string" | "str" => Ok(ColumnType::String),
+        _ => Err(ParseError::ParseError(format!(
+            "unsupported column type: {}, valid types are bool, uint32, uint64, int32, int64, \
+             float32, float64, and string",
+            raw
+        ))),
+    }
+}
+
 fn parse_sort_columns(
     raw_sort: &Option<Vec<String>>,
     datatypes: &[Datatype],
@@ -192,3 +698,156 @@ fn parse_sort_columns(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap_cryo::Parser;
+
+    fn blocks_schema(exclude_columns: &[&str], sort: &[&str]) -> Table {
+        let exclude_columns = Some(exclude_columns.iter().map(|x| x.to_string()).collect());
+        let sort = Some(sort.iter().map(|x| x.to_string()).collect());
+        Datatype::Blocks
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &exclude_columns,
+                &None,
+                sort,
+                None,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fit_sort_columns() {
+        // excluded default sort column is dropped along with the columns after it
+        let mut schema = blocks_schema(&["timestamp"], &["block_number", "timestamp", "author"]);
+        fit_sort_columns(&mut schema, false).unwrap();
+        assert_eq!(schema.sort_columns, Some(vec!["block_number".to_string()]));
+
+        let mut schema = blocks_schema(&["block_number"], &["block_number"]);
+        fit_sort_columns(&mut schema, false).unwrap();
+        assert_eq!(schema.sort_columns, None);
+
+        let mut schema = blocks_schema(&["timestamp"], &["timestamp"]);
+        let message = fit_sort_columns(&mut schema, true).unwrap_err().to_string();
+        assert!(message.contains("cannot sort blocks"), "{}", message);
+    }
+
+    #[test]
+    fn test_enable_dedup() {
+        let mut schema = blocks_schema(&[], &["block_number"]);
+        enable_dedup(&mut schema).unwrap();
+        assert!(schema.dedup);
+
+        let mut schema = blocks_schema(&["block_number"], &["timestamp"]);
+        let message = enable_dedup(&mut schema).unwrap_err().to_string();
+        assert!(message.contains("cannot dedup blocks"), "{}", message);
+        assert!(!schema.dedup);
+    }
+
+    #[test]
+    fn test_apply_column_type_overrides() {
+        let mut schemas: HashMap<_, _> =
+            [(Datatype::Blocks, blocks_schema(&["timestamp"], &[]))].into_iter().collect();
+        let overrides = ["block_number=Int32".to_string(), "gas_used=f64".to_string()];
+        apply_column_type_overrides(&overrides, &mut schemas).unwrap();
+        let overrides = &schemas[&Datatype::Blocks].column_type_overrides;
+        assert_eq!(overrides.get("block_number"), Some(&ColumnType::Int32));
+        assert_eq!(overrides.get("gas_used"), Some(&ColumnType::Float64));
+
+        for (raw, expected) in [
+            ("timestamp=int64", "not output: timestamp"),
+            ("block_number=uint256", "unsupported column type: uint256"),
+            ("block_number", "use column=type"),
+        ] {
+            let message = apply_column_type_overrides(&[raw.to_string()], &mut schemas)
+                .unwrap_err()
+                .to_string();
+            assert!(message.contains(expected), "{}", message);
+        }
+    }
+
+    #[test]
+    fn test_split_columns_by_datatype() {
+        let datatypes = [Datatype::Blocks, Datatype::Transactions];
+        let raw: Vec<_> = ["chain_id", "blocks.author", "txs.gas_price", "transactions.value"]
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        let (columns, shared) = split_columns_by_datatype(&Some(raw), &datatypes, true).unwrap();
+        assert_eq!(shared, vec!["chain_id".to_string()]);
+        let blocks: Vec<_> = ["chain_id", "author"].iter().map(|x| x.to_string()).collect();
+        assert_eq!(columns[&Datatype::Blocks], Some(blocks));
+        let txs: Vec<_> =
+            ["chain_id", "gas_price", "value"].iter().map(|x| x.to_string()).collect();
+        assert_eq!(columns[&Datatype::Transactions], Some(txs));
+
+        let raw = Some(vec!["logs.topic0".to_string()]);
+        let message = split_columns_by_datatype(&raw, &datatypes, true).unwrap_err().to_string();
+        assert!(message.contains("datatypes of this run are: blocks, transactions"), "{}", message);
+        let raw = Some(vec!["blocks.gas_price".to_string()]);
+        let message = split_columns_by_datatype(&raw, &datatypes, true).unwrap_err().to_string();
+        assert!(message.contains("valid columns of blocks: "), "{}", message);
+        let raw = Some(vec!["blocks.all".to_string()]);
+        assert!(split_columns_by_datatype(&raw, &datatypes, false).is_err());
+        assert!(split_columns_by_datatype(&raw, &datatypes, true).is_ok());
+    }
+
+    #[test]
+    fn test_ensure_selected_columns() {
+        let columns = vec!["block_number".to_string(), "foo_bar".to_string()];
+        let message = ensure_selected_columns(&columns, &[Datatype::Blocks]).unwrap_err();
+        let message = message.to_string();
+        assert!(message.contains("blocks does not support these columns: [\"foo_bar\"]"));
+        assert!(message.contains("valid columns of blocks: "));
+        assert!(ensure_selected_columns(&columns[..1], &[Datatype::Blocks]).is_ok());
+        assert!(ensure_selected_columns(&["all".to_string()], &[Datatype::Blocks]).is_ok());
+    }
+
+    #[test]
+    fn test_u256_column_names() {
+        let value_columns = |command: &str| {
+            let args = Args::parse_from(command.split_whitespace());
+            parse_schemas(&args).map(|(_, schemas)| {
+                let schema = &schemas[&Datatype::Transactions];
+                let columns = schema.u256_output_columns("value").into_iter();
+                columns.map(|(name, _)| name).collect::<Vec<_>>()
+            })
+        };
+        let txs = "cryo txs --u256-format binary decimal f64";
+        assert_eq!(
+            value_columns(&format!("{} --exclude-columns value_f64", txs)).unwrap(),
+            vec!["value_binary", "value_string"]
+        );
+        assert_eq!(
+            value_columns(&format!("{} --columns block_number value_string", txs)).unwrap(),
+            vec!["value_string"]
+        );
+        assert_eq!(
+            value_columns(&format!("{} --exclude-columns transactions.value_binary", txs)).unwrap(),
+            vec!["value_string", "value_f64"]
+        );
+        let message =
+            value_columns(&format!("{} --exclude-columns value_hex", txs)).unwrap_err().to_string();
+        assert!(message.contains("column value_hex requires hex in --u256-types"), "{}", message);
+    }
+
+    #[test]
+    fn test_parse_call_decoders() {
+        let calls = "cryo calls --to 0x00 --function";
+        let command = format!("{} 0x18160ddd totalSupply()(uint256) owner()(address)", calls);
+        let args = Args::parse_from(command.split_whitespace());
+        let (_, schemas) = parse_schemas(&args).unwrap();
+        let schema = &schemas[&Datatype::EthCalls];
+        assert_eq!(schema.call_decoders.len(), 2);
+        assert!(schema.has_column("totalSupply") && schema.has_column("owner"));
+
+        let command = format!("{} totalSupply()(uint256) totalSupply(address)(uint256)", calls);
+        let args = Args::parse_from(command.split_whitespace());
+        let message = parse_schemas(&args).unwrap_err().to_string();
+        assert!(message.contains("output column totalSupply of function"), "{}", message);
+    }
+}
@@ -1,41 +1,40 @@
 use std::env;
 
 use crate::args::Args;
-use cryo_freeze::{sources::ProviderWrapper, ParseError, Source, SourceLabels};
+use cryo_freeze::{
+    mask_rpc_url, sources::ProviderWrapper, BatchHttp, BlockSource, HttpOptions, LogWindow,
+    ParseError, RetryPolicy, RpcCache, RpcPool, Source, SourceLabels,
+};
 use ethers::prelude::*;
 use governor::{Quota, RateLimiter};
 use polars::prelude::*;
-use std::num::NonZeroU32;
+use rand::Rng;
+use std::{num::NonZeroU32, path::Path, time::Duration};
 
 pub(crate) async fn parse_source(args: &Args) -> Result<Source, ParseError> {
-    // parse network info
-    let rpc_url = parse_rpc_url(args)?;
-    let (provider, chain_id): (ProviderWrapper, u64) = if rpc_url.starts_with("http") {
-        let provider = Provider::<RetryClient<Http>>::new_client(
-            &rpc_url,
-            args.max_retries,
-            args.initial_backoff,
-        )
-        .map_err(|_e| ParseError::ParseError("could not connect to provider".to_string()))?;
-        let chain_id = provider.get_chainid().await.map_err(ParseError::ProviderError)?.as_u64();
-        (provider.into(), chain_id)
-    } else if rpc_url.starts_with("ws") {
-        let provider = Provider::<Ws>::connect(&rpc_url).await.map_err(|_| {
-            ParseError::ParseError("could not instantiate HTTP Provider".to_string())
-        })?;
-        let chain_id = provider.get_chainid().await.map_err(ParseError::ProviderError)?.as_u64();
-        (provider.into(), chain_id)
-    } else if rpc_url.ends_with(".ipc") {
-        let provider: Provider<Ipc> = Provider::connect_ipc(&rpc_url).await.map_err(|_| {
-            ParseError::ParseError("could not instantiate HTTP Provider".to_string())
-        })?;
-        let chain_id = provider.get_chainid().await.map_err(ParseError::ProviderError)?.as_u64();
-        (provider.into(), chain_id)
-    } else {
-        return Err(ParseError::ParseError(format!("invalid rpc url: {}", rpc_url)));
+    // parse network info, requests are spread across endpoints if several are given
+    let rpc_urls = parse_rpc_urls(args)?;
+    let http_options = HttpOptions {
+        request_timeout: args.request_timeout_seconds,
+        connect_timeout: args.connect_timeout_seconds,
+        keepalive: args.http_keepalive,
     };
+    let mut providers = Vec::new();
+    for rpc_url in rpc_urls.iter() {
+        providers.push(connect_provider(rpc_url, args, &http_options).await?);
+    }
+    let (provider, chain_id) = providers.remove(0);
+    if let Some((_, other_chain_id)) = providers.iter().find(|(_, id)| *id != chain_id) {
+        return Err(ParseError::ParseError(format!(
+            "rpc endpoints are on different chains: {} and {}",
+            chain_id, other_chain_id
+        )))
+    }
+    let provider = RpcPool::new(provider, providers.into_iter().map(|(x, _)| x).collect());
+    let rpc_url = rpc_urls.join(",");
 
-    let rate_limiter = match args.requests_per_second {
+    let requests_per_second = parse_requests_per_second(args)?;
+    let rate_limiter = match requests_per_second {
         Some(rate_limit) => match (NonZeroU32::new(1), NonZeroU32::new(rate_limit)) {
             (Some(one), Some(value)) => {
                 let quota = Quota::per_second(value).allow_burst(one);
@@ -47,39 +46,304 @@ pub(crate) async fn parse_source(args: &Args) -> Result<Source, ParseError> {
     };
 
     // process concurrency info
-    let max_concurrent_requests = args.max_concurrent_requests.unwrap_or(100);
-    let max_concurrent_chunks = match args.max_concurrent_chunks {
-        Some(0) => None,
-        Some(max) => Some(max),
-        None => Some(4),
-    };
+    let (max_concurrent_requests, max_concurrent_chunks) = parse_concurrency(args);
 
     let semaphore = tokio::sync::Semaphore::new(max_concurrent_requests as usize);
     let semaphore = Arc::new(Some(semaphore));
 
+    let log_window = parse_log_window(args)?;
+    let block_source = parse_block_source(args)?;
+
     let output = Source {
         chain_id,
         inner_request_size: args.inner_request_size,
+        log_window,
+        block_source,
+        receipt_request_size: args.receipt_request_size,
         max_concurrent_chunks,
         semaphore,
         rate_limiter: rate_limiter.into(),
         rpc_url,
         provider,
         labels: SourceLabels {
-            max_concurrent_requests: args.requests_per_second.map(|x| x as u64),
-            max_requests_per_second: args.requests_per_second.map(|x| x as u64),
+            max_concurrent_requests: Some(max_concurrent_requests),
+            max_requests_per_second: requests_per_second.map(|x| x as u64),
             max_retries: Some(args.max_retries),
             initial_backoff: Some(args.initial_backoff),
+            http_options,
+        },
+        cancel: None,
+        retry_policy: RetryPolicy {
+            max_retries: args.max_retries,
+            initial_backoff: args.initial_backoff,
         },
+        cache: args.cache_dir.as_ref().map(|dir| {
+            let cache = RpcCache::new(Path::new(dir), chain_id);
+            match args.reorg_buffer {
+                0 => cache,
+                reorg_buffer => cache.with_reorg_buffer(reorg_buffer),
+            }
+        }),
+        request_stats: Default::default(),
     };
 
     Ok(output)
 }
 
-pub(crate) fn parse_rpc_url(args: &Args) -> Result<String, ParseError> {
+/// parse adaptive window of log requests, which starts at --inner-request-size blocks
+///
+/// without --max-log-request-size or --max-response-rows, log requests keep a fixed size
+fn parse_log_window(args: &Args) -> Result<Option<LogWindow>, ParseError> {
+    if args.max_log_request_size.is_none() && args.max_response_rows.is_none() {
+        return Ok(None)
+    }
+    let initial_size = args.inner_request_size;
+    let max_size = args.max_log_request_size.unwrap_or(initial_size);
+    if initial_size == 0 || max_size < initial_size {
+        return Err(ParseError::ParseError(
+            "--max-log-request-size must be at least --inner-request-size, which must be at least 1"
+                .to_string(),
+        ))
+    }
+    if args.max_response_rows == Some(0) {
+        return Err(ParseError::ParseError("--max-response-rows must be at least 1".to_string()))
+    }
+    Ok(Some(LogWindow::new(initial_size, max_size, args.max_response_rows)))
+}
+
+/// parse source of blocks and transactions, which are requested over rpc unless
+/// --firehose-endpoint is given
+fn parse_block_source(args: &Args) -> Result<Option<Arc<dyn BlockSource>>, ParseError> {
+    match &args.firehose_endpoint {
+        #[cfg(feature = "firehose")]
+        Some(endpoint) => Ok(Some(Arc::new(cryo_freeze::FirehoseBlockSource::new(endpoint)?))),
+        #[cfg(not(feature = "firehose"))]
+        Some(_) => Err(ParseError::ParseError(
+            "--firehose-endpoint requires cryo to be built with the firehose feature".to_string(),
+        )),
+        None => Ok(None),
+    }
+}
+
+/// parse limits of concurrent requests and concurrent chunks
+///
+/// each chunk needs at least one request in flight, so chunks are limited to the number of
+/// requests. a chunk limit of 0 means no limit
+fn parse_concurrency(args: &Args) -> (u64, Option<u64>) {
+    let max_concurrent_requests = match args.max_concurrent_requests {
+        Some(0) => {
+            eprintln!("warning: --max-concurrent-requests must be at least 1, using 1");
+            1
+        }
+        Some(max) => max,
+        None => 100,
+    };
+    let max_concurrent_chunks = match args.max_concurrent_chunks {
+        Some(0) => None,
+        Some(max) if max > max_concurrent_requests => {
+            eprintln!(
+                "warning: --max-concurrent-chunks {} exceeds --max-concurrent-requests {}, using {}",
+                max, max_concurrent_requests, max_concurrent_requests
+            );
+            Some(max_concurrent_requests)
+        }
+        Some(max) => Some(max),
+        None => Some(4.min(max_concurrent_requests)),
+    };
+    (max_concurrent_requests, max_concurrent_chunks)
+}
+
+/// sleep for a random duration so that simultaneously launched processes are staggered
+pub(crate) async fn startup_jitter(args: &Args) -> Result<(), ParseError> {
+    let raw = match &args.startup_jitter {
+        Some(raw) => raw.clone(),
+        None => match env::var("CRYO_STARTUP_JITTER") {
+            Ok(raw) => raw,
+            Err(_) => return Ok(()),
+        },
+    };
+    let (min, max) = parse_jitter_range(&raw)?;
+    let millis = rand::thread_rng().gen_range(min.as_millis()..=max.as_millis()) as u64;
+    if !args.no_verbose && args.log_format.as_deref() != Some("json") {
+        println!("sleeping {:.1}s for startup jitter", millis as f64 / 1000.0);
+    }
+    tokio::time::sleep(Duration::from_millis(millis)).await;
+    Ok(())
+}
+
+fn parse_jitter_range(raw: &str) -> Result<(Duration, Duration), ParseError> {
+    let message =
+        || ParseError::ParseError(format!("invalid --startup-jitter, use e.g. 0-120s: {}", raw));
+    let (min, max) = match raw.split_once('-') {
+        Some((min, max)) => (min.trim(), max.trim()),
+        None => ("0", raw.trim()),
+    };
+    let unit = max.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let min = parse_duration(min, unit).ok_or_else(message)?;
+    let max = parse_duration(max, unit).ok_or_else(message)?;
+    if min > max {
+        return Err(message())
+    }
+    Ok((min, max))
+}
+
+fn parse_duration(raw: &str, default_unit: &str) -> Option<Duration> {
+    let split = raw.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(raw.len());
+    let (number, unit) = raw.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let unit = if unit.is_empty() { default_unit } else { unit };
+    let seconds = match unit {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        _ => return None,
+    };
+    // durations that are negative, infinite, or too long are invalid rather than a panic
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// requests per second, after dividing among sibling processes using --rps-share
+fn parse_requests_per_second(args: &Args) -> Result<Option<u32>, ParseError> {
+    let requests_per_second =
+        match (args.requests_per_second, env::var("CRYO_REQUESTS_PER_SECOND")) {
+            (Some(value), _) => Some(value),
+            (None, Ok(value)) => Some(value.parse::<u32>().map_err(|_| {
+                ParseError::ParseError("invalid CRYO_REQUESTS_PER_SECOND".to_string())
+            })?),
+            (None, Err(_)) => None,
+        };
+    let rps_share = args.rps_share.clone().or_else(|| env::var("CRYO_RPS_SHARE").ok());
+    match (requests_per_second, rps_share) {
+        (requests_per_second, None) => Ok(requests_per_second),
+        (Some(requests_per_second), Some(share)) => {
+            let (n, m) = parse_rps_share(&share)?;
+            let value = (requests_per_second as u64 * n / m).max(1);
+            Ok(Some(value as u32))
+        }
+        (None, Some(_)) => {
+            Err(ParseError::ParseError("--rps-share requires --requests-per-second".to_string()))
+        }
+    }
+}
+
+fn parse_rps_share(raw: &str) -> Result<(u64, u64), ParseError> {
+    let message = || ParseError::ParseError(format!("invalid --rps-share, use N/M: {}", raw));
+    let (n, m) = raw.split_once('/').ok_or_else(message)?;
+    let n: u64 = n.trim().parse().map_err(|_| message())?;
+    let m: u64 = m.trim().parse().map_err(|_| message())?;
+    if n == 0 || m == 0 || n > m {
+        return Err(message())
+    }
+    Ok((n, m))
+}
+
+/// connect to rpc endpoint, returning provider and chain id
+///
+/// http requests that time out fail with a provider error, which is retried by the source's
+/// retry policy like other transient errors
+async fn connect_provider(
+    rpc_url: &str,
+    args: &Args,
+    http_options: &HttpOptions,
+) -> Result<(ProviderWrapper, u64), ParseError> {
+    let (provider, chain_id): (ProviderWrapper, u64) = if rpc_url.starts_with("http") &&
+        args.rpc_batch_size > 1
+    {
+        // concurrent requests are grouped into json-rpc batches by the transport
+        let client =
+            http_options.build_client().map_err(|e| connection_error("http", rpc_url, e))?;
+        let provider =
+            Provider::new(BatchHttp::new_with_client(rpc_url, args.rpc_batch_size, client));
+        let chain_id = provider
+            .get_chainid()
+            .await
+            .map_err(|e| connection_error("http", rpc_url, e))?
+            .as_u64();
+        (provider.into(), chain_id)
+    } else if rpc_url.starts_with("http") {
+        // failed requests are retried by the source's retry policy rather than by the client
+        let http =
+            http_options.build_http(rpc_url).map_err(|e| connection_error("http", rpc_url, e))?;
+        let policy = Box::new(HttpRateLimitRetryPolicy);
+        let provider = Provider::new(RetryClient::new(http, policy, 0, args.initial_backoff));
+        let chain_id = provider
+            .get_chainid()
+            .await
+            .map_err(|e| connection_error("http", rpc_url, e))?
+            .as_u64();
+        (provider.into(), chain_id)
+    } else if rpc_url.starts_with("ws://") || rpc_url.starts_with("wss://") {
+        // dropped connections are reestablished by the client rather than failing requests
+        let reconnects = args.max_retries as usize;
+        let ws = Ws::connect_with_reconnects(rpc_url, reconnects)
+            .await
+            .map_err(|e| connection_error("websocket", rpc_url, e))?;
+        let provider = Provider::new(ws);
+        let chain_id = provider
+            .get_chainid()
+            .await
+            .map_err(|e| connection_error("websocket", rpc_url, e))?
+            .as_u64();
+        (provider.into(), chain_id)
+    } else if rpc_url.ends_with(".ipc") {
+        // connecting to a missing socket path can hang, so check for it first
+        if !Path::new(rpc_url).exists() {
+            return Err(ParseError::ParseError(format!("ipc socket does not exist: {}", rpc_url)))
+        }
+        let provider: Provider<Ipc> = Provider::connect_ipc(rpc_url)
+            .await
+            .map_err(|e| connection_error("ipc", rpc_url, e))?;
+        let chain_id =
+            provider.get_chainid().await.map_err(|e| connection_error("ipc", rpc_url, e))?.as_u64();
+        (provider.into(), chain_id)
+    } else {
+        return Err(ParseError::ParseError(format!("invalid rpc url: {}", rpc_url)))
+    };
+    Ok((provider, chain_id))
+}
+
+/// error for failed connection, naming the transport that was attempted
+fn connection_error(transport: &str, rpc_url: &str, e: impl std::fmt::Display) -> ParseError {
+    let rpc_url = mask_rpc_url(rpc_url);
+    ParseError::ParseError(format!("could not connect to {} rpc at {}: {}", transport, rpc_url, e))
+}
+
+/// urls of rpc endpoints, given as a comma separated list to --rpc
+///
+/// each url of the list is resolved the same way as a single url. with --env-rpc the list is read
+/// from ETH_RPC_URL instead, so that it does not appear in shell history or process listings
+pub(crate) fn parse_rpc_urls(args: &Args) -> Result<Vec<String>, ParseError> {
+    let urls = match (&args.rpc, args.env_rpc) {
+        (Some(_), true) => {
+            let message = "--rpc and --env-rpc cannot be used together";
+            return Err(ParseError::ParseError(message.to_string()))
+        }
+        (None, true) => match env::var("ETH_RPC_URL") {
+            Ok(rpc) if !rpc.trim().is_empty() => split_rpc_urls(&rpc).map(add_url_scheme).collect(),
+            _ => {
+                let message = "--env-rpc requires ETH_RPC_URL to be set";
+                return Err(ParseError::ParseError(message.to_string()))
+            }
+        },
+        (Some(rpc), false) if rpc.contains(',') => split_rpc_urls(rpc)
+            .map(|url| parse_rpc_url(Some(url)))
+            .collect::<Result<Vec<_>, _>>()?,
+        (rpc, false) => vec![parse_rpc_url(rpc.as_deref())?],
+    };
+    if urls.is_empty() {
+        return Err(ParseError::ParseError("--rpc must contain at least one url".to_string()))
+    }
+    Ok(urls)
+}
+
+fn split_rpc_urls(rpc: &str) -> impl Iterator<Item = &str> {
+    rpc.split(',').map(|url| url.trim()).filter(|url| !url.is_empty())
+}
+
+fn parse_rpc_url(rpc: Option<&str>) -> Result<String, ParseError> {
     // get MESC url
     let mesc_url = if mesc::is_mesc_enabled() {
-        let endpoint = match &args.rpc {
+        let endpoint = match rpc {
             Some(url) => mesc::get_endpoint_by_query(url, Some("cryo")),
             None => mesc::get_default_endpoint(Some("cryo")),
         };
@@ -97,8 +361,8 @@ pub(crate) fn parse_rpc_url(args: &Args) -> Result<String, ParseError> {
     // use ETH_RPC_URL if no MESC url found
     let url = if let Some(url) = mesc_url {
         url
-    } else if let Some(url) = &args.rpc {
-        url.clone()
+    } else if let Some(url) = rpc {
+        url.to_string()
     } else if let Ok(url) = env::var("ETH_RPC_URL") {
         url
     } else {
@@ -106,10 +370,66 @@ pub(crate) fn parse_rpc_url(args: &Args) -> Result<String, ParseError> {
         return Err(ParseError::ParseError(message.to_string()))
     };
 
-    // prepend http or https if need be
+    Ok(add_url_scheme(&url))
+}
+
+/// prepend http if url has no scheme and is not an ipc path
+pub(crate) fn add_url_scheme(url: &str) -> String {
     if !url.starts_with("http") & !url.starts_with("ws") & !url.ends_with(".ipc") {
-        Ok("http://".to_string() + url.as_str())
+        "http://".to_string() + url
     } else {
-        Ok(url)
+        url.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap_cryo::Parser;
+
+    #[test]
+    fn test_parse_jitter_range() {
+        let secs = Duration::from_secs;
+        let millis = Duration::from_millis;
+        for (raw, expected) in [
+            ("0-120s", Some((secs(0), secs(120)))),
+            ("500ms-2s", Some((millis(500), secs(2)))),
+            ("1-3", Some((secs(1), secs(3)))),
+            ("2m", Some((secs(0), secs(120)))),
+            ("10-5s", None),
+            ("1h", None),
+            ("a-b", None),
+            ("1-99999999999999999999999m", None),
+        ] {
+            assert_eq!(parse_jitter_range(raw).ok(), expected, "{}", raw);
+        }
+    }
+
+    #[test]
+    fn test_parse_rps_share() {
+        for (raw, expected) in [
+            ("1/3", Some((1, 3))),
+            (" 2 / 3 ", Some((2, 3))),
+            ("3/3", Some((3, 3))),
+            ("0/3", None),
+            ("1/0", None),
+            ("4/3", None),
+            ("1", None),
+            ("a/b", None),
+        ] {
+            assert_eq!(parse_rps_share(raw).ok(), expected, "{}", raw);
+        }
+    }
+
+    #[test]
+    fn test_parse_requests_per_second() {
+        let rps = |command: &str| {
+            parse_requests_per_second(&Args::parse_from(command.split_whitespace())).ok()
+        };
+        assert_eq!(rps("cryo blocks --requests-per-second 90"), Some(Some(90)));
+        assert_eq!(rps("cryo blocks --requests-per-second 90 --rps-share 1/3"), Some(Some(30)));
+        // shares of small limits keep at least one request per second
+        assert_eq!(rps("cryo blocks --requests-per-second 2 --rps-share 1/3"), Some(Some(1)));
+        assert_eq!(rps("cryo blocks --requests-per-second 90 --rps-share 0/3"), None);
     }
 }
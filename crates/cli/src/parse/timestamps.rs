@@ -2,7 +2,7 @@ use cryo_freeze::{BlockChunk, ParseError, Source};
 use polars::prelude::*;
 
 use crate::{
-    parse::blocks::{block_range_to_block_chunk, postprocess_block_chunks},
+    parse::blocks::{block_range_to_block_chunk, postprocess_block_chunks, ChainHead},
     Args,
 };
 
@@ -11,6 +11,7 @@ use super::blocks::get_latest_block_number;
 pub(crate) async fn parse_timestamps(
     args: &Args,
     source: Arc<Source>,
+    head: &ChainHead,
 ) -> Result<(Option<Vec<Option<String>>>, Option<Vec<BlockChunk>>), ParseError> {
     let (files, explicit_numbers): (Vec<&String>, Vec<&String>) = match &args.timestamps {
         Some(timestamp) => timestamp.iter().partition(|tx| std::path::Path::new(tx).exists()),
@@ -50,7 +51,7 @@ pub(crate) async fn parse_timestamps(
             let outputs = parse_timestamp_inputs(explicit_number, source.clone()).await?;
             block_chunks.extend(outputs);
         }
-        postprocess_block_chunks(block_chunks, args, source).await?
+        postprocess_block_chunks(block_chunks, args, head).await?
     } else {
         Vec::new()
     };
@@ -133,7 +134,7 @@ enum RangePosition {
     None,
 }
 
-async fn parse_timestamp_token(
+pub(crate) async fn parse_timestamp_token(
     s: &str,
     as_range: bool,
     source: Arc<Source>,
@@ -145,7 +146,7 @@ async fn parse_timestamp_token(
         [timestamp_ref] => {
             let timestamp =
                 parse_timestamp_number(timestamp_ref, RangePosition::None, source.clone()).await?;
-            let block = timestamp_to_block_number(timestamp, source).await?;
+            let block = timestamp_to_block_number(timestamp, Rounding::Floor, source).await?;
 
             Ok(BlockChunk::Numbers(vec![block]))
         }
@@ -163,8 +164,8 @@ async fn parse_timestamp_token(
             let (start_timestamp, end_timestamp) =
                 parse_timestamp_range(first_ref, second_ref, source.clone()).await?;
             let (start_block, end_block) = (
-                timestamp_to_block_number(start_timestamp, source.clone()).await?,
-                timestamp_to_block_number(end_timestamp, source).await?,
+                timestamp_to_block_number(start_timestamp, Rounding::Ceil, source.clone()).await?,
+                timestamp_to_block_number(end_timestamp, Rounding::Floor, source).await?,
             );
             block_range_to_block_chunk(start_block, end_block, as_range, None, n_keep)
         }
@@ -268,44 +269,58 @@ fn scale_timestamp_str_by_metric_unit(
         .map_err(|_e| ParseError::ParseError("Error parsing timestamp ref".to_string()))
 }
 
-// perform binary search to determine the closest block number smaller than or equal to a given
-// timestamp
-async fn timestamp_to_block_number(timestamp: u64, source: Arc<Source>) -> Result<u64, ParseError> {
+/// how to resolve a timestamp that falls between two blocks
+#[derive(Clone, Copy)]
+pub(crate) enum Rounding {
+    /// last block with timestamp less than or equal to the given timestamp
+    Floor,
+    /// first block with timestamp greater than or equal to the given timestamp
+    Ceil,
+}
+
+// perform binary search to determine the block number of a given timestamp
+// timestamps before genesis resolve to block 0, timestamps in the future resolve to latest
+pub(crate) async fn timestamp_to_block_number(
+    timestamp: u64,
+    rounding: Rounding,
+    source: Arc<Source>,
+) -> Result<u64, ParseError> {
     let latest_block_number = get_latest_block_number(source.clone()).await?;
 
+    // find first block past the timestamp, >= for Ceil and > for Floor
     let mut l = 0;
-    let mut r = latest_block_number;
-    let mut mid = (l + r) / 2;
-    let mut block = source
-        .get_block(mid)
-        .await
-        .map_err(|_e| ParseError::ParseError("Error fetching block for timestamp".to_string()))?
-        .unwrap();
-
-    while l <= r {
-        mid = (l + r) / 2;
-        block = source
-            .get_block(mid)
-            .await
-            .map_err(|_e| ParseError::ParseError("Error fetching block for timestamp".to_string()))?
-            .unwrap();
-
-        #[allow(clippy::comparison_chain)]
-        if block.timestamp == timestamp.into() {
-            return Ok(mid);
-        } else if block.timestamp < timestamp.into() {
-            l = mid + 1;
+    let mut r = latest_block_number + 1;
+    while l < r {
+        let mid = (l + r) / 2;
+        let block_timestamp = get_block_timestamp(mid, source.clone()).await?;
+        let past_timestamp = match rounding {
+            Rounding::Floor => block_timestamp > timestamp,
+            Rounding::Ceil => block_timestamp >= timestamp,
+        };
+        if past_timestamp {
+            r = mid;
         } else {
-            r = mid - 1;
+            l = mid + 1;
         }
     }
 
-    // If timestamp is between two different blocks, return the lower block.
-    if mid > 0 && block.timestamp > ethers::types::U256::from(timestamp) {
-        Ok(mid - 1)
-    } else {
-        Ok(mid)
-    }
+    let block_number = match rounding {
+        Rounding::Floor => l.saturating_sub(1),
+        Rounding::Ceil => l,
+    };
+    Ok(block_number.min(latest_block_number))
+}
+
+pub(crate) async fn get_block_timestamp(
+    block_number: u64,
+    source: Arc<Source>,
+) -> Result<u64, ParseError> {
+    source
+        .get_block(block_number)
+        .await
+        .map_err(|_e| ParseError::ParseError("Error fetching block for timestamp".to_string()))?
+        .map(|block| block.timestamp.as_u64())
+        .ok_or_else(|| ParseError::ParseError("Block not found for timestamp".to_string()))
 }
 
 async fn get_latest_timestamp(source: Arc<Source>) -> Result<u64, ParseError> {
@@ -326,12 +341,12 @@ mod tests {
     use governor::{Quota, RateLimiter};
 
     use super::*;
-    use cryo_freeze::SourceLabels;
+    use cryo_freeze::{RetryPolicy, SourceLabels};
     use ethers::prelude::*;
 
     async fn setup_source() -> Source {
-        let rpc_url = match crate::parse::source::parse_rpc_url(&Args::default()) {
-            Ok(url) => url,
+        let rpc_url = match crate::parse::source::parse_rpc_urls(&Args::default()) {
+            Ok(urls) => urls[0].clone(),
             Err(_) => std::process::exit(0),
         };
         let max_retry = 5;
@@ -353,9 +368,16 @@ mod tests {
             rate_limiter: Arc::new(rate_limiter),
             chain_id: 1,
             inner_request_size: 1,
+            log_window: None,
+            block_source: None,
+            receipt_request_size: None,
             max_concurrent_chunks: None,
             rpc_url: "".to_string(),
             labels: SourceLabels::default(),
+            cancel: None,
+            retry_policy: RetryPolicy::default(),
+            cache: None,
+            request_stats: Default::default(),
         }
     }
 
@@ -365,7 +387,7 @@ mod tests {
         let source = Arc::new(source);
 
         // Before genesis block
-        assert!(timestamp_to_block_number(1438260000, source).await.unwrap() == 0);
+        assert!(timestamp_to_block_number(1438260000, Rounding::Floor, source).await.unwrap() == 0);
     }
 
     #[tokio::test]
@@ -377,7 +399,7 @@ mod tests {
         let latest_timestamp = latest_block.timestamp.as_u64();
 
         assert_eq!(
-            timestamp_to_block_number(latest_timestamp, source).await.unwrap(),
+            timestamp_to_block_number(latest_timestamp, Rounding::Floor, source).await.unwrap(),
             latest_block_number
         );
     }
@@ -388,20 +410,45 @@ mod tests {
         let source = Arc::new(source);
 
         // Block 1000, and the timestamp surrounding block 1020
-        assert!(timestamp_to_block_number(1438272177, source.clone()).await.unwrap() == 1020);
-        assert!(timestamp_to_block_number(1438272178, source.clone()).await.unwrap() == 1020);
+        assert_eq!(
+            timestamp_to_block_number(1438272177, Rounding::Floor, source.clone()).await.unwrap(),
+            1020
+        );
+        assert_eq!(
+            timestamp_to_block_number(1438272178, Rounding::Floor, source.clone()).await.unwrap(),
+            1020
+        );
 
         // Timestamp 1438272176 is 1 seconds after block 1019 and 1 second before block 1020. Lower
         // block is returned
-        assert!(timestamp_to_block_number(1438272176, source.clone()).await.unwrap() == 1019);
+        assert_eq!(
+            timestamp_to_block_number(1438272176, Rounding::Floor, source.clone()).await.unwrap(),
+            1019
+        );
 
         // Timestamp 1438272187 is 1 seconds after block 1024 and 1 second before block 1025. Lower
         // block is returned
-        assert!(timestamp_to_block_number(1438272187, source.clone()).await.unwrap() == 1024);
+        assert_eq!(
+            timestamp_to_block_number(1438272187, Rounding::Floor, source.clone()).await.unwrap(),
+            1024
+        );
 
         // Timestamp 1438272169 is 4 seconds after block 1016 and 4 seconds before block 1017. Lower
         // block is returned
-        assert!(timestamp_to_block_number(1438272169, source.clone()).await.unwrap() == 1016);
+        assert_eq!(
+            timestamp_to_block_number(1438272169, Rounding::Floor, source.clone()).await.unwrap(),
+            1016
+        );
+
+        // Ceil returns the upper block, or the exact block when on a block boundary
+        assert_eq!(
+            timestamp_to_block_number(1438272176, Rounding::Ceil, source.clone()).await.unwrap(),
+            1020
+        );
+        assert_eq!(
+            timestamp_to_block_number(1438272177, Rounding::Ceil, source.clone()).await.unwrap(),
+            1020
+        );
     }
 
     #[tokio::test]
@@ -469,8 +516,12 @@ mod tests {
             parse_timestamp_range("1700000000", "1700000015", source.clone()).await.unwrap();
         assert_eq!(
             (
-                timestamp_to_block_number(start_timestamp, source.clone()).await.unwrap(),
-                timestamp_to_block_number(end_timestamp, source.clone()).await.unwrap()
+                timestamp_to_block_number(start_timestamp, Rounding::Floor, source.clone())
+                    .await
+                    .unwrap(),
+                timestamp_to_block_number(end_timestamp, Rounding::Floor, source.clone())
+                    .await
+                    .unwrap()
             ),
             (18573050, 18573051)
         );
@@ -479,8 +530,12 @@ mod tests {
             parse_timestamp_range("-15", "1700000015", source.clone()).await.unwrap();
         assert_eq!(
             (
-                timestamp_to_block_number(start_timestamp, source.clone()).await.unwrap(),
-                timestamp_to_block_number(end_timestamp, source.clone()).await.unwrap()
+                timestamp_to_block_number(start_timestamp, Rounding::Floor, source.clone())
+                    .await
+                    .unwrap(),
+                timestamp_to_block_number(end_timestamp, Rounding::Floor, source.clone())
+                    .await
+                    .unwrap()
             ),
             (18573050, 18573052)
         );
@@ -489,8 +544,12 @@ mod tests {
             parse_timestamp_range("1700000000", "+15", source.clone()).await.unwrap();
         assert_eq!(
             (
-                timestamp_to_block_number(start_timestamp, source.clone()).await.unwrap(),
-                timestamp_to_block_number(end_timestamp, source.clone()).await.unwrap()
+                timestamp_to_block_number(start_timestamp, Rounding::Floor, source.clone())
+                    .await
+                    .unwrap(),
+                timestamp_to_block_number(end_timestamp, Rounding::Floor, source.clone())
+                    .await
+                    .unwrap()
             ),
             (18573050, 18573051)
         );
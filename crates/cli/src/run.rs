@@ -1,16 +1,95 @@
-use crate::{args, parse, remember};
-use clap_cryo::Parser;
+use crate::{args, config, parse, remember};
+use clap_cryo::{ArgMatches, Parser};
 use color_print::cstr;
 use colored::Colorize;
-use cryo_freeze::{err, CollectError, ExecutionEnv, FreezeSummary};
-use std::{sync::Arc, time::SystemTime};
+use cryo_freeze::{
+    err, CancellationToken, CollectError, ExecutionEnv, FileFormat, FileOutput, FreezeSummary,
+    LogFormat,
+};
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+/// how long started chunks may keep running after ctrl-c before their requests are dropped
+const INTERRUPT_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+/// tokens cancelled by ctrl-c
+#[derive(Clone, Default)]
+struct Interrupt {
+    /// cancelled by first ctrl-c, stops chunks from starting
+    stop: CancellationToken,
+    /// cancelled after grace period, drops requests of started chunks
+    abort: CancellationToken,
+}
 
 /// run cli
 pub async fn run(args: args::Args) -> Result<Option<FreezeSummary>, CollectError> {
+    run_with_interrupt(args, None, None).await
+}
+
+/// run cli, stopping gracefully on ctrl-c
+///
+/// the first ctrl-c stops new chunks from starting and lets started chunks finish writing for up
+/// to a grace period, after which their requests are dropped. a second ctrl-c exits immediately
+///
+/// matches of the command line that args were parsed from decide which options take precedence
+/// over the config file
+pub async fn run_interruptible(
+    args: args::Args,
+    matches: Option<ArgMatches>,
+) -> Result<Option<FreezeSummary>, CollectError> {
+    run_with_interrupt(args, matches, Some(spawn_interrupt_handler())).await
+}
+
+fn spawn_interrupt_handler() -> Interrupt {
+    let interrupt = Interrupt::default();
+    let tokens = interrupt.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return
+        }
+        eprintln!();
+        eprintln!(
+            "interrupted, finishing started chunks for up to {}s, press ctrl-c again to exit now",
+            INTERRUPT_GRACE_PERIOD.as_secs()
+        );
+        tokens.stop.cancel();
+        let abort = tokens.abort.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(INTERRUPT_GRACE_PERIOD).await;
+            abort.cancel();
+        });
+        if tokio::signal::ctrl_c().await.is_ok() {
+            eprintln!("exiting");
+            std::process::exit(130);
+        }
+    });
+    interrupt
+}
+
+async fn run_with_interrupt(
+    args: args::Args,
+    matches: Option<ArgMatches>,
+    interrupt: Option<Interrupt>,
+) -> Result<Option<FreezeSummary>, CollectError> {
     // handle subcommands
     if args.datatype.first() == Some(&"help".to_string()) {
         return handle_help_subcommands(args).await
     }
+    if args.datatype.first() == Some(&"schema".to_string()) {
+        return handle_schema_subcommand(args)
+    }
+    if args.datatype.first() == Some(&"datasets".to_string()) {
+        return handle_datasets_subcommand(args)
+    }
+    if args.datatype.first() == Some(&"print-config".to_string()) {
+        return handle_print_config_subcommand(args, matches.as_ref()).await
+    }
+
+    // fill unspecified options from config file
+    let config = config::load_config(args.config.as_ref())?;
+    let args = config::merge_config_into_args(config, args, matches.as_ref())?;
 
     let cryo_dir: std::path::PathBuf = args.output_dir.clone().into();
     let cryo_dir = cryo_dir.join(".cryo");
@@ -48,10 +127,98 @@ pub async fn run(args: args::Args) -> Result<Option<FreezeSummary>, CollectError
         Err(e) => return Err(e.into()),
     };
 
+    let (source, env) = match interrupt {
+        Some(interrupt) => (
+            source.with_cancellation(interrupt.abort),
+            ExecutionEnv { interrupt: Some(interrupt.stop), ..env },
+        ),
+        None => (source, env),
+    };
     let source = Arc::new(source);
     let env = ExecutionEnv { t_start_parse, ..env };
     let env = env.set_start_time();
-    cryo_freeze::freeze(&query, &source, &sink, &env).await
+    let freeze_summary = cryo_freeze::freeze(&query, &source, &sink, &env).await?;
+    if let Some(freeze_summary) = &freeze_summary {
+        print_file_statistics(freeze_summary, &sink, &env);
+    }
+    Ok(freeze_summary)
+}
+
+/// print row counts, sizes, and numeric column ranges of written files, read from parquet footers
+///
+/// skipped unless output is parquet written with statistics
+fn print_file_statistics(freeze_summary: &FreezeSummary, sink: &FileOutput, env: &ExecutionEnv) {
+    if sink.format != FileFormat::Parquet ||
+        !sink.parquet_statistics ||
+        env.verbose == 0 ||
+        env.log_format != LogFormat::Text
+    {
+        return
+    }
+    let paths: Vec<_> = freeze_summary.written.iter().map(|file| file.path.clone()).collect();
+    cryo_freeze::print_file_summaries(&paths);
+}
+
+fn handle_schema_subcommand(args: args::Args) -> Result<Option<FreezeSummary>, CollectError> {
+    if !args.lineage || args.datatype.len() < 2 {
+        return Err(err("usage: cryo schema --lineage FILE..."))
+    }
+    for (i, path) in args.datatype[1..].iter().enumerate() {
+        if i > 0 {
+            println!();
+            println!();
+        }
+        let path = std::path::Path::new(path);
+        match cryo_freeze::read_file_lineage(path) {
+            Ok(Some(lineage)) => cryo_freeze::print_file_lineage(path, &lineage),
+            Ok(None) => println!("{} has no lineage metadata", path.display()),
+            Err(e) => {
+                return Err(err(format!("could not read {}: {}", path.display(), e).as_str()))
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// print options of a run resolved against the rpc as config toml, without collecting data
+async fn handle_print_config_subcommand(
+    args: args::Args,
+    matches: Option<&ArgMatches>,
+) -> Result<Option<FreezeSummary>, CollectError> {
+    let args = args::Args { datatype: args.datatype[1..].to_vec(), ..args };
+    let config = config::load_config(args.config.as_ref())?;
+    let config_datatype = config.datatype.clone();
+    let mut args = config::merge_config_into_args(config, args, matches)?;
+    // print-config itself is the datatype given on the command line
+    if args.datatype.is_empty() {
+        args.datatype = config_datatype.unwrap_or_default();
+    }
+    let (query, source, sink, _) = parse::parse_args(&args).await?;
+    print!("{}", config::resolved_config_toml(&args, &query, &source, &sink)?);
+    Ok(None)
+}
+
+/// print schema and default sort of every dataset, or of the datasets given, without rpc requests
+fn handle_datasets_subcommand(args: args::Args) -> Result<Option<FreezeSummary>, CollectError> {
+    let datatype = match args.datatype.len() {
+        1 => cryo_freeze::Datatype::all().into_iter().map(|datatype| datatype.name()).collect(),
+        _ => args.datatype[1..].to_vec(),
+    };
+    let args = args::Args { datatype, ..args };
+    let (datatypes, schemas) = super::parse::schemas::parse_schemas(&args)?;
+    for (i, datatype) in datatypes.into_iter().enumerate() {
+        if i > 0 {
+            println!();
+            println!();
+        }
+        match schemas.get(&datatype) {
+            Some(schema) => cryo_freeze::print_dataset_info(datatype, schema),
+            None => {
+                return Err(err(format!("missing schema for datatype: {:?}", datatype).as_str()))
+            }
+        }
+    }
+    Ok(None)
 }
 
 async fn handle_help_subcommands(args: args::Args) -> Result<Option<FreezeSummary>, CollectError> {
@@ -66,11 +233,17 @@ async fn handle_help_subcommands(args: args::Args) -> Result<Option<FreezeSummar
 - can use multiple parquet files     <white><bold>--blocks ./path/to/files/*.parquet[:COLUMN_NAME]</bold></white>
 - numbers can contain { _ . K M B }  <white><bold>5_000 5K 15M 15.5M</bold></white>
 - omitting range end means latest    <white><bold>15.5M:</bold></white> == <white><bold>15.5M:latest</bold></white>
+- can use block tags                 <white><bold>17M:finalized 17M:safe earliest:100</bold></white>
 - omitting range start means 0       <white><bold>:700</bold></white> == <white><bold>0:700</bold></white>
 - minus on start means minus end     <white><bold>-1000:7000</bold></white> == <white><bold>6000:7000</bold></white>
+- minus without end means last n     <white><bold>-1000:</bold></white> == <white><bold>last 1000 blocks</bold></white>
 - plus sign on end means plus start  <white><bold>15M:+1000</bold></white> == <white><bold>15M:15.001K</bold></white>
 - can use every nth value            <white><bold>2000:5000:1000</bold></white> == <white><bold>2000 3000 4000</bold></white>
 - can use n values total             <white><bold>100:200/5</bold></white> == <white><bold>100 124 149 174 199</bold></white>
+- can use timestamps with suffix t   <white><bold>1680000000:1682000000t</bold></white>
+- can use dates at 00:00 utc         <white><bold>2023-01-01:2023-02-01</bold></white>
+- can use timestamps with prefix t   <white><bold>17M:t1682000000</bold></white>
+- can use a text file of blocks      <white><bold>--blocks-from-file ./blocks.txt</bold></white>
 
 <white><bold>Transaction specification syntax</bold></white>
 - can use transaction hashes         <white><bold>--txs TX_HASH1 TX_HASH2 TX_HASH3</bold></white>
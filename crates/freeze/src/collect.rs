@@ -18,7 +18,11 @@ pub async fn collect(query: Arc<Query>, source: Arc<Source>) -> Result<DataFrame
     } else {
         query.partitions[0].clone()
     };
-    let results = collect_partition(datatype, partition, query, source).await?;
+    let results = tokio::select! {
+        biased;
+        _ = source.cancelled() => return Err(CollectError::Cancelled),
+        results = collect_partition(datatype, partition, query, source.clone()) => results?,
+    };
     if results.len() > 1 {
         Err(CollectError::CollectError("collect() only returns single dataframes".to_string()))
     } else {
@@ -0,0 +1,140 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+
+/// columns for access lists
+#[cryo_to_df::to_df(Datatype::AccessLists)]
+#[derive(Default)]
+pub struct AccessLists {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    transaction_hash: Vec<Vec<u8>>,
+    transaction_index: Vec<u32>,
+    address: Vec<Vec<u8>>,
+    storage_key: Vec<Option<Vec<u8>>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for AccessLists {
+    fn default_sort() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "transaction_index"])
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for AccessLists {
+    type Response = Vec<Transaction>;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let block_number = request.block_number()?;
+        let block = source
+            .get_block_with_txs(block_number)
+            .await?
+            .ok_or(CollectError::BlockNotFound(block_number))?;
+        Ok(block.transactions)
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::AccessLists)?;
+        process_access_lists(response, columns, schema)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for AccessLists {
+    type Response = Vec<Transaction>;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let tx = source
+            .get_transaction(request.ethers_transaction_hash()?)
+            .await?
+            .ok_or(CollectError::CollectError("transaction not found".to_string()))?;
+        Ok(vec![tx])
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::AccessLists)?;
+        process_access_lists(response, columns, schema)
+    }
+}
+
+/// process access list of each transaction into one row per storage key
+///
+/// entries that warm an address without storage keys get a single row with a null storage key.
+/// transactions without access lists, such as legacy transactions, produce no rows
+fn process_access_lists(txs: Vec<Transaction>, columns: &mut AccessLists, schema: &Table) -> R<()> {
+    for tx in txs.into_iter() {
+        let access_list = match tx.access_list {
+            Some(access_list) => access_list,
+            None => continue,
+        };
+        let block_number = tx.block_number.ok_or(err("no block number for tx"))?.as_u32();
+        let transaction_index =
+            tx.transaction_index.ok_or(err("no transaction index for tx"))?.as_u32();
+        for item in access_list.0.into_iter() {
+            let storage_keys: Vec<Option<H256>> = if item.storage_keys.is_empty() {
+                vec![None]
+            } else {
+                item.storage_keys.into_iter().map(Some).collect()
+            };
+            for storage_key in storage_keys.into_iter() {
+                columns.n_rows += 1;
+                store!(schema, columns, block_number, block_number);
+                store!(schema, columns, transaction_hash, tx.hash.as_bytes().to_vec());
+                store!(schema, columns, transaction_index, transaction_index);
+                store!(schema, columns, address, item.address.as_bytes().to_vec());
+                store!(schema, columns, storage_key, storage_key.map(|x| x.as_bytes().to_vec()));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::transaction::eip2930::{AccessList, AccessListItem};
+
+    #[test]
+    fn test_process_access_lists() {
+        let cols = Some(vec!["all".to_string()]);
+        let schema = Datatype::AccessLists
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &cols,
+                None,
+                None,
+            )
+            .unwrap();
+        let access_list = AccessList(vec![
+            AccessListItem {
+                address: H160([1; 20]),
+                storage_keys: vec![H256([2; 32]), H256([3; 32])],
+            },
+            AccessListItem { address: H160([4; 20]), storage_keys: vec![] },
+        ]);
+        let txs = vec![
+            Transaction {
+                block_number: Some(100.into()),
+                transaction_index: Some(0.into()),
+                ..Default::default()
+            },
+            Transaction {
+                block_number: Some(100.into()),
+                transaction_index: Some(1.into()),
+                access_list: Some(access_list),
+                ..Default::default()
+            },
+        ];
+        let mut columns = AccessLists::default();
+        process_access_lists(txs, &mut columns, &schema).unwrap();
+        assert_eq!(columns.n_rows, 3);
+        assert_eq!(columns.transaction_index, vec![1, 1, 1]);
+        assert_eq!(columns.address, vec![vec![1; 20], vec![1; 20], vec![4; 20]]);
+        assert_eq!(columns.storage_key, vec![Some(vec![2; 32]), Some(vec![3; 32]), None]);
+    }
+}
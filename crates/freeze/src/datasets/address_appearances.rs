@@ -43,7 +43,7 @@ impl CollectByBlock for AddressAppearances {
     async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
         let block_number = request.ethers_block_number()?;
         let block = source.get_block(request.block_number()?).await?;
-        let block = block.ok_or(CollectError::CollectError("block not found".to_string()))?;
+        let block = block.ok_or(CollectError::BlockNotFound(request.block_number()?))?;
         let filter = Filter {
             block_option: FilterBlockOption::Range {
                 from_block: Some(block_number),
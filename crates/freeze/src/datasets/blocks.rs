@@ -44,6 +44,10 @@ impl Dataset for Blocks {
             "chain_id",
         ])
     }
+
+    fn dedup_key() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number"])
+    }
 }
 
 #[async_trait::async_trait]
@@ -51,10 +55,12 @@ impl CollectByBlock for Blocks {
     type Response = Block<TxHash>;
 
     async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let block_number = request.block_number()?;
         let block = source
-            .get_block(request.block_number()?)
+            .block_source()
+            .get_block(&source, block_number)
             .await?
-            .ok_or(CollectError::CollectError("block not found".to_string()))?;
+            .ok_or(CollectError::BlockNotFound(block_number))?;
         Ok(block)
     }
 
@@ -112,3 +118,88 @@ pub(crate) fn process_block<TX>(block: Block<TX>, columns: &mut Blocks, schema:
     store!(schema, columns, withdrawals_root, block.withdrawals_root.map(|x| x.0.to_vec()));
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENESIS_BLOCK: &str = r#"{
+        "hash": "0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3",
+        "parentHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+        "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+        "miner": "0x0000000000000000000000000000000000000000",
+        "stateRoot": "0xd7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544",
+        "transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+        "receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+        "number": "0x0",
+        "gasUsed": "0x0",
+        "gasLimit": "0x1388",
+        "extraData": "0x11bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cbdb7a38e1e50b1b82fa",
+        "logsBloom": null,
+        "timestamp": "0x0",
+        "difficulty": "0x400000000",
+        "totalDifficulty": "0x400000000",
+        "uncles": [],
+        "transactions": [],
+        "size": "0x21c",
+        "mixHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+        "nonce": "0x0000000000000042"
+    }"#;
+
+    #[test]
+    fn test_process_genesis_block() {
+        let block: Block<TxHash> = serde_json::from_str(GENESIS_BLOCK).unwrap();
+        let cols = Some(vec!["all".to_string()]);
+        let u256_types = [U256Type::Binary];
+        let schema = Datatype::Blocks
+            .table_schema(&u256_types, &ColumnEncoding::Binary, &None, &None, &cols, None, None)
+            .unwrap();
+        let mut columns = Blocks::default();
+        process_block(block, &mut columns, &schema).unwrap();
+        assert_eq!(columns.n_rows, 1);
+        assert_eq!(columns.block_number, vec![Some(0)]);
+        assert_eq!(columns.base_fee_per_gas, vec![None]);
+        assert_eq!(columns.withdrawals_root, vec![None]);
+    }
+
+    #[test]
+    fn test_era_dependent_columns_are_null() {
+        let cols = Some(vec!["all".to_string()]);
+        let u256_types = [U256Type::Binary];
+        let schema = Datatype::Blocks
+            .table_schema(&u256_types, &ColumnEncoding::Binary, &None, &None, &cols, None, None)
+            .unwrap();
+
+        // last block before london, first block of london, and first block of shanghai
+        let blocks = [
+            Block::<TxHash> { number: Some(12964999.into()), ..Default::default() },
+            Block::<TxHash> {
+                number: Some(12965000.into()),
+                base_fee_per_gas: Some(1_000_000_000.into()),
+                ..Default::default()
+            },
+            Block::<TxHash> {
+                number: Some(17034870.into()),
+                base_fee_per_gas: Some(21_000_000_000u64.into()),
+                withdrawals_root: Some(H256::repeat_byte(1)),
+                ..Default::default()
+            },
+        ];
+        let mut columns = Blocks::default();
+        for block in blocks.into_iter() {
+            process_block(block, &mut columns, &schema).unwrap();
+        }
+        let schemas = [(Datatype::Blocks, schema)].into_iter().collect();
+        let mut df = columns.create_dfs(&schemas, 1).unwrap().remove(&Datatype::Blocks).unwrap();
+
+        // nulls survive a roundtrip through parquet
+        let mut buffer = Vec::new();
+        ParquetWriter::new(&mut buffer).with_statistics(true).finish(&mut df).unwrap();
+        let df = ParquetReader::new(std::io::Cursor::new(buffer)).finish().unwrap();
+        let is_null = |name: &str| -> Vec<Option<bool>> {
+            df.column(name).unwrap().is_null().into_iter().collect()
+        };
+        assert_eq!(is_null("base_fee_per_gas"), vec![Some(true), Some(false), Some(false)]);
+        assert_eq!(is_null("withdrawals_root"), vec![Some(true), Some(true), Some(false)]);
+    }
+}
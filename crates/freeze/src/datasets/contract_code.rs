@@ -0,0 +1,134 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+
+/// columns for contract code
+#[cryo_to_df::to_df(Datatype::ContractCode)]
+#[derive(Default)]
+pub struct ContractCode {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    transaction_hash: Vec<Vec<u8>>,
+    contract_address: Vec<Vec<u8>>,
+    code: Vec<Vec<u8>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for ContractCode {
+    fn default_sort() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "transaction_hash"])
+    }
+}
+
+/// block number, and transaction hash, contract address, and code of each contract creation
+type BlockCreationCodes = (u32, Vec<(Vec<u8>, Vec<u8>, Vec<u8>)>);
+
+#[async_trait::async_trait]
+impl CollectByBlock for ContractCode {
+    type Response = BlockCreationCodes;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let block_number = request.block_number()?;
+        let block = source
+            .get_block_with_txs(block_number)
+            .await?
+            .ok_or(CollectError::BlockNotFound(block_number))?;
+        let mut creations = Vec::new();
+        for tx in block.transactions.iter().filter(|tx| tx.to.is_none()) {
+            creations.push(get_creation_code(tx, block_number, &source).await?);
+        }
+        Ok((block_number as u32, creations))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::ContractCode)?;
+        process_contract_code(response, columns, schema)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for ContractCode {
+    type Response = BlockCreationCodes;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let tx = source
+            .get_transaction(request.ethers_transaction_hash()?)
+            .await?
+            .ok_or(CollectError::CollectError("transaction not found".to_string()))?;
+        let block_number = tx
+            .block_number
+            .ok_or(CollectError::CollectError("no block number for tx".to_string()))?
+            .as_u64();
+        let creations = match tx.to {
+            None => vec![get_creation_code(&tx, block_number, &source).await?],
+            Some(_) => Vec::new(),
+        };
+        Ok((block_number as u32, creations))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::ContractCode)?;
+        process_contract_code(response, columns, schema)
+    }
+}
+
+/// get code at end of block of the contract deployed by a contract creation transaction
+///
+/// the address is derived from the sender and nonce. creations that failed or whose contract
+/// self-destructed within the block have empty code
+async fn get_creation_code(
+    tx: &Transaction,
+    block_number: u64,
+    source: &Source,
+) -> R<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    let address = ethers::utils::get_contract_address(tx.from, tx.nonce);
+    let code = source.get_code(address, block_number.into()).await?;
+    Ok((tx.hash.as_bytes().to_vec(), address.as_bytes().to_vec(), code.to_vec()))
+}
+
+fn process_contract_code(
+    response: BlockCreationCodes,
+    columns: &mut ContractCode,
+    schema: &Table,
+) -> R<()> {
+    let (block_number, creations) = response;
+    for (transaction_hash, contract_address, code) in creations.into_iter() {
+        columns.n_rows += 1;
+        store!(schema, columns, block_number, block_number);
+        store!(schema, columns, transaction_hash, transaction_hash);
+        store!(schema, columns, contract_address, contract_address);
+        store!(schema, columns, code, code);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_self_destructed_contract() {
+        let cols = Some(vec!["all".to_string()]);
+        let schema = Datatype::ContractCode
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &cols,
+                None,
+                None,
+            )
+            .unwrap();
+        let creations = vec![
+            (vec![1u8; 32], vec![2u8; 20], vec![0x60, 0x80]),
+            (vec![3u8; 32], vec![4u8; 20], vec![]),
+        ];
+        let mut columns = ContractCode::default();
+        process_contract_code((17, creations), &mut columns, &schema).unwrap();
+        assert_eq!(columns.n_rows, 2);
+        assert_eq!(columns.block_number, vec![17, 17]);
+        assert_eq!(columns.code, vec![vec![0x60, 0x80], vec![]]);
+    }
+}
@@ -1,5 +1,5 @@
 use super::traces;
-use crate::*;
+use crate::{retries::is_permanent_error, *};
 use ethers::prelude::*;
 use ethers_core::utils::keccak256;
 use polars::prelude::*;
@@ -12,10 +12,12 @@ pub struct Contracts {
     block_number: Vec<u32>,
     block_hash: Vec<Vec<u8>>,
     create_index: Vec<u32>,
+    transaction_index: Vec<Option<u32>>,
     transaction_hash: Vec<Option<Vec<u8>>>,
     contract_address: Vec<Vec<u8>>,
     deployer: Vec<Vec<u8>>,
     factory: Vec<Vec<u8>>,
+    top_level: Vec<bool>,
     init_code: Vec<Vec<u8>>,
     code: Vec<Vec<u8>>,
     init_code_hash: Vec<Vec<u8>>,
@@ -32,33 +34,128 @@ impl Dataset for Contracts {
     }
 }
 
+/// description of the fallback used when the provider does not support tracing
+const TOP_LEVEL_ONLY: &str =
+    "provider does not support tracing, only top-level creations collected";
+
+/// contract creations of a block or transaction
+pub enum ContractCreations {
+    /// traces, which include creations by other contracts
+    Traces(Vec<Trace>),
+    /// block number, block hash, and each successful creation transaction with its receipt and
+    /// deployed code, used when the provider does not support tracing
+    TopLevel(u32, H256, Vec<(Transaction, TransactionReceipt, Bytes)>),
+}
+
 #[async_trait::async_trait]
 impl CollectByBlock for Contracts {
-    type Response = Vec<Trace>;
+    type Response = ContractCreations;
 
-    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
-        source.trace_block(request.ethers_block_number()?).await
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
+        let block_number = request.block_number()?;
+        if !query.degraded_datatypes.contains(&Datatype::Contracts) {
+            match source.trace_block(request.ethers_block_number()?).await {
+                Err(CollectError::ProviderError(e)) if is_permanent_error(&e) => {
+                    query.degraded_datatypes.add(Datatype::Contracts, TOP_LEVEL_ONLY)
+                }
+                result => return result.map(ContractCreations::Traces),
+            }
+        }
+        let block = source
+            .get_block_with_txs(block_number)
+            .await?
+            .ok_or(CollectError::BlockNotFound(block_number))?;
+        let block_hash = block.hash.ok_or(err("block hash not found"))?;
+        let mut creations = Vec::new();
+        for tx in block.transactions.into_iter().filter(|tx| tx.to.is_none()) {
+            if let Some(creation) = get_top_level_creation(tx, block_number, &source).await? {
+                creations.push(creation)
+            }
+        }
+        Ok(ContractCreations::TopLevel(block_number as u32, block_hash, creations))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
-        let traces =
-            if query.exclude_failed { traces::filter_failed_traces(response) } else { response };
-        process_contracts(&traces, columns, &query.schemas)
+        process_contract_creations(response, columns, query)
     }
 }
 
 #[async_trait::async_trait]
 impl CollectByTransaction for Contracts {
-    type Response = Vec<Trace>;
+    type Response = ContractCreations;
 
-    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
-        source.trace_transaction(request.ethers_transaction_hash()?).await
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
+        let tx_hash = request.ethers_transaction_hash()?;
+        if !query.degraded_datatypes.contains(&Datatype::Contracts) {
+            match source.trace_transaction(tx_hash).await {
+                Err(CollectError::ProviderError(e)) if is_permanent_error(&e) => {
+                    query.degraded_datatypes.add(Datatype::Contracts, TOP_LEVEL_ONLY)
+                }
+                result => return result.map(ContractCreations::Traces),
+            }
+        }
+        let tx = source
+            .get_transaction(tx_hash)
+            .await?
+            .ok_or(CollectError::CollectError("transaction not found".to_string()))?;
+        let block_number = tx
+            .block_number
+            .ok_or(CollectError::CollectError("no block number for tx".to_string()))?
+            .as_u64();
+        let block_hash = tx.block_hash.ok_or(err("block hash not found"))?;
+        let creations = match tx.to {
+            None => get_top_level_creation(tx, block_number, &source).await?.into_iter().collect(),
+            Some(_) => Vec::new(),
+        };
+        Ok(ContractCreations::TopLevel(block_number as u32, block_hash, creations))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
-        let traces =
-            if query.exclude_failed { traces::filter_failed_traces(response) } else { response };
-        process_contracts(&traces, columns, &query.schemas)
+        process_contract_creations(response, columns, query)
+    }
+}
+
+/// get receipt and deployed code of a contract creation transaction, or None if creation failed
+///
+/// code is read at the end of the block, so contracts that self-destructed within the block
+/// have empty code
+async fn get_top_level_creation(
+    tx: Transaction,
+    block_number: u64,
+    source: &Source,
+) -> R<Option<(Transaction, TransactionReceipt, Bytes)>> {
+    let receipt = source
+        .get_transaction_receipt(tx.hash)
+        .await?
+        .ok_or(CollectError::CollectError("could not find tx receipt".to_string()))?;
+    let address = match receipt.contract_address {
+        Some(address) if receipt.status != Some(0.into()) => address,
+        _ => return Ok(None),
+    };
+    let code = source.get_code(address, block_number.into()).await?;
+    Ok(Some((tx, receipt, code)))
+}
+
+fn process_contract_creations(
+    response: ContractCreations,
+    columns: &mut Contracts,
+    query: &Arc<Query>,
+) -> R<()> {
+    match response {
+        ContractCreations::Traces(traces) => {
+            let traces =
+                if query.exclude_failed { traces::filter_failed_traces(traces) } else { traces };
+            process_contracts(&traces, columns, &query.schemas)
+        }
+        ContractCreations::TopLevel(block_number, block_hash, creations) => {
+            process_top_level_creations(
+                block_number,
+                block_hash,
+                creations,
+                columns,
+                &query.schemas,
+            )
+        }
     }
 }
 
@@ -88,18 +185,93 @@ pub(crate) fn process_contracts(
             store!(schema, columns, block_hash, trace.block_hash.as_bytes().to_vec());
             store!(schema, columns, create_index, create_index);
             create_index += 1;
+            let tx_index = trace.transaction_position.map(|x| x as u32);
+            store!(schema, columns, transaction_index, tx_index);
             let tx = trace.transaction_hash;
             store!(schema, columns, transaction_hash, tx.map(|x| x.as_bytes().to_vec()));
             store!(schema, columns, contract_address, result.address.as_bytes().into());
             store!(schema, columns, deployer, deployer.as_bytes().into());
             store!(schema, columns, factory, create.from.as_bytes().into());
+            store!(schema, columns, top_level, trace.trace_address.is_empty());
             store!(schema, columns, init_code, create.init.to_vec());
             store!(schema, columns, code, result.code.to_vec());
-            store!(schema, columns, init_code_hash, keccak256(result.code.clone()).into());
-            store!(schema, columns, code_hash, keccak256(create.init.clone()).into());
+            store!(schema, columns, init_code_hash, keccak256(create.init.clone()).into());
+            store!(schema, columns, code_hash, keccak256(result.code.clone()).into());
             store!(schema, columns, n_init_code_bytes, create.init.len() as u32);
             store!(schema, columns, n_code_bytes, result.code.len() as u32);
         }
     }
     Ok(())
 }
+
+/// process creation transactions of block into columns
+fn process_top_level_creations(
+    block_number: u32,
+    block_hash: H256,
+    creations: Vec<(Transaction, TransactionReceipt, Bytes)>,
+    columns: &mut Contracts,
+    schemas: &Schemas,
+) -> R<()> {
+    let schema = schemas.get(&Datatype::Contracts).ok_or(err("schema not provided"))?;
+    for (create_index, (tx, receipt, code)) in creations.into_iter().enumerate() {
+        let address = receipt.contract_address.ok_or(err("no contract address in receipt"))?;
+        columns.n_rows += 1;
+        store!(schema, columns, block_number, block_number);
+        store!(schema, columns, block_hash, block_hash.as_bytes().to_vec());
+        store!(schema, columns, create_index, create_index as u32);
+        store!(schema, columns, transaction_index, tx.transaction_index.map(|x| x.as_u32()));
+        store!(schema, columns, transaction_hash, Some(tx.hash.as_bytes().to_vec()));
+        store!(schema, columns, contract_address, address.as_bytes().into());
+        store!(schema, columns, deployer, tx.from.as_bytes().into());
+        store!(schema, columns, factory, tx.from.as_bytes().into());
+        store!(schema, columns, top_level, true);
+        store!(schema, columns, init_code, tx.input.to_vec());
+        store!(schema, columns, code, code.to_vec());
+        store!(schema, columns, init_code_hash, keccak256(tx.input.clone()).into());
+        store!(schema, columns, code_hash, keccak256(code.clone()).into());
+        store!(schema, columns, n_init_code_bytes, tx.input.len() as u32);
+        store!(schema, columns, n_code_bytes, code.len() as u32);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_top_level_creations() {
+        let cols = Some(vec!["all".to_string()]);
+        let schema = Datatype::Contracts
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &cols,
+                None,
+                None,
+            )
+            .unwrap();
+        let schemas: Schemas = [(Datatype::Contracts, schema)].into_iter().collect();
+        let tx = Transaction {
+            from: H160([1; 20]),
+            transaction_index: Some(3.into()),
+            input: Bytes::from(vec![0x60, 0x80, 0x60, 0x40]),
+            ..Default::default()
+        };
+        let receipt =
+            TransactionReceipt { contract_address: Some(H160([2; 20])), ..Default::default() };
+        let code = Bytes::from(vec![0x60, 0x80]);
+        let mut columns = Contracts::default();
+        let creations = vec![(tx, receipt, code)];
+        process_top_level_creations(17, H256::zero(), creations, &mut columns, &schemas).unwrap();
+        assert_eq!(columns.n_rows, 1);
+        assert_eq!(columns.transaction_index, vec![Some(3)]);
+        assert_eq!(columns.deployer, vec![vec![1; 20]]);
+        assert_eq!(columns.contract_address, vec![vec![2; 20]]);
+        assert_eq!(columns.top_level, vec![true]);
+        assert_eq!(columns.n_init_code_bytes, vec![4]);
+        assert_eq!(columns.code_hash, vec![keccak256([0x60, 0x80]).to_vec()]);
+    }
+}
@@ -0,0 +1,258 @@
+use crate::*;
+use ethers::{abi::ParamType, prelude::*};
+use polars::prelude::*;
+
+/// columns for erc1155 transfers
+#[cryo_to_df::to_df(Datatype::Erc1155Transfers)]
+#[derive(Default)]
+pub struct Erc1155Transfers {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    block_hash: Vec<Option<Vec<u8>>>,
+    transaction_index: Vec<u32>,
+    log_index: Vec<u32>,
+    transaction_hash: Vec<Vec<u8>>,
+    erc1155: Vec<Vec<u8>>,
+    operator: Vec<Vec<u8>>,
+    from_address: Vec<Vec<u8>>,
+    to_address: Vec<Vec<u8>>,
+    token_id: Vec<U256>,
+    value: Vec<U256>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for Erc1155Transfers {
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec![
+            "block_number",
+            "transaction_index",
+            "log_index",
+            "transaction_hash",
+            "erc1155",
+            "operator",
+            "from_address",
+            "to_address",
+            "token_id",
+            "value",
+            "chain_id",
+        ])
+    }
+
+    fn default_sort() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "log_index", "token_id"])
+    }
+
+    fn optional_parameters() -> Vec<Dim> {
+        vec![Dim::Address, Dim::FromAddress, Dim::ToAddress]
+    }
+
+    fn use_block_ranges() -> bool {
+        true
+    }
+
+    fn arg_aliases() -> Option<std::collections::HashMap<Dim, Dim>> {
+        Some([(Dim::Contract, Dim::Address)].into_iter().collect())
+    }
+
+    fn column_lineage() -> Vec<(&'static str, ColumnLineage)> {
+        let single = "event TransferSingle(address indexed operator, address indexed from, \
+                      address indexed to, uint256 id, uint256 value)";
+        let batch = "ids and values of event TransferBatch(address indexed operator, \
+                     address indexed from, address indexed to, uint256[] ids, uint256[] values), \
+                     one row per id";
+        vec![
+            ("erc1155", ColumnLineage::new(&["address"], "log address")),
+            ("operator", ColumnLineage::new(&["topic1"], single)),
+            ("from_address", ColumnLineage::new(&["topic2"], single)),
+            ("to_address", ColumnLineage::new(&["topic3"], single)),
+            ("token_id", ColumnLineage::new(&["data"], batch)),
+            ("value", ColumnLineage::new(&["data"], batch)),
+        ]
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for Erc1155Transfers {
+    type Response = Vec<Log>;
+
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
+        let events =
+            vec![Some(*EVENT_ERC1155_TRANSFER_SINGLE), Some(*EVENT_ERC1155_TRANSFER_BATCH)];
+        let mut topics = [Some(ValueOrArray::Array(events)), None, None, None];
+        if let Some(from_address) = &request.from_address {
+            let mut v = vec![0u8; 12];
+            v.append(&mut from_address.to_owned());
+            topics[2] = Some(ValueOrArray::Value(Some(H256::from_slice(&v[..]))));
+        }
+        if let Some(to_address) = &request.to_address {
+            let mut v = vec![0u8; 12];
+            v.append(&mut to_address.to_owned());
+            topics[3] = Some(ValueOrArray::Value(Some(H256::from_slice(&v[..]))));
+        }
+        let filter = Filter { topics, ..request.ethers_log_filter()? };
+        let logs = source.get_logs(&filter).await?;
+        Ok(filter_erc1155_transfers(logs, &query))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::Erc1155Transfers)?;
+        process_erc1155_transfers(response, columns, schema)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for Erc1155Transfers {
+    type Response = Vec<Log>;
+
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
+        let logs = source.get_transaction_logs(request.transaction_hash()?).await?;
+        Ok(filter_erc1155_transfers(logs, &query))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::Erc1155Transfers)?;
+        process_erc1155_transfers(response, columns, schema)
+    }
+}
+
+/// keep decodable TransferSingle and TransferBatch logs, counting others as skipped, such as
+/// batches whose ids and values have different lengths
+fn filter_erc1155_transfers(logs: Vec<Log>, query: &Query) -> Vec<Log> {
+    let (transfers, malformed): (Vec<Log>, Vec<Log>) = logs
+        .into_iter()
+        .filter(|log| {
+            log.topics.first() == Some(&*EVENT_ERC1155_TRANSFER_SINGLE) ||
+                log.topics.first() == Some(&*EVENT_ERC1155_TRANSFER_BATCH)
+        })
+        .partition(|log| log.topics.len() == 4 && decode_erc1155_transfers(log).is_some());
+    query.skipped_rows.add(Datatype::Erc1155Transfers, malformed.len() as u64);
+    transfers
+}
+
+/// decode (token id, value) of each transfer of a TransferSingle or TransferBatch log
+///
+/// returns None for logs of other events or that do not follow the event encoding
+pub(crate) fn decode_erc1155_transfers(log: &Log) -> Option<Vec<(U256, U256)>> {
+    let topic0 = *log.topics.first()?;
+    if topic0 == *EVENT_ERC1155_TRANSFER_SINGLE {
+        if log.data.len() != 64 {
+            return None
+        }
+        let token_id = U256::from_big_endian(&log.data[..32]);
+        let value = U256::from_big_endian(&log.data[32..]);
+        Some(vec![(token_id, value)])
+    } else if topic0 == *EVENT_ERC1155_TRANSFER_BATCH {
+        let uint_array = ParamType::Array(Box::new(ParamType::Uint(256)));
+        let tokens = ethers::abi::decode(&[uint_array.clone(), uint_array], &log.data).ok()?;
+        let token_ids = tokens.first()?.clone().into_array()?;
+        let values = tokens.get(1)?.clone().into_array()?;
+        if token_ids.len() != values.len() {
+            return None
+        }
+        token_ids
+            .into_iter()
+            .zip(values)
+            .map(|(token_id, value)| Some((token_id.into_uint()?, value.into_uint()?)))
+            .collect()
+    } else {
+        None
+    }
+}
+
+/// process logs into columns, with one row per token id of each transfer
+fn process_erc1155_transfers(
+    logs: Vec<Log>,
+    columns: &mut Erc1155Transfers,
+    schema: &Table,
+) -> R<()> {
+    for log in logs.iter() {
+        if let (Some(bn), Some(tx), Some(ti), Some(li), Some(transfers)) = (
+            log.block_number,
+            log.transaction_hash,
+            log.transaction_index,
+            log.log_index,
+            decode_erc1155_transfers(log),
+        ) {
+            for (token_id, value) in transfers.into_iter() {
+                columns.n_rows += 1;
+                store!(schema, columns, block_number, bn.as_u32());
+                store!(
+                    schema,
+                    columns,
+                    block_hash,
+                    log.block_hash.map(|bh| bh.as_bytes().to_vec())
+                );
+                store!(schema, columns, transaction_index, ti.as_u32());
+                store!(schema, columns, log_index, li.as_u32());
+                store!(schema, columns, transaction_hash, tx.as_bytes().to_vec());
+                store!(schema, columns, erc1155, log.address.as_bytes().to_vec());
+                store!(schema, columns, operator, log.topics[1].as_bytes()[12..].to_vec());
+                store!(schema, columns, from_address, log.topics[2].as_bytes()[12..].to_vec());
+                store!(schema, columns, to_address, log.topics[3].as_bytes()[12..].to_vec());
+                store!(schema, columns, token_id, token_id);
+                store!(schema, columns, value, value);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::Token;
+
+    #[test]
+    fn test_process_erc1155_transfers() {
+        let cols = Some(vec!["all".to_string()]);
+        let schema = Datatype::Erc1155Transfers
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &cols,
+                None,
+                None,
+            )
+            .unwrap();
+        let address_topic = |x: u8| H256::from(H160([x; 20]));
+        let log = |topic0: H256, data: Vec<u8>| Log {
+            topics: vec![topic0, address_topic(9), address_topic(1), address_topic(2)],
+            data: data.into(),
+            block_number: Some(100.into()),
+            transaction_hash: Some(H256::zero()),
+            transaction_index: Some(0.into()),
+            log_index: Some(0.into()),
+            ..Default::default()
+        };
+        let uints = |values: &[u64]| {
+            Token::Array(values.iter().map(|value| Token::Uint((*value).into())).collect())
+        };
+        let single = ethers::abi::encode(&[Token::Uint(5.into()), Token::Uint(1.into())]);
+        let logs = vec![
+            log(*EVENT_ERC1155_TRANSFER_SINGLE, single),
+            log(
+                *EVENT_ERC1155_TRANSFER_BATCH,
+                ethers::abi::encode(&[uints(&[7, 8]), uints(&[2, 3])]),
+            ),
+            log(*EVENT_ERC1155_TRANSFER_BATCH, ethers::abi::encode(&[uints(&[7, 8]), uints(&[2])])),
+            log(*EVENT_ERC20_TRANSFER, vec![0; 32]),
+        ];
+        // batch with mismatched ids and values is skipped, as are logs of other events
+        assert!(decode_erc1155_transfers(&logs[2]).is_none());
+        assert!(decode_erc1155_transfers(&logs[3]).is_none());
+        let logs: Vec<Log> =
+            logs.into_iter().filter(|log| decode_erc1155_transfers(log).is_some()).collect();
+
+        let mut columns = Erc1155Transfers::default();
+        process_erc1155_transfers(logs, &mut columns, &schema).unwrap();
+        assert_eq!(columns.n_rows, 3);
+        assert_eq!(columns.token_id, vec![5.into(), 7.into(), 8.into()]);
+        assert_eq!(columns.value, vec![1.into(), 2.into(), 3.into()]);
+        assert_eq!(columns.operator, vec![vec![9; 20]; 3]);
+        assert_eq!(columns.from_address, vec![vec![1; 20]; 3]);
+        assert_eq!(columns.to_address, vec![vec![2; 20]; 3]);
+    }
+}
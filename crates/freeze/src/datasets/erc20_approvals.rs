@@ -47,6 +47,16 @@ impl Dataset for Erc20Approvals {
     fn arg_aliases() -> Option<std::collections::HashMap<Dim, Dim>> {
         Some([(Dim::Contract, Dim::Address)].into_iter().collect())
     }
+
+    fn column_lineage() -> Vec<(&'static str, ColumnLineage)> {
+        let abi = "event Approval(address indexed owner, address indexed spender, uint256 value)";
+        vec![
+            ("erc20", ColumnLineage::new(&["address"], "log address")),
+            ("from_address", ColumnLineage::new(&["topic1"], abi)),
+            ("to_address", ColumnLineage::new(&["topic2"], abi)),
+            ("value", ColumnLineage::new(&["data"], abi)),
+        ]
+    }
 }
 
 #[async_trait::async_trait]
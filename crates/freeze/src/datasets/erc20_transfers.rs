@@ -21,6 +21,10 @@ pub struct Erc20Transfers {
 
 #[async_trait::async_trait]
 impl Dataset for Erc20Transfers {
+    fn aliases() -> Vec<&'static str> {
+        vec!["token_transfers"]
+    }
+
     fn default_columns() -> Option<Vec<&'static str>> {
         Some(vec![
             "block_number",
@@ -47,13 +51,23 @@ impl Dataset for Erc20Transfers {
     fn arg_aliases() -> Option<std::collections::HashMap<Dim, Dim>> {
         Some([(Dim::Contract, Dim::Address)].into_iter().collect())
     }
+
+    fn column_lineage() -> Vec<(&'static str, ColumnLineage)> {
+        let abi = "event Transfer(address indexed from, address indexed to, uint256 value)";
+        vec![
+            ("erc20", ColumnLineage::new(&["address"], "log address")),
+            ("from_address", ColumnLineage::new(&["topic1"], abi)),
+            ("to_address", ColumnLineage::new(&["topic2"], abi)),
+            ("value", ColumnLineage::new(&["data"], abi)),
+        ]
+    }
 }
 
 #[async_trait::async_trait]
 impl CollectByBlock for Erc20Transfers {
     type Response = Vec<Log>;
 
-    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
         let mut topics = [Some(ValueOrArray::Value(Some(*EVENT_ERC20_TRANSFER))), None, None, None];
         if let Some(from_address) = &request.from_address {
             let mut v = vec![0u8; 12];
@@ -67,8 +81,7 @@ impl CollectByBlock for Erc20Transfers {
         }
         let filter = Filter { topics, ..request.ethers_log_filter()? };
         let logs = source.get_logs(&filter).await?;
-
-        Ok(logs.into_iter().filter(|x| x.topics.len() == 3 && x.data.len() == 32).collect())
+        Ok(filter_erc20_transfers(logs, &query))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
@@ -81,9 +94,9 @@ impl CollectByBlock for Erc20Transfers {
 impl CollectByTransaction for Erc20Transfers {
     type Response = Vec<Log>;
 
-    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
         let logs = source.get_transaction_logs(request.transaction_hash()?).await?;
-        Ok(logs.into_iter().filter(is_erc20_transfer).collect())
+        Ok(filter_erc20_transfers(logs, &query))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
@@ -92,8 +105,14 @@ impl CollectByTransaction for Erc20Transfers {
     }
 }
 
-fn is_erc20_transfer(log: &Log) -> bool {
-    log.topics.len() == 3 && log.data.len() == 32 && log.topics[0] == *EVENT_ERC20_TRANSFER
+/// keep well-formed Transfer logs, counting Transfer logs of other shapes (e.g. erc721) as skipped
+fn filter_erc20_transfers(logs: Vec<Log>, query: &Query) -> Vec<Log> {
+    let (transfers, malformed): (Vec<Log>, Vec<Log>) = logs
+        .into_iter()
+        .filter(|log| log.topics.first() == Some(&*EVENT_ERC20_TRANSFER))
+        .partition(|log| log.topics.len() == 3 && log.data.len() == 32);
+    query.skipped_rows.add(Datatype::Erc20Transfers, malformed.len() as u64);
+    transfers
 }
 
 /// process block into columns
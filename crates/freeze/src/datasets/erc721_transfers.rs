@@ -47,6 +47,16 @@ impl Dataset for Erc721Transfers {
     fn arg_aliases() -> Option<std::collections::HashMap<Dim, Dim>> {
         Some([(Dim::Contract, Dim::Address)].into_iter().collect())
     }
+
+    fn column_lineage() -> Vec<(&'static str, ColumnLineage)> {
+        let abi = "event Transfer(address indexed from, address indexed to, uint256 indexed tokenId)";
+        vec![
+            ("erc20", ColumnLineage::new(&["address"], "log address")),
+            ("from_address", ColumnLineage::new(&["topic1"], abi)),
+            ("to_address", ColumnLineage::new(&["topic2"], abi)),
+            ("token_id", ColumnLineage::new(&["topic3"], abi)),
+        ]
+    }
 }
 
 #[async_trait::async_trait]
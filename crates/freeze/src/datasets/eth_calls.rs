@@ -1,5 +1,6 @@
-use crate::*;
+use crate::{retries::revert_reason, *};
 use ethers::prelude::*;
+use ethers_core::abi::{ParamType, Token};
 use polars::prelude::*;
 
 /// columns for transactions
@@ -13,13 +14,32 @@ pub struct EthCalls {
     call_data_hash: Vec<Vec<u8>>,
     output_data: Vec<Option<Vec<u8>>>,
     output_data_hash: Vec<Option<Vec<u8>>>,
+    revert_reason: Vec<Option<String>>,
+    u256_output_cols: indexmap::IndexMap<String, Vec<Option<U256>>>,
+    i256_output_cols: indexmap::IndexMap<String, Vec<Option<I256>>>,
+    u64_output_cols: indexmap::IndexMap<String, Vec<Option<u64>>>,
+    i64_output_cols: indexmap::IndexMap<String, Vec<Option<i64>>>,
+    binary_output_cols: indexmap::IndexMap<String, Vec<Option<Vec<u8>>>>,
+    bool_output_cols: indexmap::IndexMap<String, Vec<Option<bool>>>,
+    string_output_cols: indexmap::IndexMap<String, Vec<Option<String>>>,
     chain_id: Vec<u64>,
 }
 
 #[async_trait::async_trait]
 impl Dataset for EthCalls {
     fn default_columns() -> Option<Vec<&'static str>> {
-        Some(vec!["block_number", "contract_address", "call_data", "output_data", "chain_id"])
+        Some(vec![
+            "block_number",
+            "contract_address",
+            "call_data",
+            "output_data",
+            "revert_reason",
+            "chain_id",
+        ])
+    }
+
+    fn aliases() -> Vec<&'static str> {
+        vec!["calls"]
     }
 
     fn default_sort() -> Option<Vec<&'static str>> {
@@ -39,7 +59,7 @@ impl Dataset for EthCalls {
     }
 }
 
-type EthCallsResponse = (u32, Vec<u8>, Vec<u8>, Option<Vec<u8>>);
+type EthCallsResponse = (u32, Vec<u8>, Vec<u8>, Option<Vec<u8>>, Option<String>);
 
 #[async_trait::async_trait]
 impl CollectByBlock for EthCalls {
@@ -52,8 +72,16 @@ impl CollectByBlock for EthCalls {
             ..Default::default()
         };
         let number = request.block_number()?;
-        let output = source.call(transaction, number.into()).await.ok().map(|x| x.to_vec());
-        Ok((number as u32, request.contract()?, request.call_data()?, output))
+        // reverted calls are stored with their revert reason, other errors fail the chunk
+        let (output, revert_reason) = match source.call(transaction, number.into()).await {
+            Ok(output) => (Some(output.to_vec()), None),
+            Err(CollectError::ProviderError(e)) => match revert_reason(&e) {
+                Some(reason) => (None, Some(reason)),
+                None => return Err(CollectError::ProviderError(e)),
+            },
+            Err(e) => return Err(e),
+        };
+        Ok((number as u32, request.contract()?, request.call_data()?, output, revert_reason))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
@@ -68,17 +96,157 @@ impl CollectByTransaction for EthCalls {
 }
 
 fn process_eth_call(response: EthCallsResponse, columns: &mut EthCalls, schema: &Table) {
-    let (block_number, contract_address, call_data, output_data) = response;
+    let (block_number, contract_address, call_data, output_data, revert_reason) = response;
     columns.n_rows += 1;
     store!(schema, columns, block_number, block_number);
     store!(schema, columns, contract_address, contract_address);
     store!(schema, columns, call_data, call_data.clone());
-    store!(schema, columns, call_data_hash, ethers_core::utils::keccak256(call_data).into());
+    store!(schema, columns, call_data_hash, ethers_core::utils::keccak256(&call_data).into());
     store!(schema, columns, output_data, output_data.clone());
     store!(
         schema,
         columns,
         output_data_hash,
-        output_data.map(|data| ethers_core::utils::keccak256(data).into())
+        output_data.as_ref().map(|data| ethers_core::utils::keccak256(data).into())
     );
+    store!(schema, columns, revert_reason, revert_reason);
+
+    // every decoder gets a value in each row, which is null unless the call is to its function
+    for decoder in schema.call_decoders.iter() {
+        let tokens = match (call_data.get(..4) == Some(&decoder.selector()[..]), &output_data) {
+            (true, Some(output_data)) => decoder.decode_output(output_data).ok(),
+            _ => None,
+        };
+        let outputs = decoder.function.outputs.iter().zip(decoder.output_columns());
+        for (i, (output, (column, _))) in outputs.enumerate() {
+            let token = tokens.as_ref().and_then(|tokens| tokens.get(i)).cloned();
+            store_output(columns, &output.kind, column, token);
+        }
+    }
+}
+
+/// store decoded output of call in the output column of its type
+fn store_output(columns: &mut EthCalls, kind: &ParamType, column: String, token: Option<Token>) {
+    match kind {
+        ParamType::Uint(bits) if *bits > 64 => {
+            let value = token.and_then(|token| token.into_uint());
+            columns.u256_output_cols.entry(column).or_default().push(value)
+        }
+        ParamType::Int(bits) if *bits > 64 => {
+            let value = token.and_then(|token| token.into_int()).map(I256::from_raw);
+            columns.i256_output_cols.entry(column).or_default().push(value)
+        }
+        // abi decoding does not range check words of small ints, values that do not fit are
+        // stored as null like other outputs that cannot be decoded
+        ParamType::Uint(_) => {
+            let value =
+                token.and_then(|token| token.into_uint()).and_then(|x| u64::try_from(x).ok());
+            columns.u64_output_cols.entry(column).or_default().push(value)
+        }
+        ParamType::Int(_) => {
+            let value = token
+                .and_then(|token| token.into_int())
+                .and_then(|x| i64::try_from(I256::from_raw(x)).ok());
+            columns.i64_output_cols.entry(column).or_default().push(value)
+        }
+        ParamType::Address | ParamType::Bytes | ParamType::FixedBytes(_) => {
+            let value = token.and_then(|token| match token {
+                Token::Address(address) => Some(address.as_bytes().to_vec()),
+                Token::Bytes(bytes) | Token::FixedBytes(bytes) => Some(bytes),
+                _ => None,
+            });
+            columns.binary_output_cols.entry(column).or_default().push(value)
+        }
+        ParamType::Bool => {
+            let value = token.and_then(|token| token.into_bool());
+            columns.bool_output_cols.entry(column).or_default().push(value)
+        }
+        ParamType::String => {
+            let value = token.and_then(|token| token.into_string());
+            columns.string_output_cols.entry(column).or_default().push(value)
+        }
+        ParamType::Array(_) | ParamType::FixedArray(_, _) | ParamType::Tuple(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_call_outputs() {
+        let mut schema = Datatype::EthCalls
+            .table_schema(
+                &[U256Type::Binary, U256Type::F64],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &None,
+                None,
+                None,
+            )
+            .unwrap();
+        let total_supply = CallDecoder::new("totalSupply()(uint256)".to_string()).unwrap();
+        let paused = CallDecoder::new("paused()(bool)".to_string()).unwrap();
+        schema.set_call_decoders(&[total_supply.clone(), paused]);
+
+        let mut columns = EthCalls::default();
+        let output = ethers_core::abi::encode(&[Token::Uint(U256::from(1000))]);
+        let call_data = total_supply.selector().to_vec();
+        process_eth_call(
+            (1, vec![0; 20], call_data.clone(), Some(output), None),
+            &mut columns,
+            &schema,
+        );
+        let reason = Some("execution reverted".to_string());
+        process_eth_call((2, vec![0; 20], call_data, None, reason.clone()), &mut columns, &schema);
+
+        assert_eq!(columns.u256_output_cols["totalSupply"], vec![Some(U256::from(1000)), None]);
+        assert_eq!(columns.bool_output_cols["paused"], vec![None, None]);
+        assert_eq!(columns.revert_reason, vec![None, reason]);
+
+        let mut schemas = std::collections::HashMap::new();
+        schemas.insert(Datatype::EthCalls, schema);
+        let df = &columns.create_dfs(&schemas, 1).unwrap()[&Datatype::EthCalls];
+        assert_eq!(df.height(), 2);
+        let total_supply = df.column("totalSupply_f64").unwrap().f64().unwrap();
+        assert_eq!(total_supply.get(0), Some(1000.0));
+        assert_eq!(total_supply.get(1), None);
+        assert_eq!(df.column("paused").unwrap().null_count(), 2);
+    }
+
+    #[test]
+    fn test_decode_out_of_range_small_int_outputs() {
+        let mut schema = Datatype::EthCalls
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &None,
+                None,
+                None,
+            )
+            .unwrap();
+        let decimals = CallDecoder::new("decimals()(uint8)".to_string()).unwrap();
+        let offset = CallDecoder::new("offset()(int32)".to_string()).unwrap();
+        schema.set_call_decoders(&[decimals.clone(), offset.clone()]);
+
+        // words that overflow the declared output types are stored as null instead of panicking
+        let mut columns = EthCalls::default();
+        let overflow = ethers_core::abi::encode(&[Token::Uint(U256::MAX)]);
+        let negative = ethers_core::abi::encode(&[Token::Int(I256::from(-5).into_raw())]);
+        let huge_negative = ethers_core::abi::encode(&[Token::Int(I256::MIN.into_raw())]);
+        for (decoder, output) in [
+            (&decimals, overflow),
+            (&decimals, ethers_core::abi::encode(&[Token::Uint(U256::from(18))])),
+            (&offset, negative),
+            (&offset, huge_negative),
+        ] {
+            let call = (1, vec![0; 20], decoder.selector().to_vec(), Some(output), None);
+            process_eth_call(call, &mut columns, &schema);
+        }
+        assert_eq!(columns.u64_output_cols["decimals"], vec![None, Some(18), None, None]);
+        assert_eq!(columns.i64_output_cols["offset"], vec![None, None, Some(-5), None]);
+    }
 }
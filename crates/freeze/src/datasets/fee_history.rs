@@ -0,0 +1,170 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+
+/// reward percentiles used when none are specified
+pub const DEFAULT_FEE_PERCENTILES: [f64; 3] = [25.0, 50.0, 75.0];
+
+/// maximum number of blocks that nodes return from a single eth_feeHistory request
+const MAX_FEE_HISTORY_BLOCKS: u64 = 1024;
+
+/// columns for fee history
+#[cryo_to_df::to_df(Datatype::FeeHistory)]
+#[derive(Default)]
+pub struct FeeHistory {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    base_fee_per_gas: Vec<Option<u64>>,
+    gas_used_ratio: Vec<f64>,
+    reward_cols: indexmap::IndexMap<String, Vec<u64>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for FeeHistory {
+    fn default_sort() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number"])
+    }
+
+    fn use_block_ranges() -> bool {
+        true
+    }
+}
+
+/// name of the reward column of a percentile, e.g. reward_25th
+pub fn reward_column(percentile: f64) -> String {
+    format!("reward_{}th", percentile)
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for FeeHistory {
+    type Response = Vec<ethers::types::FeeHistory>;
+
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
+        let schema = query.schemas.get_schema(&Datatype::FeeHistory)?;
+        let (start, end) = request.block_range()?;
+        let requests = (start..=end).step_by(MAX_FEE_HISTORY_BLOCKS as usize).map(|first| {
+            let last = std::cmp::min(first + MAX_FEE_HISTORY_BLOCKS - 1, end);
+            source.get_fee_history(last - first + 1, last, &schema.fee_percentiles)
+        });
+        futures::future::try_join_all(requests).await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::FeeHistory)?;
+        for fee_history in response.into_iter() {
+            process_fee_history(fee_history, columns, schema)?;
+        }
+        Ok(())
+    }
+}
+
+impl CollectByTransaction for FeeHistory {
+    type Response = ();
+}
+
+/// process fee history into one row per block
+///
+/// base_fee_per_gas includes the base fee of the block after the last block, which is not stored,
+/// and is empty on chains without base fees
+fn process_fee_history(
+    fee_history: ethers::types::FeeHistory,
+    columns: &mut FeeHistory,
+    schema: &Table,
+) -> R<()> {
+    let oldest_block = fee_history.oldest_block.as_u32();
+    let reward_columns: Vec<_> = schema
+        .fee_percentiles
+        .iter()
+        .map(|percentile| reward_column(*percentile))
+        .enumerate()
+        .filter(|(_, column)| schema.has_column(column))
+        .collect();
+    for (_, column) in reward_columns.iter() {
+        columns.reward_cols.entry(column.clone()).or_default();
+    }
+    for (i, gas_used_ratio) in fee_history.gas_used_ratio.into_iter().enumerate() {
+        let base_fee_per_gas = fee_history.base_fee_per_gas.get(i).map(|x| x.as_u64());
+        columns.n_rows += 1;
+        store!(schema, columns, block_number, oldest_block + i as u32);
+        store!(schema, columns, base_fee_per_gas, base_fee_per_gas);
+        store!(schema, columns, gas_used_ratio, gas_used_ratio);
+        for (p, column) in reward_columns.iter() {
+            let reward = fee_history
+                .reward
+                .get(i)
+                .and_then(|rewards| rewards.get(*p))
+                .ok_or(err("fee history is missing reward percentile of block"))?;
+            if let Some(values) = columns.reward_cols.get_mut(column) {
+                values.push(reward.as_u64());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_fee_history() {
+        let cols = Some(vec!["all".to_string()]);
+        let mut schema = Datatype::FeeHistory
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &cols,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            schema.columns(),
+            vec![
+                "block_number",
+                "base_fee_per_gas",
+                "gas_used_ratio",
+                "reward_25th",
+                "reward_50th",
+                "reward_75th",
+                "chain_id"
+            ]
+        );
+        schema.set_fee_percentiles(&[10.0, 99.5]);
+        assert_eq!(
+            schema.columns(),
+            vec![
+                "block_number",
+                "base_fee_per_gas",
+                "gas_used_ratio",
+                "reward_10th",
+                "reward_99.5th",
+                "chain_id"
+            ]
+        );
+
+        let fee_history = ethers::types::FeeHistory {
+            oldest_block: 100.into(),
+            base_fee_per_gas: vec![7.into(), 8.into(), 9.into()],
+            gas_used_ratio: vec![0.25, 0.75],
+            reward: vec![vec![1.into(), 2.into()], vec![3.into(), 4.into()]],
+        };
+        let mut columns = FeeHistory::default();
+        process_fee_history(fee_history, &mut columns, &schema).unwrap();
+        assert_eq!(columns.n_rows, 2);
+        assert_eq!(columns.block_number, vec![100, 101]);
+        assert_eq!(columns.base_fee_per_gas, vec![Some(7), Some(8)]);
+        assert_eq!(columns.gas_used_ratio, vec![0.25, 0.75]);
+        assert_eq!(columns.reward_cols["reward_10th"], vec![1, 3]);
+        assert_eq!(columns.reward_cols["reward_99.5th"], vec![2, 4]);
+
+        let schemas: std::collections::HashMap<_, _> =
+            [(Datatype::FeeHistory, schema)].into_iter().collect();
+        let dfs = columns.create_dfs(&schemas, 1).unwrap();
+        let df = &dfs[&Datatype::FeeHistory];
+        assert_eq!(df.get_column_names(), schemas[&Datatype::FeeHistory].columns());
+    }
+}
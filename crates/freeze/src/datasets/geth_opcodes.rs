@@ -32,6 +32,10 @@ impl Dataset for GethOpcodes {
         let f = |x: &&str| x != &"memory" && x != &"stack" && x != &"storage";
         Some(GethOpcodes::column_types().into_keys().filter(f).collect())
     }
+
+    fn discouraged_formats() -> Vec<DiscouragedFormat> {
+        DiscouragedFormat::text_formats("one row per opcode produces gigabytes of text")
+    }
 }
 
 #[async_trait::async_trait]
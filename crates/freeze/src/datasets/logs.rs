@@ -9,6 +9,7 @@ pub struct Logs {
     n_rows: u64,
     block_number: Vec<u32>,
     block_hash: Vec<Option<Vec<u8>>>,
+    block_timestamp: Vec<u32>,
     transaction_index: Vec<u32>,
     log_index: Vec<u32>,
     transaction_hash: Vec<Vec<u8>>,
@@ -48,6 +49,10 @@ impl Dataset for Logs {
         ])
     }
 
+    fn dedup_key() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "log_index", "transaction_hash"])
+    }
+
     fn optional_parameters() -> Vec<Dim> {
         vec![Dim::Address, Dim::Topic0, Dim::Topic1, Dim::Topic2, Dim::Topic3]
     }
@@ -59,19 +64,25 @@ impl Dataset for Logs {
     fn arg_aliases() -> Option<std::collections::HashMap<Dim, Dim>> {
         Some([(Dim::Contract, Dim::Address)].into_iter().collect())
     }
+
+    fn column_lineage() -> Vec<(&'static str, ColumnLineage)> {
+        vec![("block_timestamp", ColumnLineage::new(&["timestamp"], "timestamp of block header"))]
+    }
 }
 
 #[async_trait::async_trait]
 impl CollectByBlock for Logs {
     type Response = Vec<Log>;
 
-    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
-        source.get_logs(&request.ethers_log_filter()?).await
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
+        let logs = source.get_logs(&request.ethers_log_filter()?).await?;
+        fetch_block_timestamps(&logs, &source, &query).await?;
+        Ok(logs)
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
         let schema = query.schemas.get_schema(&Datatype::Logs)?;
-        process_logs(response, columns, schema)
+        process_logs(response, columns, schema, query)
     }
 }
 
@@ -79,18 +90,44 @@ impl CollectByBlock for Logs {
 impl CollectByTransaction for Logs {
     type Response = Vec<Log>;
 
-    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
-        source.get_transaction_logs(request.transaction_hash()?).await
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
+        let logs = source.get_transaction_logs(request.transaction_hash()?).await?;
+        fetch_block_timestamps(&logs, &source, &query).await?;
+        Ok(logs)
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
         let schema = query.schemas.get_schema(&Datatype::Logs)?;
-        process_logs(response, columns, schema)
+        process_logs(response, columns, schema, query)
     }
 }
 
+/// fetch timestamps of the blocks of logs that have not been fetched yet, if they are needed
+async fn fetch_block_timestamps(logs: &[Log], source: &Source, query: &Query) -> R<()> {
+    if !query.schemas.get_schema(&Datatype::Logs)?.has_column("block_timestamp") {
+        return Ok(())
+    }
+    let block_numbers: std::collections::BTreeSet<u64> = logs
+        .iter()
+        .filter_map(|log| log.block_number.map(|bn| bn.as_u64()))
+        .filter(|bn| query.block_timestamps.get(*bn).is_none())
+        .collect();
+    let requests = block_numbers.into_iter().map(|block_number| async move {
+        let block = source
+            .get_block(block_number)
+            .await?
+            .ok_or(CollectError::BlockNotFound(block_number))?;
+        query.block_timestamps.add(block_number, block.timestamp.as_u32());
+        Ok::<(), CollectError>(())
+    });
+    futures::future::try_join_all(requests).await?;
+    Ok(())
+}
+
 /// process block into columns
-fn process_logs(logs: Vec<Log>, columns: &mut Logs, schema: &Table) -> R<()> {
+///
+/// when decoding events, logs that do not match the event signature are counted as skipped
+fn process_logs(logs: Vec<Log>, columns: &mut Logs, schema: &Table, query: &Query) -> R<()> {
     let decode_keys = match &schema.log_decoder {
         None => None,
         Some(decoder) => {
@@ -105,6 +142,7 @@ fn process_logs(logs: Vec<Log>, columns: &mut Logs, schema: &Table) -> R<()> {
         }
     };
 
+    let mut n_undecodable = 0;
     for log in logs.iter() {
         if let (Some(bn), Some(tx), Some(ti), Some(li)) =
             (log.block_number, log.transaction_hash, log.transaction_index, log.log_index)
@@ -119,10 +157,20 @@ fn process_logs(logs: Vec<Log>, columns: &mut Logs, schema: &Table) -> R<()> {
                             }
                         }
                     }
-                    Err(_) => continue,
+                    Err(_) => {
+                        n_undecodable += 1;
+                        continue
+                    }
                 }
             };
 
+            if schema.has_column("block_timestamp") {
+                let timestamp = query.block_timestamps.get(bn.as_u64()).ok_or_else(|| {
+                    err(format!("timestamp of block {} was not fetched", bn).as_str())
+                })?;
+                columns.block_timestamp.push(timestamp);
+            }
+
             columns.n_rows += 1;
             store!(schema, columns, block_number, bn.as_u32());
             store!(schema, columns, block_hash, log.block_hash.map(|bh| bh.as_bytes().to_vec()));
@@ -150,6 +198,7 @@ fn process_logs(logs: Vec<Log>, columns: &mut Logs, schema: &Table) -> R<()> {
             }
         }
     }
+    query.skipped_rows.add(Datatype::Logs, n_undecodable);
 
     Ok(())
 }
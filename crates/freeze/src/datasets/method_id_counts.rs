@@ -0,0 +1,144 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::BTreeMap;
+
+/// columns for method id counts
+#[cryo_to_df::to_df(Datatype::MethodIdCounts)]
+#[derive(Default)]
+pub struct MethodIdCounts {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    method_id: Vec<Vec<u8>>,
+    call_count: Vec<u64>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for MethodIdCounts {
+    fn default_sort() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "method_id"])
+    }
+
+    fn column_lineage() -> Vec<(&'static str, ColumnLineage)> {
+        vec![
+            ("method_id", ColumnLineage::new(&["input"], "input[:4] of transaction")),
+            (
+                "call_count",
+                ColumnLineage::new(&["input"], "count of transactions in block with method_id"),
+            ),
+        ]
+    }
+}
+
+/// block number, and the method id of each transaction in block that calls a contract
+type BlockMethodIds = (u32, Vec<Vec<u8>>);
+
+#[async_trait::async_trait]
+impl CollectByBlock for MethodIdCounts {
+    type Response = BlockMethodIds;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let block_number = request.block_number()?;
+        let block = source
+            .get_block_with_txs(block_number)
+            .await?
+            .ok_or(CollectError::BlockNotFound(block_number))?;
+        Ok((block_number as u32, block.transactions.iter().filter_map(method_id).collect()))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::MethodIdCounts)?;
+        process_method_ids(response, columns, schema)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for MethodIdCounts {
+    type Response = BlockMethodIds;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let tx = source
+            .get_transaction(request.ethers_transaction_hash()?)
+            .await?
+            .ok_or(CollectError::CollectError("transaction not found".to_string()))?;
+        let block_number = tx.block_number.ok_or(err("no block number for tx"))?.as_u32();
+        Ok((block_number, method_id(&tx).into_iter().collect()))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::MethodIdCounts)?;
+        process_method_ids(response, columns, schema)
+    }
+}
+
+/// method id of transaction, or None for contract creations and calls without a method id
+fn method_id(tx: &Transaction) -> Option<Vec<u8>> {
+    match tx.to {
+        Some(_) => tx.input.get(..4).map(|selector| selector.to_vec()),
+        None => None,
+    }
+}
+
+/// process method ids of block into one row per method id
+fn process_method_ids(
+    response: BlockMethodIds,
+    columns: &mut MethodIdCounts,
+    schema: &Table,
+) -> R<()> {
+    let (block_number, method_ids) = response;
+    let mut counts: BTreeMap<Vec<u8>, u64> = BTreeMap::new();
+    for method_id in method_ids.into_iter() {
+        *counts.entry(method_id).or_insert(0) += 1;
+    }
+    for (method_id, call_count) in counts.into_iter() {
+        columns.n_rows += 1;
+        store!(schema, columns, block_number, block_number);
+        store!(schema, columns, method_id, method_id);
+        store!(schema, columns, call_count, call_count);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_method_ids() {
+        let cols = Some(vec!["all".to_string()]);
+        let schema = Datatype::MethodIdCounts
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &cols,
+                None,
+                None,
+            )
+            .unwrap();
+        let tx = |to: Option<H160>, input: Vec<u8>| Transaction {
+            to,
+            input: input.into(),
+            ..Default::default()
+        };
+        let transfer = vec![0xa9, 0x05, 0x9c, 0xbb, 0x00];
+        let approve = vec![0x09, 0x5e, 0xa7, 0xb3];
+        let txs = [
+            tx(Some(H160::zero()), transfer.clone()),
+            tx(Some(H160::zero()), approve.clone()),
+            tx(Some(H160::zero()), transfer.clone()),
+            // plain eth transfer and contract creation have no method id
+            tx(Some(H160::zero()), vec![]),
+            tx(None, transfer.clone()),
+        ];
+        let method_ids = txs.iter().filter_map(method_id).collect();
+        let mut columns = MethodIdCounts::default();
+        process_method_ids((100, method_ids), &mut columns, &schema).unwrap();
+        assert_eq!(columns.n_rows, 2);
+        assert_eq!(columns.block_number, vec![100, 100]);
+        assert_eq!(columns.method_id, vec![approve, transfer[..4].to_vec()]);
+        assert_eq!(columns.call_count, vec![1, 2]);
+    }
+}
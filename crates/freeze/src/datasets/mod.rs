@@ -1,3 +1,5 @@
+/// access lists
+pub mod access_lists;
 /// address appearances
 pub mod address_appearances;
 /// balance diffs
@@ -14,6 +16,8 @@ pub mod code_diffs;
 pub mod code_reads;
 /// codes
 pub mod codes;
+/// contract code
+pub mod contract_code;
 /// contracts
 pub mod contracts;
 /// erc20 approval
@@ -30,8 +34,12 @@ pub mod erc20_transfers;
 pub mod erc721_metadata;
 /// erc721 transfers
 pub mod erc721_transfers;
+/// erc1155 transfers
+pub mod erc1155_transfers;
 /// eth calls
 pub mod eth_calls;
+/// fee history
+pub mod fee_history;
 /// four byte counts
 pub mod four_byte_counts;
 /// geth balance diffs
@@ -50,16 +58,24 @@ pub mod geth_storage_diffs;
 pub mod javascript_traces;
 /// logs
 pub mod logs;
+/// method id counts
+pub mod method_id_counts;
 /// native transfers
 pub mod native_transfers;
+/// nft transfers
+pub mod nft_transfers;
 /// nonce diffs
 pub mod nonce_diffs;
 /// nonce reads
 pub mod nonce_reads;
 /// nonces
 pub mod nonces;
+/// receipts
+pub mod receipts;
 /// slots
 pub mod slots;
+/// state changes
+pub mod state_changes;
 /// storage diffs
 pub mod storage_diffs;
 /// storage reads
@@ -70,9 +86,14 @@ pub mod trace_calls;
 pub mod traces;
 /// transactions
 pub mod transactions;
+/// uncle blocks
+pub mod uncle_blocks;
 /// vm traces
 pub mod vm_traces;
+/// withdrawals
+pub mod withdrawals;
 
+pub use access_lists::*;
 pub use address_appearances::*;
 pub use balance_diffs::*;
 pub use balance_reads::*;
@@ -81,6 +102,7 @@ pub use blocks::*;
 pub use code_diffs::*;
 pub use code_reads::*;
 pub use codes::*;
+pub use contract_code::*;
 pub use contracts::*;
 pub use erc20_approvals::*;
 pub use erc20_balances::*;
@@ -89,7 +111,9 @@ pub use erc20_supplies::*;
 pub use erc20_transfers::*;
 pub use erc721_metadata::*;
 pub use erc721_transfers::*;
+pub use erc1155_transfers::*;
 pub use eth_calls::*;
+pub use fee_history::*;
 pub use four_byte_counts::*;
 pub use geth_balance_diffs::*;
 pub use geth_calls::*;
@@ -99,14 +123,20 @@ pub use geth_opcodes::*;
 pub use geth_storage_diffs::*;
 pub use javascript_traces::*;
 pub use logs::*;
+pub use method_id_counts::*;
 pub use native_transfers::*;
+pub use nft_transfers::*;
 pub use nonce_diffs::*;
 pub use nonce_reads::*;
 pub use nonces::*;
+pub use receipts::*;
 pub use slots::*;
+pub use state_changes::*;
 pub use storage_diffs::*;
 pub use storage_reads::*;
 pub use trace_calls::*;
 pub use traces::*;
 pub use transactions::*;
+pub use uncle_blocks::*;
 pub use vm_traces::*;
+pub use withdrawals::*;
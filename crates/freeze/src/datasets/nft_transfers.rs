@@ -0,0 +1,225 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+
+/// columns for nft transfers
+#[cryo_to_df::to_df(Datatype::NftTransfers)]
+#[derive(Default)]
+pub struct NftTransfers {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    transaction_hash: Vec<Vec<u8>>,
+    log_index: Vec<u32>,
+    contract_address: Vec<Vec<u8>>,
+    from_address: Vec<Vec<u8>>,
+    to_address: Vec<Vec<u8>>,
+    token_id: Vec<U256>,
+    amount: Vec<U256>,
+    standard: Vec<String>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for NftTransfers {
+    fn default_sort() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "log_index", "token_id"])
+    }
+
+    fn optional_parameters() -> Vec<Dim> {
+        vec![Dim::Address, Dim::FromAddress, Dim::ToAddress]
+    }
+
+    fn use_block_ranges() -> bool {
+        true
+    }
+
+    fn arg_aliases() -> Option<std::collections::HashMap<Dim, Dim>> {
+        Some([(Dim::Contract, Dim::Address)].into_iter().collect())
+    }
+
+    fn column_lineage() -> Vec<(&'static str, ColumnLineage)> {
+        let from = "topic1 of erc721 Transfer, topic2 of erc1155 transfers";
+        let to = "topic2 of erc721 Transfer, topic3 of erc1155 transfers";
+        let token_id = "topic3 of erc721 Transfer, ids in data of erc1155 transfers";
+        let amount = "1 for erc721 Transfer, values in data of erc1155 transfers";
+        vec![
+            ("contract_address", ColumnLineage::new(&["address"], "log address")),
+            ("from_address", ColumnLineage::new(&["topic1", "topic2"], from)),
+            ("to_address", ColumnLineage::new(&["topic2", "topic3"], to)),
+            ("token_id", ColumnLineage::new(&["topic3", "data"], token_id)),
+            ("amount", ColumnLineage::new(&["data"], amount)),
+            ("standard", ColumnLineage::new(&["topic0"], "event of topic0")),
+        ]
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for NftTransfers {
+    type Response = Vec<Log>;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let from_topic = request.from_address.as_ref().map(|address| address_topic(address));
+        let to_topic = request.to_address.as_ref().map(|address| address_topic(address));
+
+        // the from and to addresses are at different topics in erc721 and erc1155 transfers
+        let erc721_topics = [
+            Some(ValueOrArray::Value(Some(*EVENT_ERC721_TRANSFER))),
+            from_topic.clone(),
+            to_topic.clone(),
+            None,
+        ];
+        let erc721_filter = Filter { topics: erc721_topics, ..request.ethers_log_filter()? };
+        let erc1155_events =
+            vec![Some(*EVENT_ERC1155_TRANSFER_SINGLE), Some(*EVENT_ERC1155_TRANSFER_BATCH)];
+        let erc1155_topics =
+            [Some(ValueOrArray::Array(erc1155_events)), None, from_topic, to_topic];
+        let erc1155_filter = Filter { topics: erc1155_topics, ..request.ethers_log_filter()? };
+
+        let mut logs = source.get_logs(&erc721_filter).await?;
+        logs.extend(source.get_logs(&erc1155_filter).await?);
+        Ok(logs.into_iter().filter(is_nft_transfer).collect())
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::NftTransfers)?;
+        process_nft_transfers(response, columns, schema)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for NftTransfers {
+    type Response = Vec<Log>;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let logs = source.get_transaction_logs(request.transaction_hash()?).await?;
+        Ok(logs.into_iter().filter(is_nft_transfer).collect())
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::NftTransfers)?;
+        process_nft_transfers(response, columns, schema)
+    }
+}
+
+fn address_topic(address: &[u8]) -> ValueOrArray<Option<H256>> {
+    let mut v = vec![0u8; 12];
+    v.extend_from_slice(address);
+    ValueOrArray::Value(Some(H256::from_slice(&v[..])))
+}
+
+/// erc721 transfers have an indexed token id, unlike erc20 transfers with the same topic0
+fn is_nft_transfer(log: &Log) -> bool {
+    match log.topics.first() {
+        Some(topic0) if *topic0 == *EVENT_ERC721_TRANSFER => {
+            log.topics.len() == 4 && log.data.len() == 0
+        }
+        Some(topic0) => {
+            (*topic0 == *EVENT_ERC1155_TRANSFER_SINGLE || *topic0 == *EVENT_ERC1155_TRANSFER_BATCH) &&
+                log.topics.len() == 4
+        }
+        None => false,
+    }
+}
+
+/// decode log into standard, from address, to address, and each (token id, amount)
+///
+/// returns None for logs that do not follow the erc721 or erc1155 event encoding
+fn decode_nft_transfer(log: &Log) -> Option<(&'static str, &[u8], &[u8], Vec<(U256, U256)>)> {
+    let topic0 = *log.topics.first()?;
+    if log.topics.len() != 4 {
+        return None
+    }
+    if topic0 == *EVENT_ERC721_TRANSFER {
+        let token_id = U256::from_big_endian(log.topics[3].as_bytes());
+        let from_address = &log.topics[1].as_bytes()[12..];
+        let to_address = &log.topics[2].as_bytes()[12..];
+        Some(("erc721", from_address, to_address, vec![(token_id, U256::one())]))
+    } else {
+        let standard = match topic0 == *EVENT_ERC1155_TRANSFER_SINGLE {
+            true => "erc1155_single",
+            false => "erc1155_batch",
+        };
+        let transfers = erc1155_transfers::decode_erc1155_transfers(log)?;
+        let from_address = &log.topics[2].as_bytes()[12..];
+        let to_address = &log.topics[3].as_bytes()[12..];
+        Some((standard, from_address, to_address, transfers))
+    }
+}
+
+/// process logs into columns, with one row per token id of each transfer
+fn process_nft_transfers(logs: Vec<Log>, columns: &mut NftTransfers, schema: &Table) -> R<()> {
+    for log in logs.iter() {
+        if let (Some(bn), Some(tx), Some(li), Some((standard, from, to, transfers))) =
+            (log.block_number, log.transaction_hash, log.log_index, decode_nft_transfer(log))
+        {
+            for (token_id, amount) in transfers.into_iter() {
+                columns.n_rows += 1;
+                store!(schema, columns, block_number, bn.as_u32());
+                store!(schema, columns, transaction_hash, tx.as_bytes().to_vec());
+                store!(schema, columns, log_index, li.as_u32());
+                store!(schema, columns, contract_address, log.address.as_bytes().to_vec());
+                store!(schema, columns, from_address, from.to_vec());
+                store!(schema, columns, to_address, to.to_vec());
+                store!(schema, columns, token_id, token_id);
+                store!(schema, columns, amount, amount);
+                store!(schema, columns, standard, standard.to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::Token;
+
+    #[test]
+    fn test_process_nft_transfers() {
+        let cols = Some(vec!["all".to_string()]);
+        let schema = Datatype::NftTransfers
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &cols,
+                None,
+                None,
+            )
+            .unwrap();
+        let address_topic = |x: u8| H256::from(H160([x; 20]));
+        let log = |topic0: H256, topics: Vec<H256>, data: Vec<u8>| Log {
+            topics: [vec![topic0], topics].concat(),
+            data: data.into(),
+            block_number: Some(100.into()),
+            transaction_hash: Some(H256::zero()),
+            log_index: Some(0.into()),
+            ..Default::default()
+        };
+        let ids = Token::Array(vec![Token::Uint(7.into()), Token::Uint(8.into())]);
+        let amounts = Token::Array(vec![Token::Uint(2.into()), Token::Uint(3.into())]);
+        let logs = vec![
+            log(
+                *EVENT_ERC721_TRANSFER,
+                vec![address_topic(1), address_topic(2), H256::from_low_u64_be(5)],
+                vec![],
+            ),
+            log(
+                *EVENT_ERC1155_TRANSFER_BATCH,
+                vec![address_topic(9), address_topic(3), address_topic(4)],
+                ethers::abi::encode(&[ids, amounts]),
+            ),
+            // erc20 transfer, which has the same topic0 as an erc721 transfer
+            log(*EVENT_ERC20_TRANSFER, vec![address_topic(1), address_topic(2)], vec![0; 32]),
+        ];
+        let logs: Vec<Log> = logs.into_iter().filter(is_nft_transfer).collect();
+        let mut columns = NftTransfers::default();
+        process_nft_transfers(logs, &mut columns, &schema).unwrap();
+        assert_eq!(columns.n_rows, 3);
+        assert_eq!(columns.standard, vec!["erc721", "erc1155_batch", "erc1155_batch"]);
+        assert_eq!(columns.token_id, vec![5.into(), 7.into(), 8.into()]);
+        assert_eq!(columns.amount, vec![1.into(), 2.into(), 3.into()]);
+        assert_eq!(columns.from_address, vec![vec![1; 20], vec![3; 20], vec![3; 20]]);
+    }
+}
@@ -0,0 +1,172 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+
+/// columns for receipts
+#[cryo_to_df::to_df(Datatype::Receipts)]
+#[derive(Default)]
+pub struct Receipts {
+    n_rows: u64,
+    block_number: Vec<Option<u32>>,
+    block_hash: Vec<Option<Vec<u8>>>,
+    transaction_index: Vec<u32>,
+    transaction_hash: Vec<Vec<u8>>,
+    from_address: Vec<Vec<u8>>,
+    to_address: Vec<Option<Vec<u8>>>,
+    contract_address: Vec<Option<Vec<u8>>>,
+    gas_used: Vec<Option<u64>>,
+    cumulative_gas_used: Vec<u64>,
+    effective_gas_price: Vec<Option<u64>>,
+    transaction_type: Vec<Option<u32>>,
+    status: Vec<Option<u32>>,
+    n_logs: Vec<u32>,
+    logs_bloom: Vec<Vec<u8>>,
+    root: Vec<Option<Vec<u8>>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for Receipts {
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec![
+            "block_number",
+            "transaction_index",
+            "transaction_hash",
+            "from_address",
+            "to_address",
+            "contract_address",
+            "gas_used",
+            "cumulative_gas_used",
+            "effective_gas_price",
+            "status",
+            "logs_bloom",
+            "chain_id",
+        ])
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for Receipts {
+    type Response = (Vec<TransactionReceipt>, Option<Block<Transaction>>);
+
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
+        let block_number = request.block_number()?;
+        let receipts = source.get_tx_receipts_in_block_number(block_number).await?;
+
+        // receipts of some nodes omit effectiveGasPrice, derive it from the transactions instead
+        let schema = query.schemas.get_schema(&Datatype::Receipts)?;
+        let block = if schema.has_column("effective_gas_price") &&
+            receipts.iter().any(|receipt| receipt.effective_gas_price.is_none())
+        {
+            source.get_block_with_txs(block_number).await?
+        } else {
+            None
+        };
+        Ok((receipts, block))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::Receipts)?;
+        let (receipts, block) = response;
+        for receipt in receipts.into_iter() {
+            let gas_price = match &block {
+                Some(block) => {
+                    let tx = block.transactions.get(receipt.transaction_index.as_usize());
+                    receipt_gas_price(&receipt, tx, block.base_fee_per_gas, query)
+                }
+                None => receipt.effective_gas_price,
+            };
+            process_receipt(receipt, columns, schema, gas_price)?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for Receipts {
+    type Response = (TransactionReceipt, Option<(Transaction, Option<U256>)>);
+
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
+        let tx_hash = request.ethers_transaction_hash()?;
+        let receipt = source
+            .get_transaction_receipt(tx_hash)
+            .await?
+            .ok_or(CollectError::CollectError("transaction receipt not found".to_string()))?;
+
+        // receipts of some nodes omit effectiveGasPrice, derive it from the transaction instead
+        let schema = query.schemas.get_schema(&Datatype::Receipts)?;
+        if !schema.has_column("effective_gas_price") || receipt.effective_gas_price.is_some() {
+            return Ok((receipt, None))
+        }
+        let transaction = source
+            .get_transaction(tx_hash)
+            .await?
+            .ok_or(CollectError::CollectError("transaction not found".to_string()))?;
+        let block_number = receipt
+            .block_number
+            .ok_or(CollectError::CollectError("no block number for receipt".to_string()))?;
+        let block = source
+            .get_block(block_number.as_u64())
+            .await?
+            .ok_or(CollectError::CollectError("block not found".to_string()))?;
+        Ok((receipt, Some((transaction, block.base_fee_per_gas))))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::Receipts)?;
+        let (receipt, transaction) = response;
+        let gas_price = match &transaction {
+            Some((tx, base_fee_per_gas)) => {
+                receipt_gas_price(&receipt, Some(tx), *base_fee_per_gas, query)
+            }
+            None => receipt.effective_gas_price,
+        };
+        process_receipt(receipt, columns, schema, gas_price)
+    }
+}
+
+/// resolve effective gas price of receipt, consistent with the transactions dataset
+fn receipt_gas_price(
+    receipt: &TransactionReceipt,
+    tx: Option<&Transaction>,
+    base_fee_per_gas: Option<U256>,
+    query: &Query,
+) -> Option<U256> {
+    match tx {
+        Some(tx) if tx.hash == receipt.transaction_hash => {
+            effective_gas_price(tx, base_fee_per_gas, Some(receipt), query.estimate_gas_price)
+        }
+        _ => receipt.effective_gas_price,
+    }
+}
+
+/// process receipt into columns
+pub(crate) fn process_receipt(
+    receipt: TransactionReceipt,
+    columns: &mut Receipts,
+    schema: &Table,
+    gas_price: Option<U256>,
+) -> R<()> {
+    columns.n_rows += 1;
+    store!(schema, columns, block_number, receipt.block_number.map(|x| x.as_u32()));
+    store!(schema, columns, block_hash, receipt.block_hash.map(|x| x.as_bytes().to_vec()));
+    store!(schema, columns, transaction_index, receipt.transaction_index.as_u32());
+    store!(schema, columns, transaction_hash, receipt.transaction_hash.as_bytes().to_vec());
+    store!(schema, columns, from_address, receipt.from.as_bytes().to_vec());
+    store!(schema, columns, to_address, receipt.to.map(|x| x.as_bytes().to_vec()));
+    store!(
+        schema,
+        columns,
+        contract_address,
+        receipt.contract_address.map(|x| x.as_bytes().to_vec())
+    );
+    store!(schema, columns, gas_used, receipt.gas_used.map(|x| x.as_u64()));
+    store!(schema, columns, cumulative_gas_used, receipt.cumulative_gas_used.as_u64());
+    store!(schema, columns, effective_gas_price, gas_price.map(|x| x.as_u64()));
+    store!(schema, columns, transaction_type, receipt.transaction_type.map(|x| x.as_u32()));
+    store!(schema, columns, status, receipt.status.map(|x| x.as_u32()));
+    store!(schema, columns, n_logs, receipt.logs.len() as u32);
+    store!(schema, columns, logs_bloom, receipt.logs_bloom.as_bytes().to_vec());
+    store!(schema, columns, root, receipt.root.map(|x| x.as_bytes().to_vec()));
+    Ok(())
+}
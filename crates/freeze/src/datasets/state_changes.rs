@@ -0,0 +1,230 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+
+/// columns for state changes
+///
+/// single-table version of the `state_diffs` group, with one row per changed
+/// (block, transaction, address, slot) and a change_type of create, update, or delete
+#[cryo_to_df::to_df(Datatype::StateChanges)]
+#[derive(Default)]
+pub struct StateChanges {
+    pub(crate) n_rows: u64,
+    pub(crate) block_number: Vec<Option<u32>>,
+    pub(crate) transaction_index: Vec<Option<u32>>,
+    pub(crate) transaction_hash: Vec<Option<Vec<u8>>>,
+    pub(crate) address: Vec<Vec<u8>>,
+    pub(crate) diff_type: Vec<String>,
+    pub(crate) slot: Vec<Option<Vec<u8>>>,
+    pub(crate) from_value: Vec<Vec<u8>>,
+    pub(crate) to_value: Vec<Vec<u8>>,
+    pub(crate) change_type: Vec<String>,
+    pub(crate) chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for StateChanges {
+    fn default_sort() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "transaction_index", "address", "slot"])
+    }
+}
+
+type BlockTxsTraces = (Option<u32>, Vec<Option<Vec<u8>>>, Vec<ethers::types::BlockTrace>);
+
+#[async_trait::async_trait]
+impl CollectByBlock for StateChanges {
+    type Response = BlockTxsTraces;
+
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
+        let schema = query.schemas.get_schema(&Datatype::StateChanges)?;
+        let include_txs = schema.has_column("transaction_hash");
+        source.trace_block_state_diffs(request.block_number()? as u32, include_txs).await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        process_state_changes(&response, columns, &query.schemas)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for StateChanges {
+    type Response = BlockTxsTraces;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        source.trace_transaction_state_diffs(request.transaction_hash()?).await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        process_state_changes(&response, columns, &query.schemas)
+    }
+}
+
+pub(crate) fn process_state_changes(
+    response: &BlockTxsTraces,
+    columns: &mut StateChanges,
+    schemas: &Schemas,
+) -> R<()> {
+    let schema = schemas.get(&Datatype::StateChanges).ok_or(err("schema not provided"))?;
+    let (block_number, txs, traces) = response;
+    for (index, (trace, tx)) in traces.iter().zip(txs).enumerate() {
+        if let Some(ethers::types::StateDiff(state_diffs)) = &trace.state_diff {
+            for (addr, diff) in state_diffs.iter() {
+                let tx_info = (block_number, tx, index);
+                let balance = map_diff(&diff.balance, u256_to_bytes);
+                process_state_change(addr, "balance", None, &balance, tx_info, columns, schema);
+                let nonce = map_diff(&diff.nonce, u256_to_bytes);
+                process_state_change(addr, "nonce", None, &nonce, tx_info, columns, schema);
+                let code = map_diff(&diff.code, |code| code.to_vec());
+                process_state_change(addr, "code", None, &code, tx_info, columns, schema);
+                for (slot, storage) in diff.storage.iter() {
+                    let storage = map_diff(storage, |value| value.as_bytes().to_vec());
+                    let slot = Some(slot.as_bytes().to_vec());
+                    process_state_change(addr, "storage", slot, &storage, tx_info, columns, schema);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn process_state_change(
+    addr: &H160,
+    diff_type: &str,
+    slot: Option<Vec<u8>>,
+    diff: &Diff<Vec<u8>>,
+    (block_number, transaction_hash, transaction_index): (&Option<u32>, &Option<Vec<u8>>, usize),
+    columns: &mut StateChanges,
+    schema: &Table,
+) {
+    let (from, to, change_type) = match diff {
+        Diff::Same => return,
+        Diff::Born(value) => (Vec::new(), value.clone(), "create"),
+        Diff::Died(value) => (value.clone(), Vec::new(), "delete"),
+        Diff::Changed(ChangedType { from, to }) => (from.clone(), to.clone(), "update"),
+    };
+    columns.n_rows += 1;
+    store!(schema, columns, block_number, *block_number);
+    store!(schema, columns, transaction_index, Some(transaction_index as u32));
+    store!(schema, columns, transaction_hash, transaction_hash.clone());
+    store!(schema, columns, address, addr.as_bytes().to_vec());
+    store!(schema, columns, diff_type, diff_type.to_string());
+    store!(schema, columns, slot, slot);
+    store!(schema, columns, from_value, from);
+    store!(schema, columns, to_value, to);
+    store!(schema, columns, change_type, change_type.to_string());
+}
+
+fn map_diff<T, F: Fn(&T) -> Vec<u8>>(diff: &Diff<T>, f: F) -> Diff<Vec<u8>> {
+    match diff {
+        Diff::Same => Diff::Same,
+        Diff::Born(value) => Diff::Born(f(value)),
+        Diff::Died(value) => Diff::Died(f(value)),
+        Diff::Changed(ChangedType { from, to }) => {
+            Diff::Changed(ChangedType { from: f(from), to: f(to) })
+        }
+    }
+}
+
+fn u256_to_bytes(value: &U256) -> Vec<u8> {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    bytes.to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// json of a trace_replay diff of `kind`, one of =, +, -, or *
+    fn diff(kind: &str, from: &str, to: &str) -> String {
+        match kind {
+            "+" => format!(r#"{{"+": "{}"}}"#, to),
+            "-" => format!(r#"{{"-": "{}"}}"#, from),
+            "*" => format!(r#"{{"*": {{"from": "{}", "to": "{}"}}}}"#, from, to),
+            _ => r#""=""#.to_string(),
+        }
+    }
+
+    /// json of an account diff whose balance, nonce, code, and storage slot all change by `kind`
+    fn account_diff(kind: &str) -> String {
+        let word = |byte: u8| format!("0x{:064x}", byte);
+        format!(
+            r#"{{"balance": {}, "nonce": {}, "code": {}, "storage": {{"{}": {}}}}}"#,
+            diff(kind, "0x1", "0x2"),
+            diff(kind, "0x3", "0x4"),
+            diff(kind, "0x05", "0x06"),
+            word(1),
+            diff(kind, &word(7), &word(8)),
+        )
+    }
+
+    #[test]
+    fn test_process_state_changes() {
+        let cols = Some(vec!["all".to_string()]);
+        let schema = Datatype::StateChanges
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &cols,
+                None,
+                None,
+            )
+            .unwrap();
+        let schemas: Schemas = [(Datatype::StateChanges, schema)].into_iter().collect();
+        // accounts are ordered by address, so rows follow the order of kinds
+        let accounts: Vec<String> = ["*", "+", "-", "="]
+            .iter()
+            .enumerate()
+            .map(|(i, kind)| format!(r#""0x{:040x}": {}"#, i + 1, account_diff(kind)))
+            .collect();
+        let raw = format!(
+            r#"{{"output": "0x", "stateDiff": {{{}}}, "trace": null, "vmTrace": null}}"#,
+            accounts.join(", ")
+        );
+        let trace: ethers::types::BlockTrace = serde_json::from_str(&raw).unwrap();
+        let response = (Some(17), vec![Some(vec![9; 32])], vec![trace]);
+        let mut columns = StateChanges::default();
+        process_state_changes(&response, &mut columns, &schemas).unwrap();
+
+        // unchanged values produce no rows
+        assert_eq!(columns.n_rows, 12);
+        let diff_types = ["balance", "nonce", "code", "storage"];
+        assert_eq!(columns.diff_type, diff_types.repeat(3));
+        let change_types: Vec<&str> =
+            ["update", "create", "delete"].iter().flat_map(|kind| [*kind; 4]).collect();
+        assert_eq!(columns.change_type, change_types);
+        assert_eq!(columns.address[0], H160::from_low_u64_be(1).as_bytes().to_vec());
+        assert_eq!(columns.address[11], H160::from_low_u64_be(3).as_bytes().to_vec());
+        assert_eq!(columns.block_number, vec![Some(17); 12]);
+        assert_eq!(columns.transaction_hash, vec![Some(vec![9; 32]); 12]);
+
+        // balances and nonces are 32 byte words, created values have an empty from_value and
+        // deleted values an empty to_value
+        assert_eq!(columns.from_value[0], u256_to_bytes(&U256::from(1)));
+        assert_eq!(columns.to_value[0], u256_to_bytes(&U256::from(2)));
+        assert_eq!(columns.to_value[1], u256_to_bytes(&U256::from(4)));
+        assert_eq!(columns.from_value[2], vec![5]);
+        assert_eq!(columns.to_value[2], vec![6]);
+        assert_eq!(columns.slot[3], Some(H256::from_low_u64_be(1).as_bytes().to_vec()));
+        assert_eq!(columns.to_value[3], H256::from_low_u64_be(8).as_bytes().to_vec());
+        assert_eq!(columns.slot[..3], [None, None, None]);
+        assert!(columns.from_value[4..8].iter().all(|value| value.is_empty()));
+        assert_eq!(columns.to_value[6], vec![6]);
+        assert!(columns.to_value[8..12].iter().all(|value| value.is_empty()));
+        assert_eq!(columns.from_value[11], H256::from_low_u64_be(7).as_bytes().to_vec());
+    }
+
+    #[test]
+    fn test_map_diff() {
+        let double = |value: &u8| vec![value * 2];
+        assert!(matches!(map_diff(&Diff::Same, double), Diff::Same));
+        assert!(matches!(map_diff(&Diff::Born(1), double), Diff::Born(value) if value == [2]));
+        assert!(matches!(map_diff(&Diff::Died(2), double), Diff::Died(value) if value == [4]));
+        let changed = map_diff(&Diff::Changed(ChangedType { from: 1, to: 3 }), double);
+        assert!(
+            matches!(changed, Diff::Changed(ChangedType { from, to }) if from == [2] && to == [6])
+        );
+    }
+}
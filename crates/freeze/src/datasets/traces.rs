@@ -35,6 +35,12 @@ impl Dataset for Traces {
     fn optional_parameters() -> Vec<Dim> {
         vec![Dim::FromAddress, Dim::ToAddress]
     }
+
+    fn discouraged_formats() -> Vec<DiscouragedFormat> {
+        DiscouragedFormat::text_formats(
+            "call inputs and outputs make text output many times larger",
+        )
+    }
 }
 
 #[async_trait::async_trait]
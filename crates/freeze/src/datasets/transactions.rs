@@ -8,6 +8,7 @@ use polars::prelude::*;
 pub struct Transactions {
     n_rows: u64,
     block_number: Vec<Option<u32>>,
+    block_timestamp: Vec<u32>,
     transaction_index: Vec<Option<u64>>,
     transaction_hash: Vec<Vec<u8>>,
     nonce: Vec<u64>,
@@ -15,13 +16,14 @@ pub struct Transactions {
     to_address: Vec<Option<Vec<u8>>>,
     value: Vec<U256>,
     input: Vec<Vec<u8>>,
+    method_id: Vec<Option<Vec<u8>>>,
     gas_limit: Vec<u64>,
     gas_used: Vec<Option<u64>>,
     gas_price: Vec<Option<u64>>,
     transaction_type: Vec<Option<u32>>,
     max_priority_fee_per_gas: Vec<Option<u64>>,
     max_fee_per_gas: Vec<Option<u64>>,
-    success: Vec<bool>,
+    success: Vec<Option<bool>>,
     n_input_bytes: Vec<u32>,
     n_input_zero_bytes: Vec<u32>,
     n_input_nonzero_bytes: Vec<u32>,
@@ -61,9 +63,39 @@ impl Dataset for Transactions {
         ])
     }
 
+    fn dedup_key() -> Option<Vec<&'static str>> {
+        Some(vec!["transaction_hash"])
+    }
+
     fn optional_parameters() -> Vec<Dim> {
         vec![Dim::FromAddress, Dim::ToAddress]
     }
+
+    fn column_lineage() -> Vec<(&'static str, ColumnLineage)> {
+        vec![
+            (
+                "gas_price",
+                ColumnLineage::new(
+                    &["effectiveGasPrice", "gasPrice", "maxFeePerGas", "maxPriorityFeePerGas"],
+                    "receipt effectiveGasPrice, else gasPrice for types 0 and 1, else \
+                     min(maxFeePerGas, baseFeePerGas + maxPriorityFeePerGas)",
+                ),
+            ),
+            (
+                "method_id",
+                ColumnLineage::new(&["input"], "input[:4], null if input has fewer than 4 bytes"),
+            ),
+            ("success", ColumnLineage::new(&["status"], "status == 1")),
+            ("n_input_bytes", ColumnLineage::new(&["input"], "len(input)")),
+            ("n_input_zero_bytes", ColumnLineage::new(&["input"], "count of zero bytes in input")),
+            (
+                "n_input_nonzero_bytes",
+                ColumnLineage::new(&["input"], "len(input) - count of zero bytes in input"),
+            ),
+            ("n_rlp_bytes", ColumnLineage::new(&["transaction"], "len(rlp(transaction))")),
+            ("block_timestamp", ColumnLineage::new(&["timestamp"], "timestamp of block header")),
+        ]
+    }
 }
 
 /// tuple representing transaction and optional receipt
@@ -74,11 +106,16 @@ impl CollectByBlock for Transactions {
     type Response = (Block<Transaction>, Vec<TransactionAndReceipt>, bool);
 
     async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
+        let block_number = request.block_number()?;
         let block = source
-            .get_block_with_txs(request.block_number()?)
+            .block_source()
+            .get_block_with_txs(&source, block_number)
             .await?
-            .ok_or(CollectError::CollectError("block not found".to_string()))?;
+            .ok_or(CollectError::BlockNotFound(block_number))?;
         let schema = query.schemas.get_schema(&Datatype::Transactions)?;
+        if request.from_address.is_none() && request.to_address.is_none() && !query.exclude_failed {
+            query.block_transaction_counts.add(block_number, block.transactions.len() as u64);
+        }
 
         // 1. collect transactions and filter them if optional parameters are supplied
         // filter by from_address
@@ -101,18 +138,17 @@ impl CollectByBlock for Transactions {
         // 2. collect receipts if necessary
         // if transactions are filtered fetch by set of transaction hashes, else fetch all receipts
         // in block
-        let receipts: Vec<Option<_>> =
-            if schema.has_column("gas_used") | schema.has_column("success") {
-                // receipts required
-                let receipts = if request.from_address.is_some() || request.to_address.is_some() {
-                    source.get_tx_receipts(&transactions).await?
-                } else {
-                    source.get_tx_receipts_in_block(&block).await?
-                };
-                receipts.into_iter().map(Some).collect()
+        let receipts: Vec<Option<_>> = if receipts_required(schema, query.exclude_failed) {
+            // receipts required
+            let receipts = if request.from_address.is_some() || request.to_address.is_some() {
+                source.get_tx_receipts(&transactions).await?
             } else {
-                vec![None; block.transactions.len()]
+                source.get_tx_receipts_in_block(&block).await?
             };
+            receipts.into_iter().map(Some).collect()
+        } else {
+            vec![None; block.transactions.len()]
+        };
 
         let transactions_with_receips = transactions.into_iter().zip(receipts).collect();
         Ok((block, transactions_with_receips, query.exclude_failed))
@@ -122,6 +158,12 @@ impl CollectByBlock for Transactions {
         let schema = query.schemas.get_schema(&Datatype::Transactions)?;
         let (block, transactions_with_receipts, exclude_failed) = response;
         for (tx, receipt) in transactions_with_receipts.into_iter() {
+            let gas_price = effective_gas_price(
+                &tx,
+                block.base_fee_per_gas,
+                receipt.as_ref(),
+                query.estimate_gas_price,
+            );
             process_transaction(
                 tx,
                 receipt,
@@ -129,6 +171,7 @@ impl CollectByBlock for Transactions {
                 schema,
                 exclude_failed,
                 block.timestamp.as_u32(),
+                gas_price,
             )?;
         }
         Ok(())
@@ -137,7 +180,7 @@ impl CollectByBlock for Transactions {
 
 #[async_trait::async_trait]
 impl CollectByTransaction for Transactions {
-    type Response = (TransactionAndReceipt, bool, u32);
+    type Response = (TransactionAndReceipt, bool, u32, Option<U256>);
 
     async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
         let tx_hash = request.ethers_transaction_hash()?;
@@ -146,7 +189,7 @@ impl CollectByTransaction for Transactions {
             .get_transaction(tx_hash)
             .await?
             .ok_or(CollectError::CollectError("transaction not found".to_string()))?;
-        let receipt = if schema.has_column("gas_used") {
+        let receipt = if receipts_required(schema, query.exclude_failed) {
             source.get_transaction_receipt(tx_hash).await?
         } else {
             None
@@ -163,13 +206,27 @@ impl CollectByTransaction for Transactions {
 
         let timestamp = block.timestamp.as_u32();
 
-        Ok(((transaction, receipt), query.exclude_failed, timestamp))
+        Ok(((transaction, receipt), query.exclude_failed, timestamp, block.base_fee_per_gas))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
         let schema = query.schemas.get_schema(&Datatype::Transactions)?;
-        let ((transaction, receipt), exclude_failed, timestamp) = response;
-        process_transaction(transaction, receipt, columns, schema, exclude_failed, timestamp)?;
+        let ((transaction, receipt), exclude_failed, timestamp, base_fee_per_gas) = response;
+        let gas_price = effective_gas_price(
+            &transaction,
+            base_fee_per_gas,
+            receipt.as_ref(),
+            query.estimate_gas_price,
+        );
+        process_transaction(
+            transaction,
+            receipt,
+            columns,
+            schema,
+            exclude_failed,
+            timestamp,
+            gas_price,
+        )?;
         Ok(())
     }
 }
@@ -181,19 +238,21 @@ pub(crate) fn process_transaction(
     schema: &Table,
     exclude_failed: bool,
     timestamp: u32,
+    gas_price: Option<U256>,
 ) -> R<()> {
     let success = if exclude_failed | schema.has_column("success") {
-        let success = tx_success(&tx, &receipt)?;
-        if exclude_failed & !success {
+        let success = tx_success(&receipt)?;
+        if exclude_failed & (success == Some(false)) {
             return Ok(())
         }
         success
     } else {
-        false
+        None
     };
 
     columns.n_rows += 1;
     store!(schema, columns, block_number, tx.block_number.map(|x| x.as_u32()));
+    store!(schema, columns, block_timestamp, timestamp);
     store!(schema, columns, transaction_index, tx.transaction_index.map(|x| x.as_u64()));
     store!(schema, columns, transaction_hash, tx.hash.as_bytes().to_vec());
     store!(schema, columns, from_address, tx.from.as_bytes().to_vec());
@@ -201,6 +260,7 @@ pub(crate) fn process_transaction(
     store!(schema, columns, nonce, tx.nonce.as_u64());
     store!(schema, columns, value, tx.value);
     store!(schema, columns, input, tx.input.to_vec());
+    store!(schema, columns, method_id, tx.input.get(..4).map(|selector| selector.to_vec()));
     store!(schema, columns, gas_limit, tx.gas.as_u64());
     store!(schema, columns, success, success);
     if schema.has_column("n_input_bytes") |
@@ -215,7 +275,7 @@ pub(crate) fn process_transaction(
     }
     store!(schema, columns, n_rlp_bytes, tx.rlp().len() as u32);
     store!(schema, columns, gas_used, receipt.and_then(|r| r.gas_used.map(|x| x.as_u64())));
-    store!(schema, columns, gas_price, tx.gas_price.map(|gas_price| gas_price.as_u64()));
+    store!(schema, columns, gas_price, gas_price.map(|gas_price| gas_price.as_u64()));
     store!(schema, columns, transaction_type, tx.transaction_type.map(|value| value.as_u32()));
     store!(schema, columns, max_fee_per_gas, tx.max_fee_per_gas.map(|value| value.as_u64()));
     store!(
@@ -230,18 +290,120 @@ pub(crate) fn process_transaction(
     Ok(())
 }
 
-fn tx_success(tx: &Transaction, receipt: &Option<TransactionReceipt>) -> R<bool> {
-    if let Some(status) = receipt.as_ref().and_then(|x| x.status) {
-        Ok(status.as_u64() == 1)
-    } else if let (Some(1), Some(true)) =
-        (tx.chain_id.map(|x| x.as_u64()), tx.block_number.map(|x| x.as_u64() < 4370000))
-    {
-        if let Some(gas_used) = receipt.as_ref().and_then(|x| x.gas_used.map(|x| x.as_u64())) {
-            Ok(gas_used == 0)
-        } else {
-            return Err(err("could not determine status of transaction"))
+fn receipts_required(schema: &Table, exclude_failed: bool) -> bool {
+    schema.has_column("gas_used") | schema.has_column("success") | exclude_failed
+}
+
+/// success of transaction according to its receipt status
+///
+/// pre-byzantium receipts have a state root instead of a status, so success is null
+fn tx_success(receipt: &Option<TransactionReceipt>) -> R<Option<bool>> {
+    match receipt {
+        Some(receipt) => Ok(receipt.status.map(|status| status.as_u64() == 1)),
+        None => Err(err("could not determine status of transaction")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt(block_number: u64, root_or_status: &str) -> TransactionReceipt {
+        let empty_bloom = format!("0x{}", "0".repeat(512));
+        let raw = format!(
+            r#"{{
+                "transactionHash": "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060",
+                "transactionIndex": "0x0",
+                "blockNumber": "{:#x}",
+                "from": "0xa1e4380a3b1f749673e270229993ee55f35663b4",
+                "to": "0x5df9b87991262f6ba471f09758cde1c0fc1de734",
+                "cumulativeGasUsed": "0x5208",
+                "gasUsed": "0x5208",
+                "contractAddress": null,
+                "logs": [],
+                "logsBloom": "{}",
+                {}
+            }}"#,
+            block_number, empty_bloom, root_or_status
+        );
+        serde_json::from_str(&raw).unwrap()
+    }
+
+    #[test]
+    fn test_tx_success_pre_byzantium() {
+        let root =
+            r#""root": "0x96a8e009d2b88b1483e6941e6812e32263b05683fac202abc622a3e31aed1957""#;
+        let receipt = receipt(46147, root);
+        assert!(receipt.status.is_none());
+        assert_eq!(tx_success(&Some(receipt)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_tx_success_post_byzantium() {
+        let receipt_success = receipt(4370000, r#""status": "0x1""#);
+        assert_eq!(tx_success(&Some(receipt_success)).unwrap(), Some(true));
+        let receipt_failure = receipt(4370000, r#""status": "0x0""#);
+        assert_eq!(tx_success(&Some(receipt_failure)).unwrap(), Some(false));
+        assert!(tx_success(&None).is_err());
+    }
+
+    #[test]
+    fn test_exclude_failed_keeps_pre_byzantium() {
+        let cols = Some(vec!["block_number".to_string(), "success".to_string()]);
+        let schema = Datatype::Transactions
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &cols,
+                None,
+                None,
+            )
+            .unwrap();
+        let root =
+            r#""root": "0x96a8e009d2b88b1483e6941e6812e32263b05683fac202abc622a3e31aed1957""#;
+        let receipts = [
+            receipt(46147, root),
+            receipt(4370000, r#""status": "0x1""#),
+            receipt(4370000, r#""status": "0x0""#),
+        ];
+        let mut columns = Transactions::default();
+        for receipt in receipts.into_iter() {
+            let tx = Transaction { block_number: receipt.block_number, ..Default::default() };
+            process_transaction(tx, Some(receipt), &mut columns, &schema, true, 0, None).unwrap();
         }
-    } else {
-        return Err(err("could not determine status of transaction"))
+        assert_eq!(columns.n_rows, 2);
+        assert_eq!(columns.success, vec![None, Some(true)]);
+        assert_eq!(columns.block_number, vec![Some(46147), Some(4370000)]);
+    }
+
+    #[test]
+    fn test_method_id_needs_four_input_bytes() {
+        let cols = Some(vec!["method_id".to_string()]);
+        let schema = Datatype::Transactions
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &cols,
+                None,
+                None,
+            )
+            .unwrap();
+        let inputs = [
+            vec![],
+            vec![0xa9, 0x05, 0x9c],
+            vec![0xa9, 0x05, 0x9c, 0xbb],
+            vec![0xa9, 0x05, 0x9c, 0xbb, 0, 1],
+        ];
+        let mut columns = Transactions::default();
+        for input in inputs.into_iter() {
+            let tx = Transaction { input: input.into(), ..Default::default() };
+            process_transaction(tx, None, &mut columns, &schema, false, 0, None).unwrap();
+        }
+        let selector = Some(vec![0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(columns.method_id, vec![None, None, selector.clone(), selector]);
     }
 }
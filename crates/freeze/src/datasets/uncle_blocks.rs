@@ -0,0 +1,173 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+
+/// columns for uncle blocks
+#[cryo_to_df::to_df(Datatype::UncleBlocks)]
+#[derive(Default)]
+pub struct UncleBlocks {
+    n_rows: u64,
+    nephew_block_number: Vec<u32>,
+    uncle_index: Vec<u32>,
+    block_hash: Vec<Option<Vec<u8>>>,
+    parent_hash: Vec<Vec<u8>>,
+    uncles_hash: Vec<Vec<u8>>,
+    author: Vec<Option<Vec<u8>>>,
+    state_root: Vec<Vec<u8>>,
+    transactions_root: Vec<Vec<u8>>,
+    receipts_root: Vec<Vec<u8>>,
+    block_number: Vec<Option<u32>>,
+    gas_used: Vec<u64>,
+    gas_limit: Vec<u64>,
+    extra_data: Vec<Vec<u8>>,
+    logs_bloom: Vec<Option<Vec<u8>>>,
+    timestamp: Vec<u32>,
+    difficulty: Vec<u64>,
+    total_difficulty: Vec<Option<U256>>,
+    size: Vec<Option<u32>>,
+    mix_hash: Vec<Option<Vec<u8>>>,
+    nonce: Vec<Option<Vec<u8>>>,
+    base_fee_per_gas: Vec<Option<u64>>,
+    withdrawals_root: Vec<Option<Vec<u8>>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for UncleBlocks {
+    fn aliases() -> Vec<&'static str> {
+        vec!["ommers", "uncles"]
+    }
+
+    fn default_sort() -> Option<Vec<&'static str>> {
+        Some(vec!["nephew_block_number", "uncle_index"])
+    }
+
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec![
+            "nephew_block_number",
+            "uncle_index",
+            "block_number",
+            "block_hash",
+            "timestamp",
+            "author",
+            "difficulty",
+            "gas_used",
+            "gas_limit",
+            "base_fee_per_gas",
+            "extra_data",
+            "chain_id",
+        ])
+    }
+}
+
+/// number of the including block, and the uncles it includes in order of index
+type BlockUncles = (u32, Vec<Block<TxHash>>);
+
+#[async_trait::async_trait]
+impl CollectByBlock for UncleBlocks {
+    type Response = BlockUncles;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let block_number = request.block_number()?;
+        let block = source
+            .get_block(block_number)
+            .await?
+            .ok_or(CollectError::BlockNotFound(block_number))?;
+        get_uncles(block_number, block.uncles.len(), &source).await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::UncleBlocks)?;
+        process_uncles(response, columns, schema)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for UncleBlocks {
+    type Response = BlockUncles;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let transaction = source
+            .get_transaction(request.ethers_transaction_hash()?)
+            .await?
+            .ok_or(CollectError::CollectError("transaction not found".to_string()))?;
+        let block = source
+            .get_block_by_hash(transaction.block_hash.ok_or(err("no block block_hash found"))?)
+            .await?
+            .ok_or(CollectError::CollectError("block not found".to_string()))?;
+        let block_number = block.number.ok_or(err("no block number found"))?.as_u64();
+        get_uncles(block_number, block.uncles.len(), &source).await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::UncleBlocks)?;
+        process_uncles(response, columns, schema)
+    }
+}
+
+/// fetch each uncle of block concurrently, within the request limits of source
+async fn get_uncles(block_number: u64, n_uncles: usize, source: &Source) -> R<BlockUncles> {
+    let requests = (0..n_uncles as u64).map(|index| async move {
+        source.get_uncle(block_number, index).await?.ok_or(CollectError::CollectError(format!(
+            "uncle {} of block {} not found",
+            index, block_number
+        )))
+    });
+    let uncles = futures::future::try_join_all(requests).await?;
+    Ok((block_number as u32, uncles))
+}
+
+/// process uncles of block into columns
+fn process_uncles(response: BlockUncles, columns: &mut UncleBlocks, schema: &Table) -> R<()> {
+    let (nephew_block_number, uncles) = response;
+    for (uncle_index, uncle) in uncles.into_iter().enumerate() {
+        columns.n_rows += 1;
+        store!(schema, columns, nephew_block_number, nephew_block_number);
+        store!(schema, columns, uncle_index, uncle_index as u32);
+        store!(schema, columns, block_hash, uncle.hash.map(|x| x.0.to_vec()));
+        store!(schema, columns, parent_hash, uncle.parent_hash.0.to_vec());
+        store!(schema, columns, uncles_hash, uncle.uncles_hash.0.to_vec());
+        store!(schema, columns, author, uncle.author.map(|x| x.0.to_vec()));
+        store!(schema, columns, state_root, uncle.state_root.0.to_vec());
+        store!(schema, columns, transactions_root, uncle.transactions_root.0.to_vec());
+        store!(schema, columns, receipts_root, uncle.receipts_root.0.to_vec());
+        store!(schema, columns, block_number, uncle.number.map(|x| x.as_u32()));
+        store!(schema, columns, gas_used, uncle.gas_used.as_u64());
+        store!(schema, columns, gas_limit, uncle.gas_limit.as_u64());
+        store!(schema, columns, extra_data, uncle.extra_data.to_vec());
+        store!(schema, columns, logs_bloom, uncle.logs_bloom.map(|x| x.0.to_vec()));
+        store!(schema, columns, timestamp, uncle.timestamp.as_u32());
+        store!(schema, columns, difficulty, uncle.difficulty.as_u64());
+        store!(schema, columns, total_difficulty, uncle.total_difficulty);
+        store!(schema, columns, base_fee_per_gas, uncle.base_fee_per_gas.map(|x| x.as_u64()));
+        store!(schema, columns, size, uncle.size.map(|x| x.as_u32()));
+        store!(schema, columns, mix_hash, uncle.mix_hash.map(|x| x.0.to_vec()));
+        store!(schema, columns, nonce, uncle.nonce.map(|x| x.0.to_vec()));
+        store!(schema, columns, withdrawals_root, uncle.withdrawals_root.map(|x| x.0.to_vec()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_uncles() {
+        let cols = Some(vec!["all".to_string()]);
+        let u256_types = [U256Type::Binary];
+        let schema = Datatype::UncleBlocks
+            .table_schema(&u256_types, &ColumnEncoding::Binary, &None, &None, &cols, None, None)
+            .unwrap();
+        let uncles = (0..2u64)
+            .map(|i| Block::<TxHash> { number: Some((99 - i).into()), ..Default::default() })
+            .collect();
+        let mut columns = UncleBlocks::default();
+        process_uncles((100, uncles), &mut columns, &schema).unwrap();
+        process_uncles((101, vec![]), &mut columns, &schema).unwrap();
+        assert_eq!(columns.n_rows, 2);
+        assert_eq!(columns.nephew_block_number, vec![100, 100]);
+        assert_eq!(columns.uncle_index, vec![0, 1]);
+        assert_eq!(columns.block_number, vec![Some(99), Some(98)]);
+    }
+}
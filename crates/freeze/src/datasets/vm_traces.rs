@@ -35,6 +35,10 @@ impl Dataset for VmTraces {
     fn default_sort() -> Option<Vec<&'static str>> {
         Some(vec!["block_number", "transaction_index", "used"])
     }
+
+    fn discouraged_formats() -> Vec<DiscouragedFormat> {
+        DiscouragedFormat::text_formats("one row per opcode produces gigabytes of text")
+    }
 }
 
 #[async_trait::async_trait]
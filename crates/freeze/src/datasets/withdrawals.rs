@@ -0,0 +1,85 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+
+/// columns for withdrawals
+#[cryo_to_df::to_df(Datatype::Withdrawals)]
+#[derive(Default)]
+pub struct Withdrawals {
+    n_rows: u64,
+    block_number: Vec<Option<u32>>,
+    block_hash: Vec<Option<Vec<u8>>>,
+    block_timestamp: Vec<u32>,
+    withdrawal_index: Vec<u64>,
+    validator_index: Vec<u64>,
+    address: Vec<Vec<u8>>,
+    amount_gwei: Vec<u64>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for Withdrawals {
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec![
+            "block_number",
+            "block_timestamp",
+            "withdrawal_index",
+            "validator_index",
+            "address",
+            "amount_gwei",
+            "chain_id",
+        ])
+    }
+
+    fn default_sort() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "withdrawal_index"])
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for Withdrawals {
+    type Response = Block<TxHash>;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let block_number = request.block_number()?;
+        let block = source
+            .get_block(block_number)
+            .await?
+            .ok_or(CollectError::BlockNotFound(block_number))?;
+        Ok(block)
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::Withdrawals)?;
+        process_withdrawals(response, columns, schema)
+    }
+}
+
+impl CollectByTransaction for Withdrawals {
+    type Response = ();
+}
+
+/// process block withdrawals into columns
+///
+/// blocks before shapella have no withdrawals field and produce no rows
+pub(crate) fn process_withdrawals<TX>(
+    block: Block<TX>,
+    columns: &mut Withdrawals,
+    schema: &Table,
+) -> R<()> {
+    let withdrawals = match block.withdrawals {
+        Some(withdrawals) => withdrawals,
+        None => return Ok(()),
+    };
+    for withdrawal in withdrawals.iter() {
+        columns.n_rows += 1;
+        store!(schema, columns, block_number, block.number.map(|x| x.as_u32()));
+        store!(schema, columns, block_hash, block.hash.map(|x| x.0.to_vec()));
+        store!(schema, columns, block_timestamp, block.timestamp.as_u32());
+        store!(schema, columns, withdrawal_index, withdrawal.index.as_u64());
+        store!(schema, columns, validator_index, withdrawal.validator_index.as_u64());
+        store!(schema, columns, address, withdrawal.address.as_bytes().to_vec());
+        store!(schema, columns, amount_gwei, withdrawal.amount.as_u64());
+    }
+    Ok(())
+}
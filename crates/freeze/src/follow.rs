@@ -0,0 +1,131 @@
+use crate::{err, BlockChunk, CollectError, FollowConfig, Partition};
+
+/// position of a followed chain, from which chunks of new blocks are created
+///
+/// chunks follow the grid of the chunks of the initial range, so that a restarted run finds the
+/// files of chunks it has already written
+#[derive(Debug)]
+pub(crate) struct FollowCursor {
+    /// partitions of the last chunk, whose dimensions other than blocks are kept by new chunks
+    templates: Vec<Partition>,
+    /// first block of the next chunk
+    next_block: u64,
+    chunk_size: u64,
+    align: bool,
+}
+
+impl FollowCursor {
+    /// split partitions of initial range into those to collect now and a cursor for the rest
+    ///
+    /// a partial last chunk is deferred until all of its blocks are final, so that only full
+    /// chunks are written
+    pub(crate) fn new(
+        partitions: Vec<Partition>,
+        config: &FollowConfig,
+        align: bool,
+    ) -> Result<(Vec<Partition>, FollowCursor), CollectError> {
+        let mut ranges = Vec::new();
+        for partition in partitions.iter() {
+            match partition.block_numbers.as_deref() {
+                Some([BlockChunk::Range(start, end)]) => ranges.push((*start, *end)),
+                _ => return Err(err("--follow can only be used with chunks of block ranges")),
+            }
+        }
+        let last_start = match ranges.iter().map(|(start, _)| *start).max() {
+            Some(last_start) => last_start,
+            None => return Err(err("--follow requires at least one chunk of blocks")),
+        };
+        let last_end = ranges
+            .iter()
+            .filter(|(start, _)| *start == last_start)
+            .map(|(_, end)| *end)
+            .max()
+            .unwrap_or(last_start);
+        let (last, mut collect): (Vec<_>, Vec<_>) =
+            partitions.into_iter().zip(ranges).partition(|(_, (start, _))| *start == last_start);
+        let templates: Vec<Partition> =
+            last.iter().map(|(partition, _)| partition.clone()).collect();
+        let mut cursor = FollowCursor {
+            templates,
+            next_block: last_start,
+            chunk_size: config.chunk_size.max(1),
+            align,
+        };
+        if last_end >= cursor.chunk_end(last_start) {
+            cursor.next_block = last_end + 1;
+            collect.extend(last);
+        }
+        Ok((collect.into_iter().map(|(partition, _)| partition).collect(), cursor))
+    }
+
+    /// last block of chunk that starts at block
+    fn chunk_end(&self, start: u64) -> u64 {
+        match self.align {
+            true => (start / self.chunk_size + 1) * self.chunk_size - 1,
+            false => start + self.chunk_size - 1,
+        }
+    }
+
+    /// partitions of the chunks whose blocks are all final, advancing cursor past them
+    pub(crate) fn take_final_chunks(&mut self, final_block: u64) -> Vec<Partition> {
+        let mut partitions = Vec::new();
+        loop {
+            let end = self.chunk_end(self.next_block);
+            if end > final_block {
+                return partitions
+            }
+            for template in self.templates.iter() {
+                partitions.push(Partition {
+                    block_numbers: Some(vec![BlockChunk::Range(self.next_block, end)]),
+                    ..template.clone()
+                })
+            }
+            self.next_block = end + 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn partition(start: u64, end: u64) -> Partition {
+        Partition { block_numbers: Some(vec![BlockChunk::Range(start, end)]), ..Default::default() }
+    }
+
+    fn ranges(partitions: &[Partition]) -> Vec<(u64, u64)> {
+        partitions
+            .iter()
+            .filter_map(|partition| match partition.block_numbers.as_deref() {
+                Some([BlockChunk::Range(start, end)]) => Some((*start, *end)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_follow_cursor() {
+        let config = FollowConfig { poll_interval: Duration::from_secs(1), chunk_size: 100 };
+
+        // partial last chunk is deferred, and new chunks continue its grid
+        let initial = vec![partition(50, 149), partition(150, 249), partition(250, 280)];
+        let (collect, mut cursor) = FollowCursor::new(initial, &config, false).unwrap();
+        assert_eq!(ranges(&collect), vec![(50, 149), (150, 249)]);
+        assert!(cursor.take_final_chunks(348).is_empty());
+        assert_eq!(
+            ranges(&cursor.take_final_chunks(560)),
+            vec![(250, 349), (350, 449), (450, 549)]
+        );
+        assert_eq!(ranges(&cursor.take_final_chunks(649)), vec![(550, 649)]);
+
+        // aligned chunks end at multiples of chunk size, full last chunks are collected now
+        let initial = vec![partition(50, 99), partition(100, 199)];
+        let (collect, mut cursor) = FollowCursor::new(initial, &config, true).unwrap();
+        assert_eq!(ranges(&collect), vec![(50, 99), (100, 199)]);
+        assert_eq!(ranges(&cursor.take_final_chunks(299)), vec![(200, 299)]);
+
+        assert!(FollowCursor::new(vec![], &config, false).is_err());
+        assert!(FollowCursor::new(vec![Partition::default()], &config, false).is_err());
+    }
+}
@@ -1,15 +1,22 @@
 use crate::{
-    collect_partition, dataframes, err, reports, summaries, CollectError, Datatype, ExecutionEnv,
-    FileOutput, FreezeSummary, MetaDatatype, Partition, Query, Source,
+    collect_partition, dataframes, err, follow::FollowCursor, hash_rpc_url, logging, reports,
+    summaries, verify_outputs, BlockChunk, CancellationToken, ChunkData, ChunkTiming, CollectError,
+    Datatype, ExecutionEnv, FileFormat, FileOutput, FollowConfig, FreezeSummary, MetaDatatype,
+    Partition, Query, SizeEstimate, Source, TableLineage, WrittenFile, CRYO_VERSION,
+    LINEAGE_METADATA_KEY,
 };
 use chrono::{DateTime, Local};
 use futures::{stream::FuturesUnordered, StreamExt};
+use indicatif::{HumanBytes, HumanDuration};
+use polars::prelude::DataFrame;
 use std::{
-    collections::{HashMap, HashSet},
-    path::PathBuf,
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
     sync::Arc,
+    time::{Duration, Instant},
 };
-use tokio::sync::Semaphore;
+use thousands::Separable;
+use tokio::sync::{mpsc, OnceCell, OwnedSemaphorePermit, Semaphore};
 
 type PartitionPayload = (
     Partition,
@@ -19,9 +26,34 @@ type PartitionPayload = (
     Arc<Source>,
     FileOutput,
     ExecutionEnv,
-    Option<std::sync::Arc<Semaphore>>,
+    Option<ChunkPermit>,
 );
 
+/// permit of max_concurrent_chunks shared by the datatypes of a chunk
+///
+/// the datatypes of a chunk are independent requests, so they are collected concurrently while
+/// counting as a single chunk. the permit is released when every datatype of the chunk is done
+#[derive(Clone, Debug)]
+struct ChunkPermit {
+    semaphore: Arc<Semaphore>,
+    permit: Arc<OnceCell<OwnedSemaphorePermit>>,
+}
+
+impl ChunkPermit {
+    fn new(semaphore: Arc<Semaphore>) -> ChunkPermit {
+        ChunkPermit { semaphore, permit: Arc::new(OnceCell::new()) }
+    }
+
+    /// wait for permit of chunk, which is acquired by the first datatype of chunk to ask for it
+    async fn acquire(&self) -> Result<(), CollectError> {
+        self.permit
+            .get_or_try_init(|| self.semaphore.clone().acquire_owned())
+            .await
+            .map(|_| ())
+            .map_err(|_| err("chunk semaphore closed"))
+    }
+}
+
 /// collect data and output as files
 pub async fn freeze(
     query: &Query,
@@ -32,25 +64,64 @@ pub async fn freeze(
     // check validity of query
     query.is_valid()?;
 
+    // when following the chain, defer a partial last chunk until all of its blocks are final
+    let following;
+    let (query, cursor) = match &env.follow {
+        Some(config) if !env.dry && !env.estimate_size => {
+            let (partitions, cursor) =
+                FollowCursor::new(query.partitions.clone(), config, query.labels.align)?;
+            following = Query { partitions, ..query.clone() };
+            (&following, Some(cursor))
+        }
+        _ => (query, None),
+    };
+
     // get partitions
-    let (payloads, skipping) = get_payloads(query, source, sink, env)?;
+    let (payloads, skipping, skipped_paths) = get_payloads(query, source, sink, env)?;
 
     // print summary
     if env.verbose >= 1 {
-        summaries::print_cryo_intro(query, source, sink, env, payloads.len() as u64)?;
+        logging::log_run_start(query, source, sink, env, payloads.len() as u64)?;
+    }
+
+    // estimate output size from sample chunks instead of collecting
+    if env.estimate_size {
+        let estimates = estimate_output_size(query, source, sink, env).await?;
+        summaries::print_size_estimate(&estimates);
+        return Ok(None)
     }
 
     // check dry run
     if env.dry {
+        if env.verbose >= 1 {
+            let collect_paths: Vec<_> =
+                payloads.iter().flat_map(|(_, _, paths, ..)| paths.values().cloned()).collect();
+            let datatype_dirs: Vec<_> = query
+                .datatypes
+                .iter()
+                .flat_map(|x| x.datatypes())
+                .map(|datatype| (datatype, sink.get_datatype_dir(datatype)))
+                .collect();
+            summaries::print_dry_run_paths(&datatype_dirs, &collect_paths, &skipped_paths);
+        }
+        if env.verify {
+            let mut results = FreezeSummary::default();
+            verify_results(query, sink, env, &mut results)?;
+            return Ok(Some(results))
+        }
         return Ok(None)
     };
 
-    // check if empty
-    if payloads.is_empty() {
-        let results = FreezeSummary { skipped: skipping, ..Default::default() };
+    // check if empty, unless new blocks will be followed
+    if payloads.is_empty() && cursor.is_none() {
+        let mut results = FreezeSummary { skipped: skipping, ..Default::default() };
+        if sink.dataset {
+            write_datasets_metadata(query, sink)?;
+        }
         if env.verbose >= 1 {
-            summaries::print_cryo_conclusion(&results, query, env)
+            logging::log_run_end(&results, query, env)
         }
+        verify_results(query, sink, env, &mut results)?;
         return Ok(Some(results))
     }
 
@@ -60,11 +131,35 @@ pub async fn freeze(
     };
 
     // perform collection
-    let results = freeze_partitions(env, payloads, skipping).await;
+    let mut results = freeze_partitions(env, payloads, skipping).await;
+
+    // keep collecting chunks of new blocks as they become final, until interrupted
+    let followed;
+    let query = match (&env.follow, cursor) {
+        (Some(config), Some(cursor)) => {
+            followed = follow_chain(query, source, sink, env, config, cursor, &mut results).await?;
+            &followed
+        }
+        _ => query,
+    };
+    results.interrupted = env.is_interrupted();
+    results.request_stats = source.request_stats.summary();
+    results.log_window_sizes =
+        source.log_window.as_ref().map(|window| window.used_sizes()).unwrap_or_default();
+
+    // summarize parquet datasets
+    if sink.dataset {
+        write_datasets_metadata(query, sink)?;
+    }
 
     // create summary
     if env.verbose >= 1 {
-        summaries::print_cryo_conclusion(&results, query, env)
+        logging::log_run_end(&results, query, env)
+    }
+
+    // check output files, unless chunks were left uncollected by an interrupt
+    if !results.interrupted {
+        verify_results(query, sink, env, &mut results)?;
     }
 
     // create final report
@@ -76,31 +171,182 @@ pub async fn freeze(
     Ok(Some(results))
 }
 
+/// collect chunks of new blocks as they become final, until interrupted
+///
+/// returns query extended by the partitions of the new chunks
+async fn follow_chain(
+    query: &Query,
+    source: &Source,
+    sink: &FileOutput,
+    env: &ExecutionEnv,
+    config: &FollowConfig,
+    mut cursor: FollowCursor,
+    results: &mut FreezeSummary,
+) -> Result<Query, CollectError> {
+    let mut query = query.clone();
+    while !env.is_interrupted() {
+        tokio::select! {
+            biased;
+            _ = env.interrupted() => break,
+            _ = tokio::time::sleep(config.poll_interval) => {}
+        }
+        // a failed poll is retried at the next interval, so that it does not end the run
+        let latest = match source.get_block_number().await {
+            Ok(latest) => latest.as_u64(),
+            Err(CollectError::Cancelled) => break,
+            Err(e) => {
+                eprintln!("warning: could not poll latest block, retrying: {}", e);
+                continue
+            }
+        };
+        let partitions = cursor.take_final_chunks(latest.saturating_sub(query.labels.reorg_buffer));
+        if partitions.is_empty() {
+            continue
+        }
+        let new_chunks = Query { partitions: partitions.clone(), ..query.clone() };
+        let (payloads, skipping, _) = get_payloads(&new_chunks, source, sink, env)?;
+        if let Some(bar) = &env.bar {
+            bar.reset();
+        }
+        results.extend(freeze_partitions(env, payloads, skipping).await);
+        query.partitions.extend(partitions);
+    }
+    Ok(query)
+}
+
+/// check output files for missing blocks and rows, if verification is enabled
+///
+/// discrepancies are always printed, the absence of discrepancies only when verbose
+fn verify_results(
+    query: &Query,
+    sink: &FileOutput,
+    env: &ExecutionEnv,
+    results: &mut FreezeSummary,
+) -> Result<(), CollectError> {
+    if env.verify {
+        let (n_files, discrepancies) = verify_outputs(query, sink)?;
+        if env.verbose >= 1 || !discrepancies.is_empty() {
+            summaries::print_verification(n_files, &discrepancies);
+        }
+        results.discrepancies = discrepancies;
+    }
+    Ok(())
+}
+
+/// estimate size of output files by collecting and writing the first remaining chunk of each
+/// datatype
+///
+/// sample files are written to a temporary directory and removed afterwards. each sample size is
+/// multiplied by the number of chunks of its datatype that remain to be collected
+pub async fn estimate_output_size(
+    query: &Query,
+    source: &Source,
+    sink: &FileOutput,
+    env: &ExecutionEnv,
+) -> Result<Vec<SizeEstimate>, CollectError> {
+    let (payloads, _, _) = get_payloads(query, source, sink, env)?;
+    let tmp_dir = std::env::temp_dir().join(format!("cryo_estimate_{}", std::process::id()));
+    std::fs::create_dir_all(&tmp_dir).map_err(|_| err("could not create temporary directory"))?;
+    let result = write_size_samples(&payloads, sink, &tmp_dir).await;
+    let _ = std::fs::remove_dir_all(&tmp_dir);
+    result
+}
+
+async fn write_size_samples(
+    payloads: &[PartitionPayload],
+    sink: &FileOutput,
+    tmp_dir: &Path,
+) -> Result<Vec<SizeEstimate>, CollectError> {
+    let mut estimates = Vec::new();
+    let mut sampled: Vec<Vec<Datatype>> = Vec::new();
+    for (partition, meta_datatype, paths, query, source, ..) in payloads.iter() {
+        if sampled.contains(&meta_datatype.datatypes()) {
+            continue
+        }
+        sampled.push(meta_datatype.datatypes());
+        let n_chunks = payloads
+            .iter()
+            .filter(|(_, other, ..)| other.datatypes() == meta_datatype.datatypes())
+            .count() as u64;
+        let sample_chunk = partition.label(&query.partitioned_by)?;
+        let dfs = collect_partition(
+            meta_datatype.clone(),
+            partition.clone(),
+            query.clone(),
+            source.clone(),
+        )
+        .await?;
+        for (datatype, mut df) in dfs.into_iter() {
+            let path = paths
+                .get(&datatype)
+                .and_then(|path| path.file_name())
+                .map(|name| tmp_dir.join(name))
+                .ok_or_else(|| err("could not get path for datatype"))?;
+            // data files of delta tables are parquet files
+            let path = match sink.format {
+                FileFormat::Delta => path.with_extension("parquet"),
+                _ => path,
+            };
+            dataframes::df_to_file(&mut df, &path, sink, &[])
+                .map_err(|_| err("error writing sample file"))?;
+            estimates.push(SizeEstimate {
+                datatype,
+                sample_chunk: sample_chunk.clone(),
+                sample_rows: df.height() as u64,
+                sample_bytes: path.metadata().map(|metadata| metadata.len()).unwrap_or(0),
+                n_chunks,
+            });
+        }
+    }
+    estimates.sort_by_key(|estimate| estimate.datatype.name());
+    Ok(estimates)
+}
+
+fn write_datasets_metadata(query: &Query, sink: &FileOutput) -> Result<(), CollectError> {
+    for datatype in query.datatypes.iter().flat_map(|x| x.datatypes()) {
+        if query.schemas.contains_key(&datatype) {
+            dataframes::write_dataset_metadata(sink, datatype)
+                .map_err(|e| err(&format!("could not write dataset metadata: {}", e)))?;
+        }
+    }
+    Ok(())
+}
+
 fn get_payloads(
     query: &Query,
     source: &Source,
     sink: &FileOutput,
     env: &ExecutionEnv,
-) -> Result<(Vec<PartitionPayload>, Vec<Partition>), CollectError> {
-    let semaphore = source
-        .max_concurrent_chunks
-        .map(|x| std::sync::Arc::new(tokio::sync::Semaphore::new(x as usize)));
+) -> Result<(Vec<PartitionPayload>, Vec<Partition>, Vec<PathBuf>), CollectError> {
+    let semaphore = source.max_concurrent_chunks.map(|x| Arc::new(Semaphore::new(x as usize)));
     let source: Arc<Source> = Arc::new(source.clone());
     let arc_query = Arc::new(query.clone());
     let mut payloads = Vec::new();
     let mut skipping = Vec::new();
+    let mut skipped_paths = Vec::new();
     let mut all_paths = HashSet::new();
-    for datatype in query.datatypes.clone().into_iter() {
-        for partition in query.partitions.clone().into_iter() {
+    // payloads of a chunk are adjacent and share a chunk permit, so that its datatypes are
+    // collected together
+    for partition in query.partitions.clone().into_iter() {
+        let chunk_permit = semaphore.clone().map(ChunkPermit::new);
+        for datatype in query.datatypes.clone().into_iter() {
             let paths = sink.get_paths(query, &partition, Some(vec![datatype.clone()]))?;
-            if !sink.overwrite && paths.values().all(|path| path.exists()) {
-                skipping.push(partition);
+            // chunks of delta output are appended to a shared table instead of their own files
+            let appended = sink.format == FileFormat::Delta;
+            if !appended &&
+                !sink.overwrite &&
+                paths.values().all(|path| {
+                    is_complete_file(path) || is_complete_split(path, &partition, sink)
+                })
+            {
+                skipping.push(partition.clone());
+                skipped_paths.extend(paths.into_values());
                 continue
             }
 
             // check for path collisions
             let paths_set: HashSet<_> = paths.clone().into_values().collect();
-            if paths_set.intersection(&all_paths).next().is_none() {
+            if appended || paths_set.intersection(&all_paths).next().is_none() {
                 all_paths.extend(paths_set);
             } else {
                 let message =
@@ -116,12 +362,39 @@ fn get_payloads(
                 source.clone(),
                 sink.clone(),
                 env.clone(),
-                semaphore.clone(),
+                chunk_permit.clone(),
             );
             payloads.push(payload);
         }
     }
-    Ok((payloads, skipping))
+    Ok((payloads, skipping, skipped_paths))
+}
+
+/// whether a previous run has written the chunk as files of narrower block ranges
+fn is_complete_split(path: &Path, partition: &Partition, sink: &FileOutput) -> bool {
+    match (sink.target_file_size, get_chunk_block_range(partition)) {
+        (Some(_), Some(chunk_range)) => {
+            let splits = dataframes::get_existing_split_paths(path, chunk_range);
+            dataframes::splits_cover_chunk(&splits, chunk_range)
+        }
+        _ => false,
+    }
+}
+
+/// block range of partition, if partition is a single range of blocks
+fn get_chunk_block_range(partition: &Partition) -> Option<(u64, u64)> {
+    match partition.block_numbers.as_deref() {
+        Some([BlockChunk::Range(start, end)]) => Some((*start, *end)),
+        _ => None,
+    }
+}
+
+/// whether a previous run has already written the file
+///
+/// files are written to a temporary path and renamed when complete, so an existing nonempty file
+/// was not left behind by an interrupted run
+fn is_complete_file(path: &Path) -> bool {
+    path.metadata().map(|metadata| metadata.len() > 0).unwrap_or(false)
 }
 
 async fn freeze_partitions(
@@ -138,63 +411,856 @@ async fn freeze_partitions(
         }
     }
 
+    // mirror request counts of started chunks in the request bar
+    let request_ticker = match (&env.request_bar, payloads.first()) {
+        (Some(request_bar), Some((_, _, _, query, ..))) => {
+            let (request_bar, query) = (request_bar.clone(), query.clone());
+            Some(tokio::spawn(async move {
+                let mut interval = tokio::time::interval(std::time::Duration::from_millis(100));
+                loop {
+                    interval.tick().await;
+                    let (completed, started) = query.request_progress.counts();
+                    request_bar.set_length(started);
+                    request_bar.set_position(completed);
+                }
+            }))
+        }
+        _ => None,
+    };
+
+    // spawn writer task, which writes each chunk as soon as it has been collected
+    let (sender, receiver) = mpsc::channel(std::cmp::max(env.writer_queue_size, 1));
+    let writer = match payloads.first() {
+        Some((_, _, _, query, source, sink, env, _)) => {
+            let progress = WriteProgress::new(query.datatypes.len() as u64, env.clone());
+            let (source, sink) = (source.clone(), sink.clone());
+            Some(tokio::spawn(write_partitions(receiver, source, sink, progress)))
+        }
+        None => None,
+    };
+
     // spawn task for each partition
+    let abort = CancellationToken::new();
     let mut futures = FuturesUnordered::new();
-    for payload in payloads.into_iter() {
-        futures.push(tokio::spawn(
-            async move { (payload.0.clone(), freeze_partition(payload).await) },
-        ));
+    for (chunk_index, payload) in payloads.into_iter().enumerate() {
+        let sender = sender.clone();
+        let abort = abort.clone();
+        futures.push(tokio::spawn(async move {
+            let (partition, env) = (payload.0.clone(), payload.6.clone());
+            let (result, gather) = tokio::select! {
+                biased;
+                _ = abort.cancelled() => (Err(CollectError::Cancelled), None),
+                (result, gather) = freeze_partition(payload, chunk_index, sender) => (result, gather),
+            };
+            match &result {
+                Ok(()) | Err(CollectError::Cancelled) => {}
+                Err(e) => logging::log_chunk_error(&env, chunk_index, &partition, e),
+            }
+            (partition, result, gather)
+        }));
     }
+    drop(sender);
 
-    // aggregate results
-    let mut completed = Vec::new();
+    // aggregate collection failures, keeping the time spent on chunks that failed partway
     let mut errored = Vec::new();
-    let mut n_rows = 0;
+    let mut cancelled = Vec::new();
+    let mut chunk_timings = Vec::new();
     while let Some(result) = futures.next().await {
+        if let Ok((_, Err(_), Some(gather))) = &result {
+            chunk_timings.push(ChunkTiming { gather: *gather, ..Default::default() });
+        }
         match result {
-            Ok((partition, Ok(chunk_n_rows))) => {
-                n_rows += chunk_n_rows;
-                completed.push(partition)
+            Ok((_, Ok(()), _)) => {}
+            Ok((partition, Err(CollectError::Cancelled), _)) => cancelled.push(partition),
+            Ok((partition, Err(e @ CollectError::BlockNotFound(_)), _)) => {
+                // range extends past the blocks that the node serves, so stop the remaining chunks
+                abort.cancel();
+                errored.push((Some(partition), e))
             }
-            Ok((partition, Err(e))) => errored.push((Some(partition), e)),
+            Ok((partition, Err(e), _)) => errored.push((Some(partition), e)),
             Err(e) => errored.push((None, err(format!("error joining chunks: {:?}", e).as_str()))),
         }
     }
 
+    // aggregate write results
+    let mut summary = match writer {
+        Some(writer) => match writer.await {
+            Ok(summary) => summary,
+            Err(e) => FreezeSummary {
+                errored: vec![(None, err(format!("error joining writer: {:?}", e).as_str()))],
+                ..Default::default()
+            },
+        },
+        None => FreezeSummary::default(),
+    };
+    summary.errored.extend(errored);
+    summary.cancelled.extend(cancelled);
+    summary.chunk_timings.extend(chunk_timings);
+    summary.skipped = skipped;
+
+    if let Some(request_ticker) = request_ticker {
+        request_ticker.abort();
+    }
+    if let Some(request_bar) = &env.request_bar {
+        request_bar.finish_and_clear();
+    }
     if let Some(bar) = &env.bar {
         bar.finish_and_clear();
     }
 
-    FreezeSummary { completed, errored, skipped, n_rows }
+    summary
 }
 
-async fn freeze_partition(payload: PartitionPayload) -> Result<u64, CollectError> {
-    let (partition, datatype, paths, query, source, sink, env, semaphore) = payload;
+/// collected dataframes of a partition, waiting to be written
+struct WriteJob {
+    chunk_index: usize,
+    partition: Partition,
+    dfs: HashMap<Datatype, DataFrame>,
+    paths: HashMap<Datatype, PathBuf>,
+    lineage: HashMap<Datatype, TableLineage>,
+    t_start: Instant,
+    gather: Duration,
+}
+
+/// collect partition and hand it to writer, returning the time spent collecting if the chunk
+/// started collecting
+async fn freeze_partition(
+    payload: PartitionPayload,
+    chunk_index: usize,
+    sender: mpsc::Sender<WriteJob>,
+) -> (Result<(), CollectError>, Option<Duration>) {
+    let (partition, datatype, paths, query, source, _sink, env, chunk_permit) = payload;
+
+    // acquire chunk permit, giving up if interrupted before the chunk starts
+    if let Some(chunk_permit) = &chunk_permit {
+        let acquired = tokio::select! {
+            biased;
+            _ = env.interrupted() => Err(CollectError::Cancelled),
+            acquired = chunk_permit.acquire() => acquired,
+        };
+        if let Err(e) = acquired {
+            return (Err(e), None)
+        }
+    }
+    if env.is_interrupted() {
+        return (Err(CollectError::Cancelled), None)
+    }
+    let t_start = Instant::now();
+    logging::log_chunk_start(&env, chunk_index, &partition);
+
+    // collect data, dropping in-flight requests if cancelled
+    let dfs = tokio::select! {
+        biased;
+        _ = source.cancelled() => Err(CollectError::Cancelled),
+        dfs = collect_partition(datatype, partition.clone(), query.clone(), source.clone()) => dfs,
+    };
+    let gather = t_start.elapsed();
+    if source.is_cancelled() {
+        remove_tmp_files(&paths);
+        return (Err(CollectError::Cancelled), Some(gather))
+    }
+    let dfs = match dfs {
+        Ok(dfs) => dfs,
+        Err(e) => return (Err(e), Some(gather)),
+    };
+    let lineage = dfs
+        .keys()
+        .filter_map(|datatype| {
+            query.schemas.get(datatype).map(|table| (*datatype, table.lineage()))
+        })
+        .collect();
+
+    // hand dataframes to writer, waiting while its queue is full so that collection is throttled
+    // when writing falls behind. the chunk permit is held until then
+    let job = WriteJob { chunk_index, partition, dfs, paths, lineage, t_start, gather };
+    let result = sender.send(job).await.map_err(|_| {
+        CollectError::CollectError("writer stopped before chunk was written".to_string())
+    });
+    (result, Some(gather))
+}
 
-    // acquire chunk semaphore
-    let _permit = match &semaphore {
-        Some(semaphore) => Some(semaphore.acquire().await),
+/// write partitions to disk in the order they finish collecting, dropping each after writing
+async fn write_partitions(
+    mut receiver: mpsc::Receiver<WriteJob>,
+    source: Arc<Source>,
+    sink: FileOutput,
+    mut progress: WriteProgress,
+) -> FreezeSummary {
+    let mut summary = FreezeSummary::default();
+    let mut bytes_per_row = HashMap::new();
+    while let Some(job) = receiver.recv().await {
+        let WriteJob { chunk_index, partition, dfs, paths, lineage, t_start, gather } = job;
+        if source.is_cancelled() {
+            remove_tmp_files(&paths);
+            summary.cancelled.push(partition);
+            summary.chunk_timings.push(ChunkTiming { gather, ..Default::default() });
+            continue
+        }
+        let t_write = Instant::now();
+        let result = match sink.format {
+            FileFormat::Delta => write_delta_partition(dfs, &paths).await,
+            _ => write_partition(
+                dfs,
+                &paths,
+                lineage,
+                &partition,
+                &source,
+                &sink,
+                &mut bytes_per_row,
+            ),
+        };
+        match result {
+            Ok(files) => {
+                // totals of each datatype, which can span several files when chunks are split
+                let mut written: Vec<(Datatype, u64, u64)> = Vec::new();
+                let duration = t_start.elapsed();
+                for (datatype, path, n_rows, n_bytes) in files.into_iter() {
+                    match written
+                        .iter_mut()
+                        .find(|(written_datatype, _, _)| *written_datatype == datatype)
+                    {
+                        Some(totals) => *totals = (datatype, totals.1 + n_rows, totals.2 + n_bytes),
+                        None => written.push((datatype, n_rows, n_bytes)),
+                    }
+                    let file = WrittenFile { datatype, path, n_rows, n_bytes, duration };
+                    summary.written.push(file);
+                }
+                summary.n_rows += written.iter().map(|(_, n_rows, _)| n_rows).sum::<u64>();
+                progress.record(&partition, &written);
+                logging::log_chunk_done(&progress.env, chunk_index, &partition, &written, duration);
+                summary.completed.push(partition);
+                summary.chunk_timings.push(ChunkTiming {
+                    gather,
+                    write: t_write.elapsed(),
+                    rows: written
+                        .iter()
+                        .map(|(datatype, n_rows, _)| (*datatype, *n_rows))
+                        .collect(),
+                    completed: true,
+                });
+            }
+            Err(e) => {
+                progress.record(&partition, &[]);
+                logging::log_chunk_error(&progress.env, chunk_index, &partition, &e);
+                summary.errored.push((Some(partition), e));
+                summary.chunk_timings.push(ChunkTiming {
+                    gather,
+                    write: t_write.elapsed(),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+    summary
+}
+
+/// write dataframes of partition, returning path, rows, and bytes of each file written
+///
+/// with a target file size, the dataframes of block range chunks are split into files of
+/// narrower block ranges, using the bytes per row of the first file written for each datatype
+fn write_partition(
+    dfs: HashMap<Datatype, DataFrame>,
+    paths: &HashMap<Datatype, PathBuf>,
+    mut lineage: HashMap<Datatype, TableLineage>,
+    partition: &Partition,
+    source: &Source,
+    sink: &FileOutput,
+    bytes_per_row: &mut HashMap<Datatype, f64>,
+) -> Result<Vec<(Datatype, PathBuf, u64, u64)>, CollectError> {
+    let chunk_range = get_chunk_block_range(partition);
+    let block_bounds = match partition.block_numbers.as_ref() {
+        Some(chunks) => chunks.min_value().zip(chunks.max_value()),
         None => None,
     };
+    let mut written = Vec::new();
+    for (datatype, df) in dfs {
+        let path = paths.get(&datatype).ok_or_else(|| {
+            CollectError::CollectError("could not get path for datatype".to_string())
+        })?;
+        let mut lineage = lineage.remove(&datatype);
+        if let Some(lineage) = lineage.as_mut() {
+            lineage.retain_columns(&df.get_column_names());
+        }
+        let df = match sink.hive_partition_columns {
+            true => dataframes::add_hive_columns(df, sink, partition, datatype)?,
+            false => df,
+        };
+
+        let splits = match (sink.target_file_size, chunk_range, bytes_per_row.get(&datatype)) {
+            (Some(target_file_size), Some(chunk_range), Some(row_size)) => {
+                let rows_per_file = ((target_file_size as f64 / row_size) as u64).max(1);
+                dataframes::split_by_block_range(&df, chunk_range, rows_per_file)?
+            }
+            _ => Vec::new(),
+        };
+        let files = match chunk_range {
+            Some(chunk_range) if splits.len() > 1 => {
+                // remove files of an interrupted split of chunk so that block ranges cannot overlap
+                for (_, split_path) in dataframes::get_existing_split_paths(path, chunk_range) {
+                    std::fs::remove_file(split_path)
+                        .map_err(|_| err("could not remove previously split file"))?;
+                }
+                splits
+                    .into_iter()
+                    .map(|(split_range, split)| {
+                        dataframes::get_split_path(path, chunk_range, split_range)
+                            .map(|split_path| (split_path, Some(split_range), split))
+                            .ok_or_else(|| err("could not get path for split of chunk"))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?
+            }
+            _ => vec![(path.clone(), block_bounds, df)],
+        };
 
-    // collect data
-    let dfs = collect_partition(datatype, partition, query, source).await?;
+        for (path, block_range, mut df) in files.into_iter() {
+            let metadata =
+                get_file_metadata(datatype, lineage.as_ref(), block_range, source, sink)?;
+            let result = tracing::info_span!("write")
+                .in_scope(|| dataframes::df_to_file(&mut df, &path, sink, &metadata));
+            result.map_err(|_| CollectError::CollectError("error writing file".to_string()))?;
+            let n_rows = df.height() as u64;
+            let n_bytes = path.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+            if sink.target_file_size.is_some() && n_rows > 0 {
+                bytes_per_row.entry(datatype).or_insert(n_bytes as f64 / n_rows as f64);
+            }
+            written.push((datatype, path, n_rows, n_bytes));
+        }
+    }
+    Ok(written)
+}
 
-    // write dataframes to disk
-    let mut n_rows = 0;
+/// parquet footer metadata entries of file: its lineage, and unless disabled, its provenance
+///
+/// block range is omitted for chunks of transaction hashes
+fn get_file_metadata(
+    datatype: Datatype,
+    lineage: Option<&TableLineage>,
+    block_range: Option<(u64, u64)>,
+    source: &Source,
+    sink: &FileOutput,
+) -> Result<Vec<(String, String)>, CollectError> {
+    let mut metadata = Vec::new();
+    if let Some(lineage) = lineage {
+        let value = serde_json::to_string(lineage).map_err(|_| err("could not encode lineage"))?;
+        metadata.push((LINEAGE_METADATA_KEY.to_string(), value));
+    }
+    if sink.metadata {
+        metadata.push(("cryo_version".to_string(), CRYO_VERSION.to_string()));
+        metadata.push(("rpc_url_hash".to_string(), hash_rpc_url(&source.rpc_url)));
+        if let Some((block_start, block_end)) = block_range {
+            metadata.push(("block_start".to_string(), block_start.to_string()));
+            metadata.push(("block_end".to_string(), block_end.to_string()));
+        }
+        metadata.push(("datatype".to_string(), datatype.name()));
+        metadata.push(("chain_id".to_string(), source.chain_id.to_string()));
+    }
+    Ok(metadata)
+}
+
+/// append dataframes of partition to the delta table of each datatype, returning path, rows, and
+/// bytes of each table write
+#[cfg(feature = "delta")]
+async fn write_delta_partition(
+    dfs: HashMap<Datatype, DataFrame>,
+    paths: &HashMap<Datatype, PathBuf>,
+) -> Result<Vec<(Datatype, PathBuf, u64, u64)>, CollectError> {
+    use tracing::Instrument;
+
+    let mut written = Vec::new();
     for (datatype, mut df) in dfs {
-        n_rows += df.height() as u64;
         let path = paths.get(&datatype).ok_or_else(|| {
             CollectError::CollectError("could not get path for datatype".to_string())
         })?;
-        let result = dataframes::df_to_file(&mut df, path, &sink);
-        result.map_err(|_| CollectError::CollectError("error writing file".to_string()))?
+        let n_bytes = dataframes::write_delta(&mut df, path)
+            .instrument(tracing::info_span!("write"))
+            .await
+            .map_err(|_| CollectError::CollectError("error writing delta table".to_string()))?;
+        written.push((datatype, path.clone(), df.height() as u64, n_bytes));
     }
+    Ok(written)
+}
+
+#[cfg(not(feature = "delta"))]
+async fn write_delta_partition(
+    _dfs: HashMap<Datatype, DataFrame>,
+    _paths: &HashMap<Datatype, PathBuf>,
+) -> Result<Vec<(Datatype, PathBuf, u64, u64)>, CollectError> {
+    Err(err("delta output requires cryo to be built with the delta feature"))
+}
 
-    // update progress bar
-    if let Some(bar) = env.bar {
+/// running totals of written chunks, shown by the progress bar
+///
+/// when the progress bar cannot be drawn, e.g. when output is not a terminal, a line is printed
+/// for each chunk instead
+struct WriteProgress {
+    env: ExecutionEnv,
+    n_datatypes: u64,
+    n_blocks: u64,
+    written: BTreeMap<String, (u64, u64)>,
+}
+
+impl WriteProgress {
+    fn new(n_datatypes: u64, env: ExecutionEnv) -> WriteProgress {
+        WriteProgress {
+            env,
+            n_datatypes: n_datatypes.max(1),
+            n_blocks: 0,
+            written: BTreeMap::new(),
+        }
+    }
+
+    fn record(&mut self, partition: &Partition, written: &[(Datatype, u64, u64)]) {
+        let bar = match &self.env.bar {
+            Some(bar) => bar.clone(),
+            None => return,
+        };
+        if !written.is_empty() {
+            self.n_blocks += partition.block_numbers.as_ref().map(|x| x.size()).unwrap_or(0);
+        }
+        for (datatype, n_rows, n_bytes) in written.iter() {
+            let totals = self.written.entry(datatype.name()).or_insert((0, 0));
+            *totals = (totals.0 + n_rows, totals.1 + n_bytes);
+        }
         bar.inc(1);
+
+        if bar.is_hidden() {
+            let chunk: Vec<_> = written
+                .iter()
+                .map(|(datatype, n_rows, n_bytes)| {
+                    let (n_rows, n_bytes) = (n_rows.separate_with_commas(), HumanBytes(*n_bytes));
+                    format!("{} {} rows {}", datatype.name(), n_rows, n_bytes)
+                })
+                .collect();
+            let chunk = if chunk.is_empty() { "failed".to_string() } else { chunk.join(", ") };
+            println!(
+                "chunk {} / {} done: {}, {}, eta {}",
+                bar.position().separate_with_commas(),
+                bar.length().unwrap_or(0).separate_with_commas(),
+                chunk,
+                self.blocks_per_second(),
+                HumanDuration(bar.eta()),
+            );
+        } else {
+            let mut message = vec![self.blocks_per_second()];
+            for (datatype, (n_rows, n_bytes)) in self.written.iter() {
+                let n_rows = n_rows.separate_with_commas();
+                message.push(format!("{} {} rows {}", datatype, n_rows, HumanBytes(*n_bytes)));
+            }
+            bar.set_message(message.join(", "));
+        }
+    }
+
+    fn blocks_per_second(&self) -> String {
+        let elapsed = self.env.t_start.elapsed().map(|x| x.as_secs_f64()).unwrap_or(0.0);
+        let per_second = if elapsed > 0.0 {
+            self.n_blocks as f64 / self.n_datatypes as f64 / elapsed
+        } else {
+            0.0
+        };
+        format!("{:.1} blocks/s", per_second)
+    }
+}
+
+/// remove temporary files left behind by partially written outputs
+fn remove_tmp_files(paths: &HashMap<Datatype, PathBuf>) {
+    for path in paths.values() {
+        let tmp_path = path.with_extension("_tmp");
+        if tmp_path.exists() {
+            let _ = std::fs::remove_file(tmp_path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BlockSource, CancellationToken, ColumnEncoding, Dim, QueryLabels, RetryPolicy, SubDir,
+        TimeDimension, U256Type,
+    };
+    use ethers::{
+        providers::{JsonRpcError, MockResponse},
+        types::{Block, Transaction, TxHash, U64},
+    };
+
+    #[tokio::test]
+    async fn test_chunk_permit_is_shared_by_datatypes_of_chunk() {
+        let semaphore = Arc::new(Semaphore::new(1));
+        let chunk = ChunkPermit::new(semaphore.clone());
+        let (blocks, txs) = (chunk.clone(), chunk);
+        blocks.acquire().await.unwrap();
+        txs.acquire().await.unwrap();
+        assert_eq!(semaphore.available_permits(), 0);
+
+        // next chunk starts once every datatype of the chunk is done
+        let next_chunk = ChunkPermit::new(semaphore.clone());
+        drop(blocks);
+        let waiting = tokio::time::timeout(Duration::from_millis(10), next_chunk.acquire()).await;
+        assert!(waiting.is_err());
+        drop(txs);
+        next_chunk.acquire().await.unwrap();
+    }
+
+    /// block source whose requests never complete, signalling when a request is in flight
+    #[derive(Debug, Default)]
+    struct PendingBlockSource {
+        started: tokio::sync::Notify,
     }
 
-    Ok(n_rows)
+    #[async_trait::async_trait]
+    impl BlockSource for PendingBlockSource {
+        fn description(&self) -> String {
+            "pending".to_string()
+        }
+
+        async fn get_block(
+            &self,
+            _: &Source,
+            _: u64,
+        ) -> Result<Option<Block<TxHash>>, CollectError> {
+            self.started.notify_one();
+            std::future::pending().await
+        }
+
+        async fn get_block_with_txs(
+            &self,
+            _: &Source,
+            _: u64,
+        ) -> Result<Option<Block<Transaction>>, CollectError> {
+            self.started.notify_one();
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_partition_cleans_up() {
+        let output_dir = std::env::temp_dir().join(format!("cryo_cancel_{}", std::process::id()));
+        let sink = FileOutput {
+            output_dir: output_dir.clone(),
+            subdirs: vec![SubDir::Datatype],
+            ..Default::default()
+        };
+        let schema = Datatype::Blocks
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &None,
+                None,
+                None,
+            )
+            .unwrap();
+        let partition = Partition {
+            block_numbers: Some(vec![BlockChunk::Range(0, 999)]),
+            ..Default::default()
+        };
+        let query = Query {
+            datatypes: vec![MetaDatatype::Scalar(Datatype::Blocks)],
+            schemas: [(Datatype::Blocks, schema)].into_iter().collect(),
+            time_dimension: TimeDimension::Blocks,
+            partitions: vec![partition.clone()],
+            partitioned_by: vec![],
+            exclude_failed: false,
+            estimate_gas_price: false,
+            js_tracer: None,
+            labels: QueryLabels {
+                align: false,
+                reorg_buffer: 0,
+                chunks_before_deployment: 0,
+                block_tags: vec![],
+            },
+            skipped_rows: Default::default(),
+            duplicate_rows: Default::default(),
+            allow_missing_blocks: false,
+            missing_blocks: Default::default(),
+            degraded_datatypes: Default::default(),
+            request_progress: Default::default(),
+            block_transaction_counts: Default::default(),
+            block_timestamps: Default::default(),
+        };
+        let cancel = CancellationToken::new();
+        let block_source = Arc::new(PendingBlockSource::default());
+        let (source, _mock) = Source::mocked();
+        let source = Source { block_source: Some(block_source.clone()), ..source }
+            .with_cancellation(cancel.clone());
+
+        // leave a partially written file behind, as an interrupted write would
+        let dir = sink.get_datatype_dir(Datatype::Blocks);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ethereum__blocks__0_to_999.parquet");
+        std::fs::write(path.with_extension("_tmp"), b"partial").unwrap();
+        let paths: HashMap<_, _> = [(Datatype::Blocks, path.clone())].into_iter().collect();
+        let payload = (
+            partition,
+            MetaDatatype::Scalar(Datatype::Blocks),
+            paths,
+            Arc::new(query),
+            Arc::new(source),
+            sink,
+            ExecutionEnv::default(),
+            None,
+        );
+
+        // cancel while a block request of the chunk is in flight
+        let (summary, _) = tokio::join!(
+            freeze_partitions(&ExecutionEnv::default(), vec![payload], vec![]),
+            async {
+                block_source.started.notified().await;
+                cancel.cancel();
+            }
+        );
+        assert_eq!(summary.cancelled.len(), 1);
+        assert!(summary.completed.is_empty());
+        assert!(summary.errored.is_empty());
+        assert!(summary.written.is_empty());
+        assert_eq!(summary.n_rows, 0);
+        let remaining: Vec<_> =
+            std::fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        assert!(remaining.is_empty(), "{:?}", remaining);
+
+        std::fs::remove_dir_all(output_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_interrupt_stops_waiting_chunks() {
+        let output_dir =
+            std::env::temp_dir().join(format!("cryo_interrupt_{}", std::process::id()));
+        let sink = FileOutput { output_dir: output_dir.clone(), ..Default::default() };
+        let query = Query {
+            datatypes: vec![MetaDatatype::Scalar(Datatype::Blocks)],
+            schemas: HashMap::new(),
+            time_dimension: TimeDimension::Blocks,
+            partitions: vec![Partition::default()],
+            partitioned_by: vec![],
+            exclude_failed: false,
+            estimate_gas_price: false,
+            js_tracer: None,
+            labels: QueryLabels {
+                align: false,
+                reorg_buffer: 0,
+                chunks_before_deployment: 0,
+                block_tags: vec![],
+            },
+            skipped_rows: Default::default(),
+            duplicate_rows: Default::default(),
+            allow_missing_blocks: false,
+            missing_blocks: Default::default(),
+            degraded_datatypes: Default::default(),
+            request_progress: Default::default(),
+            block_transaction_counts: Default::default(),
+            block_timestamps: Default::default(),
+        };
+        let (source, _mock) = Source::mocked();
+        let source = Source { max_concurrent_chunks: Some(1), ..source };
+        let interrupt = CancellationToken::new();
+        let env = ExecutionEnv { interrupt: Some(interrupt.clone()), ..ExecutionEnv::default() };
+
+        // chunk is waiting for the only chunk permit when interrupted
+        let semaphore = Arc::new(Semaphore::new(1));
+        let held_permit = semaphore.clone().acquire_owned().await.unwrap();
+        let paths = sink.get_paths(&query, &Partition::default(), None).unwrap();
+        let payload = (
+            Partition::default(),
+            MetaDatatype::Scalar(Datatype::Blocks),
+            paths,
+            Arc::new(query),
+            Arc::new(source),
+            sink,
+            env.clone(),
+            Some(ChunkPermit::new(semaphore)),
+        );
+        interrupt.cancel();
+        let summary = freeze_partitions(&env, vec![payload], vec![]).await;
+        drop(held_permit);
+
+        assert_eq!(summary.cancelled.len(), 1);
+        assert!(summary.completed.is_empty() && summary.errored.is_empty());
+        assert!(!output_dir.exists());
+    }
+
+    #[tokio::test]
+    async fn test_follow_continues_after_failed_poll() {
+        let sink = FileOutput::default();
+        let partition =
+            Partition { block_numbers: Some(vec![BlockChunk::Range(0, 9)]), ..Default::default() };
+        let query = Query {
+            datatypes: vec![MetaDatatype::Scalar(Datatype::Blocks)],
+            schemas: HashMap::new(),
+            time_dimension: TimeDimension::Blocks,
+            partitions: vec![partition.clone()],
+            partitioned_by: vec![],
+            exclude_failed: false,
+            estimate_gas_price: false,
+            js_tracer: None,
+            labels: QueryLabels {
+                align: false,
+                reorg_buffer: 0,
+                chunks_before_deployment: 0,
+                block_tags: vec![],
+            },
+            skipped_rows: Default::default(),
+            duplicate_rows: Default::default(),
+            allow_missing_blocks: false,
+            missing_blocks: Default::default(),
+            degraded_datatypes: Default::default(),
+            request_progress: Default::default(),
+            block_transaction_counts: Default::default(),
+            block_timestamps: Default::default(),
+        };
+        let (source, mock) = Source::mocked();
+        let retry_policy = RetryPolicy { max_retries: 0, initial_backoff: 0 };
+        let source = Source { retry_policy, ..source };
+        let interrupt = CancellationToken::new();
+        let env = ExecutionEnv { interrupt: Some(interrupt.clone()), ..ExecutionEnv::default() };
+        let config = FollowConfig { poll_interval: Duration::from_millis(10), chunk_size: 10 };
+        let (_, cursor) = FollowCursor::new(vec![partition], &config, false).unwrap();
+
+        // the first poll fails, later polls find no final chunks until interrupted
+        mock.push(U64::from(5)).unwrap();
+        let error = JsonRpcError { code: -32000, message: "unavailable".to_string(), data: None };
+        mock.push_response(MockResponse::Error(error));
+        let mut results = FreezeSummary::default();
+        let (result, _) = tokio::join!(
+            follow_chain(&query, &source, &sink, &env, &config, cursor, &mut results),
+            async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                interrupt.cancel();
+            }
+        );
+        assert_eq!(result.unwrap().partitions.len(), 1);
+        assert!(results.completed.is_empty() && results.errored.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_writer_writes_chunks_as_they_arrive() {
+        let output_dir = std::env::temp_dir().join(format!("cryo_writer_{}", std::process::id()));
+        let sink = FileOutput { output_dir: output_dir.clone(), ..Default::default() };
+        std::fs::create_dir_all(&output_dir).unwrap();
+        let (source, _mock) = Source::mocked();
+        let source = Source { rpc_url: "http://localhost:8545".to_string(), ..source };
+
+        // queue holds a single chunk, so the writer must drain it for the second send to finish
+        let (sender, receiver) = mpsc::channel(1);
+        let progress = WriteProgress::new(1, ExecutionEnv::default());
+        let writer = tokio::spawn(write_partitions(receiver, Arc::new(source), sink, progress));
+        let mut paths = Vec::new();
+        for (start, numbers) in [(1000, vec![1000u32, 1001]), (0, vec![0u32])] {
+            let label = format!("{}_to_{}", start, start + 999);
+            let path = output_dir.join(format!("ethereum__blocks__{}.parquet", label));
+            let df = polars::prelude::df!("block_number" => numbers).unwrap();
+            let partition = Partition {
+                block_numbers: Some(vec![BlockChunk::Range(start, start + 999)]),
+                ..Default::default()
+            };
+            let job = WriteJob {
+                chunk_index: 0,
+                partition,
+                dfs: [(Datatype::Blocks, df)].into_iter().collect(),
+                paths: [(Datatype::Blocks, path.clone())].into_iter().collect(),
+                lineage: HashMap::new(),
+                t_start: Instant::now(),
+                gather: Duration::from_millis(5),
+            };
+            sender.send(job).await.unwrap();
+            paths.push(path);
+        }
+        drop(sender);
+
+        let summary = writer.await.unwrap();
+        assert_eq!((summary.completed.len(), summary.n_rows), (2, 3));
+        let written: Vec<_> = summary.written.iter().map(|file| file.n_rows).collect();
+        assert_eq!(written, vec![2, 1]);
+        assert!(summary.errored.is_empty());
+        assert!(paths.iter().all(|path| path.exists()));
+        let rows: Vec<_> = summary.chunk_timings.iter().map(|chunk| chunk.rows.clone()).collect();
+        assert_eq!(rows, vec![vec![(Datatype::Blocks, 2)], vec![(Datatype::Blocks, 1)]]);
+        assert!(summary.chunk_timings.iter().all(|chunk| chunk.completed));
+
+        // provenance is recorded in the parquet footer of each file
+        let read = |key| dataframes::read_footer_metadata(&paths[0], key).unwrap();
+        assert_eq!(read("datatype").as_deref(), Some("blocks"));
+        assert_eq!(read("chain_id").as_deref(), Some("1"));
+        assert_eq!(read("block_start").as_deref(), Some("1000"));
+        assert_eq!(read("block_end").as_deref(), Some("1999"));
+        assert_eq!(read("cryo_version").as_deref(), Some(CRYO_VERSION));
+        assert_eq!(read("rpc_url_hash"), Some(hash_rpc_url("http://localhost:8545")));
+        assert_eq!(read("rpc_url_hash").map(|hash| hash.len()), Some(64));
+
+        std::fs::remove_dir_all(output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_completed_chunks_are_skipped() {
+        let output_dir = std::env::temp_dir().join(format!("cryo_resume_{}", std::process::id()));
+        let sink = FileOutput {
+            output_dir: output_dir.clone(),
+            subdirs: vec![SubDir::Datatype],
+            overwrite: false,
+            ..Default::default()
+        };
+        let schema = Datatype::Blocks
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &None,
+                None,
+                None,
+            )
+            .unwrap();
+        let partitions = ["0_to_999", "1000_to_1999", "2000_to_2999"]
+            .into_iter()
+            .map(|label| Partition {
+                label: Some(vec![Some(label.to_string())]),
+                ..Default::default()
+            })
+            .collect();
+        let query = Query {
+            datatypes: vec![MetaDatatype::Scalar(Datatype::Blocks)],
+            schemas: [(Datatype::Blocks, schema)].into_iter().collect(),
+            time_dimension: TimeDimension::Blocks,
+            partitions,
+            partitioned_by: vec![Dim::BlockNumber],
+            exclude_failed: false,
+            estimate_gas_price: false,
+            js_tracer: None,
+            labels: QueryLabels {
+                align: false,
+                reorg_buffer: 0,
+                chunks_before_deployment: 0,
+                block_tags: vec![],
+            },
+            skipped_rows: Default::default(),
+            duplicate_rows: Default::default(),
+            allow_missing_blocks: false,
+            missing_blocks: Default::default(),
+            degraded_datatypes: Default::default(),
+            request_progress: Default::default(),
+            block_transaction_counts: Default::default(),
+            block_timestamps: Default::default(),
+        };
+        let (source, _mock) = Source::mocked();
+
+        // first chunk was completed, second was left empty by an interrupted run
+        let dir = sink.get_datatype_dir(Datatype::Blocks);
+        std::fs::create_dir_all(&dir).unwrap();
+        let complete_path = dir.join("ethereum__blocks__0_to_999.parquet");
+        std::fs::write(&complete_path, b"data").unwrap();
+        std::fs::write(dir.join("ethereum__blocks__1000_to_1999.parquet"), b"").unwrap();
+
+        let env = ExecutionEnv::default();
+        let (payloads, skipping, skipped_paths) =
+            get_payloads(&query, &source, &sink, &env).unwrap();
+        assert_eq!(payloads.len(), 2);
+        assert_eq!(skipping.len(), 1);
+        assert_eq!(skipped_paths, vec![complete_path]);
+
+        let sink = FileOutput { overwrite: true, ..sink };
+        let (payloads, skipping, _) = get_payloads(&query, &source, &sink, &env).unwrap();
+        assert_eq!((payloads.len(), skipping.len()), (3, 0));
+
+        std::fs::remove_dir_all(output_dir).unwrap();
+    }
 }
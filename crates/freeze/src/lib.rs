@@ -9,12 +9,18 @@
 
 mod collect;
 mod datasets;
+mod follow;
 mod freeze;
 mod multi_datasets;
 mod types;
+mod verify;
 
 pub use collect::collect;
 pub use datasets::*;
-pub use freeze::freeze;
+pub use freeze::{estimate_output_size, freeze};
 pub use multi_datasets::*;
 pub use types::*;
+pub use verify::{verify_outputs, FileDiscrepancy, VERIFIED_DATATYPES};
+
+/// token for cancelling collection, see [`Source::with_cancellation`]
+pub use tokio_util::sync::CancellationToken;
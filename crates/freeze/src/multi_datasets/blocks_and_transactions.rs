@@ -46,22 +46,24 @@ impl CollectByTransaction for BlocksAndTransactions {
     );
 
     async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
-        let ((tx, receipt), exclude_failed, timestamp) =
+        let ((tx, receipt), exclude_failed, timestamp, base_fee_per_gas) =
             <Transactions as CollectByTransaction>::extract(request, source.clone(), query).await?;
         let block_number = tx.block_number.ok_or(err("no block number for tx"))?.as_u64();
         let block = source
             .get_block(block_number)
             .await?
             .ok_or(CollectError::CollectError("block not found".to_string()))?;
-        Ok((block, ((tx, receipt), exclude_failed, timestamp)))
+        Ok((block, ((tx, receipt), exclude_failed, timestamp, base_fee_per_gas)))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
         let BlocksAndTransactions(blocks, transactions) = columns;
-        let (block, ((tx, receipt), exclude_failed, timestamp)) = response;
+        let (block, ((tx, receipt), exclude_failed, timestamp, base_fee_per_gas)) = response;
         let schema = query.schemas.get_schema(&Datatype::Blocks)?;
         blocks::process_block(block, blocks, schema)?;
         let schema = query.schemas.get_schema(&Datatype::Transactions)?;
+        let gas_price =
+            effective_gas_price(&tx, base_fee_per_gas, receipt.as_ref(), query.estimate_gas_price);
         transactions::process_transaction(
             tx,
             receipt,
@@ -69,6 +71,7 @@ impl CollectByTransaction for BlocksAndTransactions {
             schema,
             exclude_failed,
             timestamp,
+            gas_price,
         )?;
         Ok(())
     }
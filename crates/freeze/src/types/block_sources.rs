@@ -0,0 +1,54 @@
+use crate::{CollectError, Partition, Source};
+use ethers::prelude::*;
+use std::sync::Arc;
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+/// source of the blocks and transactions collected by the blocks and transactions datatypes
+///
+/// json-rpc is used unless `Source::block_source` is set to another source, such as firehose
+#[async_trait::async_trait]
+pub trait BlockSource: std::fmt::Debug + Send + Sync {
+    /// description of source shown in run summary
+    fn description(&self) -> String;
+
+    /// block source for the blocks of a partition, for sources that fetch a chunk at once
+    ///
+    /// returns None if blocks are fetched one at a time
+    fn for_partition(&self, _partition: &Partition) -> Option<Arc<dyn BlockSource>> {
+        None
+    }
+
+    /// Gets the block at `block_num` (transaction hashes only)
+    async fn get_block(&self, source: &Source, block_num: u64) -> Result<Option<Block<TxHash>>>;
+
+    /// Gets the block at `block_num` (full transactions included)
+    async fn get_block_with_txs(
+        &self,
+        source: &Source,
+        block_num: u64,
+    ) -> Result<Option<Block<Transaction>>>;
+}
+
+/// blocks requested with eth_getBlockByNumber from the rpc endpoints of source
+#[derive(Clone, Debug, Default)]
+pub struct JsonRpcBlockSource;
+
+#[async_trait::async_trait]
+impl BlockSource for JsonRpcBlockSource {
+    fn description(&self) -> String {
+        "json-rpc".to_string()
+    }
+
+    async fn get_block(&self, source: &Source, block_num: u64) -> Result<Option<Block<TxHash>>> {
+        source.get_block(block_num).await
+    }
+
+    async fn get_block_with_txs(
+        &self,
+        source: &Source,
+        block_num: u64,
+    ) -> Result<Option<Block<Transaction>>> {
+        source.get_block_with_txs(block_num).await
+    }
+}
@@ -71,22 +71,6 @@ impl NumberChunk {
             }
         }
     }
-
-    /// align boundaries of chunk to clean boundaries
-    pub fn align(self, chunk_size: u64) -> Option<NumberChunk> {
-        match self {
-            NumberChunk::Numbers(numbers) => Some(NumberChunk::Numbers(numbers)),
-            NumberChunk::Range(start, end) => {
-                let start = ((start + chunk_size - 1) / chunk_size) * chunk_size;
-                let end = (end / chunk_size) * chunk_size;
-                if end > start {
-                    Some(NumberChunk::Range(start, end))
-                } else {
-                    None
-                }
-            }
-        }
-    }
 }
 
 pub(crate) fn range_to_chunks(start: &u64, end: &u64, chunk_size: &u64) -> Vec<(u64, u64)> {
@@ -104,3 +88,20 @@ pub(crate) fn range_to_chunks(start: &u64, end: &u64, chunk_size: &u64) -> Vec<(
     }
     chunks
 }
+
+/// split inclusive range at multiples of chunk_size, so first and last chunks may be partial
+pub(crate) fn aligned_range_to_chunks(start: &u64, end: &u64, chunk_size: &u64) -> Vec<(u64, u64)> {
+    let mut chunks: Vec<(u64, u64)> = Vec::new();
+    let mut chunk_start = *start;
+    loop {
+        let boundary = (chunk_start / chunk_size).saturating_add(1).saturating_mul(*chunk_size);
+        let chunk_end = std::cmp::min(boundary.saturating_sub(1), *end);
+        chunks.push((chunk_start, chunk_end));
+        if chunk_end == *end {
+            break
+        } else {
+            chunk_start = chunk_end + 1;
+        }
+    }
+    chunks
+}
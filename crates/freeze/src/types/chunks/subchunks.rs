@@ -1,4 +1,9 @@
-use super::{chunk::BlockChunk, chunk_ops::ChunkData, number_chunk::range_to_chunks};
+use super::{
+    chunk::BlockChunk,
+    chunk_ops::ChunkData,
+    number_chunk::{aligned_range_to_chunks, range_to_chunks},
+};
+use std::collections::BTreeMap;
 
 /// Aggregation operations related to chunks
 pub trait Subchunk {
@@ -7,6 +12,11 @@ pub trait Subchunk {
 
     /// divide into number of subchunks
     fn subchunk_by_count(&self, n_chunks: &u64) -> Vec<BlockChunk>;
+
+    /// divide into subchunks whose boundaries are multiples of size, e.g. (1000 2000 3000)
+    ///
+    /// first and last subchunks may be partial
+    fn subchunk_by_aligned_size(&self, chunk_size: &u64) -> Vec<BlockChunk>;
 }
 
 impl Subchunk for BlockChunk {
@@ -30,6 +40,24 @@ impl Subchunk for BlockChunk {
         let chunk_size = (total_blocks + n_chunks - 1) / n_chunks;
         self.subchunk_by_size(&chunk_size)
     }
+
+    fn subchunk_by_aligned_size(&self, chunk_size: &u64) -> Vec<BlockChunk> {
+        match &self {
+            BlockChunk::Numbers(numbers) => {
+                let mut aligned: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+                for number in numbers.iter() {
+                    aligned.entry(number / chunk_size).or_default().push(*number);
+                }
+                aligned.into_values().map(BlockChunk::Numbers).collect()
+            }
+            BlockChunk::Range(start_block, end_block) => {
+                aligned_range_to_chunks(start_block, end_block, chunk_size)
+                    .iter()
+                    .map(|(start, end)| BlockChunk::Range(*start, *end))
+                    .collect()
+            }
+        }
+    }
 }
 
 impl Subchunk for Vec<BlockChunk> {
@@ -40,6 +68,10 @@ impl Subchunk for Vec<BlockChunk> {
     fn subchunk_by_count(&self, n_chunks: &u64) -> Vec<BlockChunk> {
         to_single_chunk(self).subchunk_by_count(n_chunks)
     }
+
+    fn subchunk_by_aligned_size(&self, chunk_size: &u64) -> Vec<BlockChunk> {
+        to_single_chunk(self).subchunk_by_aligned_size(chunk_size)
+    }
 }
 
 fn to_single_chunk(chunks: &[BlockChunk]) -> BlockChunk {
@@ -51,3 +83,43 @@ fn to_single_chunk(chunks: &[BlockChunk]) -> BlockChunk {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(chunks: Vec<BlockChunk>) -> Vec<(u64, u64)> {
+        chunks.iter().filter_map(|chunk| Some((chunk.min_value()?, chunk.max_value()?))).collect()
+    }
+
+    #[test]
+    fn test_subchunk_by_aligned_size() {
+        let chunks = BlockChunk::Range(16_999_500, 17_002_200).subchunk_by_aligned_size(&1000);
+        assert_eq!(
+            ranges(chunks),
+            vec![
+                (16_999_500, 16_999_999),
+                (17_000_000, 17_000_999),
+                (17_001_000, 17_001_999),
+                (17_002_000, 17_002_200)
+            ]
+        );
+
+        // overlapping ranges share the interior chunks
+        let chunks = BlockChunk::Range(17_000_300, 17_001_999).subchunk_by_aligned_size(&1000);
+        assert_eq!(ranges(chunks), vec![(17_000_300, 17_000_999), (17_001_000, 17_001_999)]);
+
+        // chunk size larger than range
+        let chunks = BlockChunk::Range(100, 600).subchunk_by_aligned_size(&1_000_000);
+        assert_eq!(ranges(chunks), vec![(100, 600)]);
+        let chunks = BlockChunk::Range(0, 0).subchunk_by_aligned_size(&1000);
+        assert_eq!(ranges(chunks), vec![(0, 0)]);
+
+        let chunks = BlockChunk::Numbers(vec![2500, 999, 1000, 1999, 5]);
+        let chunks = chunks.subchunk_by_aligned_size(&1000);
+        assert_eq!(
+            chunks.iter().map(|chunk| chunk.values()).collect::<Vec<_>>(),
+            vec![vec![999, 5], vec![1000, 1999], vec![2500]]
+        );
+    }
+}
@@ -53,7 +53,13 @@ pub trait CollectByBlock: 'static + Send + Default + ToDataFrames {
         let mut columns = Self::default();
         while let Some(message) = receiver.recv().await {
             match message {
-                Ok(message) => Self::transform(message, &mut columns, query)?,
+                Ok(message) => {
+                    let _span = tracing::info_span!("decode").entered();
+                    Self::transform(message, &mut columns, query)?
+                }
+                Err(CollectError::BlockNotFound(block_number)) if query.allow_missing_blocks => {
+                    query.missing_blocks.add(block_number)
+                }
                 Err(e) => return Err(e),
             }
         }
@@ -53,7 +53,10 @@ pub trait CollectByTransaction: 'static + Send + Default + ToDataFrames {
         let mut columns = Self::default();
         while let Some(message) = receiver.recv().await {
             match message {
-                Ok(message) => Self::transform(message, &mut columns, query)?,
+                Ok(message) => {
+                    let _span = tracing::info_span!("decode").entered();
+                    Self::transform(message, &mut columns, query)?
+                }
                 Err(e) => return Err(e),
             }
         }
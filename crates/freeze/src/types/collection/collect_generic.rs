@@ -3,6 +3,7 @@ use futures::Future;
 use polars::prelude::*;
 use std::collections::HashMap;
 use tokio::{sync::mpsc, task};
+use tracing::Instrument;
 
 /// collect single partition
 pub async fn collect_partition(
@@ -11,12 +12,33 @@ pub async fn collect_partition(
     query: Arc<Query>,
     source: Arc<Source>,
 ) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
-    match query.time_dimension {
-        TimeDimension::Blocks => collect_by_block(datatype, partition, source, query).await,
+    // blocks fetched at once for the partition are shared by the requests of the partition
+    let source = match source.block_source().for_partition(&partition) {
+        Some(block_source) => {
+            Arc::new(Source { block_source: Some(block_source), ..(*source).clone() })
+        }
+        None => source,
+    };
+    let dfs = match query.time_dimension {
+        TimeDimension::Blocks => collect_by_block(datatype, partition, source, query.clone()).await,
         TimeDimension::Transactions => {
-            collect_by_transaction(datatype, partition, source, query).await
+            collect_by_transaction(datatype, partition, source, query.clone()).await
         }
-    }
+    }?;
+
+    // drop excluded u256 representations and duplicate rows, e.g. logs returned by overlapping
+    // or retried requests, and cast columns whose types are overridden
+    dfs.into_iter()
+        .map(|(datatype, df)| match query.schemas.get(&datatype) {
+            Some(schema) => {
+                let df = dataframes::drop_excluded_u256_columns(df, schema);
+                let (df, n_duplicates) = dataframes::dedup_by_schema(df, schema)?;
+                query.duplicate_rows.add(datatype, n_duplicates);
+                Ok((datatype, dataframes::cast_by_schema(df, schema)?))
+            }
+            None => Ok((datatype, df)),
+        })
+        .collect()
 }
 
 /// fetch data for a given partition
@@ -38,12 +60,17 @@ where
     T: Send + 'static,
 {
     let mut handles = Vec::new();
-    for rpc_params in partition.param_sets(inner_request_size)?.into_iter() {
+    let param_sets = partition.param_sets(inner_request_size)?;
+    query.request_progress.start(param_sets.len() as u64);
+    for rpc_params in param_sets.into_iter() {
         let sender = sender.clone();
         let source = source.clone();
         let query = query.clone();
         let handle = task::spawn(async move {
-            let result = f_request(rpc_params, source.clone(), query.clone()).await;
+            let result = f_request(rpc_params, source.clone(), query.clone())
+                .instrument(tracing::info_span!("fetch"))
+                .await;
+            query.request_progress.complete();
             match sender.send(result).await {
                 Ok(_) => Ok(()),
                 Err(_) => Err(CollectError::CollectError("tokio mpsc send failure".to_string())),
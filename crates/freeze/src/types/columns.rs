@@ -1,4 +1,4 @@
-use crate::{CollectError, ColumnType, Datatype, Dim, Table};
+use crate::{CollectError, ColumnLineage, ColumnType, Datatype, Dim, DiscouragedFormat, Table};
 use polars::prelude::*;
 use std::collections::HashMap;
 
@@ -81,6 +81,13 @@ pub trait Dataset: Sync + Send {
         None
     }
 
+    /// columns that identify a row, used to drop duplicate rows
+    ///
+    /// rows of datasets without a natural key are compared in full
+    fn dedup_key() -> Option<Vec<&'static str>> {
+        None
+    }
+
     /// default columns extracted for Dataset
     fn default_columns() -> Option<Vec<&'static str>> {
         None
@@ -110,4 +117,14 @@ pub trait Dataset: Sync + Send {
     fn arg_aliases() -> Option<HashMap<Dim, Dim>> {
         None
     }
+
+    /// output formats that work poorly for dataset
+    fn discouraged_formats() -> Vec<DiscouragedFormat> {
+        vec![]
+    }
+
+    /// lineage of columns that are computed rather than copied from rpc response fields
+    fn column_lineage() -> Vec<(&'static str, ColumnLineage)> {
+        vec![]
+    }
 }
@@ -0,0 +1,80 @@
+use polars::prelude::*;
+
+use crate::types::{CollectError, ColumnType, Table};
+
+/// polars dtype of a column type that columns can be overridden to
+///
+/// types that need a conversion of values, such as u256 and hex, cannot be overrides
+fn override_dtype(column_type: ColumnType) -> Option<DataType> {
+    match column_type {
+        ColumnType::Boolean => Some(DataType::Boolean),
+        ColumnType::UInt32 => Some(DataType::UInt32),
+        ColumnType::UInt64 => Some(DataType::UInt64),
+        ColumnType::Int32 => Some(DataType::Int32),
+        ColumnType::Int64 => Some(DataType::Int64),
+        ColumnType::Float32 => Some(DataType::Float32),
+        ColumnType::Float64 => Some(DataType::Float64),
+        ColumnType::String => Some(DataType::Utf8),
+        _ => None,
+    }
+}
+
+/// cast columns to the types that schema overrides them to, failing if a value does not fit
+pub(crate) fn cast_by_schema(mut df: DataFrame, schema: &Table) -> Result<DataFrame, CollectError> {
+    for (column, column_type) in schema.column_type_overrides.iter() {
+        let dtype = override_dtype(*column_type).ok_or_else(|| {
+            CollectError::CollectError(format!("cannot override type to {}", column_type.as_str()))
+        })?;
+        let series = match df.column(column) {
+            Ok(series) => series.strict_cast(&dtype).map_err(|e| {
+                CollectError::CollectError(format!(
+                    "could not cast column {} to {}: {}",
+                    column,
+                    column_type.as_str(),
+                    e
+                ))
+            })?,
+            Err(_) => continue,
+        };
+        df.with_column(series).map_err(CollectError::PolarsError)?;
+    }
+    Ok(df)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ColumnEncoding, Datatype, U256Type};
+
+    #[test]
+    fn test_cast_by_schema() {
+        let mut schema = Datatype::Blocks
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &None,
+                None,
+                None,
+            )
+            .unwrap();
+        let df = DataFrame::new(vec![
+            Series::new("block_number", vec![1u32, 2]),
+            Series::new("gas_used", vec![21000u64, 42000]),
+        ])
+        .unwrap();
+
+        schema.column_type_overrides.insert("block_number".to_string(), ColumnType::Int64);
+        schema.column_type_overrides.insert("gas_used".to_string(), ColumnType::Float64);
+        schema.column_type_overrides.insert("base_fee_per_gas".to_string(), ColumnType::Int32);
+        let cast = cast_by_schema(df.clone(), &schema).unwrap();
+        assert_eq!(cast.column("block_number").unwrap().dtype(), &DataType::Int64);
+        assert_eq!(cast.column("gas_used").unwrap().dtype(), &DataType::Float64);
+
+        // values that do not fit the overridden type are errors rather than nulls
+        let df = DataFrame::new(vec![Series::new("gas_used", vec![u64::MAX])]).unwrap();
+        schema.column_type_overrides.insert("gas_used".to_string(), ColumnType::Int32);
+        assert!(cast_by_schema(df, &schema).is_err());
+    }
+}
@@ -49,13 +49,24 @@ macro_rules! with_series_u256 {
                 $all_series.push(Series::new(name, converted));
             }
 
+            // hex
+            if $schema.u256_types.contains(&U256Type::Hex) {
+                let name = $name.to_string() + U256Type::Hex.suffix().as_str();
+                let name = name.as_str();
+
+                let converted: Vec<String> = $value.iter().map(|v| format!("{:#x}", v)).collect();
+                $all_series.push(Series::new(name, converted));
+            }
+
             // float32
             if $schema.u256_types.contains(&U256Type::F32) {
                 let name = $name.to_string() + U256Type::F32.suffix().as_str();
                 let name = name.as_str();
 
-                let converted: Vec<Option<f32>> =
-                    $value.iter().map(|v| v.to_string().parse::<f32>().ok()).collect();
+                let converted: Vec<Option<f32>> = $value
+                    .iter()
+                    .map(|v| v.narrow_f32(&$schema.overflow_policy))
+                    .collect::<Result<_, CollectError>>()?;
                 $all_series.push(Series::new(name, converted));
             }
 
@@ -74,7 +85,10 @@ macro_rules! with_series_u256 {
                 let name = $name.to_string() + U256Type::U32.suffix().as_str();
                 let name = name.as_str();
 
-                let converted: Vec<u32> = $value.iter().map(|v| v.as_u32()).collect();
+                let converted: Vec<Option<u32>> = $value
+                    .iter()
+                    .map(|v| v.narrow_u32(&$schema.overflow_policy))
+                    .collect::<Result<_, CollectError>>()?;
                 $all_series.push(Series::new(name, converted));
             }
 
@@ -83,7 +97,10 @@ macro_rules! with_series_u256 {
                 let name = $name.to_string() + U256Type::U64.suffix().as_str();
                 let name = name.as_str();
 
-                let converted: Vec<u64> = $value.iter().map(|v| v.as_u64()).collect();
+                let converted: Vec<Option<u64>> = $value
+                    .iter()
+                    .map(|v| v.narrow_u64(&$schema.overflow_policy))
+                    .collect::<Result<_, CollectError>>()?;
                 $all_series.push(Series::new(name, converted));
             }
 
@@ -124,6 +141,16 @@ macro_rules! with_series_option_u256 {
                 $all_series.push(Series::new(name, converted));
             }
 
+            // hex
+            if $schema.u256_types.contains(&U256Type::Hex) {
+                let name = $name.to_string() + U256Type::Hex.suffix().as_str();
+                let name = name.as_str();
+
+                let converted: Vec<Option<String>> =
+                    $value.iter().map(|v| v.map(|x| format!("{:#x}", x))).collect();
+                $all_series.push(Series::new(name, converted));
+            }
+
             // float32
             if $schema.u256_types.contains(&U256Type::F32) {
                 let name = $name.to_string() + U256Type::F32.suffix().as_str();
@@ -131,8 +158,8 @@ macro_rules! with_series_option_u256 {
 
                 let converted: Vec<Option<f32>> = $value
                     .iter()
-                    .map(|v| v.map(|x| x.to_string().parse::<f32>().ok()).flatten())
-                    .collect();
+                    .map(|v| v.narrow_f32(&$schema.overflow_policy))
+                    .collect::<Result<_, CollectError>>()?;
                 $all_series.push(Series::new(name, converted));
             }
 
@@ -153,8 +180,10 @@ macro_rules! with_series_option_u256 {
                 let name = $name.to_string() + U256Type::U32.suffix().as_str();
                 let name = name.as_str();
 
-                let converted: Vec<Option<u32>> =
-                    $value.iter().map(|v| v.map(|x| x.as_u32())).collect();
+                let converted: Vec<Option<u32>> = $value
+                    .iter()
+                    .map(|v| v.narrow_u32(&$schema.overflow_policy))
+                    .collect::<Result<_, CollectError>>()?;
                 $all_series.push(Series::new(name, converted));
             }
 
@@ -163,8 +192,10 @@ macro_rules! with_series_option_u256 {
                 let name = $name.to_string() + U256Type::U64.suffix().as_str();
                 let name = name.as_str();
 
-                let converted: Vec<Option<u64>> =
-                    $value.iter().map(|v| v.map(|x| x.as_u64())).collect();
+                let converted: Vec<Option<u64>> = $value
+                    .iter()
+                    .map(|v| v.narrow_u64(&$schema.overflow_policy))
+                    .collect::<Result<_, CollectError>>()?;
                 $all_series.push(Series::new(name, converted));
             }
 
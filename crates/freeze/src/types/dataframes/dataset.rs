@@ -0,0 +1,241 @@
+use std::{
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use parquet_format_safe::{
+    thrift::protocol::{TCompactInputProtocol, TCompactOutputProtocol},
+    FileMetaData, KeyValue,
+};
+
+use crate::types::{Datatype, FileError, FileOutput};
+
+const PARQUET_MAGIC: &[u8; 4] = b"PAR1";
+
+/// write `_metadata` and `_common_metadata` summary files for the parquet files of a datatype
+///
+/// `_metadata` holds the footers of every file in the datatype directory, with each column chunk
+/// pointing at its file, so that readers such as pyarrow.dataset can plan a scan without reading
+/// each footer. `_common_metadata` holds only the shared schema.
+pub(crate) fn write_dataset_metadata(
+    file_output: &FileOutput,
+    datatype: Datatype,
+) -> Result<(), FileError> {
+    let dir = file_output.get_datatype_dir(datatype);
+    let paths = get_dataset_paths(&dir, &file_output.get_datatype_file_prefix(datatype))?;
+
+    let mut summary: Option<FileMetaData> = None;
+    for path in paths.iter() {
+        let mut metadata = read_footer(path)?;
+        let relative_path = path
+            .strip_prefix(&dir)
+            .ok()
+            .and_then(|relative_path| relative_path.to_str())
+            .ok_or(FileError::DatasetError("invalid dataset file name".to_string()))?;
+        for row_group in metadata.row_groups.iter_mut() {
+            for column in row_group.columns.iter_mut() {
+                column.file_path = Some(relative_path.to_string());
+            }
+        }
+        match summary.as_mut() {
+            None => summary = Some(metadata),
+            Some(summary) => {
+                if summary.schema != metadata.schema {
+                    return Err(FileError::DatasetError(format!(
+                        "schema of {} does not match rest of dataset",
+                        path.display()
+                    )))
+                }
+                summary.num_rows += metadata.num_rows;
+                summary.row_groups.extend(metadata.row_groups);
+            }
+        }
+    }
+
+    if let Some(summary) = summary {
+        let common = FileMetaData { num_rows: 0, row_groups: Vec::new(), ..summary.clone() };
+        write_footer_only(&dir.join("_common_metadata"), &common)?;
+        write_footer_only(&dir.join("_metadata"), &summary)?;
+    }
+    Ok(())
+}
+
+/// list completed parquet files of dataset, in name order
+fn get_dataset_paths(dir: &Path, prefix: &str) -> Result<Vec<PathBuf>, FileError> {
+    if !dir.exists() {
+        return Ok(Vec::new())
+    }
+    let entries = std::fs::read_dir(dir).map_err(|_e| FileError::FileWriteError)?;
+    let mut paths = Vec::new();
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        if path.is_dir() && name.starts_with("block_") && name.contains('=') {
+            // files of hive partitioned output are in one subdirectory per block bucket
+            paths.extend(get_dataset_paths(&path, prefix)?);
+        } else if name.starts_with(prefix) && name.ends_with(".parquet") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// read value of key_value_metadata entry in footer of parquet file
+pub(crate) fn read_footer_metadata(path: &Path, key: &str) -> Result<Option<String>, FileError> {
+    let metadata = read_footer(path)?;
+    let entries = metadata.key_value_metadata.unwrap_or_default();
+    Ok(entries.into_iter().find(|entry| entry.key == key).and_then(|entry| entry.value))
+}
+
+/// set key_value_metadata entries in footer of parquet file, rewriting only the footer
+pub(crate) fn set_footer_metadata(
+    path: &Path,
+    entries: &[(String, String)],
+) -> Result<(), FileError> {
+    let mut metadata = read_footer(path)?;
+    let key_values = metadata.key_value_metadata.get_or_insert_with(Vec::new);
+    key_values.retain(|entry| !entries.iter().any(|(key, _)| *key == entry.key));
+    for (key, value) in entries.iter() {
+        key_values.push(KeyValue { key: key.clone(), value: Some(value.clone()) });
+    }
+    let footer = encode_footer(&metadata)?;
+
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(|_e| FileError::FileWriteError)?;
+    let footer_start = footer_offset(&mut file, path)?;
+    file.set_len(footer_start).map_err(|_e| FileError::FileWriteError)?;
+    file.seek(SeekFrom::End(0))
+        .and_then(|_| file.write_all(&footer))
+        .and_then(|_| file.write_all(&(footer.len() as u32).to_le_bytes()))
+        .and_then(|_| file.write_all(PARQUET_MAGIC))
+        .map_err(|_e| FileError::FileWriteError)
+}
+
+/// byte offset at which the footer of parquet file starts
+fn footer_offset(file: &mut std::fs::File, path: &Path) -> Result<u64, FileError> {
+    let mut tail = [0u8; 8];
+    let file_len = file.seek(SeekFrom::End(-8)).map_err(|_e| FileError::FileWriteError)? + 8;
+    file.read_exact(&mut tail).map_err(|_e| FileError::FileWriteError)?;
+    if &tail[4..] != PARQUET_MAGIC {
+        return Err(FileError::DatasetError(format!("{} is not parquet", path.display())))
+    }
+    let footer_len = u32::from_le_bytes([tail[0], tail[1], tail[2], tail[3]]) as u64;
+    file_len
+        .checked_sub(8 + footer_len)
+        .ok_or(FileError::DatasetError(format!("{} has invalid footer", path.display())))
+}
+
+/// read thrift-encoded footer metadata of parquet file
+pub(crate) fn read_footer(path: &Path) -> Result<FileMetaData, FileError> {
+    let mut file = std::fs::File::open(path).map_err(|_e| FileError::FileWriteError)?;
+    let footer_start = footer_offset(&mut file, path)?;
+    let footer_end = file.seek(SeekFrom::End(-8)).map_err(|_e| FileError::FileWriteError)?;
+    let footer_len = (footer_end - footer_start) as usize;
+    let mut footer = vec![0u8; footer_len];
+    file.seek(SeekFrom::Start(footer_start)).map_err(|_e| FileError::FileWriteError)?;
+    file.read_exact(&mut footer).map_err(|_e| FileError::FileWriteError)?;
+    let mut protocol = TCompactInputProtocol::new(footer.as_slice(), footer_len * 2 + 1024);
+    FileMetaData::read_from_in_protocol(&mut protocol).map_err(|_e| FileError::FileWriteError)
+}
+
+/// thrift-encode footer metadata of parquet file
+fn encode_footer(metadata: &FileMetaData) -> Result<Vec<u8>, FileError> {
+    let mut footer = Vec::new();
+    metadata
+        .write_to_out_protocol(&mut TCompactOutputProtocol::new(&mut footer))
+        .map_err(|_e| FileError::FileWriteError)?;
+    Ok(footer)
+}
+
+/// write parquet file that contains metadata but no data pages
+fn write_footer_only(path: &Path, metadata: &FileMetaData) -> Result<(), FileError> {
+    let footer = encode_footer(metadata)?;
+
+    let tmp_path = path.with_extension("_tmp");
+    let mut file = std::fs::File::create(&tmp_path).map_err(|_e| FileError::FileWriteError)?;
+    file.write_all(PARQUET_MAGIC)
+        .and_then(|_| file.write_all(&footer))
+        .and_then(|_| file.write_all(&(footer.len() as u32).to_le_bytes()))
+        .and_then(|_| file.write_all(PARQUET_MAGIC))
+        .map_err(|_e| FileError::FileWriteError)?;
+    std::fs::rename(tmp_path, path).map_err(|_e| FileError::FileWriteError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+
+    #[test]
+    fn test_write_dataset_metadata() {
+        let output_dir = std::env::temp_dir().join(format!("cryo_dataset_{}", std::process::id()));
+        let file_output = FileOutput {
+            output_dir: output_dir.clone(),
+            subdirs: vec![crate::SubDir::Datatype],
+            row_group_size: Some(2),
+            dataset: true,
+            ..Default::default()
+        };
+        let dir = file_output.get_datatype_dir(Datatype::Blocks);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (label, numbers) in [("0_to_2", vec![0u32, 1, 2]), ("3_to_4", vec![3u32, 4])] {
+            let mut df = df!("block_number" => numbers).unwrap();
+            let path = dir.join(format!("ethereum__blocks__{}.parquet", label));
+            super::super::df_to_file(&mut df, &path, &file_output, &[]).unwrap();
+        }
+
+        write_dataset_metadata(&file_output, Datatype::Blocks).unwrap();
+        let summary = read_footer(&dir.join("_metadata")).unwrap();
+        assert_eq!(summary.num_rows, 5);
+        let file_path = summary.row_groups.last().map(|rg| rg.columns[0].file_path.clone());
+        assert_eq!(file_path, Some(Some("ethereum__blocks__3_to_4.parquet".to_string())));
+        let common = read_footer(&dir.join("_common_metadata")).unwrap();
+        assert_eq!((common.num_rows, common.row_groups.len()), (0, 0));
+
+        std::fs::remove_dir_all(output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_hive_partitioned_dataset_metadata() {
+        let output_dir = std::env::temp_dir().join(format!("cryo_hive_{}", std::process::id()));
+        let file_output = FileOutput {
+            output_dir: output_dir.clone(),
+            subdirs: vec![crate::SubDir::Datatype],
+            dataset: true,
+            partition_width: Some(3),
+            ..Default::default()
+        };
+        let dir = file_output.get_datatype_dir(Datatype::Blocks);
+        for (start, end) in [(0u32, 1u32), (2, 3), (4, 5)] {
+            let partition = crate::Partition {
+                block_numbers: Some(vec![crate::BlockChunk::Range(start as u64, end as u64)]),
+                ..Default::default()
+            };
+            let subdir = file_output.get_partition_subdir(&partition).unwrap();
+            std::fs::create_dir_all(dir.join(&subdir)).unwrap();
+            let path =
+                dir.join(subdir).join(format!("ethereum__blocks__{}_to_{}.parquet", start, end));
+            let mut df = df!("block_number" => [start, end]).unwrap();
+            super::super::df_to_file(&mut df, &path, &file_output, &[]).unwrap();
+        }
+
+        write_dataset_metadata(&file_output, Datatype::Blocks).unwrap();
+        let summary = read_footer(&dir.join("_metadata")).unwrap();
+        assert_eq!(summary.num_rows, 6);
+        let file_paths: Vec<_> =
+            summary.row_groups.iter().map(|rg| rg.columns[0].file_path.clone().unwrap()).collect();
+        assert_eq!(
+            file_paths,
+            vec![
+                "block_number=0/ethereum__blocks__0_to_1.parquet",
+                "block_number=0/ethereum__blocks__2_to_3.parquet",
+                "block_number=3/ethereum__blocks__4_to_5.parquet",
+            ]
+        );
+
+        std::fs::remove_dir_all(output_dir).unwrap();
+    }
+}
@@ -0,0 +1,63 @@
+use polars::prelude::*;
+
+use crate::types::{CollectError, Table};
+
+/// drop rows that repeat the natural key of an earlier row, if enabled by schema
+///
+/// returns the deduplicated dataframe and the number of rows dropped
+pub(crate) fn dedup_by_schema(
+    df: DataFrame,
+    schema: &Table,
+) -> Result<(DataFrame, u64), CollectError> {
+    if !schema.dedup {
+        return Ok((df, 0))
+    }
+    let key: Option<Vec<String>> = schema
+        .datatype
+        .dedup_key()
+        .map(|key| key.into_iter().map(|column| column.to_string()).collect());
+    let deduped = df
+        .unique_stable(key.as_deref(), UniqueKeepStrategy::First, None)
+        .map_err(CollectError::PolarsError)?;
+    let n_dropped = (df.height() - deduped.height()) as u64;
+    Ok((deduped, n_dropped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ColumnEncoding, Datatype, U256Type};
+
+    #[test]
+    fn test_dedup_by_natural_key() {
+        let mut schema = Datatype::Logs
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &None,
+                None,
+                None,
+            )
+            .unwrap();
+        let df = DataFrame::new(vec![
+            Series::new("block_number", vec![1u32, 1, 1, 2]),
+            Series::new("log_index", vec![0u32, 1, 0, 0]),
+            Series::new("transaction_hash", vec![vec![1u8], vec![1], vec![1], vec![2]]),
+            Series::new("data", vec![vec![0u8], vec![0], vec![9], vec![0]]),
+        ])
+        .unwrap();
+
+        let (kept, n_dropped) = dedup_by_schema(df.clone(), &schema).unwrap();
+        assert_eq!((kept.height(), n_dropped), (4, 0));
+
+        // rows are duplicates by key even when other columns differ
+        schema.dedup = true;
+        let (kept, n_dropped) = dedup_by_schema(df, &schema).unwrap();
+        assert_eq!(n_dropped, 1);
+        let log_indices: Vec<u32> =
+            kept.column("log_index").unwrap().u32().unwrap().into_no_null_iter().collect();
+        assert_eq!(log_indices, vec![0, 1, 0]);
+    }
+}
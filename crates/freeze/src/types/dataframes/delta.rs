@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use bytes::Bytes;
+use deltalake::{
+    arrow::record_batch::RecordBatch,
+    parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder, protocol::SaveMode, DeltaOps,
+};
+use polars::prelude::*;
+
+use crate::types::FileError;
+
+/// append polars dataframe to the delta table rooted at directory, creating the table if needed
+///
+/// returns the number of bytes of data files added to the table
+pub(crate) async fn write_delta(df: &mut DataFrame, table_dir: &Path) -> Result<u64, FileError> {
+    let batches = df_to_record_batches(df)?;
+    if batches.is_empty() {
+        return Ok(0)
+    }
+    std::fs::create_dir_all(table_dir).map_err(|_e| FileError::FileWriteError)?;
+    let uri = table_dir.to_str().ok_or(FileError::FileWriteError)?;
+    let bytes_before = data_file_bytes(table_dir);
+    let table = DeltaOps::try_from_uri(uri).await.map_err(|_e| FileError::FileWriteError)?;
+    table
+        .write(batches)
+        .with_save_mode(SaveMode::Append)
+        .await
+        .map_err(|_e| FileError::FileWriteError)?;
+    Ok(data_file_bytes(table_dir).saturating_sub(bytes_before))
+}
+
+/// convert polars dataframe to the arrow record batches used by deltalake
+///
+/// polars and deltalake use different arrow implementations, so the dataframe is passed through
+/// an in-memory parquet file
+fn df_to_record_batches(df: &mut DataFrame) -> Result<Vec<RecordBatch>, FileError> {
+    if df.height() == 0 {
+        return Ok(Vec::new())
+    }
+    let mut buffer = Vec::new();
+    ParquetWriter::new(&mut buffer)
+        .with_compression(ParquetCompression::Uncompressed)
+        .finish(df)
+        .map_err(|_e| FileError::FileWriteError)?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(buffer))
+        .and_then(|builder| builder.build())
+        .map_err(|_e| FileError::FileWriteError)?;
+    reader.collect::<Result<Vec<_>, _>>().map_err(|_e| FileError::FileWriteError)
+}
+
+/// total size of the data files of an unpartitioned delta table
+fn data_file_bytes(table_dir: &Path) -> u64 {
+    match std::fs::read_dir(table_dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok()?.metadata().ok())
+            .filter(|metadata| metadata.is_file())
+            .map(|metadata| metadata.len())
+            .sum(),
+        Err(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_delta_appends() {
+        let table_dir = std::env::temp_dir().join(format!("cryo_delta_{}", std::process::id()));
+        let mut first = df!("block_number" => [0u32, 1], "gas_used" => [10u64, 20]).unwrap();
+        let mut second = df!("block_number" => [2u32, 3], "gas_used" => [30u64, 40]).unwrap();
+        assert!(write_delta(&mut first, &table_dir).await.unwrap() > 0);
+        assert!(write_delta(&mut second, &table_dir).await.unwrap() > 0);
+
+        let table = deltalake::open_table(table_dir.to_str().unwrap()).await.unwrap();
+        assert_eq!(table.version(), 1);
+
+        std::fs::remove_dir_all(table_dir).unwrap();
+    }
+}
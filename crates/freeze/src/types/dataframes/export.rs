@@ -1,20 +1,28 @@
-use std::path::Path;
+use std::{io::Write, path::Path};
 
+use flate2::{write::GzEncoder, Compression};
 use polars::prelude::*;
 
-use crate::types::{FileError, FileOutput};
+use crate::types::{CsvCompression, FileError, FileOutput};
 
 /// write polars dataframe to file
+///
+/// metadata entries, such as lineage, are stored in the footer of parquet files and are not
+/// written for other formats
 pub(crate) fn df_to_file(
     df: &mut DataFrame,
     filename: &Path,
     file_output: &FileOutput,
+    metadata: &[(String, String)],
 ) -> Result<(), FileError> {
     let tmp_filename = filename.with_extension("_tmp");
     let result = match filename.extension().and_then(|ex| ex.to_str()) {
-        Some("parquet") => df_to_parquet(df, &tmp_filename, file_output),
-        Some("csv") => df_to_csv(df, &tmp_filename),
-        Some("json") => df_to_json(df, &tmp_filename),
+        Some("parquet") => df_to_parquet(df, &tmp_filename, file_output, metadata),
+        Some("csv") => df_to_csv(df, &tmp_filename, CsvCompression::None),
+        Some("gz") => df_to_csv(df, &tmp_filename, CsvCompression::Gzip),
+        Some("zst") => df_to_csv(df, &tmp_filename, CsvCompression::Zstd),
+        Some("json") => df_to_json(df, &tmp_filename, JsonFormat::Json),
+        Some("jsonl") => df_to_json(df, &tmp_filename, JsonFormat::JsonLines),
         _ => return Err(FileError::FileWriteError),
     };
     match result {
@@ -28,35 +36,175 @@ fn df_to_parquet(
     df: &mut DataFrame,
     filename: &Path,
     file_output: &FileOutput,
+    metadata: &[(String, String)],
 ) -> Result<(), FileError> {
     let file = std::fs::File::create(filename).map_err(|_e| FileError::FileWriteError)?;
+    let row_group_size = match file_output.row_group_bytes {
+        Some(row_group_bytes) => Some(rows_per_row_group(df, row_group_bytes)),
+        None => file_output.row_group_size,
+    };
+    let mut encoded = dictionary_encode(df, file_output).map_err(|_e| FileError::FileWriteError)?;
+    let df = encoded.as_mut().unwrap_or(df);
     let result = ParquetWriter::new(file)
         .with_statistics(file_output.parquet_statistics)
         .with_compression(file_output.parquet_compression)
-        .with_row_group_size(file_output.row_group_size)
+        .with_row_group_size(row_group_size)
         .finish(df);
-    match result {
-        Err(_e) => Err(FileError::FileWriteError),
-        _ => Ok(()),
+    if result.is_err() {
+        return Err(FileError::FileWriteError)
+    }
+    if metadata.is_empty() {
+        return Ok(())
     }
+    super::set_footer_metadata(filename, metadata)
+}
+
+/// number of rows per row group to reach row groups of about `row_group_bytes`
+///
+/// rows are sized by the in-memory size of dataframe, so compressed row groups are smaller
+fn rows_per_row_group(df: &DataFrame, row_group_bytes: u64) -> usize {
+    let bytes_per_row = (df.estimated_size() / df.height().max(1)).max(1);
+    ((row_group_bytes as usize) / bytes_per_row).max(1)
+}
+
+/// cast columns to the types that polars writes with the requested parquet encodings
+///
+/// polars dictionary encodes categorical columns and writes utf8 columns plain, so dictionary
+/// columns are cast to categorical and other categorical columns to utf8. returns None if no
+/// column needs a cast
+fn dictionary_encode(df: &DataFrame, file_output: &FileOutput) -> PolarsResult<Option<DataFrame>> {
+    let mut casts = Vec::new();
+    for column in df.get_columns().iter() {
+        let is_categorical = matches!(column.dtype(), DataType::Categorical(..));
+        let dictionary = match (&file_output.dictionary_columns, file_output.parquet_dictionary) {
+            (_, false) => false,
+            (Some(columns), true) => columns.iter().any(|name| name == column.name()),
+            (None, true) => is_categorical,
+        };
+        match (dictionary, column.dtype()) {
+            (true, DataType::Utf8) => {
+                casts.push(column.cast(&DataType::Categorical(None, Default::default()))?)
+            }
+            (false, _) if is_categorical => casts.push(column.cast(&DataType::Utf8)?),
+            _ => {}
+        }
+    }
+    if casts.is_empty() {
+        return Ok(None)
+    }
+    let mut df = df.clone();
+    for column in casts.into_iter() {
+        df.with_column(column)?;
+    }
+    Ok(Some(df))
 }
 
 /// write polars dataframe to csv file
-fn df_to_csv(df: &mut DataFrame, filename: &Path) -> Result<(), FileError> {
+///
+/// compressed files are encoded while they are written, so no uncompressed copy is stored
+fn df_to_csv(
+    df: &mut DataFrame,
+    filename: &Path,
+    compression: CsvCompression,
+) -> Result<(), FileError> {
     let file = std::fs::File::create(filename).map_err(|_e| FileError::FileWriteError)?;
-    let result = CsvWriter::new(file).finish(df);
-    match result {
-        Err(_e) => Err(FileError::FileWriteError),
-        _ => Ok(()),
-    }
+    let file = std::io::BufWriter::new(file);
+    let file = match compression {
+        CsvCompression::None => write_csv(df, file)?,
+        CsvCompression::Gzip => write_csv(df, GzEncoder::new(file, Compression::default()))?
+            .finish()
+            .map_err(|_e| FileError::FileWriteError)?,
+        CsvCompression::Zstd => {
+            let encoder = zstd::Encoder::new(file, 0).map_err(|_e| FileError::FileWriteError)?;
+            write_csv(df, encoder)?.finish().map_err(|_e| FileError::FileWriteError)?
+        }
+    };
+    file.into_inner().map(|_| ()).map_err(|_e| FileError::FileWriteError)
+}
+
+/// write csv to writer, returning the writer so that encoders can be finished
+fn write_csv<W: Write>(df: &mut DataFrame, mut writer: W) -> Result<W, FileError> {
+    CsvWriter::new(&mut writer).finish(df).map_err(|_e| FileError::FileWriteError)?;
+    Ok(writer)
 }
 
-/// write polars dataframe to json file
-fn df_to_json(df: &mut DataFrame, filename: &Path) -> Result<(), FileError> {
+/// write polars dataframe to json or json lines file
+fn df_to_json(df: &mut DataFrame, filename: &Path, format: JsonFormat) -> Result<(), FileError> {
     let file = std::fs::File::create(filename).map_err(|_e| FileError::FileWriteError)?;
-    let result = JsonWriter::new(file).with_json_format(JsonFormat::Json).finish(df);
+    let result = JsonWriter::new(file).with_json_format(format).finish(df);
     match result {
         Err(_e) => Err(FileError::FileWriteError),
         _ => Ok(()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_compressed_csv() {
+        let output_dir = std::env::temp_dir().join(format!("cryo_csv_{}", std::process::id()));
+        std::fs::create_dir_all(&output_dir).unwrap();
+        let mut df = df!("block_number" => [0u32, 1, 2]).unwrap();
+
+        let path = output_dir.join("ethereum__blocks__0_to_2.csv.gz");
+        df_to_csv(&mut df, &path, CsvCompression::Gzip).unwrap();
+        let mut csv = String::new();
+        let file = std::fs::File::open(&path).unwrap();
+        flate2::read::GzDecoder::new(file).read_to_string(&mut csv).unwrap();
+        assert_eq!(csv, "block_number\n0\n1\n2\n");
+
+        let path = output_dir.join("ethereum__blocks__0_to_2.csv.zst");
+        df_to_csv(&mut df, &path, CsvCompression::Zstd).unwrap();
+        let csv = zstd::decode_all(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "block_number\n0\n1\n2\n");
+
+        std::fs::remove_dir_all(output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_parquet_row_group_bytes_and_dictionary() {
+        let output_dir = std::env::temp_dir().join(format!("cryo_parquet_{}", std::process::id()));
+        std::fs::create_dir_all(&output_dir).unwrap();
+        let n_rows = 10_000u32;
+        let addresses: Vec<String> = (0..n_rows).map(|i| format!("0x{:040x}", i % 4)).collect();
+        let mut df = df!(
+            "block_number" => (0..n_rows).collect::<Vec<u32>>(),
+            "address" => addresses.clone(),
+            "event_name" => addresses.clone()
+        )
+        .unwrap();
+        let file_output = FileOutput {
+            output_dir: output_dir.clone(),
+            row_group_bytes: Some(100_000),
+            dictionary_columns: Some(vec!["address".to_string()]),
+            ..Default::default()
+        };
+        let path = output_dir.join("ethereum__logs__0_to_9999.parquet");
+        df_to_file(&mut df, &path, &file_output, &[]).unwrap();
+
+        // row groups of ~100KB hold about a thousand of these ~100 byte rows
+        let footer = super::super::read_footer(&path).unwrap();
+        assert!(footer.row_groups.len() >= 5, "{} row groups", footer.row_groups.len());
+        let is_dictionary = |column: usize| {
+            footer.row_groups.iter().all(|row_group| {
+                let encodings = &row_group.columns[column].meta_data.as_ref().unwrap().encodings;
+                encodings.contains(&parquet_format_safe::Encoding::RLE_DICTIONARY) ||
+                    encodings.contains(&parquet_format_safe::Encoding::PLAIN_DICTIONARY)
+            })
+        };
+        assert!(is_dictionary(1));
+        assert!(!is_dictionary(2));
+
+        // dictionary columns read back with their original values
+        let reread = ParquetReader::new(std::fs::File::open(&path).unwrap()).finish().unwrap();
+        assert_eq!(reread.height(), n_rows as usize);
+        let address = reread.column("address").unwrap().cast(&DataType::Utf8).unwrap();
+        let address: Vec<_> = address.utf8().unwrap().into_no_null_iter().collect();
+        assert_eq!(address, addresses.iter().map(|x| x.as_str()).collect::<Vec<_>>());
+
+        std::fs::remove_dir_all(output_dir).unwrap();
+    }
+}
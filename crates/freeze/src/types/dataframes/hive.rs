@@ -0,0 +1,28 @@
+use polars::prelude::*;
+
+use crate::types::{CollectError, Datatype, FileOutput, HiveKey, Partition};
+
+/// add the keys of the hive partitions of files of datatype in partition as constant columns
+///
+/// block buckets are omitted for partitions without block numbers, like their subdirectories
+pub(crate) fn add_hive_columns(
+    mut df: DataFrame,
+    sink: &FileOutput,
+    partition: &Partition,
+    datatype: Datatype,
+) -> Result<DataFrame, CollectError> {
+    let n_rows = df.height();
+    for key in sink.hive_partitions.iter() {
+        let name = key.name();
+        let series = match key {
+            HiveKey::Network => Series::new(&name, vec![sink.prefix.clone(); n_rows]),
+            HiveKey::Datatype => Series::new(&name, vec![sink.get_datatype_name(datatype); n_rows]),
+            HiveKey::BlockBucket(_) => match sink.get_block_bucket(partition) {
+                Some((_, bucket)) => Series::new(&name, vec![bucket; n_rows]),
+                None => continue,
+            },
+        };
+        df.with_column(series).map_err(CollectError::PolarsError)?;
+    }
+    Ok(df)
+}
@@ -1,12 +1,30 @@
+mod cast;
+mod dataset;
+mod dedup;
+#[cfg(feature = "delta")]
+mod delta;
 mod export;
+mod hive;
 mod read;
 mod sort;
+mod split;
 mod u256s;
 
 #[macro_use]
 mod creation;
 
+pub(crate) use cast::cast_by_schema;
+pub(crate) use dataset::{
+    read_footer, read_footer_metadata, set_footer_metadata, write_dataset_metadata,
+};
+pub(crate) use dedup::dedup_by_schema;
+#[cfg(feature = "delta")]
+pub(crate) use delta::write_delta;
 pub(crate) use export::*;
+pub(crate) use hive::add_hive_columns;
 pub use read::*;
 pub(crate) use sort::SortableDataFrame;
+pub(crate) use split::{
+    get_existing_split_paths, get_split_path, split_by_block_range, splits_cover_chunk,
+};
 pub use u256s::*;
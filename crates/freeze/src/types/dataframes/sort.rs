@@ -9,10 +9,38 @@ pub(crate) trait SortableDataFrame {
 impl SortableDataFrame for Result<DataFrame, CollectError> {
     fn sort_by_schema(self, schema: &Table) -> Self {
         match (self, &schema.sort_columns) {
-            (Ok(df), Some(sort_columns)) => {
-                df.sort(sort_columns, false, false).map_err(CollectError::PolarsError)
-            }
+            (Ok(df), Some(sort_columns)) => df
+                .sort(sort_columns, schema.sort_descending, false)
+                .map_err(CollectError::PolarsError),
             (df, _) => df,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ColumnEncoding, Datatype, U256Type};
+
+    #[test]
+    fn test_sort_descending() {
+        let sort = Some(vec!["block_number".to_string()]);
+        let mut schema = Datatype::Blocks
+            .table_schema(
+                &[U256Type::Binary],
+                &ColumnEncoding::Binary,
+                &None,
+                &None,
+                &None,
+                sort,
+                None,
+            )
+            .unwrap();
+        schema.sort_descending = true;
+        let df = df!("block_number" => [2u32, 3, 1]).unwrap();
+        let sorted = Result::<_, CollectError>::Ok(df).sort_by_schema(&schema).unwrap();
+        let block_numbers: Vec<u32> =
+            sorted.column("block_number").unwrap().u32().unwrap().into_no_null_iter().collect();
+        assert_eq!(block_numbers, vec![3, 2, 1]);
+    }
+}
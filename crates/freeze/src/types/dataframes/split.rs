@@ -0,0 +1,151 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use polars::prelude::*;
+
+use crate::{err, BlockChunk, ChunkData, CollectError};
+
+/// split dataframe of a block range chunk into sub-ranges of about `rows_per_file` rows each
+///
+/// sub-ranges are contiguous, do not overlap, and together cover the whole chunk range. they only
+/// break between blocks, so a block with more than `rows_per_file` rows is not divided
+pub(crate) fn split_by_block_range(
+    df: &DataFrame,
+    chunk_range: (u64, u64),
+    rows_per_file: u64,
+) -> Result<Vec<((u64, u64), DataFrame)>, CollectError> {
+    let (start, end) = chunk_range;
+    let block_numbers: Vec<Option<u64>> = df
+        .column("block_number")
+        .and_then(|column| column.cast(&DataType::UInt64))
+        .map_err(|_| err("could not read block_number column"))?
+        .u64()
+        .map_err(|_| err("could not read block_number column"))?
+        .into_iter()
+        .collect();
+
+    // rows without a block number in the chunk range would be lost, so keep them in one file
+    if block_numbers.iter().any(|bn| bn.map(|bn| bn < start || bn > end).unwrap_or(true)) {
+        return Ok(vec![(chunk_range, df.clone())])
+    }
+
+    let mut block_counts: BTreeMap<u64, u64> = BTreeMap::new();
+    for block_number in block_numbers.iter().flatten() {
+        *block_counts.entry(*block_number).or_insert(0) += 1;
+    }
+    let mut ranges = Vec::new();
+    let (mut range_start, mut n_rows) = (start, 0);
+    for (block_number, count) in block_counts.iter() {
+        n_rows += count;
+        if n_rows >= rows_per_file && *block_number < end {
+            ranges.push((range_start, *block_number));
+            range_start = block_number + 1;
+            n_rows = 0;
+        }
+    }
+    ranges.push((range_start, end));
+
+    ranges
+        .into_iter()
+        .map(|(range_start, range_end)| {
+            let mask: BooleanChunked = block_numbers
+                .iter()
+                .map(|bn| bn.map(|bn| bn >= range_start && bn <= range_end).unwrap_or(false))
+                .collect();
+            let split = df.filter(&mask).map_err(|_| err("could not split dataframe"))?;
+            Ok(((range_start, range_end), split))
+        })
+        .collect()
+}
+
+/// path of file holding a sub-range of a chunk, given the path of the chunk
+pub(crate) fn get_split_path(
+    path: &Path,
+    chunk_range: (u64, u64),
+    split_range: (u64, u64),
+) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let chunk_stub = BlockChunk::Range(chunk_range.0, chunk_range.1).stub().ok()?;
+    let split_stub = BlockChunk::Range(split_range.0, split_range.1).stub().ok()?;
+    if !name.contains(&chunk_stub) {
+        return None
+    }
+    Some(path.with_file_name(name.replacen(&chunk_stub, &split_stub, 1)))
+}
+
+/// existing files that hold sub-ranges of a chunk, ordered by block range
+pub(crate) fn get_existing_split_paths(
+    path: &Path,
+    chunk_range: (u64, u64),
+) -> Vec<((u64, u64), PathBuf)> {
+    let (start, end) = chunk_range;
+    let (name, dir) = match (path.file_name().and_then(|name| name.to_str()), path.parent()) {
+        (Some(name), Some(dir)) => (name, dir),
+        _ => return Vec::new(),
+    };
+    let chunk_stub = match BlockChunk::Range(start, end).stub() {
+        Ok(chunk_stub) => chunk_stub,
+        Err(_) => return Vec::new(),
+    };
+    let (before, after) = match name.split_once(&chunk_stub) {
+        Some(pieces) => pieces,
+        None => return Vec::new(),
+    };
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut splits: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter_map(|split_path| {
+            let split_name = split_path.file_name()?.to_str()?;
+            let stub = split_name.strip_prefix(before)?.strip_suffix(after)?;
+            let (split_start, split_end) = stub.split_once("_to_")?;
+            let split_range = (split_start.parse().ok()?, split_end.parse().ok()?);
+            if start <= split_range.0 && split_range.0 <= split_range.1 && split_range.1 <= end {
+                Some((split_range, split_path))
+            } else {
+                None
+            }
+        })
+        .collect();
+    splits.sort();
+    splits
+}
+
+/// whether sub-range files cover the whole chunk range without gaps or overlaps
+pub(crate) fn splits_cover_chunk(
+    splits: &[((u64, u64), PathBuf)],
+    chunk_range: (u64, u64),
+) -> bool {
+    let mut next_block = chunk_range.0;
+    for ((split_start, split_end), _) in splits.iter() {
+        if *split_start != next_block {
+            return false
+        }
+        next_block = split_end + 1;
+    }
+    !splits.is_empty() && next_block == chunk_range.1 + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_by_block_range() {
+        let df = df!("block_number" => [100u32, 100, 101, 101, 101, 104, 107]).unwrap();
+        let splits = split_by_block_range(&df, (100, 109), 3).unwrap();
+        let ranges: Vec<_> = splits.iter().map(|(range, _)| *range).collect();
+        assert_eq!(ranges, vec![(100, 101), (102, 109)]);
+        let heights: Vec<_> = splits.iter().map(|(_, split)| split.height()).collect();
+        assert_eq!(heights, vec![5, 2]);
+
+        let path = Path::new("/data/ethereum__logs__00000100_to_00000109.parquet");
+        let split_path = get_split_path(path, (100, 109), (102, 109)).unwrap();
+        assert_eq!(split_path, Path::new("/data/ethereum__logs__00000102_to_00000109.parquet"));
+    }
+}
@@ -10,6 +10,7 @@ pub trait ToU256Series {
         name: String,
         dtype: U256Type,
         column_encoding: &ColumnEncoding,
+        overflow_policy: &OverflowPolicy,
     ) -> Result<Series, CollectError>;
 }
 
@@ -19,6 +20,7 @@ impl ToU256Series for Vec<U256> {
         name: String,
         dtype: U256Type,
         column_encoding: &ColumnEncoding,
+        overflow_policy: &OverflowPolicy,
     ) -> Result<Series, CollectError> {
         let name = name + dtype.suffix().as_str();
         let name = name.as_str();
@@ -37,7 +39,7 @@ impl ToU256Series for Vec<U256> {
             }
             U256Type::F32 => {
                 let converted: Vec<Option<f32>> =
-                    self.iter().map(|v| v.to_string().parse::<f32>().ok()).collect();
+                    self.iter().map(|v| v.narrow_f32(overflow_policy)).collect::<R<_>>()?;
                 Ok(Series::new(name, converted))
             }
             U256Type::F64 => {
@@ -46,16 +48,22 @@ impl ToU256Series for Vec<U256> {
                 Ok(Series::new(name, converted))
             }
             U256Type::U32 => {
-                let converted: Vec<u32> = self.iter().map(|v| v.as_u32()).collect();
+                let converted: Vec<Option<u32>> =
+                    self.iter().map(|v| v.narrow_u32(overflow_policy)).collect::<R<_>>()?;
                 Ok(Series::new(name, converted))
             }
             U256Type::U64 => {
-                let converted: Vec<u64> = self.iter().map(|v| v.as_u64()).collect();
+                let converted: Vec<Option<u64>> =
+                    self.iter().map(|v| v.narrow_u64(overflow_policy)).collect::<R<_>>()?;
                 Ok(Series::new(name, converted))
             }
             U256Type::Decimal128 => {
                 Err(CollectError::CollectError("DECIMAL128 not implemented".to_string()))
             }
+            U256Type::Hex => {
+                let converted: Vec<String> = self.iter().map(|v| format!("{:#x}", v)).collect();
+                Ok(Series::new(name, converted))
+            }
         }
     }
 }
@@ -66,6 +74,7 @@ impl ToU256Series for Vec<Option<U256>> {
         name: String,
         dtype: U256Type,
         column_encoding: &ColumnEncoding,
+        overflow_policy: &OverflowPolicy,
     ) -> Result<Series, CollectError> {
         let name = name + dtype.suffix().as_str();
         let name = name.as_str();
@@ -85,10 +94,8 @@ impl ToU256Series for Vec<Option<U256>> {
                 Ok(Series::new(name, converted))
             }
             U256Type::F32 => {
-                let converted: Vec<Option<f32>> = self
-                    .iter()
-                    .map(|v| v.map(|x| x.to_string().parse::<f32>().ok()).flatten())
-                    .collect();
+                let converted: Vec<Option<f32>> =
+                    self.iter().map(|v| v.narrow_f32(overflow_policy)).collect::<R<_>>()?;
                 Ok(Series::new(name, converted))
             }
             U256Type::F64 => {
@@ -100,17 +107,22 @@ impl ToU256Series for Vec<Option<U256>> {
             }
             U256Type::U32 => {
                 let converted: Vec<Option<u32>> =
-                    self.iter().map(|v| v.map(|x| x.as_u32())).collect();
+                    self.iter().map(|v| v.narrow_u32(overflow_policy)).collect::<R<_>>()?;
                 Ok(Series::new(name, converted))
             }
             U256Type::U64 => {
                 let converted: Vec<Option<u64>> =
-                    self.iter().map(|v| v.map(|x| x.as_u64())).collect();
+                    self.iter().map(|v| v.narrow_u64(overflow_policy)).collect::<R<_>>()?;
                 Ok(Series::new(name, converted))
             }
             U256Type::Decimal128 => {
                 Err(CollectError::CollectError("DECIMAL128 not implemented".to_string()))
             }
+            U256Type::Hex => {
+                let converted: Vec<Option<String>> =
+                    self.iter().map(|v| v.map(|x| format!("{:#x}", x))).collect();
+                Ok(Series::new(name, converted))
+            }
         }
     }
 }
@@ -121,6 +133,7 @@ impl ToU256Series for Vec<I256> {
         name: String,
         dtype: U256Type,
         column_encoding: &ColumnEncoding,
+        overflow_policy: &OverflowPolicy,
     ) -> Result<Series, CollectError> {
         let name = name + dtype.suffix().as_str();
         let name = name.as_str();
@@ -139,7 +152,7 @@ impl ToU256Series for Vec<I256> {
             }
             U256Type::F32 => {
                 let converted: Vec<Option<f32>> =
-                    self.iter().map(|v| v.to_string().parse::<f32>().ok()).collect();
+                    self.iter().map(|v| v.narrow_f32(overflow_policy)).collect::<R<_>>()?;
                 Ok(Series::new(name, converted))
             }
             U256Type::F64 => {
@@ -148,16 +161,22 @@ impl ToU256Series for Vec<I256> {
                 Ok(Series::new(name, converted))
             }
             U256Type::U32 => {
-                let converted: Vec<u32> = self.iter().map(|v| v.as_u32()).collect();
+                let converted: Vec<Option<u32>> =
+                    self.iter().map(|v| v.narrow_u32(overflow_policy)).collect::<R<_>>()?;
                 Ok(Series::new(name, converted))
             }
             U256Type::U64 => {
-                let converted: Vec<u64> = self.iter().map(|v| v.as_u64()).collect();
+                let converted: Vec<Option<u64>> =
+                    self.iter().map(|v| v.narrow_u64(overflow_policy)).collect::<R<_>>()?;
                 Ok(Series::new(name, converted))
             }
             U256Type::Decimal128 => {
                 Err(CollectError::CollectError("DECIMAL128 not implemented".to_string()))
             }
+            U256Type::Hex => {
+                let converted: Vec<String> = self.iter().map(i256_to_hex).collect();
+                Ok(Series::new(name, converted))
+            }
         }
     }
 }
@@ -168,6 +187,7 @@ impl ToU256Series for Vec<Option<I256>> {
         name: String,
         dtype: U256Type,
         column_encoding: &ColumnEncoding,
+        overflow_policy: &OverflowPolicy,
     ) -> Result<Series, CollectError> {
         let name = name + dtype.suffix().as_str();
         let name = name.as_str();
@@ -187,10 +207,8 @@ impl ToU256Series for Vec<Option<I256>> {
                 Ok(Series::new(name, converted))
             }
             U256Type::F32 => {
-                let converted: Vec<Option<f32>> = self
-                    .iter()
-                    .map(|v| v.map(|x| x.to_string().parse::<f32>().ok()).flatten())
-                    .collect();
+                let converted: Vec<Option<f32>> =
+                    self.iter().map(|v| v.narrow_f32(overflow_policy)).collect::<R<_>>()?;
                 Ok(Series::new(name, converted))
             }
             U256Type::F64 => {
@@ -202,17 +220,195 @@ impl ToU256Series for Vec<Option<I256>> {
             }
             U256Type::U32 => {
                 let converted: Vec<Option<u32>> =
-                    self.iter().map(|v| v.map(|x| x.as_u32())).collect();
+                    self.iter().map(|v| v.narrow_u32(overflow_policy)).collect::<R<_>>()?;
                 Ok(Series::new(name, converted))
             }
             U256Type::U64 => {
                 let converted: Vec<Option<u64>> =
-                    self.iter().map(|v| v.map(|x| x.as_u64())).collect();
+                    self.iter().map(|v| v.narrow_u64(overflow_policy)).collect::<R<_>>()?;
                 Ok(Series::new(name, converted))
             }
             U256Type::Decimal128 => {
                 Err(CollectError::CollectError("DECIMAL128 not implemented".to_string()))
             }
+            U256Type::Hex => {
+                let converted: Vec<Option<String>> =
+                    self.iter().map(|v| v.as_ref().map(i256_to_hex)).collect();
+                Ok(Series::new(name, converted))
+            }
+        }
+    }
+}
+
+/// hex string of the sign and magnitude of value, e.g. -0x1a
+fn i256_to_hex(value: &I256) -> String {
+    match value.is_negative() {
+        true => format!("-{:#x}", value.unsigned_abs()),
+        false => format!("{:#x}", value.into_raw()),
+    }
+}
+
+/// drop output columns of u256 representations that schema excludes
+pub(crate) fn drop_excluded_u256_columns(df: DataFrame, schema: &Table) -> DataFrame {
+    let columns: Vec<&String> = schema
+        .excluded_u256_columns
+        .iter()
+        .filter(|column| df.get_column_index(column).is_some())
+        .collect();
+    match columns.is_empty() {
+        true => df,
+        false => df.drop_many(&columns),
+    }
+}
+
+/// Converts U256-like data into narrower numeric types according to an OverflowPolicy
+pub trait NarrowU256 {
+    /// convert to u32, returning None for nulls and for overflows under OverflowPolicy::Null
+    fn narrow_u32(&self, policy: &OverflowPolicy) -> R<Option<u32>>;
+
+    /// convert to u64, returning None for nulls and for overflows under OverflowPolicy::Null
+    fn narrow_u64(&self, policy: &OverflowPolicy) -> R<Option<u64>>;
+
+    /// convert to f32, returning None for nulls and for overflows under OverflowPolicy::Null
+    fn narrow_f32(&self, policy: &OverflowPolicy) -> R<Option<f32>>;
+}
+
+fn overflow<T>(
+    value: &dyn std::fmt::Display,
+    saturated: T,
+    policy: &OverflowPolicy,
+) -> R<Option<T>> {
+    match policy {
+        OverflowPolicy::Error => Err(CollectError::CollectError(format!(
+            "value {} overflows {}",
+            value,
+            std::any::type_name::<T>()
+        ))),
+        OverflowPolicy::Saturate => Ok(Some(saturated)),
+        OverflowPolicy::Null => Ok(None),
+    }
+}
+
+impl NarrowU256 for U256 {
+    fn narrow_u32(&self, policy: &OverflowPolicy) -> R<Option<u32>> {
+        match u32::try_from(*self) {
+            Ok(value) => Ok(Some(value)),
+            Err(_) => overflow(self, u32::MAX, policy),
+        }
+    }
+
+    fn narrow_u64(&self, policy: &OverflowPolicy) -> R<Option<u64>> {
+        match u64::try_from(*self) {
+            Ok(value) => Ok(Some(value)),
+            Err(_) => overflow(self, u64::MAX, policy),
+        }
+    }
+
+    fn narrow_f32(&self, policy: &OverflowPolicy) -> R<Option<f32>> {
+        match self.to_string().parse::<f32>() {
+            Ok(value) if value.is_finite() => Ok(Some(value)),
+            _ => overflow(self, f32::MAX, policy),
+        }
+    }
+}
+
+impl NarrowU256 for I256 {
+    fn narrow_u32(&self, policy: &OverflowPolicy) -> R<Option<u32>> {
+        match self.is_negative() {
+            true => overflow(self, 0, policy),
+            false => self.into_raw().narrow_u32(policy),
+        }
+    }
+
+    fn narrow_u64(&self, policy: &OverflowPolicy) -> R<Option<u64>> {
+        match self.is_negative() {
+            true => overflow(self, 0, policy),
+            false => self.into_raw().narrow_u64(policy),
         }
     }
+
+    fn narrow_f32(&self, policy: &OverflowPolicy) -> R<Option<f32>> {
+        match self.to_string().parse::<f32>() {
+            Ok(value) if value.is_finite() => Ok(Some(value)),
+            _ if self.is_negative() => overflow(self, f32::MIN, policy),
+            _ => overflow(self, f32::MAX, policy),
+        }
+    }
+}
+
+impl<T: NarrowU256> NarrowU256 for Option<T> {
+    fn narrow_u32(&self, policy: &OverflowPolicy) -> R<Option<u32>> {
+        self.as_ref().map_or(Ok(None), |value| value.narrow_u32(policy))
+    }
+
+    fn narrow_u64(&self, policy: &OverflowPolicy) -> R<Option<u64>> {
+        self.as_ref().map_or(Ok(None), |value| value.narrow_u64(policy))
+    }
+
+    fn narrow_f32(&self, policy: &OverflowPolicy) -> R<Option<f32>> {
+        self.as_ref().map_or(Ok(None), |value| value.narrow_f32(policy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_narrow_u256_overflow() {
+        let big = U256::from(u64::MAX) + 1;
+        assert!(big.narrow_u64(&OverflowPolicy::Error).is_err());
+        assert_eq!(big.narrow_u64(&OverflowPolicy::Saturate).unwrap(), Some(u64::MAX));
+        assert_eq!(big.narrow_u64(&OverflowPolicy::Null).unwrap(), None);
+        assert_eq!(big.narrow_u32(&OverflowPolicy::Saturate).unwrap(), Some(u32::MAX));
+        assert_eq!(U256::from(7).narrow_u32(&OverflowPolicy::Error).unwrap(), Some(7));
+
+        let huge = U256::MAX;
+        assert!(huge.narrow_f32(&OverflowPolicy::Error).is_err());
+        assert_eq!(huge.narrow_f32(&OverflowPolicy::Saturate).unwrap(), Some(f32::MAX));
+        assert_eq!(Some(huge).narrow_f32(&OverflowPolicy::Null).unwrap(), None);
+        assert_eq!(None::<U256>.narrow_f32(&OverflowPolicy::Error).unwrap(), None);
+    }
+
+    #[test]
+    fn test_narrow_i256_overflow() {
+        let negative = I256::from(-1);
+        assert!(negative.narrow_u64(&OverflowPolicy::Error).is_err());
+        assert_eq!(negative.narrow_u64(&OverflowPolicy::Saturate).unwrap(), Some(0));
+        assert_eq!(I256::MIN.narrow_f32(&OverflowPolicy::Saturate).unwrap(), Some(f32::MIN));
+        assert_eq!(I256::from(5).narrow_u32(&OverflowPolicy::Error).unwrap(), Some(5));
+    }
+
+    #[test]
+    fn test_u256_series_overflow() {
+        let values = vec![U256::from(1), U256::from(u64::MAX) + 1];
+        let encoding = ColumnEncoding::Binary;
+        let result =
+            values.to_u256_series("x".into(), U256Type::U64, &encoding, &OverflowPolicy::Error);
+        assert!(result.is_err());
+        let series = values
+            .to_u256_series("x".into(), U256Type::U64, &encoding, &OverflowPolicy::Null)
+            .unwrap();
+        assert_eq!(series.name(), "x_u64");
+        assert_eq!(series.null_count(), 1);
+    }
+
+    #[test]
+    fn test_u256_string_series_round_trip() {
+        let big = U256::from(1) << 200;
+        let values = vec![Some(big), None];
+        let (encoding, policy) = (ColumnEncoding::Binary, OverflowPolicy::Error);
+        let series = values.to_u256_series("value".into(), U256Type::String, &encoding, &policy);
+        let series = series.unwrap();
+        assert_eq!(series.name(), "value_string");
+        let decimal = series.utf8().unwrap().get(0).unwrap().to_string();
+        assert_eq!(decimal, "1606938044258990275541962092341162602522202993782792835301376");
+        assert_eq!(U256::from_dec_str(&decimal).unwrap(), big);
+
+        let series = values.to_u256_series("value".into(), U256Type::Hex, &encoding, &policy);
+        let series = series.unwrap();
+        assert_eq!(series.name(), "value_hex");
+        assert_eq!(series.utf8().unwrap().get(0), Some(format!("0x1{}", "0".repeat(50)).as_str()));
+        assert_eq!(i256_to_hex(&I256::from(-26)), "-0x1a");
+    }
 }
@@ -41,6 +41,13 @@ macro_rules! define_datatypes {
                 }
             }
 
+            /// columns that identify a row of datatype, None if rows are compared in full
+            pub fn dedup_key(&self) -> Option<Vec<&'static str>> {
+                match *self {
+                    $(Datatype::$datatype => $datatype::dedup_key(),)*
+                }
+            }
+
             /// default columns of datatype
             pub fn default_columns(&self) -> Vec<&'static str> {
                 match *self {
@@ -90,6 +97,31 @@ macro_rules! define_datatypes {
                 }
             }
 
+            /// lineage of columns of datatype that are computed rather than copied from rpc
+            /// response fields
+            pub fn column_lineage(&self) -> Vec<(&'static str, ColumnLineage)> {
+                match *self {
+                    $(Datatype::$datatype => $datatype::column_lineage(),)*
+                }
+            }
+
+            /// warning for output format that works poorly for datatype, with the reason and
+            /// the format to use instead
+            pub fn format_warning(&self, format: &FileFormat) -> Option<String> {
+                let discouraged = match *self {
+                    $(Datatype::$datatype => $datatype::discouraged_formats(),)*
+                };
+                discouraged.into_iter().find(|d| &d.format == format).map(|d| {
+                    format!(
+                        "{} output of {} is not recommended ({}), use {} instead",
+                        format.as_str(),
+                        self.name(),
+                        d.reason,
+                        d.alternative.as_str()
+                    )
+                })
+            }
+
             /// whether datatype can be collected by block
             pub fn can_collect_by_block(&self) -> bool {
                 match *self {
@@ -115,7 +147,7 @@ macro_rules! define_datatypes {
             let task = match datatype {
                 MetaDatatype::Scalar(datatype) => {
                     let inner_request_size = if datatype.use_block_ranges() {
-                        Some(source.inner_request_size)
+                        Some(source.block_range_size(&datatype))
                     } else {
                         None
                     };
@@ -156,7 +188,7 @@ macro_rules! define_datatypes {
             let task = match datatype {
                 MetaDatatype::Scalar(datatype) => {
                     let inner_request_size = if datatype.use_block_ranges() {
-                        Some(source.inner_request_size)
+                        Some(source.block_range_size(&datatype))
                     } else {
                         None
                     };
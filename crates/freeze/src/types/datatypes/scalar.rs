@@ -3,6 +3,7 @@ use polars::prelude::*;
 use std::collections::HashMap;
 
 define_datatypes!(
+    AccessLists,
     AddressAppearances,
     BalanceDiffs,
     BalanceReads,
@@ -11,6 +12,7 @@ define_datatypes!(
     CodeDiffs,
     CodeReads,
     Codes,
+    ContractCode,
     Contracts,
     Erc20Balances,
     Erc20Metadata,
@@ -19,7 +21,9 @@ define_datatypes!(
     Erc20Approvals,
     Erc721Metadata,
     Erc721Transfers,
+    Erc1155Transfers,
     EthCalls,
+    FeeHistory,
     FourByteCounts,
     GethCalls,
     GethCodeDiffs,
@@ -29,29 +33,61 @@ define_datatypes!(
     GethOpcodes,
     JavascriptTraces,
     Logs,
+    MethodIdCounts,
     NativeTransfers,
+    NftTransfers,
     NonceDiffs,
     NonceReads,
     Nonces,
+    Receipts,
     Slots,
+    StateChanges,
     StorageDiffs,
     StorageReads,
     Traces,
     TraceCalls,
     Transactions,
+    UncleBlocks,
     VmTraces,
+    Withdrawals,
 );
 
 impl Datatype {
+    /// all datatypes, each with its aliases
+    pub fn all_with_aliases() -> Vec<(Datatype, Vec<&'static str>)> {
+        Datatype::all().into_iter().map(|datatype| (datatype, datatype.aliases())).collect()
+    }
+
+    /// datatypes selected by `all`, which can be collected from blocks without other parameters
+    pub fn all_without_parameters() -> Vec<Datatype> {
+        Datatype::all()
+            .into_iter()
+            .filter(|datatype| {
+                datatype.can_collect_by_block() && datatype.required_parameters().is_empty()
+            })
+            .collect()
+    }
+
+    /// names of all datatypes, with aliases in parentheses, e.g. `logs (events)`
+    pub fn names_with_aliases() -> Vec<String> {
+        Datatype::all_with_aliases()
+            .into_iter()
+            .map(|(datatype, aliases)| match aliases.is_empty() {
+                true => datatype.name(),
+                false => format!("{} ({})", datatype.name(), aliases.join(", ")),
+            })
+            .collect()
+    }
+
     fn alias_map() -> Result<HashMap<String, Datatype>, ParseError> {
         let mut map = HashMap::new();
-        for datatype in Datatype::all() {
+        for (datatype, aliases) in Datatype::all_with_aliases() {
             let key = datatype.name();
             if map.contains_key(&key) {
                 return Err(ParseError::ParseError("conflict in datatype names".to_string()))
             }
             map.insert(key, datatype);
-            for key in datatype.aliases().into_iter() {
+            for key in aliases.into_iter() {
                 if map.contains_key(key) {
                     return Err(ParseError::ParseError("conflict in datatype names".to_string()))
                 }
@@ -67,7 +103,41 @@ impl std::str::FromStr for Datatype {
 
     fn from_str(s: &str) -> Result<Datatype, ParseError> {
         let mut map = Datatype::alias_map()?;
-        map.remove(s)
-            .ok_or_else(|| ParseError::ParseError(format!("no datatype matches input: {}", s)))
+        map.remove(s).ok_or_else(|| {
+            ParseError::ParseError(format!(
+                "no datatype matches input: {}, valid datatypes are: {}",
+                s,
+                Datatype::names_with_aliases().join(", ")
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_warnings() {
+        for datatype in [Datatype::Traces, Datatype::VmTraces, Datatype::GethOpcodes] {
+            assert!(datatype.format_warning(&FileFormat::Csv).is_some());
+            assert!(datatype.format_warning(&FileFormat::Json).is_some());
+            assert!(datatype.format_warning(&FileFormat::Parquet).is_none());
+        }
+        assert!(Datatype::Blocks.format_warning(&FileFormat::Csv).is_none());
+        let warning = Datatype::VmTraces.format_warning(&FileFormat::Csv).unwrap();
+        assert!(warning.starts_with("csv output of vm_traces is not recommended"));
+        assert!(warning.ends_with("use parquet instead"));
+    }
+
+    #[test]
+    fn test_datatype_aliases() {
+        let datatype: Datatype = "token_transfers".parse().unwrap();
+        assert_eq!(datatype, Datatype::Erc20Transfers);
+        let datatype: Datatype = "erc20_transfers".parse().unwrap();
+        assert_eq!(datatype, Datatype::Erc20Transfers);
+        let message = "erc20_transfer".parse::<Datatype>().unwrap_err().to_string();
+        assert!(message.contains("no datatype matches input: erc20_transfer"), "{}", message);
+        assert!(message.contains("logs (events)") && message.contains("transactions (txs)"));
     }
 }
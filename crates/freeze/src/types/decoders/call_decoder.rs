@@ -0,0 +1,131 @@
+use crate::ColumnType;
+use ethers::prelude::*;
+use ethers_core::abi::{HumanReadableParser, LenientTokenizer, ParamType, Token, Tokenizer};
+
+/// container for encoding eth_calls to a function and decoding their outputs
+#[derive(Clone, Debug, PartialEq)]
+pub struct CallDecoder {
+    /// the raw function signature string ex: balanceOf(address)(uint256) or function
+    /// balanceOf(address owner) view returns (uint256)
+    pub raw: String,
+    /// decoded abi type of function signature string
+    pub function: abi::Function,
+}
+
+impl CallDecoder {
+    /// create a new CallDecoder from a function signature
+    /// ex: CallDecoder::new("getReserves()(uint112 reserve0, uint112 reserve1, uint32
+    /// timestamp)".to_string())
+    pub fn new(function_signature: String) -> Result<Self, String> {
+        let function = HumanReadableParser::parse_function(function_signature.as_str())
+            .map_err(|e| {
+                format!(
+                    "incorrectly formatted function {} (expect something like balanceOf(address)(uint256)) err: {}",
+                    function_signature, e
+                )
+            })?;
+        let decoder = Self { function, raw: function_signature };
+        for output in decoder.function.outputs.iter() {
+            output_column_type(&output.kind).ok_or_else(|| {
+                format!("unsupported output type {} in function {}", output.kind, decoder.raw)
+            })?;
+        }
+        Ok(decoder)
+    }
+
+    /// 4 byte selector of function
+    pub fn selector(&self) -> [u8; 4] {
+        self.function.short_signature()
+    }
+
+    /// encode call data of a call to function, with one string per function input
+    pub fn encode_call_data(&self, args: &[&str]) -> Result<Vec<u8>, String> {
+        if args.len() != self.function.inputs.len() {
+            return Err(format!(
+                "function {} takes {} args but {} were given",
+                self.function.name,
+                self.function.inputs.len(),
+                args.len()
+            ))
+        }
+        let tokens = self
+            .function
+            .inputs
+            .iter()
+            .zip(args.iter())
+            .map(|(input, arg)| {
+                LenientTokenizer::tokenize(&input.kind, arg.trim())
+                    .map_err(|e| format!("could not encode arg {} as {}: {}", arg, input.kind, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.function.encode_input(&tokens).map_err(|e| e.to_string())
+    }
+
+    /// names and types of the columns of the decoded outputs of function
+    ///
+    /// columns are named by function, followed by the name or index of the output when the
+    /// function has named or multiple outputs, ex: balanceOf or getReserves__reserve0
+    pub fn output_columns(&self) -> Vec<(String, ColumnType)> {
+        let outputs = &self.function.outputs;
+        outputs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, output)| {
+                let name = match (output.name.is_empty(), outputs.len()) {
+                    (true, 1) => self.function.name.clone(),
+                    (true, _) => format!("{}__{}", self.function.name, i),
+                    (false, _) => format!("{}__{}", self.function.name, output.name),
+                };
+                output_column_type(&output.kind).map(|column_type| (name, column_type))
+            })
+            .collect()
+    }
+
+    /// decode output data of a call to function, with one token per output column
+    pub fn decode_output(&self, output_data: &[u8]) -> Result<Vec<Token>, String> {
+        self.function.decode_output(output_data).map_err(|e| e.to_string())
+    }
+}
+
+/// column type of a decoded output, or None if outputs of type cannot be stored in a column
+fn output_column_type(kind: &ParamType) -> Option<ColumnType> {
+    match kind {
+        ParamType::Uint(bits) | ParamType::Int(bits) if *bits > 64 => Some(ColumnType::UInt256),
+        ParamType::Uint(_) => Some(ColumnType::UInt64),
+        ParamType::Int(_) => Some(ColumnType::Int64),
+        ParamType::Address | ParamType::Bytes | ParamType::FixedBytes(_) => {
+            Some(ColumnType::Binary)
+        }
+        ParamType::Bool => Some(ColumnType::Boolean),
+        ParamType::String => Some(ColumnType::String),
+        ParamType::Array(_) | ParamType::FixedArray(_, _) | ParamType::Tuple(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_decoder() {
+        let decoder = CallDecoder::new("balanceOf(address)(uint256)".to_string()).unwrap();
+        assert_eq!(decoder.selector(), [0x70, 0xa0, 0x82, 0x31]);
+        assert_eq!(decoder.output_columns(), vec![("balanceOf".to_string(), ColumnType::UInt256)]);
+        let call_data =
+            decoder.encode_call_data(&["0x00000000000000000000000000000000000000aa"]).unwrap();
+        assert_eq!(call_data.len(), 36);
+        assert_eq!(call_data[35], 0xaa);
+        assert!(decoder.encode_call_data(&[]).is_err());
+
+        let raw =
+            "function getReserves() view returns (uint112 reserve0, uint112 reserve1, uint32 \
+                   timestamp)";
+        let decoder = CallDecoder::new(raw.to_string()).unwrap();
+        let names: Vec<_> = decoder.output_columns().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(
+            names,
+            vec!["getReserves__reserve0", "getReserves__reserve1", "getReserves__timestamp"]
+        );
+        assert!(CallDecoder::new("getValues()(uint256[])".to_string()).is_err());
+    }
+}
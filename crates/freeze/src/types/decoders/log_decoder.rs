@@ -1,4 +1,4 @@
-use crate::{err, CollectError, ColumnEncoding, ToU256Series, U256Type};
+use crate::{err, CollectError, ColumnEncoding, OverflowPolicy, ToU256Series, U256Type};
 use ethers::prelude::*;
 use ethers_core::abi::{AbiEncode, EventParam, HumanReadableParser, ParamType, RawLog, Token};
 use polars::prelude::*;
@@ -66,6 +66,7 @@ impl LogDecoder {
         chunk_len: usize,
         u256_types: &[U256Type],
         column_encoding: &ColumnEncoding,
+        overflow_policy: &OverflowPolicy,
     ) -> Result<Vec<Series>, CollectError> {
         // This is a smooth brain way of doing this, but I can't think of a better way right now
         let mut ints: Vec<i64> = vec![];
@@ -158,6 +159,7 @@ impl LogDecoder {
                     name.clone(),
                     u256_type.clone(),
                     column_encoding,
+                    overflow_policy,
                 )?)
             }
             Ok(series_vec)
@@ -168,6 +170,7 @@ impl LogDecoder {
                     name.clone(),
                     u256_type.clone(),
                     column_encoding,
+                    overflow_policy,
                 )?)
             }
             Ok(series_vec)
@@ -1,3 +1,6 @@
+/// call decoder
+pub mod call_decoder;
 /// log decoder
 pub mod log_decoder;
+pub use call_decoder::*;
 pub use log_decoder::*;
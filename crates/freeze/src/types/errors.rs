@@ -85,6 +85,14 @@ pub enum CollectError {
     /// Generic RPC Error
     #[error("RPC call error")]
     RPCError(String),
+
+    /// Collection was cancelled by its cancellation token
+    #[error("Collection cancelled")]
+    Cancelled,
+
+    /// Block of a requested range that the provider has not produced or has pruned
+    #[error("block {0} not found, skip missing blocks with --allow-missing-blocks")]
+    BlockNotFound(u64),
 }
 
 /// Error related to parsing
@@ -143,4 +151,8 @@ pub enum FileError {
     /// Error in writing file
     #[error("Error writing file")]
     FileWriteError,
+
+    /// Error in summarizing parquet dataset
+    #[error("Dataset error: {0}")]
+    DatasetError(String),
 }
@@ -1,6 +1,11 @@
-use crate::CollectError;
-use indicatif::ProgressBar;
-use std::{path::PathBuf, sync::Arc, time::SystemTime};
+use crate::{start_timing_report, CancellationToken, CollectError, LogFormat, TimingReport};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget};
+use std::{
+    io::IsTerminal,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
 /// configuration of execution environment
 #[derive(Clone)]
@@ -9,10 +14,14 @@ pub struct ExecutionEnv {
     pub dry: bool,
     /// verbose output
     pub verbose: u32,
+    /// format of summaries and chunk progress
+    pub log_format: LogFormat,
     /// whether to generate report
     pub report: bool,
-    /// progress bar
+    /// progress bar of chunks
     pub bar: Option<Arc<ProgressBar>>,
+    /// progress bar of requests of chunks that have started collecting, drawn below `bar`
+    pub request_bar: Option<Arc<ProgressBar>>,
     /// cli command
     pub cli_command: Option<Vec<String>>,
     /// input args
@@ -25,6 +34,29 @@ pub struct ExecutionEnv {
     pub t_end: Option<SystemTime>,
     /// report directory
     pub report_dir: Option<PathBuf>,
+    /// durations of pipeline phases
+    pub timing: Option<TimingReport>,
+    /// print request and chunk statistics after collection
+    pub stats: bool,
+    /// number of collected chunks that can wait to be written
+    pub writer_queue_size: usize,
+    /// estimate output size from a sample chunk instead of collecting
+    pub estimate_size: bool,
+    /// check output files for missing blocks and rows after collection
+    pub verify: bool,
+    /// token that stops chunks from starting when cancelled, while started chunks finish
+    pub interrupt: Option<CancellationToken>,
+    /// keep collecting chunks of new blocks after the initial range, until interrupted
+    pub follow: Option<FollowConfig>,
+}
+
+/// configuration of following the chain for new blocks
+#[derive(Clone, Debug)]
+pub struct FollowConfig {
+    /// time between polls for the latest block
+    pub poll_interval: Duration,
+    /// number of blocks per chunk of new blocks
+    pub chunk_size: u64,
 }
 
 impl ExecutionEnv {
@@ -37,6 +69,19 @@ impl ExecutionEnv {
     pub fn set_end_time(self) -> Self {
         ExecutionEnv { t_end: Some(SystemTime::now()), ..self }
     }
+
+    /// whether interrupt token has been cancelled
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupt.as_ref().map(|interrupt| interrupt.is_cancelled()).unwrap_or(false)
+    }
+
+    /// wait until interrupt token is cancelled, or forever if there is no token
+    pub async fn interrupted(&self) {
+        match &self.interrupt {
+            Some(interrupt) => interrupt.cancelled().await,
+            None => std::future::pending().await,
+        }
+    }
 }
 
 impl Default for ExecutionEnv {
@@ -45,28 +90,53 @@ impl Default for ExecutionEnv {
     }
 }
 
-fn new_bar(n: u64) -> Result<Arc<ProgressBar>, CollectError> {
-    let bar = Arc::new(ProgressBar::new(n));
+const DEFAULT_WRITER_QUEUE_SIZE: usize = 4;
+
+/// create bars of chunks and of requests
+///
+/// bars are hidden when stdout is not a terminal, in which case progress is printed as lines
+fn new_bars(n: u64) -> Result<(Arc<ProgressBar>, Arc<ProgressBar>), CollectError> {
+    let multi = if std::io::stdout().is_terminal() {
+        MultiProgress::new()
+    } else {
+        MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
+    };
+    let bar = Arc::new(multi.add(ProgressBar::new(n)));
     bar.set_style(
         indicatif::ProgressStyle::default_bar()
-            .template("{wide_msg} ⏳ = {eta_precise} \n{wide_bar:.green}  {human_pos} / {human_len}  ⌛ = {elapsed_precise} ")
+            .template("{wide_msg} ⏳ = {eta_precise} \n{wide_bar:.green}  {human_pos} / {human_len} chunks  {per_sec}  ⌛ = {elapsed_precise} ")
+            .map_err(|_| CollectError::CollectError("error creating progress bar".to_string()))?,
+    );
+    let request_bar = Arc::new(multi.add(ProgressBar::new(0)));
+    request_bar.set_style(
+        indicatif::ProgressStyle::default_bar()
+            .template("{wide_bar:.cyan}  {human_pos} / {human_len} requests of started chunks ")
             .map_err(|_| CollectError::CollectError("error creating progress bar".to_string()))?,
     );
-    Ok(bar)
+    Ok((bar, request_bar))
 }
 
 /// build ExecutionEnv using builder pattern
 pub struct ExecutionEnvBuilder {
     dry: bool,
     verbose: u32,
+    log_format: LogFormat,
     report: bool,
     bar: Option<Arc<ProgressBar>>,
+    request_bar: Option<Arc<ProgressBar>>,
     cli_command: Option<Vec<String>>,
     args: Option<String>,
     t_start_parse: Option<SystemTime>,
     t_start: SystemTime,
     t_end: Option<SystemTime>,
     report_dir: Option<PathBuf>,
+    timing: Option<TimingReport>,
+    stats: bool,
+    writer_queue_size: usize,
+    estimate_size: bool,
+    verify: bool,
+    interrupt: Option<CancellationToken>,
+    follow: Option<FollowConfig>,
 }
 
 impl Default for ExecutionEnvBuilder {
@@ -74,14 +144,23 @@ impl Default for ExecutionEnvBuilder {
         ExecutionEnvBuilder {
             dry: false,
             verbose: 1,
+            log_format: LogFormat::Text,
             report: true,
             bar: None,
+            request_bar: None,
             cli_command: Some(std::env::args().collect()),
             args: None,
             t_start_parse: None,
             t_start: SystemTime::now(),
             t_end: None,
             report_dir: None,
+            timing: None,
+            stats: false,
+            writer_queue_size: DEFAULT_WRITER_QUEUE_SIZE,
+            estimate_size: false,
+            verify: false,
+            interrupt: None,
+            follow: None,
         }
     }
 }
@@ -104,6 +183,12 @@ impl ExecutionEnvBuilder {
         self
     }
 
+    /// format of summaries and chunk progress
+    pub fn log_format(mut self, log_format: LogFormat) -> Self {
+        self.log_format = log_format;
+        self
+    }
+
     /// generate report
     pub fn report(mut self, report: bool) -> Self {
         self.report = report;
@@ -118,10 +203,54 @@ impl ExecutionEnvBuilder {
 
     /// progress bar size
     pub fn bar(mut self, n: u64) -> Result<Self, CollectError> {
-        self.bar = Some(new_bar(n)?);
+        let (bar, request_bar) = new_bars(n)?;
+        self.bar = Some(bar);
+        self.request_bar = Some(request_bar);
         Ok(self)
     }
 
+    /// aggregate durations of pipeline phases
+    pub fn timing_report(mut self, timing_report: bool) -> Result<Self, CollectError> {
+        self.timing = if timing_report { Some(start_timing_report()?) } else { None };
+        Ok(self)
+    }
+
+    /// print request and chunk statistics after collection
+    pub fn stats(mut self, stats: bool) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// number of collected chunks that can wait to be written
+    pub fn writer_queue_size(mut self, writer_queue_size: usize) -> Self {
+        self.writer_queue_size = writer_queue_size;
+        self
+    }
+
+    /// estimate output size from a sample chunk instead of collecting
+    pub fn estimate_size(mut self, estimate_size: bool) -> Self {
+        self.estimate_size = estimate_size;
+        self
+    }
+
+    /// check output files for missing blocks and rows after collection
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// stop starting chunks when token is cancelled
+    pub fn interrupt(mut self, interrupt: CancellationToken) -> Self {
+        self.interrupt = Some(interrupt);
+        self
+    }
+
+    /// keep collecting chunks of new blocks after the initial range
+    pub fn follow(mut self, follow: Option<FollowConfig>) -> Self {
+        self.follow = follow;
+        self
+    }
+
     /// cli command
     pub fn cli_command(mut self, cli_command: Vec<String>) -> Self {
         self.cli_command = Some(cli_command);
@@ -139,14 +268,23 @@ impl ExecutionEnvBuilder {
         ExecutionEnv {
             dry: self.dry,
             verbose: self.verbose,
+            log_format: self.log_format,
             report: self.report,
             bar: self.bar,
+            request_bar: self.request_bar,
             cli_command: self.cli_command,
             args: self.args,
             t_start_parse: self.t_start_parse,
             t_start: self.t_start,
             t_end: self.t_end,
             report_dir: self.report_dir,
+            timing: self.timing,
+            stats: self.stats,
+            writer_queue_size: self.writer_queue_size,
+            estimate_size: self.estimate_size,
+            verify: self.verify,
+            interrupt: self.interrupt,
+            follow: self.follow,
         }
     }
 }
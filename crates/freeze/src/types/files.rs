@@ -1,4 +1,6 @@
-use crate::{CollectError, Datatype, MetaDatatype, ParseError, Partition, Query};
+use crate::{
+    err, BlockChunk, ChunkData, CollectError, Datatype, MetaDatatype, ParseError, Partition, Query,
+};
 use std::{collections::HashMap, path::PathBuf};
 
 /// Options for file output
@@ -18,12 +20,44 @@ pub struct FileOutput {
     pub format: FileFormat,
     /// Number of rows per parquet row group
     pub row_group_size: Option<usize>,
+    /// Approximate uncompressed size in bytes of parquet row groups, used instead of
+    /// `row_group_size` when given
+    pub row_group_bytes: Option<u64>,
     /// Parquet statistics recording flag
     pub parquet_statistics: bool,
+    /// Whether to dictionary encode parquet columns
+    pub parquet_dictionary: bool,
+    /// String columns to dictionary encode, other columns are written plain
+    pub dictionary_columns: Option<Vec<String>>,
     /// Parquet compression options
     pub parquet_compression: polars::prelude::ParquetCompression,
+    /// Whether to write each datatype as a parquet dataset with `_metadata` summary files
+    pub dataset: bool,
+    /// Approximate size in bytes of output files, block range chunks are split to reach it
+    pub target_file_size: Option<u64>,
+    /// Number of blocks in each `block_number=<bucket>` subdirectory of hive partitioned output
+    pub partition_width: Option<u64>,
+    /// Keys of hive partitioned `key=value` subdirectories, in order of nesting
+    pub hive_partitions: Vec<HiveKey>,
+    /// Whether to also write the keys of hive partitions as columns of output files
+    pub hive_partition_columns: bool,
+    /// Template of file names, [`DEFAULT_FILE_NAME_TEMPLATE`] if not given
+    pub file_name_template: Option<String>,
+    /// String prepended to the name of every output file, e.g. a run id
+    pub output_prefix: Option<String>,
+    /// Compression of csv files, applied while writing
+    pub csv_compression: CsvCompression,
+    /// Whether to record cryo version, rpc url hash, and block range in parquet footers
+    pub metadata: bool,
 }
 
+/// placeholders that can be used in file name templates
+pub const FILE_NAME_PLACEHOLDERS: [&str; 7] =
+    ["network", "datatype", "chunk", "start_block", "end_block", "chunk_index", "ext"];
+
+/// file name template that produces the default file names
+pub const DEFAULT_FILE_NAME_TEMPLATE: &str = "{network}__{datatype}__{chunk}.{ext}";
+
 /// Possible item to use as subdirectory
 #[derive(Clone, Debug)]
 pub enum SubDir {
@@ -35,6 +69,57 @@ pub enum SubDir {
     Custom(String),
 }
 
+/// Key of a `key=value` subdirectory of hive partitioned output
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HiveKey {
+    /// `network=<network>`
+    Network,
+    /// `datatype=<datatype>`
+    Datatype,
+    /// `block_<width>=<bucket>`, bucket is first block of chunk divided by width
+    BlockBucket(u64),
+}
+
+impl HiveKey {
+    /// name of key in subdirectory names, which is also the name of its column
+    pub fn name(&self) -> String {
+        match self {
+            HiveKey::Network => "network".to_string(),
+            HiveKey::Datatype => "datatype".to_string(),
+            HiveKey::BlockBucket(width) => format!("block_{}", width),
+        }
+    }
+}
+
+#[cfg(test)]
+impl Default for FileOutput {
+    fn default() -> FileOutput {
+        FileOutput {
+            output_dir: std::env::temp_dir(),
+            prefix: "ethereum".to_string(),
+            suffix: None,
+            subdirs: vec![],
+            overwrite: true,
+            format: FileFormat::Parquet,
+            row_group_size: None,
+            row_group_bytes: None,
+            parquet_statistics: true,
+            parquet_dictionary: true,
+            dictionary_columns: None,
+            parquet_compression: polars::prelude::ParquetCompression::Uncompressed,
+            dataset: false,
+            target_file_size: None,
+            partition_width: None,
+            hive_partitions: Vec::new(),
+            hive_partition_columns: false,
+            file_name_template: None,
+            output_prefix: None,
+            csv_compression: CsvCompression::None,
+            metadata: true,
+        }
+    }
+}
+
 impl FileOutput {
     /// get output file paths
     pub fn get_paths(
@@ -59,52 +144,188 @@ impl FileOutput {
         Ok(paths)
     }
 
-    /// get output file path
+    /// get output file path, creating its directory
     pub fn get_path(
         &self,
         query: &Query,
         partition: &Partition,
         datatype: Datatype,
     ) -> Result<PathBuf, CollectError> {
-        let filename = if let Some(suffix) = self.suffix.clone() {
-            format!(
-                "{}__{}__{}__{}.{}",
-                self.prefix.clone(),
-                datatype.name(),
-                suffix,
-                partition.label(&query.partitioned_by)?,
-                self.format.as_str(),
-            )
-        } else {
-            format!(
-                "{}__{}__{}.{}",
-                self.prefix.clone(),
-                datatype.name(),
-                partition.label(&query.partitioned_by)?,
-                self.format.as_str(),
-            )
+        let path = self.get_file_path(query, partition, datatype)?;
+        if self.format == FileFormat::Delta {
+            return Ok(path)
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|_| ParseError::ParseError("could not create dir".to_string()))?;
+        }
+
+        Ok(path)
+    }
+
+    /// get output file path, without creating its directory
+    ///
+    /// delta output is appended to one table per datatype, so every partition gets the directory
+    /// of the table
+    pub fn get_file_path(
+        &self,
+        query: &Query,
+        partition: &Partition,
+        datatype: Datatype,
+    ) -> Result<PathBuf, CollectError> {
+        if self.format == FileFormat::Delta {
+            return Ok(self.output_dir.join(self.get_datatype_name(datatype)))
+        }
+        let template = self.file_name_template.as_deref().unwrap_or(DEFAULT_FILE_NAME_TEMPLATE);
+        let mut filename = self.render_file_name(template, query, partition, datatype)?;
+        if let Some(output_prefix) = &self.output_prefix {
+            // templates can contain further subdirectories, the prefix goes on the file name
+            let name_start = filename.rfind('/').map(|i| i + 1).unwrap_or(0);
+            filename.insert_str(name_start, output_prefix);
+        }
+        let output_dir = match self.get_partition_subdir(partition) {
+            Some(partition_subdir) => self.get_datatype_dir(datatype).join(partition_subdir),
+            None => self.get_datatype_dir(datatype),
         };
-        let filename = std::path::Path::new(&filename).to_path_buf();
+        Ok(output_dir.join(filename))
+    }
+
+    /// extension of output files, including the extension of csv compression
+    pub fn extension(&self) -> String {
+        match (&self.format, self.csv_compression.extension()) {
+            (FileFormat::Csv, Some(extension)) => format!("csv.{}", extension),
+            (format, _) => format.as_str().to_string(),
+        }
+    }
+
+    /// fill in placeholders of file name template for the file of datatype in partition
+    ///
+    /// `{chunk_index}` is the position of the partition among the partitions of the query
+    pub fn render_file_name(
+        &self,
+        template: &str,
+        query: &Query,
+        partition: &Partition,
+        datatype: Datatype,
+    ) -> Result<String, CollectError> {
+        let mut rendered = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => return Err(err("unclosed { in file name template")),
+            };
+            rendered.push_str(&rest[..start]);
+            let value = match &rest[start + 1..end] {
+                "network" => self.prefix.clone(),
+                "datatype" => self.get_datatype_name(datatype),
+                "chunk" => partition.label(&query.partitioned_by)?,
+                "start_block" => format_block_bound(partition, |blocks| blocks.min_value())?,
+                "end_block" => format_block_bound(partition, |blocks| blocks.max_value())?,
+                "chunk_index" => {
+                    let label = partition.label(&query.partitioned_by)?;
+                    query
+                        .partitions
+                        .iter()
+                        .position(|other| {
+                            other.label(&query.partitioned_by).ok().as_ref() == Some(&label)
+                        })
+                        .ok_or(err("partition is not part of query"))?
+                        .to_string()
+                }
+                "ext" => self.extension(),
+                placeholder => {
+                    return Err(err(&format!(
+                        "unknown placeholder {{{}}} in file name template, valid placeholders: {}",
+                        placeholder,
+                        FILE_NAME_PLACEHOLDERS.map(|name| format!("{{{}}}", name)).join(", ")
+                    )))
+                }
+            };
+            rendered.push_str(&value);
+            rest = &rest[end + 1..];
+        }
+        rendered.push_str(rest);
+        Ok(rendered)
+    }
+
+    /// get directory where files of datatype are written
+    pub fn get_datatype_dir(&self, datatype: Datatype) -> PathBuf {
         let mut output_dir = std::path::Path::new(&self.output_dir).to_path_buf();
         for subdir in self.subdirs.iter() {
             let subdir_str: String = match subdir {
                 SubDir::Network => self.prefix.clone(),
-                SubDir::Datatype => match &self.suffix {
-                    Some(suffix) => datatype.name() + "__" + suffix.as_str(),
-                    None => datatype.name(),
-                },
+                SubDir::Datatype => self.get_datatype_name(datatype),
                 SubDir::Custom(subdir_str) => subdir_str.to_string(),
             };
             output_dir = output_dir.join(std::path::Path::new(&subdir_str));
         }
+        for key in self.hive_partitions.iter() {
+            let value = match key {
+                HiveKey::Network => self.prefix.clone(),
+                HiveKey::Datatype => self.get_datatype_name(datatype),
+                HiveKey::BlockBucket(_) => continue,
+            };
+            output_dir = output_dir.join(format!("{}={}", key.name(), value));
+        }
+        output_dir
+    }
 
-        std::fs::create_dir_all(output_dir.clone())
-            .map_err(|_| ParseError::ParseError("could not create dir".to_string()))?;
+    /// get hive partition subdirectory of partition, named by the bucket of its first block
+    ///
+    /// partitions without block numbers are written directly to the datatype directory
+    pub fn get_partition_subdir(&self, partition: &Partition) -> Option<String> {
+        if let Some((width, bucket)) = self.get_block_bucket(partition) {
+            return Some(format!("{}={}", HiveKey::BlockBucket(width).name(), bucket))
+        }
+        let partition_width = self.partition_width?;
+        let first_block = partition.block_numbers.as_ref()?.min_value()?;
+        Some(format!("block_number={}", first_block - first_block % partition_width))
+    }
 
-        Ok(output_dir.join(filename))
+    /// get width and bucket of the block bucket hive partition of partition
+    pub fn get_block_bucket(&self, partition: &Partition) -> Option<(u64, u64)> {
+        let width = self.hive_partitions.iter().find_map(|key| match key {
+            HiveKey::BlockBucket(width) => Some(*width),
+            _ => None,
+        })?;
+        let first_block = partition.block_numbers.as_ref()?.min_value()?;
+        Some((width, first_block / width))
+    }
+
+    /// get name of datatype in file names and subdirectories, including suffix
+    pub fn get_datatype_name(&self, datatype: Datatype) -> String {
+        match &self.suffix {
+            Some(suffix) => datatype.name() + "__" + suffix.as_str(),
+            None => datatype.name(),
+        }
+    }
+
+    /// get file name prefix shared by all files of datatype
+    pub fn get_datatype_file_prefix(&self, datatype: Datatype) -> String {
+        let output_prefix = self.output_prefix.as_deref().unwrap_or_default();
+        match &self.suffix {
+            Some(suffix) => {
+                format!("{}{}__{}__{}__", output_prefix, self.prefix, datatype.name(), suffix)
+            }
+            None => format!("{}{}__{}__", output_prefix, self.prefix, datatype.name()),
+        }
     }
 }
 
+/// format first or last block of the block range of partition, as in default file names
+fn format_block_bound(
+    partition: &Partition,
+    bound: impl Fn(&Vec<BlockChunk>) -> Option<u64>,
+) -> Result<String, CollectError> {
+    let block = partition
+        .block_numbers
+        .as_ref()
+        .and_then(bound)
+        .ok_or(err("file name template uses block range but partition has no blocks"))?;
+    BlockChunk::format_item(block).map_err(|_| err("could not format block number"))
+}
+
 /// File format
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum FileFormat {
@@ -114,6 +335,10 @@ pub enum FileFormat {
     Csv,
     /// Json file format
     Json,
+    /// Newline-delimited json file format
+    Jsonl,
+    /// Delta Lake table, requires the `delta` feature
+    Delta,
 }
 
 impl FileFormat {
@@ -123,10 +348,56 @@ impl FileFormat {
             FileFormat::Parquet => "parquet",
             FileFormat::Csv => "csv",
             FileFormat::Json => "json",
+            FileFormat::Jsonl => "jsonl",
+            FileFormat::Delta => "delta",
+        }
+    }
+}
+
+/// Compression of csv output files
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum CsvCompression {
+    /// uncompressed csv
+    #[default]
+    None,
+    /// gzip compressed csv, with extension .csv.gz
+    Gzip,
+    /// zstd compressed csv, with extension .csv.zst
+    Zstd,
+}
+
+impl CsvCompression {
+    /// extension appended to the csv extension of compressed files
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            CsvCompression::None => None,
+            CsvCompression::Gzip => Some("gz"),
+            CsvCompression::Zstd => Some("zst"),
         }
     }
 }
 
+/// Output format that works poorly for a dataset
+#[derive(Clone, Debug)]
+pub struct DiscouragedFormat {
+    /// format that works poorly
+    pub format: FileFormat,
+    /// why the format works poorly
+    pub reason: &'static str,
+    /// format to use instead
+    pub alternative: FileFormat,
+}
+
+impl DiscouragedFormat {
+    /// discourage every text format in favor of parquet
+    pub fn text_formats(reason: &'static str) -> Vec<DiscouragedFormat> {
+        [FileFormat::Csv, FileFormat::Json, FileFormat::Jsonl]
+            .into_iter()
+            .map(|format| DiscouragedFormat { format, reason, alternative: FileFormat::Parquet })
+            .collect()
+    }
+}
+
 /// Encoding for binary data in a column
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum ColumnEncoding {
@@ -145,3 +416,96 @@ impl ColumnEncoding {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dim, MetaDatatype, QueryLabels, TimeDimension};
+
+    #[test]
+    fn test_file_name_templates() {
+        let output_dir =
+            std::env::temp_dir().join(format!("cryo_templates_{}", std::process::id()));
+        let file_output = FileOutput {
+            output_dir: output_dir.clone(),
+            suffix: Some("v2".to_string()),
+            overwrite: false,
+            ..Default::default()
+        };
+        let partitions: Vec<Partition> = [(0, 999), (1000, 1999)]
+            .into_iter()
+            .map(|(start, end)| Partition {
+                block_numbers: Some(vec![BlockChunk::Range(start, end)]),
+                ..Default::default()
+            })
+            .collect();
+        let query = Query {
+            datatypes: vec![MetaDatatype::Scalar(Datatype::Logs)],
+            schemas: HashMap::new(),
+            time_dimension: TimeDimension::Blocks,
+            partitions: partitions.clone(),
+            partitioned_by: vec![Dim::BlockNumber],
+            exclude_failed: false,
+            estimate_gas_price: false,
+            js_tracer: None,
+            labels: QueryLabels {
+                align: false,
+                reorg_buffer: 0,
+                chunks_before_deployment: 0,
+                block_tags: vec![],
+            },
+            skipped_rows: Default::default(),
+            duplicate_rows: Default::default(),
+            allow_missing_blocks: false,
+            missing_blocks: Default::default(),
+            degraded_datatypes: Default::default(),
+            request_progress: Default::default(),
+            block_transaction_counts: Default::default(),
+            block_timestamps: Default::default(),
+        };
+
+        // default template reproduces the conventional file names
+        let path = file_output.get_path(&query, &partitions[1], Datatype::Logs).unwrap();
+        let expected = format!(
+            "{}{}.parquet",
+            file_output.get_datatype_file_prefix(Datatype::Logs),
+            partitions[1].label(&query.partitioned_by).unwrap()
+        );
+        assert_eq!(path, output_dir.join(&expected));
+        assert_eq!(expected, "ethereum__logs__v2__00001000_to_00001999.parquet");
+
+        let template = "{network}/{datatype}/{chunk_index}_{start_block}-{end_block}.{ext}";
+        let file_output =
+            FileOutput { file_name_template: Some(template.to_string()), ..file_output };
+        let path = file_output.get_path(&query, &partitions[1], Datatype::Logs).unwrap();
+        assert_eq!(path, output_dir.join("ethereum/logs__v2/1_00001000-00001999.parquet"));
+        assert!(output_dir.join("ethereum/logs__v2").is_dir());
+
+        let result =
+            file_output.render_file_name("{block}.{ext}", &query, &partitions[0], Datatype::Logs);
+        assert!(result.is_err());
+
+        // hive partitions nest key=value directories, with the block bucket last
+        let file_output = FileOutput {
+            file_name_template: None,
+            hive_partitions: vec![HiveKey::Network, HiveKey::Datatype, HiveKey::BlockBucket(1000)],
+            ..file_output
+        };
+        let path = file_output.get_path(&query, &partitions[1], Datatype::Logs).unwrap();
+        let hive_dir = output_dir.join("network=ethereum/datatype=logs__v2/block_1000=1");
+        assert_eq!(path, hive_dir.join(expected));
+        assert_eq!(file_output.get_block_bucket(&partitions[0]), Some((1000, 0)));
+
+        // output prefix goes on the file name, after subdirectories of templates
+        let file_output = FileOutput {
+            hive_partitions: Vec::new(),
+            file_name_template: Some("{datatype}/{chunk}.{ext}".to_string()),
+            output_prefix: Some("run1__".to_string()),
+            ..file_output
+        };
+        let path = file_output.get_file_path(&query, &partitions[0], Datatype::Logs).unwrap();
+        assert_eq!(path, output_dir.join("logs__v2/run1__00000000_to_00000999.parquet"));
+
+        std::fs::remove_dir_all(output_dir).unwrap();
+    }
+}
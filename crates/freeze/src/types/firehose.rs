@@ -0,0 +1,503 @@
+use crate::{mask_rpc_url, BlockSource, ChunkData, CollectError, ParseError, Partition, Source};
+use ethers::prelude::*;
+use std::{collections::HashMap, sync::Arc};
+use tonic::{
+    client::Grpc,
+    codec::{CompressionEncoding, ProstCodec},
+    codegen::http::uri::PathAndQuery,
+    transport::{Channel, ClientTlsConfig, Endpoint},
+};
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+/// name under which firehose requests are counted in request stats
+const FIREHOSE_METHOD: &str = "firehose_blocks";
+
+/// grpc path of the block stream of firehose
+const STREAM_BLOCKS_PATH: &str = "/sf.firehose.v2.Stream/Blocks";
+
+/// full blocks with traces can be much larger than the default grpc message limit of 4MB
+const MAX_MESSAGE_SIZE: usize = 1 << 30;
+
+/// blocks streamed from a firehose endpoint over grpc instead of requested over json-rpc
+///
+/// the blocks of a partition are streamed as a single block range when the first of them is
+/// requested, and only the fields of blocks and transactions are read from the stream.
+/// receipts are still requested over json-rpc
+#[derive(Clone, Debug)]
+pub struct FirehoseBlockSource {
+    /// url of firehose endpoint
+    pub endpoint: String,
+    channel: Channel,
+    range: Option<Arc<StreamedRange>>,
+}
+
+/// contiguous range of blocks of a partition, streamed once and shared by its requests
+#[derive(Debug)]
+struct StreamedRange {
+    start: u64,
+    end: u64,
+    blocks: tokio::sync::OnceCell<HashMap<u64, pb::Block>>,
+}
+
+impl FirehoseBlockSource {
+    /// create block source that connects to `endpoint` when the first block is requested
+    ///
+    /// endpoints without a scheme, e.g. mainnet.eth.streamingfast.io:443, use https
+    pub fn new(endpoint: &str) -> ::core::result::Result<FirehoseBlockSource, ParseError> {
+        let url = if endpoint.contains("://") {
+            endpoint.to_string()
+        } else {
+            format!("https://{}", endpoint)
+        };
+        let invalid = |e: tonic::transport::Error| {
+            ParseError::ParseError(format!("invalid firehose endpoint {}: {}", endpoint, e))
+        };
+        let mut channel = Endpoint::from_shared(url.clone()).map_err(invalid)?;
+        if url.starts_with("https://") {
+            channel = channel.tls_config(ClientTlsConfig::new()).map_err(invalid)?;
+        }
+        Ok(FirehoseBlockSource {
+            endpoint: endpoint.to_string(),
+            channel: channel.connect_lazy(),
+            range: None,
+        })
+    }
+
+    /// get the block at `block_num`, from the stream of the range of partition if it has one
+    async fn fetch_block(&self, source: &Source, block_num: u64) -> Result<Option<pb::Block>> {
+        match &self.range {
+            Some(range) if (range.start..=range.end).contains(&block_num) => {
+                let blocks = range
+                    .blocks
+                    .get_or_try_init(|| self.fetch_blocks(source, range.start, range.end))
+                    .await?;
+                Ok(blocks.get(&block_num).cloned())
+            }
+            _ => Ok(self.fetch_blocks(source, block_num, block_num).await?.remove(&block_num)),
+        }
+    }
+
+    /// stream the blocks from `start` to `end`, retried as the json-rpc requests of source
+    async fn fetch_blocks(
+        &self,
+        source: &Source,
+        start: u64,
+        end: u64,
+    ) -> Result<HashMap<u64, pb::Block>> {
+        let _permit = source.permit_request().await?;
+        source.retry(FIREHOSE_METHOD, || self.stream_blocks(start, end)).await?
+    }
+
+    /// stream the blocks from `start` to `end`, decoding each message as it arrives so that the
+    /// raw messages of the range are never held at once
+    ///
+    /// stream failures are returned as provider errors to be retried, and decoding failures as
+    /// the inner error since they fail again when retried
+    async fn stream_blocks(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> ::core::result::Result<Result<HashMap<u64, pb::Block>>, ProviderError> {
+        let request = pb::Request {
+            start_block_num: start as i64,
+            cursor: String::new(),
+            stop_block_num: end,
+            final_blocks_only: false,
+        };
+        let mut client = Grpc::new(self.channel.clone())
+            .accept_compressed(CompressionEncoding::Gzip)
+            .max_decoding_message_size(MAX_MESSAGE_SIZE);
+        client.ready().await.map_err(|e| firehose_error(&self.endpoint, e))?;
+        let codec: ProstCodec<pb::Request, pb::Response> = ProstCodec::default();
+        let path = PathAndQuery::from_static(STREAM_BLOCKS_PATH);
+        let mut stream = client
+            .server_streaming(tonic::Request::new(request), path, codec)
+            .await
+            .map_err(|e| firehose_error(&self.endpoint, e))?
+            .into_inner();
+
+        // a block can be undone by a reorg before the stream ends
+        let mut blocks = HashMap::new();
+        while let Some(response) =
+            stream.message().await.map_err(|e| firehose_error(&self.endpoint, e))?
+        {
+            let block = match response.block.as_ref().map(pb::Block::from_any) {
+                Some(Ok(block)) => block,
+                Some(Err(e)) => return Ok(Err(e)),
+                None => continue,
+            };
+            if !(start..=end).contains(&block.number) {
+                continue
+            }
+            if response.step == pb::STEP_UNDO {
+                blocks.remove(&block.number);
+            } else {
+                blocks.insert(block.number, block);
+            }
+        }
+        Ok(Ok(blocks))
+    }
+}
+
+#[async_trait::async_trait]
+impl BlockSource for FirehoseBlockSource {
+    fn description(&self) -> String {
+        format!("firehose {}", mask_rpc_url(&self.endpoint))
+    }
+
+    fn for_partition(&self, partition: &Partition) -> Option<Arc<dyn BlockSource>> {
+        // only contiguous ranges are streamed at once, other blocks are streamed one at a time
+        let chunks = partition.block_numbers.as_ref()?;
+        let start = chunks.iter().filter_map(|chunk| chunk.min_value()).min()?;
+        let end = chunks.iter().filter_map(|chunk| chunk.max_value()).max()?;
+        let size: u64 = chunks.iter().map(|chunk| chunk.size()).sum();
+        if size != end - start + 1 {
+            return None
+        }
+        let range = StreamedRange { start, end, blocks: tokio::sync::OnceCell::new() };
+        Some(Arc::new(FirehoseBlockSource { range: Some(Arc::new(range)), ..self.clone() }))
+    }
+
+    async fn get_block(&self, source: &Source, block_num: u64) -> Result<Option<Block<TxHash>>> {
+        Ok(self.fetch_block(source, block_num).await?.map(|block| block.to_block()))
+    }
+
+    async fn get_block_with_txs(
+        &self,
+        source: &Source,
+        block_num: u64,
+    ) -> Result<Option<Block<Transaction>>> {
+        Ok(self.fetch_block(source, block_num).await?.map(|block| block.to_block_with_txs()))
+    }
+}
+
+fn firehose_error(endpoint: &str, e: impl std::fmt::Display) -> ProviderError {
+    ProviderError::CustomError(format!("firehose request to {} failed: {}", endpoint, e))
+}
+
+/// subset of the sf.firehose.v2 and sf.ethereum.type.v2 protobuf messages read by cryo
+///
+/// fields that are not listed are skipped when decoding
+mod pb {
+    use crate::CollectError;
+    use ethers::prelude::*;
+    use prost::Message;
+
+    pub(super) const STEP_UNDO: i32 = 2;
+    const CALL_TYPE_CREATE: i32 = 5;
+    const BLOCK_TYPE_URL: &str = "sf.ethereum.type.v2.Block";
+
+    #[derive(Clone, PartialEq, Message)]
+    pub(super) struct Request {
+        #[prost(int64, tag = "1")]
+        pub(super) start_block_num: i64,
+        #[prost(string, tag = "2")]
+        pub(super) cursor: String,
+        #[prost(uint64, tag = "3")]
+        pub(super) stop_block_num: u64,
+        #[prost(bool, tag = "4")]
+        pub(super) final_blocks_only: bool,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    pub(super) struct Response {
+        #[prost(message, optional, tag = "1")]
+        pub(super) block: Option<prost_types::Any>,
+        #[prost(int32, tag = "6")]
+        pub(super) step: i32,
+        #[prost(string, tag = "10")]
+        pub(super) cursor: String,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    pub(super) struct Block {
+        #[prost(bytes = "vec", tag = "2")]
+        pub(super) hash: Vec<u8>,
+        #[prost(uint64, tag = "3")]
+        pub(super) number: u64,
+        #[prost(uint64, tag = "4")]
+        pub(super) size: u64,
+        #[prost(message, optional, tag = "5")]
+        pub(super) header: Option<BlockHeader>,
+        #[prost(message, repeated, tag = "6")]
+        pub(super) uncles: Vec<BlockHeader>,
+        #[prost(message, repeated, tag = "10")]
+        pub(super) transaction_traces: Vec<TransactionTrace>,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    pub(super) struct BlockHeader {
+        #[prost(bytes = "vec", tag = "1")]
+        pub(super) parent_hash: Vec<u8>,
+        #[prost(bytes = "vec", tag = "2")]
+        pub(super) uncle_hash: Vec<u8>,
+        #[prost(bytes = "vec", tag = "3")]
+        pub(super) coinbase: Vec<u8>,
+        #[prost(bytes = "vec", tag = "4")]
+        pub(super) state_root: Vec<u8>,
+        #[prost(bytes = "vec", tag = "5")]
+        pub(super) transactions_root: Vec<u8>,
+        #[prost(bytes = "vec", tag = "6")]
+        pub(super) receipt_root: Vec<u8>,
+        #[prost(bytes = "vec", tag = "7")]
+        pub(super) logs_bloom: Vec<u8>,
+        #[prost(message, optional, tag = "8")]
+        pub(super) difficulty: Option<BigInt>,
+        #[prost(uint64, tag = "9")]
+        pub(super) number: u64,
+        #[prost(uint64, tag = "10")]
+        pub(super) gas_limit: u64,
+        #[prost(uint64, tag = "11")]
+        pub(super) gas_used: u64,
+        #[prost(message, optional, tag = "12")]
+        pub(super) timestamp: Option<prost_types::Timestamp>,
+        #[prost(bytes = "vec", tag = "13")]
+        pub(super) extra_data: Vec<u8>,
+        #[prost(bytes = "vec", tag = "14")]
+        pub(super) mix_hash: Vec<u8>,
+        #[prost(uint64, tag = "15")]
+        pub(super) nonce: u64,
+        #[prost(bytes = "vec", tag = "16")]
+        pub(super) hash: Vec<u8>,
+        #[prost(message, optional, tag = "17")]
+        pub(super) total_difficulty: Option<BigInt>,
+        #[prost(message, optional, tag = "18")]
+        pub(super) base_fee_per_gas: Option<BigInt>,
+        #[prost(bytes = "vec", tag = "19")]
+        pub(super) withdrawals_root: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    pub(super) struct BigInt {
+        #[prost(bytes = "vec", tag = "1")]
+        pub(super) bytes: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    pub(super) struct TransactionTrace {
+        #[prost(bytes = "vec", tag = "1")]
+        pub(super) to: Vec<u8>,
+        #[prost(uint64, tag = "2")]
+        pub(super) nonce: u64,
+        #[prost(message, optional, tag = "3")]
+        pub(super) gas_price: Option<BigInt>,
+        #[prost(uint64, tag = "4")]
+        pub(super) gas_limit: u64,
+        #[prost(message, optional, tag = "5")]
+        pub(super) value: Option<BigInt>,
+        #[prost(bytes = "vec", tag = "6")]
+        pub(super) input: Vec<u8>,
+        #[prost(bytes = "vec", tag = "7")]
+        pub(super) v: Vec<u8>,
+        #[prost(bytes = "vec", tag = "8")]
+        pub(super) r: Vec<u8>,
+        #[prost(bytes = "vec", tag = "9")]
+        pub(super) s: Vec<u8>,
+        #[prost(message, optional, tag = "11")]
+        pub(super) max_fee_per_gas: Option<BigInt>,
+        #[prost(int32, tag = "12")]
+        pub(super) r#type: i32,
+        #[prost(message, optional, tag = "13")]
+        pub(super) max_priority_fee_per_gas: Option<BigInt>,
+        #[prost(uint32, tag = "20")]
+        pub(super) index: u32,
+        #[prost(bytes = "vec", tag = "21")]
+        pub(super) hash: Vec<u8>,
+        #[prost(bytes = "vec", tag = "22")]
+        pub(super) from: Vec<u8>,
+        #[prost(message, repeated, tag = "32")]
+        pub(super) calls: Vec<Call>,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    pub(super) struct Call {
+        #[prost(int32, tag = "4")]
+        pub(super) call_type: i32,
+    }
+
+    impl Block {
+        pub(super) fn from_any(any: &prost_types::Any) -> Result<Block, CollectError> {
+            if !any.type_url.ends_with(BLOCK_TYPE_URL) {
+                return Err(CollectError::CollectError(format!(
+                    "unsupported firehose block type {}, expected {}",
+                    any.type_url, BLOCK_TYPE_URL
+                )))
+            }
+            let mut block = Block::decode(any.value.as_slice()).map_err(|e| {
+                CollectError::CollectError(format!("could not decode firehose block: {}", e))
+            })?;
+            // only the root call of each transaction is read, to detect contract creations
+            for tx in block.transaction_traces.iter_mut() {
+                tx.calls.truncate(1);
+            }
+            Ok(block)
+        }
+
+        pub(super) fn to_block(&self) -> ethers::types::Block<TxHash> {
+            let transactions = self.transaction_traces.iter().map(|tx| h256(&tx.hash)).collect();
+            self.to_ethers_block(transactions)
+        }
+
+        pub(super) fn to_block_with_txs(&self) -> ethers::types::Block<Transaction> {
+            let transactions =
+                self.transaction_traces.iter().map(|tx| self.to_transaction(tx)).collect();
+            self.to_ethers_block(transactions)
+        }
+
+        fn to_ethers_block<TX>(&self, transactions: Vec<TX>) -> ethers::types::Block<TX> {
+            let header = self.header.clone().unwrap_or_default();
+            ethers::types::Block {
+                hash: Some(h256(&self.hash)),
+                parent_hash: h256(&header.parent_hash),
+                uncles_hash: h256(&header.uncle_hash),
+                author: Some(address(&header.coinbase)),
+                state_root: h256(&header.state_root),
+                transactions_root: h256(&header.transactions_root),
+                receipts_root: h256(&header.receipt_root),
+                number: Some(self.number.into()),
+                gas_used: header.gas_used.into(),
+                gas_limit: header.gas_limit.into(),
+                extra_data: header.extra_data.into(),
+                logs_bloom: (header.logs_bloom.len() == 256)
+                    .then(|| Bloom::from_slice(&header.logs_bloom)),
+                timestamp: header.timestamp.map(|t| t.seconds.max(0) as u64).unwrap_or(0).into(),
+                difficulty: big_int(&header.difficulty).unwrap_or_default(),
+                total_difficulty: big_int(&header.total_difficulty),
+                uncles: self.uncles.iter().map(|uncle| h256(&uncle.hash)).collect(),
+                transactions,
+                size: Some(self.size.into()),
+                mix_hash: Some(h256(&header.mix_hash)),
+                nonce: Some(H64::from_low_u64_be(header.nonce)),
+                base_fee_per_gas: big_int(&header.base_fee_per_gas),
+                withdrawals_root: (!header.withdrawals_root.is_empty())
+                    .then(|| h256(&header.withdrawals_root)),
+                ..Default::default()
+            }
+        }
+
+        fn to_transaction(&self, tx: &TransactionTrace) -> Transaction {
+            // firehose sets the to address of contract creations to the created contract
+            let is_create = tx.calls.first().map(|call| call.call_type) == Some(CALL_TYPE_CREATE);
+            Transaction {
+                hash: h256(&tx.hash),
+                nonce: tx.nonce.into(),
+                block_hash: Some(h256(&self.hash)),
+                block_number: Some(self.number.into()),
+                transaction_index: Some(tx.index.into()),
+                from: address(&tx.from),
+                to: (!is_create && !tx.to.is_empty()).then(|| address(&tx.to)),
+                value: big_int(&tx.value).unwrap_or_default(),
+                gas_price: big_int(&tx.gas_price),
+                gas: tx.gas_limit.into(),
+                input: tx.input.clone().into(),
+                v: U64::from_big_endian(&tx.v[tx.v.len().saturating_sub(8)..]),
+                r: U256::from_big_endian(&tx.r),
+                s: U256::from_big_endian(&tx.s),
+                transaction_type: Some((tx.r#type.max(0) as u64).into()),
+                max_priority_fee_per_gas: big_int(&tx.max_priority_fee_per_gas),
+                max_fee_per_gas: big_int(&tx.max_fee_per_gas),
+                ..Default::default()
+            }
+        }
+    }
+
+    /// hash from bytes, left padded if shorter than 32 bytes
+    fn h256(bytes: &[u8]) -> H256 {
+        let mut padded = [0u8; 32];
+        let bytes = &bytes[bytes.len().saturating_sub(32)..];
+        padded[32 - bytes.len()..].copy_from_slice(bytes);
+        H256(padded)
+    }
+
+    /// address from bytes, left padded if shorter than 20 bytes
+    fn address(bytes: &[u8]) -> Address {
+        let mut padded = [0u8; 20];
+        let bytes = &bytes[bytes.len().saturating_sub(20)..];
+        padded[20 - bytes.len()..].copy_from_slice(bytes);
+        Address::from(padded)
+    }
+
+    fn big_int(value: &Option<BigInt>) -> Option<U256> {
+        value.as_ref().map(|value| U256::from_big_endian(&value.bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BlockChunk;
+    use prost::Message;
+
+    #[test]
+    fn test_firehose_block_conversion() {
+        let block = pb::Block {
+            hash: vec![0xaa; 32],
+            number: 17,
+            size: 1000,
+            header: Some(pb::BlockHeader {
+                parent_hash: vec![0xbb; 32],
+                coinbase: vec![0xcc; 20],
+                gas_used: 21000,
+                timestamp: Some(prost_types::Timestamp { seconds: 1700000000, nanos: 0 }),
+                base_fee_per_gas: Some(pb::BigInt { bytes: vec![0x01, 0x00] }),
+                ..Default::default()
+            }),
+            uncles: vec![],
+            transaction_traces: vec![
+                pb::TransactionTrace {
+                    hash: vec![0x11; 32],
+                    from: vec![0x22; 20],
+                    to: vec![0x33; 20],
+                    value: Some(pb::BigInt { bytes: vec![0x05] }),
+                    index: 0,
+                    ..Default::default()
+                },
+                pb::TransactionTrace {
+                    hash: vec![0x44; 32],
+                    to: vec![0x55; 20],
+                    index: 1,
+                    calls: vec![pb::Call { call_type: 5 }],
+                    ..Default::default()
+                },
+            ],
+        };
+        let any = prost_types::Any {
+            type_url: "type.googleapis.com/sf.ethereum.type.v2.Block".to_string(),
+            value: block.encode_to_vec(),
+        };
+        let decoded = pb::Block::from_any(&any).unwrap();
+        assert_eq!(decoded, block);
+
+        let converted = decoded.to_block_with_txs();
+        assert_eq!(converted.number, Some(U64::from(17)));
+        assert_eq!(converted.hash, Some(H256([0xaa; 32])));
+        assert_eq!(converted.author, Some(Address::from([0xcc; 20])));
+        assert_eq!(converted.timestamp, U256::from(1700000000));
+        assert_eq!(converted.base_fee_per_gas, Some(U256::from(256)));
+        assert_eq!(converted.transactions[0].to, Some(Address::from([0x33; 20])));
+        assert_eq!(converted.transactions[0].value, U256::from(5));
+        assert_eq!(converted.transactions[1].to, None);
+        assert_eq!(converted.transactions[1].block_number, Some(U64::from(17)));
+        assert_eq!(decoded.to_block().transactions, vec![H256([0x11; 32]), H256([0x44; 32])]);
+
+        let other =
+            prost_types::Any { type_url: "sf.near.type.v1.Block".to_string(), value: vec![] };
+        assert!(pb::Block::from_any(&other).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_firehose_streams_contiguous_partitions() {
+        let firehose = FirehoseBlockSource::new("localhost:13042").unwrap();
+        let partition = |chunks| Partition { block_numbers: Some(chunks), ..Default::default() };
+        let range = vec![BlockChunk::Range(10, 19)];
+        assert!(firehose.for_partition(&partition(range)).is_some());
+        let contiguous = vec![BlockChunk::Numbers(vec![10, 11]), BlockChunk::Range(12, 19)];
+        assert!(firehose.for_partition(&partition(contiguous)).is_some());
+
+        // sparse blocks are streamed one at a time instead of streaming the blocks between them
+        let sparse = vec![BlockChunk::Numbers(vec![10, 1000])];
+        assert!(firehose.for_partition(&partition(sparse)).is_none());
+        assert!(firehose.for_partition(&Partition::default()).is_none());
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{ColumnType, FileError, Table, CRYO_VERSION};
+use ethers::abi::ParamType;
+use std::{collections::BTreeMap, path::Path};
+
+/// key of parquet key_value_metadata entry that holds the lineage of a file
+pub const LINEAGE_METADATA_KEY: &str = "cryo.lineage";
+
+/// provenance of a column that is computed rather than copied from an rpc response field
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ColumnLineage {
+    /// rpc fields or columns that the column is computed from
+    pub sources: Vec<String>,
+    /// how the column is computed from its sources, as a formula or abi fragment
+    pub transformation: String,
+}
+
+impl ColumnLineage {
+    /// create lineage of column
+    pub fn new(sources: &[&str], transformation: &str) -> ColumnLineage {
+        ColumnLineage {
+            sources: sources.iter().map(|source| source.to_string()).collect(),
+            transformation: transformation.to_string(),
+        }
+    }
+}
+
+/// lineage of the derived columns of an output file
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TableLineage {
+    /// version of cryo that wrote the file
+    pub cryo_version: String,
+    /// datatype of the file
+    pub datatype: String,
+    /// lineage of each derived column, columns not listed are copied from rpc responses
+    pub columns: BTreeMap<String, ColumnLineage>,
+}
+
+impl TableLineage {
+    /// drop lineage of columns that are not in column list
+    pub fn retain_columns(&mut self, columns: &[&str]) {
+        self.columns.retain(|name, _| columns.contains(&name.as_str()))
+    }
+}
+
+impl Table {
+    /// lineage of the derived columns of table
+    pub fn lineage(&self) -> TableLineage {
+        let mut columns: BTreeMap<String, ColumnLineage> = self
+            .datatype
+            .column_lineage()
+            .into_iter()
+            .filter(|(name, _)| self.has_column(name))
+            .map(|(name, lineage)| (name.to_string(), lineage))
+            .collect();
+
+        // decoded eth_call outputs, expanded below if they are u256 columns
+        for decoder in self.call_decoders.iter() {
+            for (name, _) in decoder.output_columns() {
+                let transformation = format!("abi decoded using `{}`", decoder.raw);
+                columns.insert(name, ColumnLineage::new(&["output_data"], &transformation));
+            }
+        }
+
+        // u256 columns are only written in their alternate representations
+        let u256_columns: Vec<&str> = self
+            .columns()
+            .into_iter()
+            .filter(|name| self.column_type(name) == Some(ColumnType::UInt256))
+            .collect();
+        for name in u256_columns.into_iter() {
+            let lineage = columns
+                .remove(name)
+                .unwrap_or_else(|| ColumnLineage::new(&[name], &format!("{} as u256", name)));
+            self.insert_u256_lineage(&mut columns, name, &lineage);
+        }
+
+        // decoded event parameters
+        if let Some(decoder) = &self.log_decoder {
+            let mut topic_index = 0;
+            for input in decoder.event.inputs.iter() {
+                let source = if input.indexed {
+                    topic_index += 1;
+                    format!("topic{}", topic_index)
+                } else {
+                    "data".to_string()
+                };
+                let name = format!("event__{}", input.name);
+                let lineage = ColumnLineage {
+                    sources: vec![source],
+                    transformation: format!("abi decoded using `{}`", decoder.raw),
+                };
+                match input.kind {
+                    ParamType::Uint(size) | ParamType::Int(size) if size > 64 => {
+                        self.insert_u256_lineage(&mut columns, &name, &lineage)
+                    }
+                    _ => {
+                        columns.insert(name, lineage);
+                    }
+                }
+            }
+        }
+
+        // fee history rewards
+        for percentile in self.fee_percentiles.iter() {
+            let name = crate::reward_column(*percentile);
+            if self.has_column(&name) {
+                let transformation = format!("reward at {}th percentile", percentile);
+                columns.insert(name, ColumnLineage::new(&["reward"], &transformation));
+            }
+        }
+
+        TableLineage {
+            cryo_version: CRYO_VERSION.to_string(),
+            datatype: self.datatype.name(),
+            columns,
+        }
+    }
+
+    /// add lineage of each representation of a u256 column
+    fn insert_u256_lineage(
+        &self,
+        columns: &mut BTreeMap<String, ColumnLineage>,
+        name: &str,
+        lineage: &ColumnLineage,
+    ) {
+        for (output_column, u256_type) in self.u256_output_columns(name).into_iter() {
+            let transformation =
+                format!("{}, converted to {}", lineage.transformation, &u256_type.suffix()[1..]);
+            let converted = ColumnLineage { sources: lineage.sources.clone(), transformation };
+            columns.insert(output_column, converted);
+        }
+    }
+}
+
+/// read lineage stored in footer of parquet file, if file has lineage
+pub fn read_file_lineage(path: &Path) -> Result<Option<TableLineage>, FileError> {
+    match super::dataframes::read_footer_metadata(path, LINEAGE_METADATA_KEY)? {
+        Some(value) => serde_json::from_str(&value)
+            .map(Some)
+            .map_err(|_| FileError::DatasetError(format!("invalid lineage in {}", path.display()))),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FileOutput;
+    use polars::prelude::*;
+
+    #[test]
+    fn test_lineage_roundtrip() {
+        let output_dir = std::env::temp_dir().join(format!("cryo_lineage_{}", std::process::id()));
+        std::fs::create_dir_all(&output_dir).unwrap();
+        let file_output = FileOutput { output_dir: output_dir.clone(), ..Default::default() };
+        let columns = [("success", ColumnLineage::new(&["status"], "status == 1"))];
+        let lineage = TableLineage {
+            cryo_version: CRYO_VERSION.to_string(),
+            datatype: "transactions".to_string(),
+            columns: columns.into_iter().map(|(name, x)| (name.to_string(), x)).collect(),
+        };
+
+        let path = output_dir.join("ethereum__transactions__0_to_1.parquet");
+        let mut df = df!("block_number" => [0u32, 1], "success" => [true, false]).unwrap();
+        let metadata =
+            [(LINEAGE_METADATA_KEY.to_string(), serde_json::to_string(&lineage).unwrap())];
+        crate::dataframes::df_to_file(&mut df, &path, &file_output, &metadata).unwrap();
+        assert_eq!(read_file_lineage(&path).unwrap(), Some(lineage));
+        let reread = ParquetReader::new(std::fs::File::open(&path).unwrap()).finish().unwrap();
+        assert_eq!(reread.shape(), (2, 2));
+
+        std::fs::remove_dir_all(output_dir).unwrap();
+    }
+}
@@ -0,0 +1,103 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// number of blocks per eth_getLogs request, adapted to the size of responses
+///
+/// the window is halved when a response is too large, and grows back by a quarter after each
+/// response that fits, up to `max_size`. clones share the window, so concurrent chunks adapt
+/// together
+#[derive(Clone, Debug)]
+pub struct LogWindow {
+    /// largest number of blocks per request
+    pub max_size: u64,
+    /// largest number of logs per response, larger responses are requested again in smaller
+    /// windows
+    pub max_response_rows: Option<u64>,
+    size: Arc<AtomicU64>,
+    used_sizes: Arc<Mutex<BTreeMap<u64, u64>>>,
+}
+
+impl LogWindow {
+    /// create window that starts at `initial_size` blocks
+    pub fn new(initial_size: u64, max_size: u64, max_response_rows: Option<u64>) -> LogWindow {
+        let max_size = max_size.max(1);
+        LogWindow {
+            max_size,
+            max_response_rows,
+            size: Arc::new(AtomicU64::new(initial_size.clamp(1, max_size))),
+            used_sizes: Default::default(),
+        }
+    }
+
+    /// current number of blocks per request
+    pub fn size(&self) -> u64 {
+        self.size.load(Ordering::Relaxed)
+    }
+
+    /// whether a response of `n_rows` logs should be requested again in a smaller window
+    pub fn exceeds_max_rows(&self, n_rows: usize) -> bool {
+        matches!(self.max_response_rows, Some(max_rows) if n_rows as u64 > max_rows)
+    }
+
+    /// halve window after a request of `size` blocks was too large
+    ///
+    /// returns the new size, which never grows from a concurrent shrink
+    pub fn shrink(&self, size: u64) -> u64 {
+        let target = (size / 2).max(1);
+        let previous = self
+            .size
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| Some(current.min(target)))
+            .unwrap_or(target);
+        previous.min(target)
+    }
+
+    /// record a request of `size` blocks whose response fit, growing the window
+    ///
+    /// the window only grows if it was not resized by another request in the meantime
+    pub fn record_success(&self, size: u64) {
+        if let Ok(mut used_sizes) = self.used_sizes.lock() {
+            *used_sizes.entry(size).or_insert(0) += 1;
+        }
+        let grown = (size + (size / 4).max(1)).min(self.max_size);
+        let _ = self.size.compare_exchange(size, grown, Ordering::Relaxed, Ordering::Relaxed);
+    }
+
+    /// number of successful requests of each window size, ordered by size
+    pub fn used_sizes(&self) -> Vec<(u64, u64)> {
+        match self.used_sizes.lock() {
+            Ok(used_sizes) => used_sizes.iter().map(|(size, count)| (*size, *count)).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_window() {
+        let window = LogWindow::new(8, 10, Some(100));
+        assert_eq!(window.shrink(8), 4);
+        assert_eq!(window.shrink(8), 4);
+        assert_eq!(window.shrink(1), 1);
+        window.record_success(1);
+        assert_eq!(window.size(), 2);
+        for _ in 0..10 {
+            window.record_success(window.size());
+        }
+        assert_eq!(window.size(), 10);
+
+        // a stale success does not undo a shrink
+        window.shrink(10);
+        window.record_success(10);
+        assert_eq!(window.size(), 5);
+        assert_eq!(window.used_sizes()[..3], [(1, 1), (2, 1), (3, 1)]);
+        assert!(window.exceeds_max_rows(101) && !window.exceeds_max_rows(100));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::{
+    summaries, ChunkData, CollectError, Datatype, ExecutionEnv, FileOutput, FreezeSummary,
+    ParseError, Partition, Query, Source,
+};
+use serde_json::{json, Map, Value};
+use std::time::Duration;
+
+/// format of the summaries and chunk progress printed during a freeze
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// human-readable summaries, with progress bars when stdout is a terminal
+    #[default]
+    Text,
+    /// newline-delimited json events, one per line
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<LogFormat, ParseError> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(ParseError::ParseError(format!(
+                "invalid log format: {}, valid formats are text and json",
+                s
+            ))),
+        }
+    }
+}
+
+/// log start of a freeze, printing the intro summary in text format
+pub(crate) fn log_run_start(
+    query: &Query,
+    source: &Source,
+    sink: &FileOutput,
+    env: &ExecutionEnv,
+    n_chunks_remaining: u64,
+) -> Result<(), CollectError> {
+    match env.log_format {
+        LogFormat::Text => {
+            summaries::print_cryo_intro(query, source, sink, env, n_chunks_remaining)
+        }
+        LogFormat::Json => {
+            let datatypes: Vec<_> =
+                query.datatypes.iter().flat_map(|x| x.datatypes()).map(|x| x.name()).collect();
+            emit(
+                "run_start",
+                json!({
+                    "datatypes": datatypes,
+                    "n_chunks": query.partitions.len() * query.datatypes.len(),
+                    "n_chunks_remaining": n_chunks_remaining,
+                    "chain_id": source.chain_id,
+                    "rpc_url": summaries::mask_rpc_url(&source.rpc_url),
+                    "output_dir": sink.output_dir.to_string_lossy(),
+                    "output_format": sink.format.as_str(),
+                }),
+            );
+            Ok(())
+        }
+    }
+}
+
+/// log that a chunk started collecting, which is only logged in json format
+pub(crate) fn log_chunk_start(env: &ExecutionEnv, chunk_index: usize, partition: &Partition) {
+    if is_json(env) {
+        emit("chunk_start", chunk_fields(chunk_index, partition));
+    }
+}
+
+/// log that the files of a chunk were written, which is shown by the progress bar in text format
+pub(crate) fn log_chunk_done(
+    env: &ExecutionEnv,
+    chunk_index: usize,
+    partition: &Partition,
+    written: &[(Datatype, u64, u64)],
+    duration: Duration,
+) {
+    if is_json(env) {
+        let mut fields = chunk_fields(chunk_index, partition);
+        let written: Vec<_> = written
+            .iter()
+            .map(|(datatype, n_rows, n_bytes)| {
+                json!({"datatype": datatype.name(), "n_rows": n_rows, "n_bytes": n_bytes})
+            })
+            .collect();
+        fields["written"] = Value::from(written);
+        fields["duration_seconds"] = Value::from(duration.as_secs_f64());
+        emit("chunk_done", fields);
+    }
+}
+
+/// log that a chunk failed, which is reported by the conclusion summary in text format
+pub(crate) fn log_chunk_error(
+    env: &ExecutionEnv,
+    chunk_index: usize,
+    partition: &Partition,
+    error: &CollectError,
+) {
+    if is_json(env) {
+        let mut fields = chunk_fields(chunk_index, partition);
+        fields["error"] = Value::from(error.to_string());
+        emit("chunk_error", fields);
+    }
+}
+
+/// log end of a freeze, printing the conclusion summary in text format
+pub(crate) fn log_run_end(results: &FreezeSummary, query: &Query, env: &ExecutionEnv) {
+    match env.log_format {
+        LogFormat::Text => summaries::print_cryo_conclusion(results, query, env),
+        LogFormat::Json => {
+            let duration = env.t_start.elapsed().map(|x| x.as_secs_f64()).unwrap_or(0.0);
+            let n_bytes: u64 = results.written.iter().map(|file| file.n_bytes).sum();
+            emit(
+                "run_end",
+                json!({
+                    "n_chunks_completed": results.completed.len(),
+                    "n_chunks_skipped": results.skipped.len(),
+                    "n_chunks_errored": results.errored.len(),
+                    "n_chunks_cancelled": results.cancelled.len(),
+                    "n_rows": results.n_rows,
+                    "n_bytes": n_bytes,
+                    "interrupted": results.interrupted,
+                    "duration_seconds": duration,
+                }),
+            );
+        }
+    }
+}
+
+fn is_json(env: &ExecutionEnv) -> bool {
+    env.verbose >= 1 && env.log_format == LogFormat::Json
+}
+
+/// fields identifying a chunk, block bounds are null for chunks of transaction hashes
+fn chunk_fields(chunk_index: usize, partition: &Partition) -> Value {
+    let block_bounds = match partition.block_numbers.as_ref() {
+        Some(chunks) => chunks.min_value().zip(chunks.max_value()),
+        None => None,
+    };
+    json!({
+        "chunk_index": chunk_index,
+        "block_start": block_bounds.map(|(start, _)| start),
+        "block_end": block_bounds.map(|(_, end)| end),
+    })
+}
+
+/// create event with its name and time, followed by its fields
+fn event(name: &str, fields: Value) -> Value {
+    let mut event = Map::new();
+    event.insert("event".to_string(), Value::from(name));
+    let timestamp = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+    event.insert("timestamp".to_string(), Value::from(timestamp));
+    if let Value::Object(fields) = fields {
+        event.extend(fields);
+    }
+    Value::Object(event)
+}
+
+/// print event as a single line of json
+fn emit(name: &str, fields: Value) {
+    println!("{}", event(name, fields));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BlockChunk;
+
+    #[test]
+    fn test_chunk_event() {
+        let partition = Partition {
+            block_numbers: Some(vec![BlockChunk::Range(1000, 1999)]),
+            ..Default::default()
+        };
+        let line = event("chunk_start", chunk_fields(3, &partition)).to_string();
+        assert!(!line.contains('\n'));
+        let parsed: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed["event"], "chunk_start");
+        assert_eq!(parsed["chunk_index"], 3);
+        assert_eq!(parsed["block_start"], 1000);
+        assert_eq!(parsed["block_end"], 1999);
+        assert!(parsed["timestamp"].is_string());
+
+        let fields = chunk_fields(0, &Partition::default());
+        assert_eq!((&fields["block_start"], &fields["block_end"]), (&Value::Null, &Value::Null));
+        assert_eq!("json".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert!("yaml".parse::<LogFormat>().is_err());
+    }
+}
@@ -27,6 +27,10 @@ pub mod collection;
 /// execution environment
 pub mod execution;
 
+/// text or json output of summaries and chunk progress
+pub mod logging;
+pub use logging::LogFormat;
+
 /// report generation
 pub mod reports;
 pub use reports::CRYO_VERSION;
@@ -38,6 +42,54 @@ pub mod dataframes;
 /// function and event signatures
 pub mod signatures;
 
+/// gas pricing
+pub mod pricing;
+pub use pricing::effective_gas_price;
+
+/// lineage of derived columns
+pub mod lineage;
+pub use lineage::{read_file_lineage, ColumnLineage, TableLineage, LINEAGE_METADATA_KEY};
+
+/// retrying of failed rpc requests
+pub mod retries;
+pub use retries::RetryPolicy;
+
+/// sources of blocks and transactions
+pub mod block_sources;
+pub use block_sources::{BlockSource, JsonRpcBlockSource};
+
+/// blocks streamed from firehose endpoints
+#[cfg(feature = "firehose")]
+pub mod firehose;
+#[cfg(feature = "firehose")]
+pub use firehose::FirehoseBlockSource;
+
+/// adaptive block windows of log requests
+pub mod log_windows;
+pub use log_windows::LogWindow;
+
+/// on-disk caching of rpc responses
+pub mod rpc_cache;
+pub use rpc_cache::RpcCache;
+
+/// batching of rpc requests over http
+pub mod rpc_batch;
+pub use rpc_batch::{BatchHttp, BatchHttpError, HttpOptions};
+
+/// timing of pipeline phases
+pub mod timing;
+pub use timing::{start_timing_report, PhaseTiming, TimingReport, PIPELINE_PHASES};
+
+/// statistics of output files
+pub mod output_utils;
+pub use output_utils::{
+    print_file_summaries, print_file_summary, read_file_summary, ColumnRange, FileSummary,
+};
+
+/// request and chunk statistics of the pipeline
+pub mod stats;
+pub use stats::{ChunkTiming, DurationStats, MethodStats, PipelineStats, RequestStats, RowStats};
+
 /// error specifications
 pub mod errors;
 /// type specifications for output data formats
@@ -56,18 +108,27 @@ pub use chunks::{
 pub use conversions::{bytes_to_u32, ToVecHex, ToVecU8};
 pub use dataframes::*;
 pub use datatypes::*;
-pub use files::{ColumnEncoding, FileFormat, FileOutput, SubDir};
-pub use queries::{Query, QueryLabels, TimeDimension};
-pub use schemas::{ColumnType, SchemaFunctions, Schemas, Table, U256Type};
-pub use sources::{Fetcher, RateLimiter, Source, SourceLabels};
+pub use files::{
+    ColumnEncoding, CsvCompression, DiscouragedFormat, FileFormat, FileOutput, HiveKey, SubDir,
+    DEFAULT_FILE_NAME_TEMPLATE, FILE_NAME_PLACEHOLDERS,
+};
+pub use queries::{
+    BlockTimestamps, BlockTransactionCounts, DegradedDatatypes, MissingBlocks, Query, QueryLabels,
+    RequestProgress, SkippedRows, TimeDimension,
+};
+pub use schemas::{ColumnType, OverflowPolicy, SchemaFunctions, Schemas, Table, U256Type};
+pub use sources::{Fetcher, RateLimiter, RpcPool, Source, SourceLabels};
 // pub(crate) use summaries::FreezeSummaryAgg;
 // pub use summaries::{FreezeChunkSummary, FreezeSummary};
-pub use summaries::{print_all_datasets, print_dataset_info, FreezeSummary};
+pub use summaries::{
+    hash_rpc_url, mask_rpc_url, print_all_datasets, print_dataset_info, print_file_lineage,
+    FreezeSummary, SizeEstimate, WrittenFile,
+};
 
 pub use errors::{err, ChunkError, CollectError, FileError, FreezeError, ParseError, R};
 
 pub use collection::*;
-pub use execution::{ExecutionEnv, ExecutionEnvBuilder};
+pub use execution::{ExecutionEnv, ExecutionEnvBuilder, FollowConfig};
 
 pub use signatures::*;
 
@@ -0,0 +1,223 @@
+use crate::{
+    dataframes::read_footer,
+    summaries::{print_bullet, print_bullet_indent, print_header},
+    FileError,
+};
+use indicatif::HumanBytes;
+use parquet_format_safe::{ConvertedType, FileMetaData, LogicalType, Statistics, Type};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+use thousands::Separable;
+
+/// row count, size on disk, and ranges of numeric columns of a parquet file
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileSummary {
+    /// rows in file
+    pub n_rows: u64,
+    /// size of file in bytes
+    pub n_bytes: u64,
+    /// min and max of each numeric column that has statistics, in schema order
+    pub column_ranges: Vec<ColumnRange>,
+}
+
+/// min and max value of a column, from the statistics of its column chunks
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnRange {
+    /// name of column
+    pub name: String,
+    /// smallest value of column
+    pub min: String,
+    /// largest value of column
+    pub max: String,
+}
+
+/// numeric value of a column statistic
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+enum StatValue {
+    Int(i128),
+    Float(f64),
+}
+
+impl std::fmt::Display for StatValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatValue::Int(value) => write!(f, "{}", value),
+            StatValue::Float(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// read summary of parquet file from the metadata in its footer
+///
+/// column ranges are only available if the file was written with statistics
+pub fn read_file_summary(path: &Path) -> Result<FileSummary, FileError> {
+    let metadata = read_footer(path)?;
+    let n_bytes = std::fs::metadata(path).map_err(|_e| FileError::FileWriteError)?.len();
+    Ok(FileSummary {
+        n_rows: metadata.num_rows.max(0) as u64,
+        n_bytes,
+        column_ranges: column_ranges(&metadata),
+    })
+}
+
+/// print row count, size, and numeric column ranges of parquet file
+pub fn print_file_summary(path: &Path) -> Result<(), FileError> {
+    let summary = read_file_summary(path)?;
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    print_bullet(
+        name,
+        format!("{} rows, {}", summary.n_rows.separate_with_commas(), HumanBytes(summary.n_bytes)),
+    );
+    for range in summary.column_ranges.iter() {
+        print_bullet_indent(&range.name, format!("{} to {}", range.min, range.max), 4);
+    }
+    Ok(())
+}
+
+/// print summaries of parquet files written by a freeze
+pub fn print_file_summaries(paths: &[PathBuf]) {
+    if paths.is_empty() {
+        return
+    }
+    println!();
+    println!();
+    print_header("output file statistics");
+    for path in paths.iter() {
+        if let Err(e) = print_file_summary(path) {
+            print_bullet(path.display().to_string(), format!("could not read statistics: {}", e));
+        }
+    }
+}
+
+/// min and max of each numeric column, combined over row groups
+fn column_ranges(metadata: &FileMetaData) -> Vec<ColumnRange> {
+    // leaf columns of flat schemas are the schema elements after the root
+    let unsigned: HashMap<&str, bool> = metadata
+        .schema
+        .iter()
+        .skip(1)
+        .map(|element| (element.name.as_str(), is_unsigned(element)))
+        .collect();
+
+    let mut ranges: Vec<(String, Option<(StatValue, StatValue)>)> = Vec::new();
+    for row_group in metadata.row_groups.iter() {
+        for (i, column) in row_group.columns.iter().enumerate() {
+            let meta_data = match &column.meta_data {
+                Some(meta_data) => meta_data,
+                None => continue,
+            };
+            let name = meta_data.path_in_schema.join(".");
+            let is_unsigned = unsigned.get(name.as_str()).copied().unwrap_or(false);
+            let range = meta_data
+                .statistics
+                .as_ref()
+                .and_then(|statistics| decode_range(meta_data.type_, is_unsigned, statistics));
+            if ranges.len() <= i {
+                ranges.push((name, range));
+                continue
+            }
+            ranges[i].1 = match (ranges[i].1, range) {
+                (Some((min, max)), Some((other_min, other_max))) => {
+                    let min = if other_min < min { other_min } else { min };
+                    let max = if other_max > max { other_max } else { max };
+                    Some((min, max))
+                }
+                // a row group without statistics leaves the range of column unknown
+                _ => None,
+            };
+        }
+    }
+    ranges
+        .into_iter()
+        .filter_map(|(name, range)| {
+            range.map(|(min, max)| ColumnRange { name, min: min.to_string(), max: max.to_string() })
+        })
+        .collect()
+}
+
+fn is_unsigned(element: &parquet_format_safe::SchemaElement) -> bool {
+    match (&element.logical_type, element.converted_type) {
+        (Some(LogicalType::INTEGER(int_type)), _) => !int_type.is_signed,
+        (_, Some(converted_type)) => {
+            converted_type == ConvertedType::UINT_8 ||
+                converted_type == ConvertedType::UINT_16 ||
+                converted_type == ConvertedType::UINT_32 ||
+                converted_type == ConvertedType::UINT_64
+        }
+        _ => false,
+    }
+}
+
+/// decode min and max of a column chunk, or None if column is not numeric
+///
+/// the deprecated min and max fields use signed order, so they are only used for signed columns
+fn decode_range(
+    physical_type: Type,
+    is_unsigned: bool,
+    statistics: &Statistics,
+) -> Option<(StatValue, StatValue)> {
+    let (min, max) = match (&statistics.min_value, &statistics.max_value) {
+        (Some(min), Some(max)) => (min, max),
+        _ if !is_unsigned => (statistics.min.as_ref()?, statistics.max.as_ref()?),
+        _ => return None,
+    };
+    Some((
+        decode_value(physical_type, is_unsigned, min)?,
+        decode_value(physical_type, is_unsigned, max)?,
+    ))
+}
+
+fn decode_value(physical_type: Type, is_unsigned: bool, bytes: &[u8]) -> Option<StatValue> {
+    let value = match physical_type {
+        Type::INT32 => {
+            let bytes: [u8; 4] = bytes.try_into().ok()?;
+            match is_unsigned {
+                true => StatValue::Int(u32::from_le_bytes(bytes) as i128),
+                false => StatValue::Int(i32::from_le_bytes(bytes) as i128),
+            }
+        }
+        Type::INT64 => {
+            let bytes: [u8; 8] = bytes.try_into().ok()?;
+            match is_unsigned {
+                true => StatValue::Int(u64::from_le_bytes(bytes) as i128),
+                false => StatValue::Int(i64::from_le_bytes(bytes) as i128),
+            }
+        }
+        Type::FLOAT => StatValue::Float(f32::from_le_bytes(bytes.try_into().ok()?) as f64),
+        Type::DOUBLE => StatValue::Float(f64::from_le_bytes(bytes.try_into().ok()?)),
+        _ => return None,
+    };
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+
+    #[test]
+    fn test_read_file_summary() {
+        let path = std::env::temp_dir().join("cryo_test_read_file_summary.parquet");
+        let mut df = df!(
+            "block_number" => [5u32, 3_000_000_000, 7],
+            "value" => [-1i64, 2, 3],
+            "hash" => ["a", "b", "c"]
+        )
+        .unwrap();
+        let file = std::fs::File::create(&path).unwrap();
+        ParquetWriter::new(file).with_statistics(true).finish(&mut df).unwrap();
+
+        let summary = read_file_summary(&path).unwrap();
+        assert_eq!(summary.n_rows, 3);
+        assert_eq!(summary.n_bytes, std::fs::metadata(&path).unwrap().len());
+        let ranges: Vec<_> = summary
+            .column_ranges
+            .iter()
+            .map(|range| (range.name.as_str(), range.min.as_str(), range.max.as_str()))
+            .collect();
+        assert_eq!(ranges, vec![("block_number", "5", "3000000000"), ("value", "-1", "3")]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -554,6 +554,7 @@ impl PartitionStats {
 }
 
 /// labels for Partition
+#[derive(Clone)]
 pub struct PartitionLabels {
     /// block number labels
     pub block_number_labels: Option<Vec<Option<String>>>,
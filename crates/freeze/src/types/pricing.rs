@@ -0,0 +1,120 @@
+use ethers::prelude::*;
+
+/// Price per unit of gas actually paid by a transaction
+///
+/// Resolution order:
+/// 1. `effectiveGasPrice` of the receipt, when a receipt is available
+/// 2. `gasPrice` of legacy (type 0) and access list (type 1) transactions
+/// 3. `min(maxFeePerGas, baseFeePerGas + maxPriorityFeePerGas)` for dynamic fee (type 2) and
+///    blob (type 3) transactions, using the base fee of the block header
+/// 4. `gasPrice` of other transaction types, such as deposit transactions of rollups
+///
+/// If none of these can be resolved, the price is null, unless `estimate` is set, in which case
+/// `maxFeePerGas` is used as an upper bound estimate. Blob gas is priced separately and is not
+/// included.
+pub fn effective_gas_price(
+    tx: &Transaction,
+    base_fee_per_gas: Option<U256>,
+    receipt: Option<&TransactionReceipt>,
+    estimate: bool,
+) -> Option<U256> {
+    if let Some(price) = receipt.and_then(|receipt| receipt.effective_gas_price) {
+        return Some(price)
+    }
+    match tx.transaction_type.map(|x| x.as_u64()) {
+        Some(2) | Some(3) => {
+            match (tx.max_fee_per_gas, tx.max_priority_fee_per_gas, base_fee_per_gas) {
+                (Some(max_fee), Some(max_priority_fee), Some(base_fee)) => {
+                    Some(max_fee.min(base_fee.saturating_add(max_priority_fee)))
+                }
+                (Some(max_fee), _, _) if estimate => Some(max_fee),
+                _ => None,
+            }
+        }
+        _ => tx.gas_price,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gwei(value: u64) -> U256 {
+        U256::from(value) * U256::exp10(9)
+    }
+
+    fn legacy_tx(transaction_type: Option<u64>) -> Transaction {
+        Transaction {
+            transaction_type: transaction_type.map(U64::from),
+            gas_price: Some(gwei(20)),
+            ..Default::default()
+        }
+    }
+
+    fn dynamic_fee_tx(transaction_type: u64) -> Transaction {
+        Transaction {
+            transaction_type: Some(U64::from(transaction_type)),
+            gas_price: None,
+            max_fee_per_gas: Some(gwei(50)),
+            max_priority_fee_per_gas: Some(gwei(2)),
+            ..Default::default()
+        }
+    }
+
+    fn receipt_with_price(effective_gas_price: Option<U256>) -> TransactionReceipt {
+        TransactionReceipt { effective_gas_price, ..Default::default() }
+    }
+
+    #[test]
+    fn test_legacy_pre_london() {
+        let tx = legacy_tx(None);
+        assert_eq!(effective_gas_price(&tx, None, None, false), Some(gwei(20)));
+        let tx = legacy_tx(Some(0));
+        assert_eq!(effective_gas_price(&tx, None, None, false), Some(gwei(20)));
+    }
+
+    #[test]
+    fn test_access_list() {
+        let tx = legacy_tx(Some(1));
+        assert_eq!(effective_gas_price(&tx, None, None, false), Some(gwei(20)));
+        assert_eq!(effective_gas_price(&tx, Some(gwei(10)), None, false), Some(gwei(20)));
+    }
+
+    #[test]
+    fn test_dynamic_fee() {
+        let tx = dynamic_fee_tx(2);
+        // priority fee fits under max fee
+        assert_eq!(effective_gas_price(&tx, Some(gwei(30)), None, false), Some(gwei(32)));
+        // capped by max fee
+        assert_eq!(effective_gas_price(&tx, Some(gwei(49)), None, false), Some(gwei(50)));
+        // base fee absent
+        assert_eq!(effective_gas_price(&tx, None, None, false), None);
+        assert_eq!(effective_gas_price(&tx, None, None, true), Some(gwei(50)));
+    }
+
+    #[test]
+    fn test_blob() {
+        let tx = dynamic_fee_tx(3);
+        assert_eq!(effective_gas_price(&tx, Some(gwei(10)), None, false), Some(gwei(12)));
+        assert_eq!(effective_gas_price(&tx, None, None, false), None);
+    }
+
+    #[test]
+    fn test_other_types_use_gas_price() {
+        // types such as optimism deposits keep the gasPrice of the rpc, even if they set fee fields
+        let deposit = legacy_tx(Some(0x7e));
+        assert_eq!(effective_gas_price(&deposit, Some(gwei(10)), None, false), Some(gwei(20)));
+        let tx = Transaction { transaction_type: Some(U64::from(0x7e)), ..dynamic_fee_tx(2) };
+        assert_eq!(effective_gas_price(&tx, Some(gwei(30)), None, true), None);
+    }
+
+    #[test]
+    fn test_receipt_takes_precedence() {
+        let tx = dynamic_fee_tx(2);
+        let receipt = receipt_with_price(Some(gwei(31)));
+        assert_eq!(effective_gas_price(&tx, Some(gwei(30)), Some(&receipt), false), Some(gwei(31)));
+        let tx = legacy_tx(Some(0));
+        let receipt = receipt_with_price(None);
+        assert_eq!(effective_gas_price(&tx, None, Some(&receipt), false), Some(gwei(20)));
+    }
+}
@@ -1,5 +1,11 @@
 use crate::{CollectError, Datatype, Dim, MetaDatatype, Partition, Table};
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
 
 /// Query
 #[derive(Clone)]
@@ -16,10 +22,166 @@ pub struct Query {
     pub partitioned_by: Vec<Dim>,
     /// Exclude failed
     pub exclude_failed: bool,
+    /// Estimate gas prices that cannot be resolved exactly
+    pub estimate_gas_price: bool,
     /// Javascript tracer
     pub js_tracer: Option<String>,
     /// Labels (these are non-functional)
     pub labels: QueryLabels,
+    /// Rows skipped during collection
+    pub skipped_rows: Arc<SkippedRows>,
+    /// Duplicate rows dropped before writing
+    pub duplicate_rows: Arc<SkippedRows>,
+    /// Skip blocks that the provider cannot return instead of failing their chunk
+    pub allow_missing_blocks: bool,
+    /// Blocks skipped during collection because the provider could not return them
+    pub missing_blocks: Arc<MissingBlocks>,
+    /// Datatypes collected in a degraded mode because the provider lacks a required method
+    pub degraded_datatypes: Arc<DegradedDatatypes>,
+    /// Requests of chunks that have started collecting
+    pub request_progress: Arc<RequestProgress>,
+    /// Transaction counts of blocks fetched during collection, used to verify output files
+    pub block_transaction_counts: Arc<BlockTransactionCounts>,
+    /// Timestamps of blocks fetched during collection, used to fill block_timestamp columns
+    pub block_timestamps: Arc<BlockTimestamps>,
+}
+
+/// counts of rows skipped during collection because their raw data was malformed or could not
+/// be decoded
+#[derive(Debug, Default)]
+pub struct SkippedRows(Mutex<HashMap<Datatype, u64>>);
+
+impl SkippedRows {
+    /// record rows skipped for a datatype
+    pub fn add(&self, datatype: Datatype, n_rows: u64) {
+        if n_rows > 0 {
+            if let Ok(mut counts) = self.0.lock() {
+                *counts.entry(datatype).or_insert(0) += n_rows;
+            }
+        }
+    }
+
+    /// number of rows skipped for each datatype, ordered by datatype name
+    pub fn counts(&self) -> Vec<(Datatype, u64)> {
+        let mut counts: Vec<_> = match self.0.lock() {
+            Ok(counts) => counts.iter().map(|(datatype, n)| (*datatype, *n)).collect(),
+            Err(_) => Vec::new(),
+        };
+        counts.sort_by_key(|(datatype, _)| datatype.name());
+        counts
+    }
+}
+
+/// blocks skipped during collection because the provider has not produced them or has pruned
+/// them, recorded when missing blocks are allowed
+#[derive(Debug, Default)]
+pub struct MissingBlocks(Mutex<BTreeSet<u64>>);
+
+impl MissingBlocks {
+    /// record a missing block
+    pub fn add(&self, block_number: u64) {
+        if let Ok(mut block_numbers) = self.0.lock() {
+            block_numbers.insert(block_number);
+        }
+    }
+
+    /// missing block numbers, in ascending order
+    pub fn block_numbers(&self) -> Vec<u64> {
+        match self.0.lock() {
+            Ok(block_numbers) => block_numbers.iter().copied().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+/// number of transactions in each block, recorded when a block is fetched with its transactions
+#[derive(Debug, Default)]
+pub struct BlockTransactionCounts(Mutex<HashMap<u64, u64>>);
+
+impl BlockTransactionCounts {
+    /// record number of transactions of a block
+    pub fn add(&self, block_number: u64, n_transactions: u64) {
+        if let Ok(mut counts) = self.0.lock() {
+            counts.insert(block_number, n_transactions);
+        }
+    }
+
+    /// number of transactions of a block, if block has been fetched
+    pub fn get(&self, block_number: u64) -> Option<u64> {
+        self.0.lock().ok().and_then(|counts| counts.get(&block_number).copied())
+    }
+}
+
+/// timestamps of blocks, cached so that rows of the same block only fetch its header once
+#[derive(Debug, Default)]
+pub struct BlockTimestamps(Mutex<HashMap<u64, u32>>);
+
+impl BlockTimestamps {
+    /// record timestamp of a block
+    pub fn add(&self, block_number: u64, timestamp: u32) {
+        if let Ok(mut timestamps) = self.0.lock() {
+            timestamps.insert(block_number, timestamp);
+        }
+    }
+
+    /// timestamp of a block, if block has been fetched
+    pub fn get(&self, block_number: u64) -> Option<u32> {
+        self.0.lock().ok().and_then(|timestamps| timestamps.get(&block_number).copied())
+    }
+}
+
+/// counts of rpc requests issued and completed by chunks that have started collecting, read by
+/// the progress bar
+#[derive(Debug, Default)]
+pub struct RequestProgress {
+    started: AtomicU64,
+    completed: AtomicU64,
+}
+
+impl RequestProgress {
+    /// record requests of a chunk that has started collecting
+    pub fn start(&self, n_requests: u64) {
+        self.started.fetch_add(n_requests, Ordering::Relaxed);
+    }
+
+    /// record a completed request
+    pub fn complete(&self) {
+        self.completed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// number of completed requests and number of requests of started chunks
+    pub fn counts(&self) -> (u64, u64) {
+        (self.completed.load(Ordering::Relaxed), self.started.load(Ordering::Relaxed))
+    }
+}
+
+/// datatypes that fell back to a less complete collection method during collection, with a
+/// description of what the fallback omits
+#[derive(Debug, Default)]
+pub struct DegradedDatatypes(Mutex<HashMap<Datatype, String>>);
+
+impl DegradedDatatypes {
+    /// record that a datatype was collected in a degraded mode
+    pub fn add(&self, datatype: Datatype, description: &str) {
+        if let Ok(mut degraded) = self.0.lock() {
+            degraded.entry(datatype).or_insert_with(|| description.to_string());
+        }
+    }
+
+    /// whether a datatype has been collected in a degraded mode
+    pub fn contains(&self, datatype: &Datatype) -> bool {
+        self.0.lock().map(|degraded| degraded.contains_key(datatype)).unwrap_or(false)
+    }
+
+    /// degraded datatypes and descriptions of their fallbacks, ordered by datatype name
+    pub fn descriptions(&self) -> Vec<(Datatype, String)> {
+        let mut descriptions: Vec<_> = match self.0.lock() {
+            Ok(degraded) => degraded.iter().map(|(datatype, x)| (*datatype, x.clone())).collect(),
+            Err(_) => Vec::new(),
+        };
+        descriptions.sort_by_key(|(datatype, _)| datatype.name());
+        descriptions
+    }
 }
 
 /// query labels (non-functional)
@@ -29,6 +191,10 @@ pub struct QueryLabels {
     pub align: bool,
     /// reorg buffer
     pub reorg_buffer: u64,
+    /// partitions dropped because they precede deployment of their contracts
+    pub chunks_before_deployment: u64,
+    /// block tags such as `latest` and the block numbers they resolved to
+    pub block_tags: Vec<(String, u64)>,
 }
 
 impl Query {
@@ -1,9 +1,14 @@
-use crate::{err, CollectError, ExecutionEnv, FileOutput, FreezeSummary, Query};
+use crate::{
+    err, ChunkData, CollectError, DurationStats, ExecutionEnv, FileOutput, FreezeSummary,
+    PhaseTiming, PipelineStats, Query,
+};
 use chrono::{DateTime, Local};
 use std::{
+    collections::BTreeMap,
     fs::File,
     io::Write,
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
 #[derive(serde::Serialize, Debug)]
@@ -11,8 +16,25 @@ struct FreezeReport {
     cryo_version: String,
     // node_client: String,
     cli_command: Option<Vec<String>>,
+    query: SerializedQuery,
     results: Option<SerializedFreezeSummary>,
     args: Option<String>,
+    timing: Option<Vec<SerializedPhaseTiming>>,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct SerializedQuery {
+    datatypes: Vec<String>,
+    start_block: Option<u64>,
+    end_block: Option<u64>,
+    n_chunks: usize,
+    schemas: BTreeMap<String, Vec<SerializedColumn>>,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct SerializedColumn {
+    name: String,
+    column_type: String,
 }
 
 #[derive(serde::Serialize, Debug)]
@@ -20,6 +42,80 @@ struct SerializedFreezeSummary {
     completed_paths: Vec<PathBuf>,
     errored_paths: Vec<PathBuf>,
     n_skipped: u64,
+    n_cancelled: u64,
+    interrupted: bool,
+    n_rows: u64,
+    duration_seconds: f64,
+    files: Vec<SerializedWrittenFile>,
+    errors: Vec<SerializedChunkError>,
+    stats: SerializedPipelineStats,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct SerializedPipelineStats {
+    n_chunks: u64,
+    n_failed_chunks: u64,
+    gather: SerializedDurationStats,
+    write: SerializedDurationStats,
+    rows: Vec<SerializedRowStats>,
+    requests: Vec<SerializedMethodStats>,
+    log_window_sizes: Vec<SerializedLogWindowSize>,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct SerializedDurationStats {
+    total_seconds: f64,
+    mean_seconds: f64,
+    p95_seconds: f64,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct SerializedRowStats {
+    datatype: String,
+    total: u64,
+    mean: f64,
+    p95: u64,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct SerializedMethodStats {
+    method: String,
+    n_requests: u64,
+    n_retries: u64,
+    n_errors: u64,
+    latency: SerializedDurationStats,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct SerializedLogWindowSize {
+    n_blocks: u64,
+    n_requests: u64,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct SerializedWrittenFile {
+    datatype: String,
+    path: PathBuf,
+    n_rows: u64,
+    n_bytes: u64,
+    duration_seconds: f64,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct SerializedChunkError {
+    paths: Vec<PathBuf>,
+    error: String,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct SerializedPhaseTiming {
+    phase: String,
+    count: usize,
+    total_seconds: f64,
+    p50_seconds: f64,
+    p90_seconds: f64,
+    p99_seconds: f64,
+    max_seconds: f64,
 }
 
 pub(crate) fn get_report_path(
@@ -57,43 +153,96 @@ pub(crate) fn write_report(
     // determine version
     let cryo_version = CRYO_VERSION.to_string();
     let serialized_summary = match freeze_summary {
-        Some(x) => Some(serialize_summary(x, query, sink)?),
+        Some(x) => Some(serialize_summary(x, query, sink, env)?),
         None => None,
     };
     let report = FreezeReport {
         cryo_version,
         cli_command: env.cli_command.clone(),
+        query: serialize_query(query),
         args: env.args.clone(),
         results: serialized_summary,
+        timing: env.timing.as_ref().map(|timing| serialize_timing(&timing.summary())),
     };
-    let serialized = serde_json::to_string(&report)
+    let serialized = serde_json::to_string_pretty(&report)
         .map_err(|_| CollectError::CollectError("could not serialize report".to_string()))?;
 
     // create path
     let path = get_report_path(env, sink, freeze_summary.is_some())?;
 
     // save to file
-    let mut file = File::create(&path)
-        .map_err(|_| CollectError::CollectError("could not create report file".to_string()))?;
-    file.write_all(serialized.as_bytes())
-        .map_err(|_| CollectError::CollectError("could not write report data".to_string()))?;
+    write_file(&path, &serialized)?;
 
-    // delete initial report
+    // delete initial report, and place copy of final report next to the output files
     if freeze_summary.is_some() {
         let incomplete_path = get_report_path(env, sink, false)?;
         std::fs::remove_file(incomplete_path)
             .map_err(|_| err("could not delete initial report file"))?;
+        write_file(&get_summary_path(&report.query, sink), &serialized)?;
     }
 
     Ok(path)
 }
 
+/// path of run summary in output dir, named `<network>__<range>__summary.json`
+fn get_summary_path(query: &SerializedQuery, sink: &FileOutput) -> PathBuf {
+    let filename = match (query.start_block, query.end_block) {
+        (Some(start), Some(end)) => {
+            format!("{}__{:0>8}_to_{:0>8}__summary.json", sink.prefix, start, end)
+        }
+        _ => format!("{}__summary.json", sink.prefix),
+    };
+    sink.output_dir.join(filename)
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), CollectError> {
+    let mut file = File::create(path)
+        .map_err(|_| CollectError::CollectError("could not create report file".to_string()))?;
+    file.write_all(contents.as_bytes())
+        .map_err(|_| CollectError::CollectError("could not write report data".to_string()))
+}
+
+fn serialize_query(query: &Query) -> SerializedQuery {
+    let block_ranges: Vec<_> = query
+        .partitions
+        .iter()
+        .filter_map(|partition| partition.block_numbers.as_ref())
+        .map(|block_numbers| (block_numbers.min_value(), block_numbers.max_value()))
+        .collect();
+    let schemas = query
+        .schemas
+        .iter()
+        .map(|(datatype, table)| {
+            let columns = table
+                .columns()
+                .into_iter()
+                .map(|name| SerializedColumn {
+                    name: name.to_string(),
+                    column_type: table
+                        .column_type(name)
+                        .map(|column_type| column_type.as_str().to_string())
+                        .unwrap_or_default(),
+                })
+                .collect();
+            (datatype.name(), columns)
+        })
+        .collect();
+    SerializedQuery {
+        datatypes: query.datatypes.iter().flat_map(|x| x.datatypes()).map(|x| x.name()).collect(),
+        start_block: block_ranges.iter().filter_map(|(min, _)| *min).min(),
+        end_block: block_ranges.iter().filter_map(|(_, max)| *max).max(),
+        n_chunks: query.partitions.len(),
+        schemas,
+    }
+}
+
 fn serialize_summary(
     summary: &FreezeSummary,
     query: &Query,
     sink: &FileOutput,
+    env: &ExecutionEnv,
 ) -> Result<SerializedFreezeSummary, CollectError> {
-    let completed_paths: Vec<PathBuf> = summary
+    let mut completed_paths: Vec<PathBuf> = summary
         .completed
         .iter()
         .map(|partition| {
@@ -105,7 +254,7 @@ fn serialize_summary(
         .flatten()
         .collect();
 
-    let errored_paths: Vec<PathBuf> = summary
+    let mut errored_paths: Vec<PathBuf> = summary
         .errored
         .iter()
         .filter_map(|(partition_option, _error)| {
@@ -119,12 +268,111 @@ fn serialize_summary(
         .flatten()
         .collect();
 
+    completed_paths.sort();
+    errored_paths.sort();
+
+    let mut files: Vec<SerializedWrittenFile> = summary
+        .written
+        .iter()
+        .map(|file| SerializedWrittenFile {
+            datatype: file.datatype.name(),
+            path: file.path.clone(),
+            n_rows: file.n_rows,
+            n_bytes: file.n_bytes,
+            duration_seconds: file.duration.as_secs_f64(),
+        })
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut errors = Vec::new();
+    for (partition, error) in summary.errored.iter() {
+        let mut paths: Vec<PathBuf> = match partition {
+            Some(partition) => sink.get_paths(query, partition, None)?.into_values().collect(),
+            None => Vec::new(),
+        };
+        paths.sort();
+        errors.push(SerializedChunkError { paths, error: error.to_string() });
+    }
+    errors.sort_by(|a, b| a.paths.cmp(&b.paths));
+
+    let duration_seconds =
+        SystemTime::now().duration_since(env.t_start).map(|d| d.as_secs_f64()).unwrap_or(0.0);
+
     Ok(SerializedFreezeSummary {
         completed_paths,
         errored_paths,
         n_skipped: summary.skipped.len() as u64,
+        n_cancelled: summary.cancelled.len() as u64,
+        interrupted: summary.interrupted,
+        n_rows: summary.n_rows,
+        duration_seconds,
+        files,
+        errors,
+        stats: serialize_stats(summary),
     })
 }
 
+fn serialize_stats(summary: &FreezeSummary) -> SerializedPipelineStats {
+    let stats = PipelineStats::new(&summary.chunk_timings);
+    SerializedPipelineStats {
+        n_chunks: stats.n_chunks,
+        n_failed_chunks: stats.n_failed,
+        gather: serialize_durations(&stats.gather),
+        write: serialize_durations(&stats.write),
+        rows: stats
+            .rows
+            .iter()
+            .map(|rows| SerializedRowStats {
+                datatype: rows.datatype.name(),
+                total: rows.total,
+                mean: rows.mean,
+                p95: rows.p95,
+            })
+            .collect(),
+        requests: summary
+            .request_stats
+            .iter()
+            .map(|method| SerializedMethodStats {
+                method: method.method.clone(),
+                n_requests: method.n_requests,
+                n_retries: method.n_retries,
+                n_errors: method.n_errors,
+                latency: serialize_durations(&method.latency),
+            })
+            .collect(),
+        log_window_sizes: summary
+            .log_window_sizes
+            .iter()
+            .map(|(n_blocks, n_requests)| SerializedLogWindowSize {
+                n_blocks: *n_blocks,
+                n_requests: *n_requests,
+            })
+            .collect(),
+    }
+}
+
+fn serialize_durations(durations: &DurationStats) -> SerializedDurationStats {
+    SerializedDurationStats {
+        total_seconds: durations.total.as_secs_f64(),
+        mean_seconds: durations.mean.as_secs_f64(),
+        p95_seconds: durations.p95.as_secs_f64(),
+    }
+}
+
+fn serialize_timing(phases: &[PhaseTiming]) -> Vec<SerializedPhaseTiming> {
+    phases
+        .iter()
+        .map(|phase| SerializedPhaseTiming {
+            phase: phase.phase.to_string(),
+            count: phase.count,
+            total_seconds: phase.total.as_secs_f64(),
+            p50_seconds: phase.p50.as_secs_f64(),
+            p90_seconds: phase.p90.as_secs_f64(),
+            p99_seconds: phase.p99.as_secs_f64(),
+            max_seconds: phase.max.as_secs_f64(),
+        })
+        .collect()
+}
+
 /// cryo version
 pub const CRYO_VERSION: &str = env!("GIT_DESCRIPTION");
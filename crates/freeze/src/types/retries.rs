@@ -0,0 +1,271 @@
+use ethers::prelude::*;
+use ethers_core::abi::ParamType;
+use rand::Rng;
+use std::time::Duration;
+
+/// JSON-RPC error codes that will not succeed when retried
+const PERMANENT_ERROR_CODES: [i64; 3] = [
+    -32600, // invalid request
+    -32601, // method not found
+    -32602, // invalid params
+];
+
+/// messages of rate limit errors, which are always retried regardless of their error code
+const RATE_LIMIT_MESSAGES: [&str; 3] = ["rate limit", "too many requests", "limit exceeded"];
+
+/// messages of http errors that will not succeed when retried
+const PERMANENT_HTTP_MESSAGES: [&str; 4] =
+    ["400 bad request", "401 unauthorized", "403 forbidden", "404 not found"];
+
+/// messages of errors of eth_getLogs requests whose block range returns too many logs
+const LARGE_RESPONSE_MESSAGES: [&str; 6] = [
+    "response too large",
+    "response size exceeded",
+    "returned more than",
+    "too many results",
+    "block range is too",
+    "exceed maximum block range",
+];
+
+/// JSON-RPC error code of reverted eth_calls
+const REVERT_ERROR_CODE: i64 = 3;
+
+/// selector of `Error(string)`, used by require and revert with a reason string
+const REVERT_ERROR_SELECTOR: &[u8] = &[0x08, 0xc3, 0x79, 0xa0];
+
+/// selector of `Panic(uint256)`, used by failed asserts and arithmetic errors
+const REVERT_PANIC_SELECTOR: &[u8] = &[0x4e, 0x48, 0x7b, 0x71];
+
+/// Policy for retrying failed RPC requests with exponential backoff
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// maximum number of retries after the initial attempt
+    pub max_retries: u32,
+    /// backoff before the first retry, in milliseconds, doubled for each further retry
+    pub initial_backoff: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy { max_retries: 5, initial_backoff: 200 }
+    }
+}
+
+impl RetryPolicy {
+    /// whether request should be retried after failing `attempt` times
+    pub fn should_retry(&self, error: &ProviderError, attempt: u32) -> bool {
+        attempt <= self.max_retries && !is_permanent_error(error)
+    }
+
+    /// backoff after failing `attempt` times, with up to 50% random jitter added
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let base = self.initial_backoff.saturating_mul(1 << attempt.saturating_sub(1).min(16));
+        let jitter = rand::thread_rng().gen_range(0..=base / 2);
+        Duration::from_millis(base.saturating_add(jitter))
+    }
+}
+
+/// whether error is caused by the request itself rather than the state of the provider
+///
+/// rate limit errors are never permanent
+pub fn is_permanent_error(error: &ProviderError) -> bool {
+    let message = error.to_string().to_lowercase();
+    if RATE_LIMIT_MESSAGES.iter().any(|pattern| message.contains(pattern)) {
+        return false
+    }
+    match error {
+        ProviderError::JsonRpcClientError(e) => match e.as_error_response() {
+            Some(response) => {
+                let message = response.message.to_lowercase();
+                PERMANENT_ERROR_CODES.contains(&response.code) ||
+                    is_revert(error) ||
+                    is_response_too_large(error) ||
+                    message.contains("method not found") ||
+                    message.contains("does not exist")
+            }
+            None => PERMANENT_HTTP_MESSAGES.iter().any(|pattern| message.contains(pattern)),
+        },
+        ProviderError::EnsError(_) |
+        ProviderError::EnsNotOwned(_) |
+        ProviderError::SerdeJson(_) |
+        ProviderError::HexError(_) |
+        ProviderError::UnsupportedRPC |
+        ProviderError::UnsupportedNodeClient |
+        ProviderError::SignerUnavailable => true,
+        _ => false,
+    }
+}
+
+/// whether error is caused by the provider not supporting or not exposing the requested method
+pub fn is_unsupported_method(error: &ProviderError) -> bool {
+    match error {
+        ProviderError::JsonRpcClientError(e) => match e.as_error_response() {
+            Some(response) => {
+                let message = response.message.to_lowercase();
+                response.code == -32601 ||
+                    message.contains("method not found") ||
+                    message.contains("does not exist") ||
+                    message.contains("not supported")
+            }
+            None => false,
+        },
+        ProviderError::UnsupportedRPC | ProviderError::UnsupportedNodeClient => true,
+        _ => false,
+    }
+}
+
+/// whether error is a request whose response would be too large, which is retried in smaller
+/// requests rather than as is
+pub fn is_response_too_large(error: &ProviderError) -> bool {
+    let message = error.to_string().to_lowercase();
+    LARGE_RESPONSE_MESSAGES.iter().any(|pattern| message.contains(pattern))
+}
+
+/// whether error is an eth_call that reverted, which is deterministic at a given block
+pub fn is_revert(error: &ProviderError) -> bool {
+    match error {
+        ProviderError::JsonRpcClientError(e) => match e.as_error_response() {
+            Some(response) => {
+                response.code == REVERT_ERROR_CODE ||
+                    response.message.to_lowercase().contains("revert")
+            }
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// get reason of a reverted eth_call, or None if error is not a revert
+///
+/// reasons of `Error(string)` and `Panic(uint256)` reverts are decoded from the revert data,
+/// custom errors are left as the message of the node followed by their hex revert data
+pub fn revert_reason(error: &ProviderError) -> Option<String> {
+    if !is_revert(error) {
+        return None
+    }
+    let response = match error {
+        ProviderError::JsonRpcClientError(e) => e.as_error_response()?,
+        _ => return None,
+    };
+    let data = match response.data.as_ref().and_then(|data| data.as_str()) {
+        Some(data) if data.len() > 2 => data,
+        _ => return Some(response.message.clone()),
+    };
+    let bytes = match ethers_core::utils::hex::decode(data) {
+        Ok(bytes) if bytes.len() >= 4 => bytes,
+        _ => return Some(format!("{}: {}", response.message, data)),
+    };
+    let (selector, body) = bytes.split_at(4);
+    let decoded = match selector {
+        REVERT_ERROR_SELECTOR => ethers_core::abi::decode(&[ParamType::String], body)
+            .ok()
+            .and_then(|tokens| tokens.into_iter().next())
+            .and_then(|token| token.into_string()),
+        REVERT_PANIC_SELECTOR => ethers_core::abi::decode(&[ParamType::Uint(256)], body)
+            .ok()
+            .and_then(|tokens| tokens.into_iter().next())
+            .and_then(|token| token.into_uint())
+            .map(|code| format!("panic code {:#x}", code)),
+        _ => None,
+    };
+    Some(decoded.unwrap_or_else(|| format!("{}: {}", response.message, data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles() {
+        let policy = RetryPolicy { max_retries: 5, initial_backoff: 100 };
+        for (attempt, base) in [(1, 100), (2, 200), (3, 400)] {
+            let backoff = policy.backoff(attempt);
+            assert!(backoff >= Duration::from_millis(base));
+            assert!(backoff <= Duration::from_millis(base * 3 / 2));
+        }
+    }
+
+    #[test]
+    fn test_permanent_errors_not_retried() {
+        let policy = RetryPolicy::default();
+        assert!(!policy.should_retry(&ProviderError::UnsupportedRPC, 1));
+        let transient = ProviderError::CustomError("503 Service Unavailable".to_string());
+        assert!(policy.should_retry(&transient, 1));
+        assert!(policy.should_retry(&transient, 5));
+        assert!(!policy.should_retry(&transient, 6));
+    }
+
+    #[test]
+    fn test_rate_limits_retried() {
+        let policy = RetryPolicy::default();
+        let rate_limited = JsonRpcError {
+            code: -32600,
+            message: "rate limit exceeded, too many requests".to_string(),
+            data: None,
+        };
+        let rate_limited = ProviderError::JsonRpcClientError(Box::new(
+            HttpClientError::JsonRpcError(rate_limited),
+        ));
+        assert!(policy.should_retry(&rate_limited, 1));
+        let invalid =
+            JsonRpcError { code: -32600, message: "invalid request".to_string(), data: None };
+        let invalid =
+            ProviderError::JsonRpcClientError(Box::new(HttpClientError::JsonRpcError(invalid)));
+        assert!(!policy.should_retry(&invalid, 1));
+    }
+
+    #[test]
+    fn test_unsupported_method() {
+        let message = "the method debug_traceBlockByNumber does not exist/is not available";
+        let unsupported = JsonRpcError { code: -32601, message: message.to_string(), data: None };
+        let unsupported =
+            ProviderError::JsonRpcClientError(Box::new(HttpClientError::JsonRpcError(unsupported)));
+        assert!(is_unsupported_method(&unsupported));
+        let invalid =
+            JsonRpcError { code: -32602, message: "invalid params".to_string(), data: None };
+        let invalid =
+            ProviderError::JsonRpcClientError(Box::new(HttpClientError::JsonRpcError(invalid)));
+        assert!(!is_unsupported_method(&invalid));
+    }
+
+    #[test]
+    fn test_response_too_large() {
+        let message = "query returned more than 10000 results";
+        let too_large = JsonRpcError { code: -32005, message: message.to_string(), data: None };
+        let too_large =
+            ProviderError::JsonRpcClientError(Box::new(HttpClientError::JsonRpcError(too_large)));
+        assert!(is_response_too_large(&too_large));
+        assert!(!RetryPolicy::default().should_retry(&too_large, 1));
+        let transient = ProviderError::CustomError("503 Service Unavailable".to_string());
+        assert!(!is_response_too_large(&transient));
+    }
+
+    #[test]
+    fn test_revert_reason() {
+        let data = "0x08c379a0\
+            0000000000000000000000000000000000000000000000000000000000000020\
+            000000000000000000000000000000000000000000000000000000000000000c\
+            6e6f7420616c6c6f776564000000000000000000000000000000000000000000";
+        let reverted = JsonRpcError {
+            code: 3,
+            message: "execution reverted: not allowed".to_string(),
+            data: Some(serde_json::Value::String(data.to_string())),
+        };
+        let reverted =
+            ProviderError::JsonRpcClientError(Box::new(HttpClientError::JsonRpcError(reverted)));
+        assert_eq!(revert_reason(&reverted), Some("not allowed".to_string()));
+        assert!(!RetryPolicy::default().should_retry(&reverted, 1));
+
+        let reverted =
+            JsonRpcError { code: -32000, message: "execution reverted".to_string(), data: None };
+        let reverted =
+            ProviderError::JsonRpcClientError(Box::new(HttpClientError::JsonRpcError(reverted)));
+        assert_eq!(revert_reason(&reverted), Some("execution reverted".to_string()));
+
+        let missing =
+            JsonRpcError { code: -32000, message: "missing trie node".to_string(), data: None };
+        let missing =
+            ProviderError::JsonRpcClientError(Box::new(HttpClientError::JsonRpcError(missing)));
+        assert_eq!(revert_reason(&missing), None);
+    }
+}
@@ -0,0 +1,309 @@
+use ethers::providers::{Http, JsonRpcClient, JsonRpcError, ProviderError, RpcError};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use tokio::sync::{mpsc, oneshot};
+
+/// http transport that sends concurrent requests to the endpoint as json-rpc batches
+///
+/// requests issued while a batch is being assembled share one http request, up to `batch_size`
+/// requests per batch, and responses are matched back to their requests by id
+#[derive(Debug)]
+pub struct BatchHttp {
+    id: AtomicU64,
+    sender: mpsc::UnboundedSender<PendingRequest>,
+}
+
+/// error of a request sent through a json-rpc batch
+#[derive(thiserror::Error, Debug)]
+pub enum BatchHttpError {
+    /// error response of the individual request
+    #[error(transparent)]
+    JsonRpcError(#[from] JsonRpcError),
+
+    /// http request of the whole batch failed
+    #[error("batch request failed: {0}")]
+    BatchError(String),
+
+    /// batch response has no response with the id of the request
+    #[error("batch response is missing response of request {0}")]
+    MissingResponse(u64),
+
+    /// response could not be deserialized
+    #[error("Deserialization Error: {err}. Response: {text}")]
+    SerdeJson {
+        /// underlying error
+        err: serde_json::Error,
+        /// contents of the response that could not be deserialized
+        text: String,
+    },
+
+    /// batching task stopped before responding
+    #[error("batch transport is closed")]
+    Closed,
+}
+
+impl From<BatchHttpError> for ProviderError {
+    fn from(src: BatchHttpError) -> ProviderError {
+        ProviderError::JsonRpcClientError(Box::new(src))
+    }
+}
+
+impl RpcError for BatchHttpError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            BatchHttpError::JsonRpcError(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            BatchHttpError::SerdeJson { err, .. } => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Request {
+    jsonrpc: &'static str,
+    id: u64,
+    method: String,
+    params: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    id: Option<u64>,
+    #[serde(default)]
+    result: Value,
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Debug)]
+struct PendingRequest {
+    request: Request,
+    response: oneshot::Sender<Result<Value, BatchHttpError>>,
+}
+
+/// timeouts and connection reuse of the http clients of rpc endpoints
+///
+/// options that are not given keep the defaults of reqwest, which has no timeouts
+#[derive(Clone, Debug, Default)]
+pub struct HttpOptions {
+    /// timeout of each request in seconds, 0 for no timeout
+    pub request_timeout: Option<u64>,
+    /// timeout of establishing a connection in seconds, 0 for no timeout
+    pub connect_timeout: Option<u64>,
+    /// seconds that idle connections are kept open for reuse, 0 to not reuse connections
+    pub keepalive: Option<u64>,
+}
+
+impl HttpOptions {
+    /// build http client with timeouts and connection reuse of options
+    pub fn build_client(&self) -> Result<reqwest::Client, reqwest::Error> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(seconds) = self.request_timeout.filter(|seconds| *seconds > 0) {
+            builder = builder.timeout(Duration::from_secs(seconds));
+        }
+        if let Some(seconds) = self.connect_timeout.filter(|seconds| *seconds > 0) {
+            builder = builder.connect_timeout(Duration::from_secs(seconds));
+        }
+        builder = match self.keepalive {
+            Some(0) => builder.pool_max_idle_per_host(0),
+            Some(seconds) => builder
+                .pool_idle_timeout(Duration::from_secs(seconds))
+                .tcp_keepalive(Duration::from_secs(seconds)),
+            None => builder,
+        };
+        builder.build()
+    }
+
+    /// http transport of url that sends requests with a client built from options
+    pub fn build_http(&self, url: &str) -> Result<Http, String> {
+        let url = reqwest::Url::parse(url).map_err(|e| e.to_string())?;
+        let client = self.build_client().map_err(|e| e.to_string())?;
+        Ok(Http::new_with_client(url, client))
+    }
+}
+
+impl BatchHttp {
+    /// create transport for url, must be called from within a tokio runtime
+    pub fn new(url: &str, batch_size: u64) -> BatchHttp {
+        BatchHttp::new_with_client(url, batch_size, reqwest::Client::new())
+    }
+
+    /// create transport for url that sends batches with client, must be called from within a
+    /// tokio runtime
+    pub fn new_with_client(url: &str, batch_size: u64, client: reqwest::Client) -> BatchHttp {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(assemble_batches(client, url.to_string(), batch_size.max(1), receiver));
+        BatchHttp { id: AtomicU64::new(1), sender }
+    }
+}
+
+#[async_trait::async_trait]
+impl JsonRpcClient for BatchHttp {
+    type Error = BatchHttpError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, BatchHttpError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = serde_json::to_value(params)
+            .map_err(|err| BatchHttpError::SerdeJson { err, text: method.to_string() })?;
+        let id = self.id.fetch_add(1, Ordering::SeqCst);
+        let request = Request { jsonrpc: "2.0", id, method: method.to_string(), params };
+        let (sender, receiver) = oneshot::channel();
+        self.sender
+            .send(PendingRequest { request, response: sender })
+            .map_err(|_| BatchHttpError::Closed)?;
+        let result = receiver.await.map_err(|_| BatchHttpError::Closed)??;
+        serde_json::from_value(result.clone())
+            .map_err(|err| BatchHttpError::SerdeJson { err, text: result.to_string() })
+    }
+}
+
+/// group queued requests into batches, sending each batch without waiting for earlier batches
+async fn assemble_batches(
+    client: reqwest::Client,
+    url: String,
+    batch_size: u64,
+    mut receiver: mpsc::UnboundedReceiver<PendingRequest>,
+) {
+    while let Some(first) = receiver.recv().await {
+        // let requests issued concurrently with the first request join its batch
+        tokio::task::yield_now().await;
+        let mut batch = vec![first];
+        while (batch.len() as u64) < batch_size {
+            match receiver.try_recv() {
+                Ok(pending) => batch.push(pending),
+                Err(_) => break,
+            }
+        }
+        tokio::spawn(send_batch(client.clone(), url.clone(), batch));
+    }
+}
+
+async fn send_batch(client: reqwest::Client, url: String, batch: Vec<PendingRequest>) {
+    let requests: Vec<_> = batch.iter().map(|pending| &pending.request).collect();
+    let mut responses = match post_batch(&client, &url, &requests).await {
+        Ok(body) => parse_batch_response(&body),
+        Err(e) => Err(BatchResponseError::Http(e)),
+    };
+    for pending in batch.into_iter() {
+        let id = pending.request.id;
+        let response = match &mut responses {
+            Ok(responses) => match responses.remove(&id) {
+                Some(Ok(result)) => Ok(result),
+                Some(Err(e)) => Err(BatchHttpError::JsonRpcError(e)),
+                None => Err(BatchHttpError::MissingResponse(id)),
+            },
+            Err(BatchResponseError::JsonRpc(e)) => Err(BatchHttpError::JsonRpcError(e.clone())),
+            Err(BatchResponseError::Http(e)) => Err(BatchHttpError::BatchError(e.clone())),
+        };
+        let _ = pending.response.send(response);
+    }
+}
+
+/// body of response to batch, or description of http failure including its status
+async fn post_batch(
+    client: &reqwest::Client,
+    url: &str,
+    requests: &[&Request],
+) -> Result<Vec<u8>, String> {
+    let response = client.post(url).json(requests).send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    let body = response.bytes().await.map_err(|e| e.to_string())?;
+    match status.is_success() {
+        true => Ok(body.to_vec()),
+        false => Err(format!("{}: {}", status, String::from_utf8_lossy(&body))),
+    }
+}
+
+/// failure of a whole batch, which is reported to each request of the batch
+#[derive(Debug)]
+enum BatchResponseError {
+    /// endpoint rejected the batch with a single error response, e.g. when batches are too large
+    JsonRpc(JsonRpcError),
+    /// http request failed or response was not json-rpc
+    Http(String),
+}
+
+/// results of each request of batch response, keyed by request id
+fn parse_batch_response(
+    body: &[u8],
+) -> Result<HashMap<u64, Result<Value, JsonRpcError>>, BatchResponseError> {
+    match serde_json::from_slice::<Vec<Response>>(body) {
+        Ok(responses) => Ok(responses
+            .into_iter()
+            .filter_map(|response| {
+                let result = match response.error {
+                    Some(error) => Err(error),
+                    None => Ok(response.result),
+                };
+                Some((response.id?, result))
+            })
+            .collect()),
+        Err(_) => match serde_json::from_slice::<Response>(body) {
+            Ok(Response { error: Some(error), .. }) => Err(BatchResponseError::JsonRpc(error)),
+            _ => Err(BatchResponseError::Http(String::from_utf8_lossy(body).to_string())),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::providers::{Middleware, Provider};
+
+    #[test]
+    fn test_parse_batch_response() {
+        // responses may arrive in any order and are matched by id
+        let body = br#"[
+            {"jsonrpc": "2.0", "id": 2, "result": "0x2"},
+            {"jsonrpc": "2.0", "id": 3, "error": {"code": -32602, "message": "invalid params"}},
+            {"jsonrpc": "2.0", "id": 1, "result": null}
+        ]"#;
+        let mut responses = parse_batch_response(body).unwrap();
+        assert_eq!(responses.remove(&1).unwrap().unwrap(), Value::Null);
+        assert_eq!(responses.remove(&2).unwrap().unwrap(), Value::String("0x2".to_string()));
+        assert_eq!(responses.remove(&3).unwrap().unwrap_err().code, -32602);
+        assert!(responses.is_empty());
+
+        // endpoints that reject a batch respond with a single error
+        let body = br#"{"jsonrpc": "2.0", "id": null, "error": {"code": -32600, "message": "batch too large"}}"#;
+        match parse_batch_response(body) {
+            Err(BatchResponseError::JsonRpc(error)) => assert_eq!(error.code, -32600),
+            other => panic!("unexpected response: {:?}", other),
+        }
+        assert!(matches!(parse_batch_response(b"bad gateway"), Err(BatchResponseError::Http(_))));
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_is_retried() {
+        // endpoint accepts connections but never responds
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                connections.push(stream);
+            }
+        });
+
+        let options = HttpOptions { request_timeout: Some(1), ..Default::default() };
+        let provider = Provider::new(options.build_http(&url).unwrap());
+        let start = std::time::Instant::now();
+        let error = provider.get_chainid().await.unwrap_err();
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert!(crate::RetryPolicy::default().should_retry(&error, 1), "{}", error);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::CRYO_VERSION;
+use ethers::utils::keccak256;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// blocks behind latest block that are considered final, on chains without a finalized block
+const DEFAULT_CACHE_REORG_BUFFER: u64 = 64;
+
+/// on-disk cache of raw rpc responses, as gzipped json files keyed by hash of method and params
+///
+/// entries are stored under a directory of the cryo version that wrote them, so upgrading cryo
+/// starts from an empty cache. only responses of final blocks are stored, so that reorgs and
+/// pending transactions never leave stale entries
+#[derive(Clone, Debug)]
+pub struct RpcCache {
+    /// directory of entries for current cryo version and chain
+    pub dir: PathBuf,
+    /// blocks behind latest block that are considered final if provider has no finalized block
+    pub reorg_buffer: u64,
+    /// highest block whose responses can be stored, resolved on first write
+    pub(crate) final_block: Arc<tokio::sync::OnceCell<Option<u64>>>,
+    warned: Arc<AtomicBool>,
+}
+
+impl RpcCache {
+    /// create cache of chain within root directory
+    pub fn new(root: &Path, chain_id: u64) -> RpcCache {
+        let dir = root.join(format!("cryo_{}", CRYO_VERSION)).join(chain_id.to_string());
+        RpcCache {
+            dir,
+            reorg_buffer: DEFAULT_CACHE_REORG_BUFFER,
+            final_block: Arc::new(tokio::sync::OnceCell::new()),
+            warned: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// consider blocks final once they are `reorg_buffer` blocks behind latest block
+    pub fn with_reorg_buffer(self, reorg_buffer: u64) -> RpcCache {
+        RpcCache { reorg_buffer, ..self }
+    }
+
+    /// path of entry of request
+    fn path(&self, method: &str, params: &serde_json::Value) -> PathBuf {
+        let hash = keccak256(format!("{}{}", method, params));
+        let name: String = hash.iter().map(|byte| format!("{:02x}", byte)).collect();
+        self.dir.join(method).join(name + ".json.gz")
+    }
+
+    /// read cached response of request, if present and readable
+    pub fn get<T: DeserializeOwned>(&self, method: &str, params: &serde_json::Value) -> Option<T> {
+        let file = std::fs::File::open(self.path(method, params)).ok()?;
+        serde_json::from_reader(GzDecoder::new(std::io::BufReader::new(file))).ok()
+    }
+
+    /// store response of request, null responses such as missing blocks are not stored
+    ///
+    /// failures are reported once as a warning rather than failing the request
+    pub fn put<T: Serialize>(&self, method: &str, params: &serde_json::Value, response: &T) {
+        if let Err(e) = self.try_put(method, params, response) {
+            if !self.warned.swap(true, Ordering::Relaxed) {
+                eprintln!("warning: could not write to rpc cache in {}: {}", self.dir.display(), e);
+            }
+        }
+    }
+
+    fn try_put<T: Serialize>(
+        &self,
+        method: &str,
+        params: &serde_json::Value,
+        response: &T,
+    ) -> std::io::Result<()> {
+        let response = serde_json::to_vec(response)?;
+        if response == b"null" {
+            return Ok(())
+        }
+        let path = self.path(method, params);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // write to temporary file first so concurrent readers never see partial entries
+        let tmp_path = path.with_extension(format!("tmp{}", std::process::id()));
+        let mut encoder = GzEncoder::new(std::fs::File::create(&tmp_path)?, Compression::fast());
+        encoder.write_all(&response)?;
+        encoder.finish()?;
+        std::fs::rename(tmp_path, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Block, TxHash, U64};
+
+    #[test]
+    fn test_rpc_cache_roundtrip() {
+        let root = std::env::temp_dir().join(format!("cryo_rpc_cache_{}", std::process::id()));
+        let cache = RpcCache::new(&root, 1);
+        let params = serde_json::json!([17]);
+        let block = Block::<TxHash> { number: Some(U64::from(17)), ..Default::default() };
+
+        assert!(cache.get::<Block<TxHash>>("eth_getBlockByNumber", &params).is_none());
+        cache.put("eth_getBlockByNumber", &params, &Some(block.clone()));
+        let cached: Option<Block<TxHash>> = cache.get("eth_getBlockByNumber", &params);
+        assert_eq!(cached, Some(block));
+
+        // missing responses are not cached, and other chains or versions do not share entries
+        cache.put("eth_getBlockByNumber", &serde_json::json!([18]), &None::<Block<TxHash>>);
+        let missing: Option<Option<Block<TxHash>>> =
+            cache.get("eth_getBlockByNumber", &serde_json::json!([18]));
+        assert!(missing.is_none());
+        assert!(RpcCache::new(&root, 10)
+            .get::<Block<TxHash>>("eth_getBlockByNumber", &params)
+            .is_none());
+        assert!(cache.dir.starts_with(root.join(format!("cryo_{}", CRYO_VERSION))));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -1,7 +1,10 @@
 /// types and functions related to schemas
 use std::collections::HashMap;
 
-use crate::{err, CollectError, ColumnEncoding, Datatype, LogDecoder};
+use crate::{
+    err, reward_column, CallDecoder, CollectError, ColumnEncoding, Datatype, LogDecoder,
+    DEFAULT_FEE_PERCENTILES,
+};
 use indexmap::{IndexMap, IndexSet};
 use thiserror::Error;
 
@@ -31,6 +34,12 @@ pub struct Table {
     /// sort order for rows
     pub sort_columns: Option<Vec<String>>,
 
+    /// whether rows are sorted in descending rather than ascending order
+    pub sort_descending: bool,
+
+    /// whether to drop duplicate rows before writing
+    pub dedup: bool,
+
     /// representations to use for u256 columns
     pub u256_types: Vec<U256Type>,
 
@@ -39,6 +48,22 @@ pub struct Table {
 
     /// log decoder for table
     pub log_decoder: Option<LogDecoder>,
+
+    /// how to handle u256 values that do not fit in narrower u256 representations
+    pub overflow_policy: OverflowPolicy,
+
+    /// reward percentiles of fee history table, each with its own reward column
+    pub fee_percentiles: Vec<f64>,
+
+    /// function decoders of eth_calls table, each with columns of its decoded outputs
+    pub call_decoders: Vec<CallDecoder>,
+
+    /// types that columns are cast to before writing, keyed by name of output column
+    pub column_type_overrides: IndexMap<String, ColumnType>,
+
+    /// output columns of u256 representations to leave out, e.g. value_f64, while the other
+    /// representations of their column are kept
+    pub excluded_u256_columns: Vec<String>,
 }
 
 impl Table {
@@ -56,6 +81,56 @@ impl Table {
     pub fn columns(&self) -> Vec<&str> {
         self.columns.keys().map(|x| x.as_str()).collect()
     }
+
+    /// set reward percentiles, replacing the reward columns of the previous percentiles
+    ///
+    /// reward columns are placed before chain_id, which is the last column of each dataframe
+    pub fn set_fee_percentiles(&mut self, percentiles: &[f64]) {
+        for percentile in self.fee_percentiles.iter() {
+            self.columns.shift_remove(&reward_column(*percentile));
+        }
+        let chain_id = self.columns.shift_remove("chain_id");
+        for percentile in percentiles.iter() {
+            self.columns.insert(reward_column(*percentile), ColumnType::UInt64);
+        }
+        if let Some(chain_id) = chain_id {
+            self.columns.insert("chain_id".to_string(), chain_id);
+        }
+        self.fee_percentiles = percentiles.to_vec();
+    }
+
+    /// set function decoders, replacing the output columns of the previous decoders
+    ///
+    /// output columns are placed before chain_id, which is the last column of each dataframe
+    pub fn set_call_decoders(&mut self, decoders: &[CallDecoder]) {
+        for decoder in self.call_decoders.iter() {
+            for (column, _) in decoder.output_columns() {
+                self.columns.shift_remove(&column);
+            }
+        }
+        let chain_id = self.columns.shift_remove("chain_id");
+        for decoder in decoders.iter() {
+            for (column, mut column_type) in decoder.output_columns() {
+                if (self.binary_type == ColumnEncoding::Hex) & (column_type == ColumnType::Binary) {
+                    column_type = ColumnType::Hex;
+                }
+                self.columns.insert(column, column_type);
+            }
+        }
+        if let Some(chain_id) = chain_id {
+            self.columns.insert("chain_id".to_string(), chain_id);
+        }
+        self.call_decoders = decoders.to_vec();
+    }
+
+    /// get output columns of the representations of a u256 column, with their representation
+    pub fn u256_output_columns(&self, column: &str) -> Vec<(String, U256Type)> {
+        self.u256_types
+            .iter()
+            .map(|u256_type| (column.to_string() + u256_type.suffix().as_str(), u256_type.clone()))
+            .filter(|(name, _)| !self.excluded_u256_columns.contains(name))
+            .collect()
+    }
 }
 
 /// representation of a U256 datum
@@ -63,7 +138,7 @@ impl Table {
 pub enum U256Type {
     /// Binary representation
     Binary,
-    /// String representation
+    /// Decimal string representation, exact for any value
     String,
     /// F32 representation
     F32,
@@ -75,6 +150,8 @@ pub enum U256Type {
     U64,
     /// Decimal128 representation
     Decimal128,
+    /// Hex string representation, e.g. 0x1a
+    Hex,
 }
 
 impl U256Type {
@@ -88,6 +165,7 @@ impl U256Type {
             U256Type::U32 => ColumnType::UInt32,
             U256Type::U64 => ColumnType::UInt64,
             U256Type::Decimal128 => ColumnType::Decimal128,
+            U256Type::Hex => ColumnType::Hex,
         }
     }
 
@@ -101,10 +179,25 @@ impl U256Type {
             U256Type::U32 => "_u32".to_string(),
             U256Type::U64 => "_u64".to_string(),
             U256Type::Decimal128 => "_d128".to_string(),
+            U256Type::Hex => "_hex".to_string(),
         }
     }
 }
 
+/// how to handle values that overflow a narrower numeric representation
+///
+/// applies when converting u256 and i256 values to `u32`, `u64`, or `f32` columns
+#[derive(Hash, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OverflowPolicy {
+    /// Fail the chunk with an error
+    #[default]
+    Error,
+    /// Clamp to the largest (or smallest) representable value
+    Saturate,
+    /// Store a null value
+    Null,
+}
+
 /// datatype of column
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ColumnType {
@@ -194,14 +287,24 @@ impl Datatype {
             columns.insert((*column.clone()).to_string(), *ctype);
         }
 
-        let schema = Table {
+        let mut schema = Table {
             datatype: *self,
             sort_columns: sort,
+            sort_descending: false,
+            dedup: false,
             columns,
             u256_types: u256_types.to_owned(),
             binary_type: binary_column_format.clone(),
             log_decoder,
+            overflow_policy: OverflowPolicy::default(),
+            fee_percentiles: Vec::new(),
+            call_decoders: Vec::new(),
+            column_type_overrides: IndexMap::new(),
+            excluded_u256_columns: Vec::new(),
         };
+        if *self == Datatype::FeeHistory {
+            schema.set_fee_percentiles(&DEFAULT_FEE_PERCENTILES);
+        }
         Ok(schema)
     }
 }
@@ -377,4 +480,19 @@ mod tests {
         assert_eq!(7, table.columns().len());
         assert_eq!(["chain_id", "receipts_root"], table.columns()[5..7]);
     }
+
+    #[test]
+    fn test_set_call_decoders() {
+        let mut table = Datatype::EthCalls
+            .table_schema(&get_u256_types(), &ColumnEncoding::Hex, &None, &None, &None, None, None)
+            .unwrap();
+        let decoder = CallDecoder::new("owner()(address)".to_string()).unwrap();
+        table.set_call_decoders(&[decoder]);
+        let n_columns = table.columns().len();
+        assert_eq!(["owner", "chain_id"], table.columns()[n_columns - 2..]);
+        assert_eq!(table.column_type("owner"), Some(ColumnType::Hex));
+        table.set_call_decoders(&[]);
+        assert!(!table.has_column("owner"));
+        assert_eq!(n_columns - 1, table.columns().len());
+    }
 }
@@ -1,4 +1,7 @@
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
 
 use ethers::prelude::*;
 use governor::{
@@ -10,8 +13,13 @@ use tokio::{
     sync::{AcquireError, Semaphore, SemaphorePermit},
     task,
 };
+use tokio_util::sync::CancellationToken;
 
-use crate::CollectError;
+use crate::{
+    retries::{is_permanent_error, is_response_too_large, is_unsupported_method},
+    BatchHttp, BlockSource, CollectError, Datatype, HttpOptions, JsonRpcBlockSource, LogWindow,
+    RequestStats, RetryPolicy, RpcCache,
+};
 
 /// RateLimiter based on governor crate
 pub type RateLimiter = governor::RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>;
@@ -19,15 +27,21 @@ pub type RateLimiter = governor::RateLimiter<NotKeyed, InMemoryState, DefaultClo
 /// Options for fetching data from node
 #[derive(Clone, Debug)]
 pub struct Source {
-    /// provider
-    pub provider: ProviderWrapper,
+    /// providers of rpc endpoints
+    pub provider: RpcPool,
     /// chain_id of network
     pub chain_id: u64,
     /// number of blocks per log request
     pub inner_request_size: u64,
+    /// adaptive number of blocks per log request, if log requests are not of fixed size
+    pub log_window: Option<LogWindow>,
+    /// source of blocks and transactions, instead of json-rpc, such as a firehose endpoint
+    pub block_source: Option<Arc<dyn BlockSource>>,
+    /// number of receipts requested at a time when falling back to `eth_getTransactionReceipt`
+    pub receipt_request_size: Option<u64>,
     /// Maximum chunks collected concurrently
     pub max_concurrent_chunks: Option<u64>,
-    /// Rpc Url
+    /// Rpc Url, or comma separated urls of each endpoint of pool
     pub rpc_url: String,
     /// semaphore for controlling concurrency
     pub semaphore: Arc<Option<Semaphore>>,
@@ -35,6 +49,14 @@ pub struct Source {
     pub rate_limiter: Arc<Option<RateLimiter>>,
     /// Labels (these are non-functional)
     pub labels: SourceLabels,
+    /// token for cooperatively cancelling requests
+    pub cancel: Option<CancellationToken>,
+    /// policy for retrying failed requests
+    pub retry_policy: RetryPolicy,
+    /// on-disk cache of rpc responses
+    pub cache: Option<RpcCache>,
+    /// counts and latencies of rpc requests of each method
+    pub request_stats: RequestStats,
 }
 
 /// A non-generic wrapper over different provider types for use as a trait object
@@ -44,6 +66,8 @@ pub enum ProviderWrapper {
     MockProvider(Arc<Provider<MockProvider>>),
     /// http client
     RetryClientHttp(Arc<Provider<RetryClient<Http>>>),
+    /// http client that sends requests as json-rpc batches
+    BatchHttp(Arc<Provider<BatchHttp>>),
     /// websocket client
     WsClient(Arc<Provider<Ws>>),
     /// ipc client
@@ -62,6 +86,12 @@ impl From<Provider<RetryClient<Http>>> for ProviderWrapper {
     }
 }
 
+impl From<Provider<BatchHttp>> for ProviderWrapper {
+    fn from(value: Provider<BatchHttp>) -> ProviderWrapper {
+        ProviderWrapper::BatchHttp(Arc::new(value))
+    }
+}
+
 impl From<Provider<Ws>> for ProviderWrapper {
     fn from(value: Provider<Ws>) -> ProviderWrapper {
         ProviderWrapper::WsClient(Arc::new(value))
@@ -74,13 +104,74 @@ impl From<Provider<Ipc>> for ProviderWrapper {
     }
 }
 
-/// extract the provider from a source and run specified method
+/// providers of one or more rpc endpoints, handed out in round-robin order
+///
+/// clones share the rotation, so consecutive requests of all tasks are spread across endpoints
+#[derive(Clone, Debug)]
+pub struct RpcPool {
+    providers: Arc<Vec<ProviderWrapper>>,
+    next: Arc<AtomicUsize>,
+}
+
+impl RpcPool {
+    /// create pool from a provider and providers of any additional endpoints
+    pub fn new(provider: ProviderWrapper, others: Vec<ProviderWrapper>) -> RpcPool {
+        let mut providers = vec![provider];
+        providers.extend(others);
+        RpcPool { providers: Arc::new(providers), next: Arc::new(AtomicUsize::new(0)) }
+    }
+
+    /// provider to send the next request to
+    pub fn next_provider(&self) -> &ProviderWrapper {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.providers.len();
+        &self.providers[index]
+    }
+}
+
+impl From<ProviderWrapper> for RpcPool {
+    fn from(value: ProviderWrapper) -> RpcPool {
+        RpcPool::new(value, Vec::new())
+    }
+}
+
+impl From<Provider<MockProvider>> for RpcPool {
+    fn from(value: Provider<MockProvider>) -> RpcPool {
+        ProviderWrapper::from(value).into()
+    }
+}
+
+impl From<Provider<RetryClient<Http>>> for RpcPool {
+    fn from(value: Provider<RetryClient<Http>>) -> RpcPool {
+        ProviderWrapper::from(value).into()
+    }
+}
+
+impl From<Provider<BatchHttp>> for RpcPool {
+    fn from(value: Provider<BatchHttp>) -> RpcPool {
+        ProviderWrapper::from(value).into()
+    }
+}
+
+impl From<Provider<Ws>> for RpcPool {
+    fn from(value: Provider<Ws>) -> RpcPool {
+        ProviderWrapper::from(value).into()
+    }
+}
+
+impl From<Provider<Ipc>> for RpcPool {
+    fn from(value: Provider<Ipc>) -> RpcPool {
+        ProviderWrapper::from(value).into()
+    }
+}
+
+/// extract the next provider of a source's pool and run specified method
 #[macro_export]
 macro_rules! source_provider {
     ($source:expr, $method:ident($($arg:expr),*)) => {
-        match &$source.provider {
+        match $source.provider.next_provider() {
             ProviderWrapper::MockProvider(provider) => provider.$method($($arg),*),
             ProviderWrapper::RetryClientHttp(provider) => provider.$method($($arg),*),
+            ProviderWrapper::BatchHttp(provider) => provider.$method($($arg),*),
             ProviderWrapper::WsClient(provider) => provider.$method($($arg),*),
             ProviderWrapper::IpcClient(provider) => provider.$method($($arg),*),
         }
@@ -96,8 +187,10 @@ impl Source {
     ) -> Result<Vec<TransactionReceipt>> {
         let block_number =
             block.number.ok_or(CollectError::CollectError("no block number".to_string()))?.as_u64();
-        if let Ok(receipts) = self.get_block_receipts(block_number).await {
-            return Ok(receipts);
+        if let Some(receipts) = self.try_block_receipts(block_number).await? {
+            if receipts.len() == block.transactions.len() {
+                return Ok(receipts)
+            }
         }
 
         self.get_tx_receipts(&block.transactions).await
@@ -108,25 +201,87 @@ impl Source {
         &self,
         transactions: &Vec<Transaction>,
     ) -> Result<Vec<TransactionReceipt>> {
-        let mut tasks = Vec::new();
-        for tx in transactions {
-            let tx_hash = tx.hash;
-            let source = self.clone();
-            let task = task::spawn(async move {
-                match source.get_transaction_receipt(tx_hash).await? {
-                    Some(receipt) => Ok(receipt),
-                    None => {
-                        Err(CollectError::CollectError("could not find tx receipt".to_string()))
+        let tx_hashes: Vec<TxHash> = transactions.iter().map(|tx| tx.hash).collect();
+        self.get_tx_receipts_by_hash(&tx_hashes).await
+    }
+
+    /// Returns all receipts for a block, given the block's transaction hashes.
+    /// Tries to use `eth_getBlockReceipts` first, and falls back to `eth_getTransactionReceipt`
+    pub async fn get_tx_receipts_in_block_by_hash(
+        &self,
+        block_number: u64,
+        tx_hashes: &[TxHash],
+    ) -> Result<Vec<TransactionReceipt>> {
+        if let Some(receipts) = self.try_block_receipts(block_number).await? {
+            if receipts.len() == tx_hashes.len() {
+                return Ok(receipts)
+            }
+        }
+
+        self.get_tx_receipts_by_hash(tx_hashes).await
+    }
+
+    /// Returns all receipts for a block, given only the block number.
+    /// Tries to use `eth_getBlockReceipts` first, and only fetches the block's transaction hashes
+    /// if it needs to fall back to `eth_getTransactionReceipt`
+    pub async fn get_tx_receipts_in_block_number(
+        &self,
+        block_number: u64,
+    ) -> Result<Vec<TransactionReceipt>> {
+        if let Some(receipts) = self.try_block_receipts(block_number).await? {
+            return Ok(receipts)
+        }
+        let block = self
+            .get_block(block_number)
+            .await?
+            .ok_or(CollectError::CollectError("block not found".to_string()))?;
+        self.get_tx_receipts_by_hash(&block.transactions).await
+    }
+
+    /// Returns receipts of block using `eth_getBlockReceipts`, or None if the node does not
+    /// support the method. Other errors are returned rather than triggering a fallback.
+    async fn try_block_receipts(
+        &self,
+        block_number: u64,
+    ) -> Result<Option<Vec<TransactionReceipt>>> {
+        match self.get_block_receipts(block_number).await {
+            Ok(receipts) => Ok(Some(receipts)),
+            Err(CollectError::ProviderError(e)) if is_permanent_error(&e) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns receipts for transaction hashes using `eth_getTransactionReceipt`
+    /// requests are issued in groups of `receipt_request_size` receipts at a time
+    pub async fn get_tx_receipts_by_hash(
+        &self,
+        tx_hashes: &[TxHash],
+    ) -> Result<Vec<TransactionReceipt>> {
+        let group_size = match self.receipt_request_size {
+            Some(size) if size > 0 => size as usize,
+            _ => std::cmp::max(tx_hashes.len(), 1),
+        };
+        let mut receipts = Vec::with_capacity(tx_hashes.len());
+        for group in tx_hashes.chunks(group_size) {
+            let mut tasks = Vec::new();
+            for tx_hash in group.iter() {
+                let tx_hash = *tx_hash;
+                let source = self.clone();
+                let task = task::spawn(async move {
+                    match source.get_transaction_receipt(tx_hash).await? {
+                        Some(receipt) => Ok(receipt),
+                        None => {
+                            Err(CollectError::CollectError("could not find tx receipt".to_string()))
+                        }
                     }
+                });
+                tasks.push(task);
+            }
+            for task in tasks {
+                match task.await {
+                    Ok(receipt) => receipts.push(receipt?),
+                    Err(e) => return Err(CollectError::TaskFailed(e)),
                 }
-            });
-            tasks.push(task);
-        }
-        let mut receipts = Vec::new();
-        for task in tasks {
-            match task.await {
-                Ok(receipt) => receipts.push(receipt?),
-                Err(e) => return Err(CollectError::TaskFailed(e)),
             }
         }
 
@@ -143,34 +298,31 @@ const DEFAULT_MAX_CONCURRENT_REQUESTS: u64 = 100;
 /// builder
 impl Source {
     /// initialize source
+    ///
+    /// the transport is chosen by the scheme of the url: http(s), ws(s), or a path ending in .ipc
     pub async fn init(rpc_url: Option<String>) -> Result<Source> {
-        let rpc_url = parse_rpc_url(rpc_url);
-        let provider = Provider::<RetryClient<Http>>::new_client(
-            &rpc_url,
-            DEFAULT_MAX_RETRIES,
-            DEFAULT_INTIAL_BACKOFF,
-        )
-        .map_err(|_| CollectError::RPCError("could not connect to provider".to_string()))?;
-        let chain_id = provider
-            .get_chainid()
-            .await
-            .map_err(|_| CollectError::RPCError("could not get chain_id".to_string()))?
-            .as_u64();
+        let rpc_url = parse_rpc_url(rpc_url)?;
+        let provider = connect_provider(&rpc_url).await?;
+        let chain_id = match &provider {
+            ProviderWrapper::MockProvider(provider) => provider.get_chainid().await,
+            ProviderWrapper::RetryClientHttp(provider) => provider.get_chainid().await,
+            ProviderWrapper::BatchHttp(provider) => provider.get_chainid().await,
+            ProviderWrapper::WsClient(provider) => provider.get_chainid().await,
+            ProviderWrapper::IpcClient(provider) => provider.get_chainid().await,
+        }
+        .map_err(|e| connection_error(&rpc_url, e))?
+        .as_u64();
 
         let rate_limiter = None;
         let semaphore = None;
 
-        let provider = Provider::<RetryClient<Http>>::new_client(
-            &rpc_url,
-            DEFAULT_MAX_RETRIES,
-            DEFAULT_INTIAL_BACKOFF,
-        )
-        .map_err(|_| CollectError::RPCError("could not connect to provider".to_string()))?;
-
         let source = Source {
-            provider: ProviderWrapper::RetryClientHttp(Arc::new(provider)),
+            provider: provider.into(),
             chain_id,
             inner_request_size: DEFAULT_INNER_REQUEST_SIZE,
+            log_window: None,
+            block_source: None,
+            receipt_request_size: None,
             max_concurrent_chunks: Some(DEFAULT_MAX_CONCURRENT_CHUNKS),
             rpc_url,
             labels: SourceLabels {
@@ -178,35 +330,118 @@ impl Source {
                 max_requests_per_second: Some(0),
                 max_retries: Some(DEFAULT_MAX_RETRIES),
                 initial_backoff: Some(DEFAULT_INTIAL_BACKOFF),
+                http_options: HttpOptions::default(),
             },
             rate_limiter: rate_limiter.into(),
             semaphore: semaphore.into(),
+            cancel: None,
+            retry_policy: RetryPolicy::default(),
+            cache: None,
+            request_stats: Default::default(),
         };
 
         Ok(source)
     }
 
+    /// source backed by a mock provider, returned with the mock that responses are pushed to
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn mocked() -> (Source, MockProvider) {
+        let (provider, mock) = Provider::mocked();
+        let source = Source {
+            provider: provider.into(),
+            chain_id: 1,
+            inner_request_size: 1,
+            log_window: None,
+            block_source: None,
+            receipt_request_size: None,
+            max_concurrent_chunks: None,
+            rpc_url: "".to_string(),
+            semaphore: Arc::new(None),
+            rate_limiter: Arc::new(None),
+            labels: SourceLabels::default(),
+            cancel: None,
+            retry_policy: RetryPolicy::default(),
+            cache: None,
+            request_stats: Default::default(),
+        };
+        (source, mock)
+    }
+
+    /// cancel requests of source when token is cancelled
+    pub fn with_cancellation(self, cancel: CancellationToken) -> Source {
+        Source { cancel: Some(cancel), ..self }
+    }
+
+    /// whether cancellation token of source has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().map(|cancel| cancel.is_cancelled()).unwrap_or(false)
+    }
+
+    /// wait until cancellation token of source is cancelled, or forever if there is no token
+    pub async fn cancelled(&self) {
+        match &self.cancel {
+            Some(cancel) => cancel.cancelled().await,
+            None => std::future::pending().await,
+        }
+    }
+
     // /// set rate limit
     // pub fn rate_limit(mut self, _requests_per_second: u64) -> Source {
     //     todo!();
     // }
 }
 
-fn parse_rpc_url(rpc_url: Option<String>) -> String {
-    let mut url = match rpc_url {
-        Some(url) => url.clone(),
-        _ => match std::env::var("ETH_RPC_URL") {
-            Ok(url) => url,
-            Err(_e) => {
-                println!("must provide --rpc or set ETH_RPC_URL");
-                std::process::exit(0);
-            }
-        },
+fn parse_rpc_url(rpc_url: Option<String>) -> Result<String> {
+    let url = match rpc_url {
+        Some(url) => url,
+        None => std::env::var("ETH_RPC_URL").map_err(|_| {
+            CollectError::RPCError("must provide rpc url or set ETH_RPC_URL".to_string())
+        })?,
     };
-    if !url.starts_with("http") {
-        url = "http://".to_string() + url.as_str();
+    if url.starts_with("http") || url.starts_with("ws") || url.ends_with(".ipc") {
+        Ok(url)
+    } else {
+        Ok("http://".to_string() + url.as_str())
+    }
+}
+
+/// connect to rpc url using the transport of its scheme
+async fn connect_provider(rpc_url: &str) -> Result<ProviderWrapper> {
+    if rpc_url.starts_with("ws://") || rpc_url.starts_with("wss://") {
+        let ws = Ws::connect_with_reconnects(rpc_url, DEFAULT_MAX_RETRIES as usize)
+            .await
+            .map_err(|e| connection_error(rpc_url, e))?;
+        Ok(Provider::new(ws).into())
+    } else if rpc_url.ends_with(".ipc") {
+        // connecting to a missing socket path can hang, so check for it first
+        if !std::path::Path::new(rpc_url).exists() {
+            return Err(CollectError::RPCError(format!("ipc socket does not exist: {}", rpc_url)))
+        }
+        let provider: Provider<Ipc> =
+            Provider::connect_ipc(rpc_url).await.map_err(|e| connection_error(rpc_url, e))?;
+        Ok(provider.into())
+    } else {
+        let provider = Provider::<RetryClient<Http>>::new_client(
+            rpc_url,
+            DEFAULT_MAX_RETRIES,
+            DEFAULT_INTIAL_BACKOFF,
+        )
+        .map_err(|e| connection_error(rpc_url, e))?;
+        Ok(provider.into())
+    }
+}
+
+/// error for failed connection, naming the transport that was attempted
+fn connection_error(rpc_url: &str, e: impl std::fmt::Display) -> CollectError {
+    let transport = if rpc_url.starts_with("ws") {
+        "websocket"
+    } else if rpc_url.ends_with(".ipc") {
+        "ipc"
+    } else {
+        "http"
     };
-    url
+    let rpc_url = crate::mask_rpc_url(rpc_url);
+    CollectError::RPCError(format!("could not connect to {} rpc at {}: {}", transport, rpc_url, e))
 }
 
 // builder
@@ -245,6 +480,8 @@ pub struct SourceLabels {
     pub max_retries: Option<u32>,
     /// Initial backoff
     pub initial_backoff: Option<u64>,
+    /// Timeouts and connection reuse of http clients
+    pub http_options: HttpOptions,
 }
 
 /// Wrapper over `Provider<P>` that adds concurrency and rate limiting controls
@@ -262,10 +499,94 @@ type Result<T> = ::core::result::Result<T, CollectError>;
 
 // impl<P: JsonRpcClient> Fetcher<P> {
 impl Source {
+    /// number of blocks per request of a datatype that requests block ranges
+    ///
+    /// datatypes that request logs use block ranges of the largest log window, which are split
+    /// into adaptive windows by `get_logs`
+    pub fn block_range_size(&self, datatype: &Datatype) -> u64 {
+        match (&self.log_window, datatype) {
+            (_, Datatype::FeeHistory) | (None, _) => self.inner_request_size,
+            (Some(window), _) => window.max_size,
+        }
+    }
+
+    /// source of the blocks and transactions of the blocks and transactions datatypes
+    pub fn block_source(&self) -> &dyn BlockSource {
+        match &self.block_source {
+            Some(block_source) => block_source.as_ref(),
+            None => &JsonRpcBlockSource,
+        }
+    }
+
     /// Returns an array (possibly empty) of logs that match the filter
+    ///
+    /// block ranges are requested in adaptive windows if a log window is set
     pub async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(source_provider!(self, get_logs(filter)).await)
+        match (&self.log_window, filter.block_option) {
+            (
+                Some(window),
+                FilterBlockOption::Range {
+                    from_block: Some(BlockNumber::Number(from_block)),
+                    to_block: Some(BlockNumber::Number(to_block)),
+                },
+            ) => {
+                self.get_logs_in_windows(filter, window, from_block.as_u64(), to_block.as_u64())
+                    .await
+            }
+            _ => self.get_logs_request(filter).await,
+        }
+    }
+
+    /// Returns logs of block range in consecutive windows, each starting after the last block of
+    /// the previous window. windows that are too large are requested again at half their size
+    async fn get_logs_in_windows(
+        &self,
+        filter: &Filter,
+        window: &LogWindow,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<Log>> {
+        let mut logs = Vec::new();
+        let mut start = from_block;
+        while start <= to_block {
+            let size = window.size().min(to_block - start + 1);
+            let end = start + size - 1;
+            let request = filter.clone().from_block(start).to_block(end);
+            match self.get_logs_request(&request).await {
+                Ok(response) if size > 1 && window.exceeds_max_rows(response.len()) => {
+                    window.shrink(size);
+                }
+                Ok(response) => {
+                    window.record_success(size);
+                    logs.extend(response);
+                    start = end + 1;
+                }
+                Err(CollectError::ProviderError(e)) if size > 1 && is_response_too_large(&e) => {
+                    window.shrink(size);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(logs)
+    }
+
+    /// Returns logs of filter using a single `eth_getLogs` request
+    async fn get_logs_request(&self, filter: &Filter) -> Result<Vec<Log>> {
+        let fixed = match filter.block_option {
+            FilterBlockOption::Range { from_block: Some(from), to_block: Some(to) } => {
+                is_fixed_block(&from) && is_fixed_block(&to)
+            }
+            FilterBlockOption::AtBlockHash(_) => true,
+            _ => false,
+        };
+        let params = fixed.then(|| serde_json::json!([filter]));
+        let to_block = filter.get_to_block().map(|block| block.as_u64());
+        let block = |logs: &Vec<Log>| {
+            to_block.or_else(|| {
+                logs.iter().filter_map(|log| log.block_number).max().map(|x| x.as_u64())
+            })
+        };
+        self.cached("eth_getLogs", params, block, || source_provider!(self, get_logs(filter))).await
     }
 
     /// Replays all transactions in a block returning the requested traces for each transaction
@@ -274,10 +595,15 @@ impl Source {
         block: BlockNumber,
         trace_types: Vec<TraceType>,
     ) -> Result<Vec<BlockTrace>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(
-            source_provider!(self, trace_replay_block_transactions(block, trace_types)).await,
+        let params = is_fixed_block(&block).then(|| serde_json::json!([block, trace_types]));
+        let block_number = block.as_number().map(|block| block.as_u64());
+        self.cached(
+            "trace_replayBlockTransactions",
+            params,
+            |_| block_number,
+            || source_provider!(self, trace_replay_block_transactions(block, trace_types.clone())),
         )
+        .await
     }
 
     /// Get state diff traces of block
@@ -327,8 +653,17 @@ impl Source {
         tx_hash: TxHash,
         trace_types: Vec<TraceType>,
     ) -> Result<BlockTrace> {
-        let _permit = self.permit_request().await;
-        Self::map_err(source_provider!(self, trace_replay_transaction(tx_hash, trace_types)).await)
+        let params = Some(serde_json::json!([tx_hash, trace_types]));
+        // replays do not include a block number, it is looked up only when the replay is cached
+        let block_number = match &self.cache {
+            Some(_) => self.get_transaction(tx_hash).await?.and_then(|tx| tx.block_number),
+            None => None,
+        };
+        let block = |_: &BlockTrace| block_number.map(|block| block.as_u64());
+        self.cached("trace_replayTransaction", params, block, || {
+            source_provider!(self, trace_replay_transaction(tx_hash, trace_types.clone()))
+        })
+        .await
     }
 
     /// Get state diff traces of transaction
@@ -361,8 +696,15 @@ impl Source {
 
     /// Gets the transaction with transaction_hash
     pub async fn get_transaction(&self, tx_hash: TxHash) -> Result<Option<Transaction>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(source_provider!(self, get_transaction(tx_hash)).await)
+        let params = Some(serde_json::json!([tx_hash]));
+        // pending transactions have no block number, and are not cached
+        let block = |tx: &Option<Transaction>| {
+            tx.as_ref().and_then(|tx| tx.block_number).map(|block| block.as_u64())
+        };
+        self.cached("eth_getTransactionByHash", params, block, || {
+            source_provider!(self, get_transaction(tx_hash))
+        })
+        .await
     }
 
     /// Gets the transaction receipt with transaction_hash
@@ -370,49 +712,128 @@ impl Source {
         &self,
         tx_hash: TxHash,
     ) -> Result<Option<TransactionReceipt>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(source_provider!(self, get_transaction_receipt(tx_hash)).await)
+        let params = Some(serde_json::json!([tx_hash]));
+        let block = |receipt: &Option<TransactionReceipt>| {
+            receipt.as_ref().and_then(|receipt| receipt.block_number).map(|block| block.as_u64())
+        };
+        self.cached("eth_getTransactionReceipt", params, block, || {
+            source_provider!(self, get_transaction_receipt(tx_hash))
+        })
+        .await
     }
 
     /// Gets the block at `block_num` (transaction hashes only)
     pub async fn get_block(&self, block_num: u64) -> Result<Option<Block<TxHash>>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(source_provider!(self, get_block(block_num)).await)
+        let params = Some(serde_json::json!([block_num, false]));
+        self.cached(
+            "eth_getBlockByNumber",
+            params,
+            |_| Some(block_num),
+            || source_provider!(self, get_block(block_num)),
+        )
+        .await
     }
 
     /// Gets the block at `block_num` (transaction hashes only)
     pub async fn get_block_by_hash(&self, block_hash: H256) -> Result<Option<Block<TxHash>>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(source_provider!(self, get_block(BlockId::Hash(block_hash))).await)
+        let params = Some(serde_json::json!([block_hash, false]));
+        let block = |block: &Option<Block<TxHash>>| {
+            block.as_ref().and_then(|block| block.number).map(|block| block.as_u64())
+        };
+        self.cached("eth_getBlockByHash", params, block, || {
+            source_provider!(self, get_block(BlockId::Hash(block_hash)))
+        })
+        .await
+    }
+
+    /// Gets uncle `idx` of the block at `block_num`
+    pub async fn get_uncle(&self, block_num: u64, idx: u64) -> Result<Option<Block<TxHash>>> {
+        let params = Some(serde_json::json!([block_num, idx]));
+        self.cached(
+            "eth_getUncleByBlockNumberAndIndex",
+            params,
+            |_| Some(block_num),
+            || source_provider!(self, get_uncle(block_num, idx.into())),
+        )
+        .await
     }
 
     /// Gets the block at `block_num` (full transactions included)
     pub async fn get_block_with_txs(&self, block_num: u64) -> Result<Option<Block<Transaction>>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(source_provider!(self, get_block_with_txs(block_num)).await)
+        let params = Some(serde_json::json!([block_num, true]));
+        self.cached(
+            "eth_getBlockByNumber",
+            params,
+            |_| Some(block_num),
+            || source_provider!(self, get_block_with_txs(block_num)),
+        )
+        .await
     }
 
     /// Returns all receipts for a block.
     /// Note that this uses the `eth_getBlockReceipts` method which is not supported by all nodes.
-    /// Consider using `FetcherExt::get_tx_receipts_in_block` which takes a block, and falls back to
+    /// Consider using `Source::get_tx_receipts_in_block_number` which falls back to
     /// `eth_getTransactionReceipt` if `eth_getBlockReceipts` is not supported.
     pub async fn get_block_receipts(&self, block_num: u64) -> Result<Vec<TransactionReceipt>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(source_provider!(self, get_block_receipts(block_num)).await)
+        let params = Some(serde_json::json!([block_num]));
+        self.cached(
+            "eth_getBlockReceipts",
+            params,
+            |_| Some(block_num),
+            || source_provider!(self, get_block_receipts(block_num)),
+        )
+        .await
+    }
+
+    /// Returns base fees, gas used ratios, and priority fee percentiles of the `block_count`
+    /// blocks ending at `last_block`
+    pub async fn get_fee_history(
+        &self,
+        block_count: u64,
+        last_block: u64,
+        reward_percentiles: &[f64],
+    ) -> Result<FeeHistory> {
+        let params =
+            Some(serde_json::json!([U64::from(block_count), last_block, reward_percentiles]));
+        self.cached(
+            "eth_feeHistory",
+            params,
+            |_| Some(last_block),
+            || {
+                source_provider!(
+                    self,
+                    fee_history(
+                        block_count,
+                        BlockNumber::Number(last_block.into()),
+                        reward_percentiles
+                    )
+                )
+            },
+        )
+        .await
     }
 
     /// Returns traces created at given block
     pub async fn trace_block(&self, block_num: BlockNumber) -> Result<Vec<Trace>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(source_provider!(self, trace_block(block_num)).await)
+        let params = is_fixed_block(&block_num).then(|| serde_json::json!([block_num]));
+        let block = block_num.as_number().map(|block| block.as_u64());
+        self.cached(
+            "trace_block",
+            params,
+            |_| block,
+            || source_provider!(self, trace_block(block_num)),
+        )
+        .await
     }
 
     /// Returns all traces of a given transaction
     pub async fn trace_transaction(&self, tx_hash: TxHash) -> Result<Vec<Trace>> {
-        let _permit = self.permit_request().await;
-        source_provider!(self, trace_transaction(tx_hash))
-            .await
-            .map_err(CollectError::ProviderError)
+        let params = Some(serde_json::json!([tx_hash]));
+        let block = |traces: &Vec<Trace>| traces.first().map(|trace| trace.block_number);
+        self.cached("trace_transaction", params, block, || {
+            source_provider!(self, trace_transaction(tx_hash))
+        })
+        .await
     }
 
     /// Deprecated
@@ -421,11 +842,10 @@ impl Source {
         transaction: TransactionRequest,
         block_number: BlockNumber,
     ) -> Result<Bytes> {
-        let _permit = self.permit_request().await;
+        let _permit = self.permit_request().await?;
         let tx: ethers::core::types::transaction::eip2718::TypedTransaction = transaction.into();
-        source_provider!(self, call(&tx, Some(block_number.into())))
+        self.retry("eth_call", || source_provider!(self, call(&tx, Some(block_number.into()))))
             .await
-            .map_err(CollectError::ProviderError)
     }
 
     /// Returns traces for given call data
@@ -435,10 +855,14 @@ impl Source {
         trace_type: Vec<TraceType>,
         block_number: Option<BlockNumber>,
     ) -> Result<BlockTrace> {
-        let _permit = self.permit_request().await;
-        source_provider!(self, trace_call(transaction, trace_type, block_number))
-            .await
-            .map_err(CollectError::ProviderError)
+        let _permit = self.permit_request().await?;
+        self.retry("trace_call", || {
+            source_provider!(
+                self,
+                trace_call(transaction.clone(), trace_type.clone(), block_number)
+            )
+        })
+        .await
     }
 
     /// Get nonce of address
@@ -447,26 +871,44 @@ impl Source {
         address: H160,
         block_number: BlockNumber,
     ) -> Result<U256> {
-        let _permit = self.permit_request().await;
-        source_provider!(self, get_transaction_count(address, Some(block_number.into())))
-            .await
-            .map_err(CollectError::ProviderError)
+        let params =
+            is_fixed_block(&block_number).then(|| serde_json::json!([address, block_number]));
+        let block = block_number.as_number().map(|block| block.as_u64());
+        self.cached(
+            "eth_getTransactionCount",
+            params,
+            |_| block,
+            || source_provider!(self, get_transaction_count(address, Some(block_number.into()))),
+        )
+        .await
     }
 
     /// Get code at address
     pub async fn get_balance(&self, address: H160, block_number: BlockNumber) -> Result<U256> {
-        let _permit = self.permit_request().await;
-        source_provider!(self, get_balance(address, Some(block_number.into())))
-            .await
-            .map_err(CollectError::ProviderError)
+        let params =
+            is_fixed_block(&block_number).then(|| serde_json::json!([address, block_number]));
+        let block = block_number.as_number().map(|block| block.as_u64());
+        self.cached(
+            "eth_getBalance",
+            params,
+            |_| block,
+            || source_provider!(self, get_balance(address, Some(block_number.into()))),
+        )
+        .await
     }
 
     /// Get code at address
     pub async fn get_code(&self, address: H160, block_number: BlockNumber) -> Result<Bytes> {
-        let _permit = self.permit_request().await;
-        source_provider!(self, get_code(address, Some(block_number.into())))
-            .await
-            .map_err(CollectError::ProviderError)
+        let params =
+            is_fixed_block(&block_number).then(|| serde_json::json!([address, block_number]));
+        let block = block_number.as_number().map(|block| block.as_u64());
+        self.cached(
+            "eth_getCode",
+            params,
+            |_| block,
+            || source_provider!(self, get_code(address, Some(block_number.into()))),
+        )
+        .await
     }
 
     /// Get stored data at given location
@@ -476,15 +918,33 @@ impl Source {
         slot: H256,
         block_number: BlockNumber,
     ) -> Result<H256> {
-        let _permit = self.permit_request().await;
-        source_provider!(self, get_storage_at(address, slot, Some(block_number.into())))
-            .await
-            .map_err(CollectError::ProviderError)
+        let params =
+            is_fixed_block(&block_number).then(|| serde_json::json!([address, slot, block_number]));
+        let block = block_number.as_number().map(|block| block.as_u64());
+        self.cached(
+            "eth_getStorageAt",
+            params,
+            |_| block,
+            || source_provider!(self, get_storage_at(address, slot, Some(block_number.into()))),
+        )
+        .await
     }
 
     /// Get the block number
     pub async fn get_block_number(&self) -> Result<U64> {
-        Self::map_err(source_provider!(self, get_block_number()).await)
+        let _permit = self.permit_request().await?;
+        self.retry("eth_blockNumber", || source_provider!(self, get_block_number())).await
+    }
+
+    /// Gets the number of the block that a block tag such as `safe` or `finalized` refers to
+    pub async fn get_tagged_block_number(&self, tag: BlockNumber) -> Result<Option<U64>> {
+        let _permit = self.permit_request().await?;
+        let block = self
+            .retry("eth_getBlockByNumber", || {
+                source_provider!(self, get_block(BlockId::Number(tag)))
+            })
+            .await?;
+        Ok(block.and_then(|block| block.number))
     }
 
     // extra helpers below
@@ -520,11 +980,16 @@ impl Source {
             data: Some(call_data.into()),
             ..Default::default()
         };
-        let _permit = self.permit_request().await;
         let tx: ethers::core::types::transaction::eip2718::TypedTransaction = transaction.into();
-        source_provider!(self, call(&tx, Some(block_number.into())))
-            .await
-            .map_err(CollectError::ProviderError)
+        let params = is_fixed_block(&block_number).then(|| serde_json::json!([tx, block_number]));
+        let block = block_number.as_number().map(|block| block.as_u64());
+        self.cached(
+            "eth_call",
+            params,
+            |_| block,
+            || source_provider!(self, call(&tx, Some(block_number.into()))),
+        )
+        .await
     }
 
     /// Return output data of a contract call
@@ -540,10 +1005,14 @@ impl Source {
             data: Some(call_data.into()),
             ..Default::default()
         };
-        let _permit = self.permit_request().await;
-        source_provider!(self, trace_call(transaction, trace_type, block_number))
-            .await
-            .map_err(CollectError::ProviderError)
+        let _permit = self.permit_request().await?;
+        self.retry("trace_call", || {
+            source_provider!(
+                self,
+                trace_call(transaction.clone(), trace_type.clone(), block_number)
+            )
+        })
+        .await
     }
 
     /// get geth debug block traces
@@ -554,10 +1023,15 @@ impl Source {
         include_transaction_hashes: bool,
     ) -> Result<(Option<u32>, Vec<Option<Vec<u8>>>, Vec<GethTrace>)> {
         let traces = {
-            let _permit = self.permit_request().await;
-            source_provider!(self, debug_trace_block_by_number(Some(block_number.into()), options))
-                .await
-                .map_err(CollectError::ProviderError)?
+            let _permit = self.permit_request().await?;
+            self.retry("debug_traceBlockByNumber", || {
+                source_provider!(
+                    self,
+                    debug_trace_block_by_number(Some(block_number.into()), options.clone())
+                )
+            })
+            .await
+            .map_err(|e| require_debug_method(e, "debug_traceBlockByNumber"))?
         };
 
         let txs = if include_transaction_hashes {
@@ -725,7 +1199,7 @@ impl Source {
                 }
                 _ => {
                     println!("{:?}", trace);
-                    return Err(CollectError::CollectError("invalid trace result".to_string()));
+                    return Err(CollectError::CollectError("invalid trace result".to_string()))
                 }
             }
         }
@@ -742,10 +1216,12 @@ impl Source {
         let ethers_tx = H256::from_slice(&transaction_hash);
 
         let trace = {
-            let _permit = self.permit_request().await;
-            source_provider!(self, debug_trace_transaction(ethers_tx, options))
-                .await
-                .map_err(CollectError::ProviderError)?
+            let _permit = self.permit_request().await?;
+            self.retry("debug_traceTransaction", || {
+                source_provider!(self, debug_trace_transaction(ethers_tx, options.clone()))
+            })
+            .await
+            .map_err(|e| require_debug_method(e, "debug_traceTransaction"))?
         };
         let traces = vec![trace];
 
@@ -917,24 +1393,147 @@ impl Source {
         Ok((block, txs, diffs))
     }
 
-    async fn permit_request(
+    pub(crate) async fn permit_request(
         &self,
-    ) -> Option<::core::result::Result<SemaphorePermit<'_>, AcquireError>> {
+    ) -> Result<Option<::core::result::Result<SemaphorePermit<'_>, AcquireError>>> {
+        if self.is_cancelled() {
+            return Err(CollectError::Cancelled)
+        }
         let permit = match &*self.semaphore {
             Some(semaphore) => Some(semaphore.acquire().await),
             _ => None,
         };
+        if self.is_cancelled() {
+            return Err(CollectError::Cancelled)
+        }
+        Ok(permit)
+    }
+
+    /// wait until rate limiter allows another request to be sent
+    ///
+    /// the limiter is shared by all clones of source, so the cap applies to all requests in flight
+    async fn wait_for_rate_limit(&self) -> Result<()> {
         if let Some(limiter) = &*self.rate_limiter {
-            limiter.until_ready().await;
+            tokio::select! {
+                _ = self.cancelled() => return Err(CollectError::Cancelled),
+                _ = limiter.until_ready() => {}
+            }
         }
-        permit
+        Ok(())
     }
 
-    fn map_err<T>(res: ::core::result::Result<T, ProviderError>) -> Result<T> {
-        res.map_err(CollectError::ProviderError)
+    /// run request, retrying transient failures according to retry policy
+    ///
+    /// each attempt counts against the rate limit, and is recorded in request stats under method
+    pub(crate) async fn retry<T, F, Fut>(&self, method: &str, request: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = ::core::result::Result<T, ProviderError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            self.wait_for_rate_limit().await?;
+            let t_request = std::time::Instant::now();
+            let result = request().await;
+            self.request_stats.record(method, t_request.elapsed(), attempt > 0, result.is_err());
+            match result {
+                Ok(result) => return Ok(result),
+                Err(e) => {
+                    attempt += 1;
+                    if !self.retry_policy.should_retry(&e, attempt) {
+                        return Err(CollectError::ProviderError(e))
+                    }
+                    tokio::select! {
+                        _ = self.cancelled() => return Err(CollectError::Cancelled),
+                        _ = tokio::time::sleep(self.retry_policy.backoff(attempt)) => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// run request as in `retry`, reading and writing its response in the rpc cache if enabled
+    ///
+    /// params of None mark requests whose response can change, such as requests at a block tag,
+    /// which are never cached. responses are only written if the block that `block` gives for
+    /// them is final, so responses of recent blocks and pending transactions are not cached
+    async fn cached<T, F, Fut, B>(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+        block: B,
+        request: F,
+    ) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = ::core::result::Result<T, ProviderError>>,
+        B: Fn(&T) -> Option<u64>,
+    {
+        let cache = match (&self.cache, params) {
+            (Some(cache), Some(params)) => Some((cache, params)),
+            _ => None,
+        };
+        if let Some((cache, params)) = &cache {
+            if let Some(response) = cache.get(method, params) {
+                return Ok(response)
+            }
+        }
+        let response = {
+            let _permit = self.permit_request().await?;
+            self.retry(method, request).await?
+        };
+        if let Some((cache, params)) = &cache {
+            if self.is_final_block(cache, block(&response)).await {
+                cache.put(method, params, &response);
+            }
+        }
+        Ok(response)
+    }
+
+    /// whether block is final, meaning at or below the finalized block of the provider, or at
+    /// least the reorg buffer of the cache behind the latest block if there is no finalized block
+    async fn is_final_block(&self, cache: &RpcCache, block: Option<u64>) -> bool {
+        let block = match block {
+            Some(block) => block,
+            None => return false,
+        };
+        let final_block = cache
+            .final_block
+            .get_or_init(|| async {
+                match self.get_tagged_block_number(BlockNumber::Finalized).await {
+                    Ok(Some(finalized)) => Some(finalized.as_u64()),
+                    _ => self
+                        .get_block_number()
+                        .await
+                        .ok()
+                        .and_then(|latest| latest.as_u64().checked_sub(cache.reorg_buffer)),
+                }
+            })
+            .await;
+        final_block.map_or(false, |final_block| block <= final_block)
     }
 }
 
+/// replace error of unsupported debug method with an explanation of what the method is needed for
+fn require_debug_method(error: CollectError, method: &str) -> CollectError {
+    match error {
+        CollectError::ProviderError(e) if is_unsupported_method(&e) => {
+            CollectError::RPCError(format!(
+                "provider does not support {}, which geth datatypes such as geth_storage_diffs \
+                require. use a node with the debug namespace enabled: {}",
+                method, e
+            ))
+        }
+        error => error,
+    }
+}
+
+/// whether block number refers to a fixed block, so that responses at it can be cached
+fn is_fixed_block(block_number: &BlockNumber) -> bool {
+    matches!(block_number, BlockNumber::Number(_))
+}
+
 use crate::err;
 use std::collections::BTreeMap;
 
@@ -948,3 +1547,137 @@ fn parse_geth_diff_object(
 
     Ok(DiffMode { pre, post })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::providers::MockResponse;
+    use governor::Quota;
+    use std::{num::NonZeroU32, time::Instant};
+
+    #[tokio::test]
+    async fn test_rpc_pool_round_robin() {
+        let (first, first_mock) = Provider::mocked();
+        let (second, second_mock) = Provider::mocked();
+        for _ in 0..2 {
+            first_mock.push(U64::from(1)).unwrap();
+            second_mock.push(U64::from(2)).unwrap();
+        }
+        let pool = RpcPool::new(first.into(), vec![second.into()]);
+        let source = Source { provider: pool, ..Source::mocked().0 };
+
+        // clones share the rotation, as concurrent chunks do
+        let mut block_numbers = Vec::new();
+        for _ in 0..4 {
+            block_numbers.push(source.clone().get_block_number().await.unwrap().as_u64());
+        }
+        assert_eq!(block_numbers, vec![1, 2, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_shared_across_tasks() {
+        let (source, mock) = Source::mocked();
+        let n_requests = 6;
+        for _ in 0..n_requests {
+            mock.push(U64::from(1)).unwrap();
+        }
+        let quota = Quota::per_second(NonZeroU32::new(20).unwrap())
+            .allow_burst(NonZeroU32::new(1).unwrap());
+        let source = Source { rate_limiter: Arc::new(Some(RateLimiter::direct(quota))), ..source };
+
+        // requests are issued concurrently, as they would be by concurrent chunks
+        let start = Instant::now();
+        let tasks: Vec<_> = (0..n_requests)
+            .map(|_| {
+                let source = source.clone();
+                task::spawn(async move {
+                    source.get_block_number().await.unwrap();
+                    Instant::now()
+                })
+            })
+            .collect();
+        let mut timestamps = Vec::new();
+        for task in tasks {
+            timestamps.push(task.await.unwrap());
+        }
+        timestamps.sort();
+
+        // 20 requests per second allows one request every 50ms
+        for pair in timestamps.windows(2) {
+            assert!(pair[1] - pair[0] >= std::time::Duration::from_millis(40));
+        }
+        assert!(start.elapsed() >= std::time::Duration::from_millis(240));
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_in_adaptive_windows() {
+        let (source, mock) = Source::mocked();
+        let message = "query returned more than 10000 results".to_string();
+        let too_large = JsonRpcError { code: -32005, message, data: None };
+
+        // responses are popped from the back, so they are pushed in reverse order of requests
+        mock.push(Vec::<Log>::new()).unwrap();
+        mock.push(vec![Log::default(); 2]).unwrap();
+        mock.push(Vec::<Log>::new()).unwrap();
+        mock.push(vec![Log::default(); 3]).unwrap();
+        mock.push(vec![Log::default()]).unwrap();
+        mock.push_response(MockResponse::Error(too_large));
+        let source = Source {
+            inner_request_size: 4,
+            log_window: Some(LogWindow::new(4, 8, Some(2))),
+            ..source
+        };
+
+        let logs = source.get_logs(&Filter::new().from_block(0).to_block(7)).await.unwrap();
+        assert_eq!(logs.len(), 3);
+
+        // windows that are too large are requested again from the same block, so each block is
+        // covered by exactly one successful request
+        for (from_block, to_block) in [(0, 3), (0, 1), (2, 4), (2, 2), (3, 4), (5, 7)] {
+            let request = Filter::new().from_block(from_block).to_block(to_block);
+            mock.assert_request("eth_getLogs", [request]).unwrap();
+        }
+        let window = source.log_window.unwrap();
+        assert_eq!(window.used_sizes(), vec![(1, 1), (2, 2), (3, 1)]);
+        assert_eq!(window.size(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_missing_ipc_socket() {
+        let rpc_url = "/nonexistent/cryo/geth.ipc".to_string();
+        match Source::init(Some(rpc_url)).await {
+            Err(CollectError::RPCError(message)) => assert!(message.contains("does not exist")),
+            _ => panic!("expected error for missing ipc socket"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_stores_only_final_blocks() {
+        let (source, mock) = Source::mocked();
+        let root = std::env::temp_dir().join(format!("cryo_final_cache_{}", std::process::id()));
+        let cache = RpcCache::new(&root, 1);
+        let source = Source { cache: Some(cache.clone()), ..source };
+
+        // mocked responses are returned last in first out
+        let block =
+            |number: u64| Block::<TxHash> { number: Some(number.into()), ..Default::default() };
+        mock.push(block(80)).unwrap();
+        mock.push(Transaction { block_number: None, ..Default::default() }).unwrap();
+        mock.push(block(90)).unwrap();
+        mock.push(block(100)).unwrap();
+
+        // block above finalized block is not cached, blocks at or below it are
+        source.get_block(100).await.unwrap();
+        source.get_transaction(TxHash::zero()).await.unwrap();
+        source.get_block(80).await.unwrap();
+        let get = |params| cache.get::<Block<TxHash>>("eth_getBlockByNumber", &params);
+        assert!(get(serde_json::json!([100, false])).is_none());
+        assert_eq!(get(serde_json::json!([80, false])), Some(block(80)));
+
+        // pending transactions are not cached
+        let params = serde_json::json!([TxHash::zero()]);
+        assert!(cache.get::<Transaction>("eth_getTransactionByHash", &params).is_none());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
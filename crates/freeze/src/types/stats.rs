@@ -0,0 +1,318 @@
+use crate::{timing::percentile, Datatype};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
+
+/// latency histograms split each power of two of microseconds into 2^4 buckets, so percentiles
+/// are within 1/16 of the recorded latency
+const SUB_BUCKET_BITS: u32 = 4;
+
+/// latencies of 2^40 microseconds (about 12 days) or more share the last bucket
+const MAX_LATENCY_BITS: u32 = 40;
+
+const N_LATENCY_BUCKETS: usize =
+    ((MAX_LATENCY_BITS - SUB_BUCKET_BITS + 1) << SUB_BUCKET_BITS) as usize;
+
+/// latencies and retries of rpc requests of each method, shared by the concurrent chunks of a run
+///
+/// requests answered by the rpc cache are not counted. requests are counted with atomics and
+/// latencies in fixed size histograms, so recording takes no lock once a method has been seen
+#[derive(Clone, Debug, Default)]
+pub struct RequestStats(Arc<RwLock<BTreeMap<String, Arc<MethodRequests>>>>);
+
+#[derive(Debug)]
+struct MethodRequests {
+    n_requests: AtomicU64,
+    n_retries: AtomicU64,
+    n_errors: AtomicU64,
+    total_micros: AtomicU64,
+    max_micros: AtomicU64,
+    latency_histogram: Box<[AtomicU64]>,
+}
+
+impl Default for MethodRequests {
+    fn default() -> MethodRequests {
+        MethodRequests {
+            n_requests: AtomicU64::new(0),
+            n_retries: AtomicU64::new(0),
+            n_errors: AtomicU64::new(0),
+            total_micros: AtomicU64::new(0),
+            max_micros: AtomicU64::new(0),
+            latency_histogram: (0..N_LATENCY_BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+}
+
+impl MethodRequests {
+    fn record(&self, latency: Duration, retry: bool, failed: bool) {
+        let micros = latency.as_micros().min(u64::MAX as u128) as u64;
+        self.n_requests.fetch_add(1, Ordering::Relaxed);
+        self.n_retries.fetch_add(retry as u64, Ordering::Relaxed);
+        self.n_errors.fetch_add(failed as u64, Ordering::Relaxed);
+        self.total_micros.fetch_add(micros, Ordering::Relaxed);
+        self.max_micros.fetch_max(micros, Ordering::Relaxed);
+        self.latency_histogram[latency_bucket(micros)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn latency(&self) -> DurationStats {
+        let n_requests = self.n_requests.load(Ordering::Relaxed);
+        let total_micros = self.total_micros.load(Ordering::Relaxed);
+        if n_requests == 0 {
+            return DurationStats::default()
+        }
+
+        // nearest-rank percentile, as the upper bound of its bucket
+        let rank = ((95 * n_requests + 99) / 100).clamp(1, n_requests);
+        let mut seen = 0;
+        let mut p95 = 0;
+        for (index, count) in self.latency_histogram.iter().enumerate() {
+            seen += count.load(Ordering::Relaxed);
+            if seen >= rank {
+                p95 = latency_bucket_max(index);
+                break
+            }
+        }
+        DurationStats {
+            total: Duration::from_micros(total_micros),
+            mean: Duration::from_micros(total_micros / n_requests),
+            p95: Duration::from_micros(p95.min(self.max_micros.load(Ordering::Relaxed))),
+        }
+    }
+}
+
+/// histogram bucket of latency
+fn latency_bucket(micros: u64) -> usize {
+    let micros = micros.min((1 << MAX_LATENCY_BITS) - 1);
+    if micros < 1 << SUB_BUCKET_BITS {
+        return micros as usize
+    }
+    let shift = 63 - micros.leading_zeros() - SUB_BUCKET_BITS;
+    (((shift + 1) << SUB_BUCKET_BITS) as u64 + (micros >> shift) - (1 << SUB_BUCKET_BITS)) as usize
+}
+
+/// largest latency of histogram bucket
+fn latency_bucket_max(index: usize) -> u64 {
+    let index = index as u64;
+    let sub_buckets = 1 << SUB_BUCKET_BITS;
+    if index < sub_buckets {
+        return index
+    }
+    let shift = (index >> SUB_BUCKET_BITS) - 1;
+    let sub_bucket = index % sub_buckets;
+    ((sub_buckets + sub_bucket + 1) << shift) - 1
+}
+
+/// statistics of the rpc requests of a single method
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MethodStats {
+    /// name of rpc method
+    pub method: String,
+    /// number of requests issued, including retries
+    pub n_requests: u64,
+    /// number of requests that retried a failed request
+    pub n_retries: u64,
+    /// number of requests that failed
+    pub n_errors: u64,
+    /// latency of requests
+    pub latency: DurationStats,
+}
+
+impl RequestStats {
+    /// record a request issued to rpc endpoint, which is a retry unless it is the first attempt
+    pub fn record(&self, method: &str, latency: Duration, retry: bool, failed: bool) {
+        let requests = match self.0.read().ok().and_then(|methods| methods.get(method).cloned()) {
+            Some(requests) => requests,
+            None => match self.0.write() {
+                Ok(mut methods) => methods.entry(method.to_string()).or_default().clone(),
+                Err(_) => return,
+            },
+        };
+        requests.record(latency, retry, failed)
+    }
+
+    /// summarize requests of each method, ordered by method name
+    pub fn summary(&self) -> Vec<MethodStats> {
+        let methods = match self.0.read() {
+            Ok(methods) => methods,
+            Err(_) => return Vec::new(),
+        };
+        methods
+            .iter()
+            .map(|(method, requests)| MethodStats {
+                method: method.clone(),
+                n_requests: requests.n_requests.load(Ordering::Relaxed),
+                n_retries: requests.n_retries.load(Ordering::Relaxed),
+                n_errors: requests.n_errors.load(Ordering::Relaxed),
+                latency: requests.latency(),
+            })
+            .collect()
+    }
+}
+
+/// time spent on a chunk and the rows that it produced
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChunkTiming {
+    /// time spent collecting data of chunk
+    pub gather: Duration,
+    /// time spent writing files of chunk
+    pub write: Duration,
+    /// rows written for each datatype
+    pub rows: Vec<(Datatype, u64)>,
+    /// whether chunk was written, chunks that fail partway keep the time spent before failing
+    pub completed: bool,
+}
+
+/// total, mean, and 95th percentile of durations
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DurationStats {
+    /// summed duration
+    pub total: Duration,
+    /// mean duration
+    pub mean: Duration,
+    /// 95th percentile duration
+    pub p95: Duration,
+}
+
+impl DurationStats {
+    /// summarize durations
+    pub fn new(mut durations: Vec<Duration>) -> DurationStats {
+        durations.sort();
+        let total: Duration = durations.iter().sum();
+        let mean = match durations.len() as u32 {
+            0 => Duration::ZERO,
+            n => total / n,
+        };
+        DurationStats { total, mean, p95: percentile(&durations, 95) }
+    }
+}
+
+/// total, mean, and 95th percentile of rows per chunk of a datatype
+#[derive(Clone, Debug, PartialEq)]
+pub struct RowStats {
+    /// datatype of rows
+    pub datatype: Datatype,
+    /// rows across chunks
+    pub total: u64,
+    /// mean rows per chunk
+    pub mean: f64,
+    /// 95th percentile of rows per chunk
+    pub p95: u64,
+}
+
+/// statistics of chunks, aggregated across the chunks of a run
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PipelineStats {
+    /// number of chunks that started collecting
+    pub n_chunks: u64,
+    /// number of chunks that failed or were cancelled after they started collecting
+    pub n_failed: u64,
+    /// time spent collecting chunks
+    pub gather: DurationStats,
+    /// time spent writing chunks
+    pub write: DurationStats,
+    /// rows per chunk of each datatype, over completed chunks
+    pub rows: Vec<RowStats>,
+}
+
+impl PipelineStats {
+    /// aggregate stats of chunks
+    pub fn new(chunks: &[ChunkTiming]) -> PipelineStats {
+        let mut rows: BTreeMap<String, (Datatype, Vec<u64>)> = BTreeMap::new();
+        for chunk in chunks.iter().filter(|chunk| chunk.completed) {
+            for (datatype, n_rows) in chunk.rows.iter() {
+                rows.entry(datatype.name()).or_insert((*datatype, Vec::new())).1.push(*n_rows);
+            }
+        }
+        let rows = rows
+            .into_values()
+            .map(|(datatype, mut counts)| {
+                counts.sort();
+                let total: u64 = counts.iter().sum();
+                RowStats {
+                    datatype,
+                    total,
+                    mean: total as f64 / counts.len() as f64,
+                    p95: percentile(&counts, 95),
+                }
+            })
+            .collect();
+        PipelineStats {
+            n_chunks: chunks.len() as u64,
+            n_failed: chunks.iter().filter(|chunk| !chunk.completed).count() as u64,
+            gather: DurationStats::new(chunks.iter().map(|chunk| chunk.gather).collect()),
+            write: DurationStats::new(chunks.iter().map(|chunk| chunk.write).collect()),
+            rows,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_stats() {
+        let stats = RequestStats::default();
+        let handles: Vec<_> = (1..=20u64)
+            .map(|ms| {
+                let stats = stats.clone();
+                std::thread::spawn(move || {
+                    stats.record("eth_getLogs", Duration::from_millis(ms), ms > 18, ms > 17)
+                })
+            })
+            .collect();
+        handles.into_iter().for_each(|handle| handle.join().unwrap());
+        stats.record("eth_blockNumber", Duration::from_millis(3), false, false);
+
+        let summary = stats.summary();
+        assert_eq!(
+            summary.iter().map(|x| x.method.as_str()).collect::<Vec<_>>(),
+            vec!["eth_blockNumber", "eth_getLogs"]
+        );
+        let logs = &summary[1];
+        assert_eq!((logs.n_requests, logs.n_retries, logs.n_errors), (20, 2, 3));
+        assert_eq!(logs.latency.total, Duration::from_millis(210));
+        assert_eq!(logs.latency.mean, Duration::from_micros(10500));
+        // p95 is the upper bound of the histogram bucket of 19ms, within 1/16 of it
+        assert!(logs.latency.p95 >= Duration::from_millis(19));
+        assert!(logs.latency.p95 < Duration::from_millis(19) * 17 / 16);
+    }
+
+    #[test]
+    fn test_latency_buckets() {
+        for micros in [0, 1, 15, 16, 17, 100, 19000, 1 << 30, (1 << 40) - 1] {
+            let bucket = latency_bucket(micros);
+            assert!(bucket < N_LATENCY_BUCKETS);
+            assert!(latency_bucket_max(bucket) >= micros);
+            assert!(latency_bucket_max(bucket) - micros <= micros / 16);
+            assert!(bucket == 0 || latency_bucket_max(bucket - 1) < micros);
+        }
+        assert_eq!(latency_bucket(u64::MAX), N_LATENCY_BUCKETS - 1);
+    }
+
+    #[test]
+    fn test_pipeline_stats() {
+        let chunk = |gather_ms, n_rows, completed| ChunkTiming {
+            gather: Duration::from_millis(gather_ms),
+            write: Duration::from_millis(1),
+            rows: if completed { vec![(Datatype::Blocks, n_rows)] } else { vec![] },
+            completed,
+        };
+        let chunks = [chunk(10, 100, true), chunk(30, 300, true), chunk(50, 0, false)];
+        let stats = PipelineStats::new(&chunks);
+        assert_eq!((stats.n_chunks, stats.n_failed), (3, 1));
+        assert_eq!(stats.gather.total, Duration::from_millis(90));
+        assert_eq!(stats.gather.mean, Duration::from_millis(30));
+        assert_eq!(stats.gather.p95, Duration::from_millis(50));
+        assert_eq!(
+            stats.rows,
+            vec![RowStats { datatype: Datatype::Blocks, total: 400, mean: 200.0, p95: 300 }]
+        );
+        assert_eq!(PipelineStats::new(&[]), PipelineStats::default());
+    }
+}
@@ -0,0 +1,169 @@
+use crate::CollectError;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+use tracing::{span, Subscriber};
+use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, Layer};
+
+/// phases of the per-chunk pipeline, in the order they occur
+///
+/// each phase is recorded as a tracing span of the same name. `write` includes encoding the
+/// dataframe into the output format, since polars encodes while writing.
+pub const PIPELINE_PHASES: [&str; 5] = ["fetch", "decode", "build", "sort", "write"];
+
+static TIMING_REPORT: OnceLock<TimingReport> = OnceLock::new();
+
+/// durations of pipeline spans, aggregated per phase across concurrent chunks
+#[derive(Clone, Debug, Default)]
+pub struct TimingReport {
+    durations: Arc<Mutex<HashMap<&'static str, Vec<Duration>>>>,
+}
+
+/// summary statistics of the spans of a single phase
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PhaseTiming {
+    /// name of phase
+    pub phase: &'static str,
+    /// number of spans recorded
+    pub count: usize,
+    /// summed duration of spans
+    pub total: Duration,
+    /// median span duration
+    pub p50: Duration,
+    /// 90th percentile span duration
+    pub p90: Duration,
+    /// 99th percentile span duration
+    pub p99: Duration,
+    /// longest span duration
+    pub max: Duration,
+}
+
+impl TimingReport {
+    /// record duration of a span
+    pub fn record(&self, phase: &'static str, duration: Duration) {
+        if let Ok(mut durations) = self.durations.lock() {
+            durations.entry(phase).or_default().push(duration);
+        }
+    }
+
+    /// discard all recorded durations
+    pub fn clear(&self) {
+        if let Ok(mut durations) = self.durations.lock() {
+            durations.clear();
+        }
+    }
+
+    /// summarize each phase that has recorded spans, in pipeline order
+    pub fn summary(&self) -> Vec<PhaseTiming> {
+        let durations = match self.durations.lock() {
+            Ok(durations) => durations,
+            Err(_) => return Vec::new(),
+        };
+        PIPELINE_PHASES
+            .iter()
+            .filter_map(|phase| {
+                let mut sorted = durations.get(phase)?.clone();
+                sorted.sort();
+                Some(PhaseTiming {
+                    phase: *phase,
+                    count: sorted.len(),
+                    total: sorted.iter().sum(),
+                    p50: percentile(&sorted, 50),
+                    p90: percentile(&sorted, 90),
+                    p99: percentile(&sorted, 99),
+                    max: sorted.last().copied().unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// nearest-rank percentile of sorted values, or the default value if there are none
+pub(crate) fn percentile<T: Copy + Default>(sorted: &[T], pct: usize) -> T {
+    if sorted.is_empty() {
+        return T::default()
+    }
+    let rank = (pct * sorted.len() + 99) / 100;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// start aggregating pipeline span durations
+///
+/// installs the global tracing subscriber on first use, later calls reuse it and clear the
+/// durations of the previous run
+pub fn start_timing_report() -> Result<TimingReport, CollectError> {
+    if let Some(report) = TIMING_REPORT.get() {
+        report.clear();
+        return Ok(report.clone())
+    }
+    let report = TimingReport::default();
+    let subscriber = tracing_subscriber::registry().with(TimingLayer { report: report.clone() });
+    tracing::subscriber::set_global_default(subscriber).map_err(|_| {
+        CollectError::CollectError("could not install timing subscriber".to_string())
+    })?;
+    Ok(TIMING_REPORT.get_or_init(|| report).clone())
+}
+
+struct SpanStart(&'static str, Instant);
+
+struct TimingLayer {
+    report: TimingReport,
+}
+
+impl<S> Layer<S> for TimingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let metadata = attrs.metadata();
+        if !metadata.target().starts_with("cryo_freeze") {
+            return
+        }
+        let phase = match PIPELINE_PHASES.iter().find(|phase| **phase == metadata.name()) {
+            Some(phase) => *phase,
+            None => return,
+        };
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanStart(phase, Instant::now()));
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(&id) {
+            if let Some(SpanStart(phase, start)) = span.extensions().get::<SpanStart>() {
+                self.report.record(*phase, start.elapsed());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phase_percentiles() {
+        let report = TimingReport::default();
+        let handles: Vec<_> = (1..=100u64)
+            .map(|ms| {
+                let report = report.clone();
+                std::thread::spawn(move || report.record("fetch", Duration::from_millis(ms)))
+            })
+            .collect();
+        handles.into_iter().for_each(|handle| handle.join().unwrap());
+        report.record("write", Duration::from_millis(7));
+
+        let summary = report.summary();
+        assert_eq!(summary.iter().map(|x| x.phase).collect::<Vec<_>>(), vec!["fetch", "write"]);
+        let fetch = &summary[0];
+        assert_eq!(fetch.count, 100);
+        assert_eq!(fetch.total, Duration::from_millis(5050));
+        assert_eq!(fetch.p50, Duration::from_millis(50));
+        assert_eq!(fetch.p90, Duration::from_millis(90));
+        assert_eq!(fetch.p99, Duration::from_millis(99));
+        assert_eq!(fetch.max, Duration::from_millis(100));
+        assert_eq!(summary[1].p99, Duration::from_millis(7));
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{
+    dataframes, err, BlockChunk, ChunkData, CollectError, Datatype, FileFormat, FileOutput,
+    Partition, Query,
+};
+use polars::prelude::*;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+/// datatypes whose output files can be checked against the blocks of their chunk
+pub const VERIFIED_DATATYPES: [Datatype; 3] =
+    [Datatype::Blocks, Datatype::Transactions, Datatype::Logs];
+
+/// maximum number of values listed in a discrepancy message
+const MAX_LISTED_VALUES: usize = 5;
+
+/// problem found in an output file by verification
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileDiscrepancy {
+    /// datatype of file
+    pub datatype: Datatype,
+    /// path of file
+    pub path: PathBuf,
+    /// description of problem
+    pub message: String,
+}
+
+/// check that the output files of each chunk hold the rows of every block of the chunk
+///
+/// - blocks files must contain each block of the chunk exactly once
+/// - transactions files must contain as many transactions per block as the blocks fetched during
+///   collection, for blocks fetched in this run
+/// - logs files must exist and contain only blocks of the chunk, since blocks without logs have
+///   no rows
+///
+/// chunks of transaction hashes and datatypes other than [`VERIFIED_DATATYPES`] are not checked
+pub fn verify_outputs(
+    query: &Query,
+    sink: &FileOutput,
+) -> Result<(usize, Vec<FileDiscrepancy>), CollectError> {
+    let missing_blocks: BTreeSet<u64> = query.missing_blocks.block_numbers().into_iter().collect();
+    let mut n_files = 0;
+    let mut discrepancies = Vec::new();
+    for partition in query.partitions.iter() {
+        let expected_blocks = match get_partition_blocks(partition) {
+            Some(blocks) => blocks.difference(&missing_blocks).copied().collect(),
+            None => continue,
+        };
+        let mut paths: Vec<_> = sink
+            .get_paths(query, partition, None)?
+            .into_iter()
+            .filter(|(datatype, _)| VERIFIED_DATATYPES.contains(datatype))
+            .collect();
+        paths.sort_by_key(|(datatype, _)| datatype.name());
+        for (datatype, path) in paths.into_iter() {
+            n_files += 1;
+            let messages = match read_chunk_block_numbers(&path, partition, sink) {
+                Ok(block_numbers) => check_block_numbers(
+                    datatype,
+                    &block_numbers,
+                    &expected_blocks,
+                    |block_number| query.block_transaction_counts.get(block_number),
+                ),
+                Err(e) => vec![e.to_string()],
+            };
+            discrepancies.extend(messages.into_iter().map(|message| FileDiscrepancy {
+                datatype,
+                path: path.clone(),
+                message,
+            }));
+        }
+    }
+    Ok((n_files, discrepancies))
+}
+
+/// block numbers of partition, or None if partition is not chunked by block
+fn get_partition_blocks(partition: &Partition) -> Option<BTreeSet<u64>> {
+    partition.block_numbers.as_ref().map(|block_chunks| block_chunks.values().into_iter().collect())
+}
+
+/// read block numbers of every row of a chunk, from its file or from the files it was split into
+fn read_chunk_block_numbers(
+    path: &Path,
+    partition: &Partition,
+    sink: &FileOutput,
+) -> Result<Vec<u64>, CollectError> {
+    if path.exists() {
+        return read_block_numbers(path, &sink.format)
+    }
+    let chunk_range = match (sink.target_file_size, partition.block_numbers.as_deref()) {
+        (Some(_), Some([BlockChunk::Range(start, end)])) => (*start, *end),
+        _ => return Err(err("file is missing")),
+    };
+    let splits = dataframes::get_existing_split_paths(path, chunk_range);
+    if !dataframes::splits_cover_chunk(&splits, chunk_range) {
+        return Err(err("file is missing and split files do not cover its block range"))
+    }
+    let mut block_numbers = Vec::new();
+    for (_, split_path) in splits.iter() {
+        block_numbers.extend(read_block_numbers(split_path, &sink.format)?);
+    }
+    Ok(block_numbers)
+}
+
+/// read block_number column of a parquet or csv file
+fn read_block_numbers(path: &Path, format: &FileFormat) -> Result<Vec<u64>, CollectError> {
+    let columns = Some(vec!["block_number".to_string()]);
+    let df = match format {
+        FileFormat::Parquet => {
+            std::fs::File::open(path).map_err(|_| err("could not open file")).and_then(|file| {
+                ParquetReader::new(file)
+                    .with_columns(columns)
+                    .finish()
+                    .map_err(|_| err("could not read block_number column"))
+            })?
+        }
+        FileFormat::Csv => CsvReader::from_path(path)
+            .and_then(|reader| reader.has_header(true).with_columns(columns).finish())
+            .map_err(|_| err("could not read block_number column"))?,
+        _ => return Err(err("only parquet and csv files can be verified")),
+    };
+    let block_numbers = df
+        .column("block_number")
+        .and_then(|column| column.cast(&DataType::UInt64))
+        .map_err(|_| err("could not read block_number column"))?;
+    let block_numbers =
+        block_numbers.u64().map_err(|_| err("could not read block_number column"))?;
+    if block_numbers.null_count() > 0 {
+        return Err(err("block_number column has null values"))
+    }
+    Ok(block_numbers.into_no_null_iter().collect())
+}
+
+/// compare block numbers of the rows of a file to the blocks of its chunk
+fn check_block_numbers<F>(
+    datatype: Datatype,
+    block_numbers: &[u64],
+    expected_blocks: &BTreeSet<u64>,
+    expected_transaction_count: F,
+) -> Vec<String>
+where
+    F: Fn(u64) -> Option<u64>,
+{
+    let mut counts: BTreeMap<u64, u64> = BTreeMap::new();
+    for block_number in block_numbers.iter() {
+        *counts.entry(*block_number).or_insert(0) += 1;
+    }
+
+    let mut messages = Vec::new();
+    let outside: Vec<u64> =
+        counts.keys().filter(|bn| !expected_blocks.contains(bn)).copied().collect();
+    if !outside.is_empty() {
+        messages.push(format!("rows of blocks outside of chunk: {}", list_values(&outside)));
+    }
+    match datatype {
+        Datatype::Blocks => {
+            let missing: Vec<u64> =
+                expected_blocks.iter().filter(|bn| !counts.contains_key(bn)).copied().collect();
+            if !missing.is_empty() {
+                messages.push(format!("missing blocks: {}", list_values(&missing)));
+            }
+            let duplicated: Vec<u64> =
+                counts.iter().filter(|(_, n)| **n > 1).map(|(bn, _)| *bn).collect();
+            if !duplicated.is_empty() {
+                messages.push(format!("duplicated blocks: {}", list_values(&duplicated)));
+            }
+        }
+        Datatype::Transactions => {
+            let mismatched: Vec<String> = expected_blocks
+                .iter()
+                .filter_map(|bn| {
+                    let expected = expected_transaction_count(*bn)?;
+                    let actual = counts.get(bn).copied().unwrap_or(0);
+                    if actual == expected {
+                        None
+                    } else {
+                        Some(format!("block {} has {} of {}", bn, actual, expected))
+                    }
+                })
+                .collect();
+            if !mismatched.is_empty() {
+                messages.push(format!("transaction counts differ: {}", list_values(&mismatched)));
+            }
+        }
+        _ => {}
+    }
+    messages
+}
+
+/// list first few values, with the number of values not listed
+fn list_values<T: ToString>(values: &[T]) -> String {
+    let listed: Vec<String> =
+        values.iter().take(MAX_LISTED_VALUES).map(|value| value.to_string()).collect();
+    let more = values.len().saturating_sub(MAX_LISTED_VALUES);
+    if more > 0 {
+        format!("{}, and {} more", listed.join(", "), more)
+    } else {
+        listed.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_block_numbers() {
+        let expected: BTreeSet<u64> = (100..=104).collect();
+        let no_counts = |_| None;
+        assert!(check_block_numbers(
+            Datatype::Blocks,
+            &[100, 101, 102, 103, 104],
+            &expected,
+            no_counts
+        )
+        .is_empty());
+
+        let messages =
+            check_block_numbers(Datatype::Blocks, &[100, 101, 101, 104, 105], &expected, no_counts);
+        assert_eq!(
+            messages,
+            vec![
+                "rows of blocks outside of chunk: 105",
+                "missing blocks: 102, 103",
+                "duplicated blocks: 101",
+            ]
+        );
+
+        // blocks without logs have no rows
+        assert!(check_block_numbers(Datatype::Logs, &[101, 101], &expected, no_counts).is_empty());
+
+        let tx_counts = |bn| if bn == 103 { None } else { Some(2) };
+        let messages = check_block_numbers(
+            Datatype::Transactions,
+            &[100, 100, 101, 102, 102, 104, 104],
+            &expected,
+            tx_counts,
+        );
+        assert_eq!(messages, vec!["transaction counts differ: block 101 has 1 of 2"]);
+    }
+}
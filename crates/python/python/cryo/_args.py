@@ -30,6 +30,8 @@ def parse_cli_args(
         pass
     elif file_format == 'json':
         kwargs['json'] = True
+    elif file_format == 'jsonl':
+        kwargs['jsonl'] = True
     elif file_format == 'csv':
         kwargs['csv'] = True
     # elif file_format == 'avro':
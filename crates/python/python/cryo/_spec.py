@@ -11,6 +11,7 @@ if typing.TYPE_CHECKING:
         Literal['parquet'],
         Literal['csv'],
         Literal['json'],
+        Literal['jsonl'],
         Literal['avro'],
     ]
     PythonOutput = Union[
@@ -23,19 +24,42 @@ if typing.TYPE_CHECKING:
     class CryoCliArgs(TypedDict, total=False):
         datatypes: typing.Sequence[Datatype]
         blocks: typing.Sequence[str] | None
+        blocks_from_file: str | None
+        start_block: str | None
+        start_block_exclusive: str | None
+        end_block: str | None
         align: bool
         reorg_buffer: int
+        follow: bool
+        poll_interval: float | None
+        skip_before_deployment: bool
         include_columns: typing.Sequence[str] | None
         exclude_columns: typing.Sequence[str] | None
         columns: typing.Sequence[str] | None
+        include_block_timestamp: bool
+        overflow_policy: Literal['error', 'saturate', 'null'] | None
+        column_type_override: typing.Sequence[str] | None
         hex: bool
         sort: typing.Sequence[str] | None
+        sort_desc: bool
+        no_sort: bool
+        dedup: bool
         rpc: str | None
+        env_rpc: bool
+        firehose_endpoint: str | None
         network_name: str | None
         requests_per_second: int | None
         max_concurrent_requests: int | None
         max_concurrent_chunks: int | None
+        request_timeout_seconds: int | None
+        connect_timeout_seconds: int | None
+        http_keepalive: int | None
+        rpc_batch_size: int
+        writer_queue_size: int | None
+        cache_dir: str | None
         dry: bool
+        dry_run_estimate_size: bool
+        verify: bool
         chunk_size: int | None
         n_chunks: int | None
         output_dir: str | None
@@ -43,15 +67,38 @@ if typing.TYPE_CHECKING:
         overwrite: bool
         csv: bool
         json: bool
+        jsonl: bool
+        csv_compression: str | None
+        dataset: bool
+        target_file_mb: int | None
+        partition_width: int | None
+        hive_partitions: typing.Sequence[str] | None
+        partition_columns: Literal['keep', 'drop'] | None
+        file_name_template: str | None
+        output_prefix: str | None
+        strict_formats: bool
+        config: str | None
         row_group_size: int | None
         n_row_groups: int | None
+        row_group_mb: int | None
         no_stats: bool
+        no_dictionary: bool
+        dictionary_columns: typing.Sequence[str] | None
+        no_metadata: bool
         compression: str | None
+        timing_report: bool
+        stats: bool
+        slots_file: str | None
+        addresses_file: str | None
         contract: str | bytes | None
         topic0: str | bytes | None
         topic1: str | bytes | None
         topic2: str | bytes | None
         topic3: str | bytes | None
         inner_request_size: int | None
+        max_log_request_size: int | None
+        max_response_rows: int | None
+        fee_percentiles: typing.Sequence[float] | None
         no_verbose: bool
+        log_format: Literal['text', 'json'] | None
 
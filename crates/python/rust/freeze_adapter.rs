@@ -27,7 +27,7 @@ use cryo_cli::{run, Args};
         max_concurrent_chunks = None,
         chunk_order = None,
         max_retries = 10,
-        initial_backoff = 500,
+        initial_backoff = 200,
         dry = false,
         chunk_size = 1000,
         n_chunks = None,
@@ -61,6 +61,59 @@ use cryo_cli::{run, Args};
         verbose = false,
         no_verbose = false,
         event_signature = None,
+        receipt_request_size = None,
+        rps_share = None,
+        startup_jitter = None,
+        estimate_gas_price = false,
+        jsonl = false,
+        blocks_from_file = None,
+        dataset = false,
+        config = None,
+        overflow_policy = None,
+        strict_formats = false,
+        output_format = None,
+        timing_report = false,
+        skip_before_deployment = false,
+        writer_queue_size = None,
+        lineage = false,
+        target_file_mb = None,
+        allow_missing_blocks = false,
+        partition_width = None,
+        env_rpc = false,
+        file_name_template = None,
+        cache_dir = None,
+        sort_desc = false,
+        csv_compression = None,
+        dry_run_estimate_size = false,
+        slots_file = None,
+        verify = false,
+        include_block_timestamp = false,
+        fee_percentiles = None,
+        no_sort = false,
+        dedup = false,
+        no_metadata = false,
+        stats = false,
+        addresses_file = None,
+        rpc_batch_size = 1,
+        log_format = None,
+        request_timeout_seconds = None,
+        connect_timeout_seconds = None,
+        http_keepalive = None,
+        follow = false,
+        poll_interval = None,
+        column_type_override = None,
+        hive_partitions = None,
+        partition_columns = None,
+        output_prefix = None,
+        max_log_request_size = None,
+        max_response_rows = None,
+        start_block = None,
+        start_block_exclusive = None,
+        end_block = None,
+        firehose_endpoint = None,
+        row_group_mb = None,
+        no_dictionary = false,
+        dictionary_columns = None,
     )
 )]
 #[allow(clippy::too_many_arguments)]
@@ -122,6 +175,59 @@ pub fn _freeze(
     verbose: bool,
     no_verbose: bool,
     event_signature: Option<String>,
+    receipt_request_size: Option<u64>,
+    rps_share: Option<String>,
+    startup_jitter: Option<String>,
+    estimate_gas_price: bool,
+    jsonl: bool,
+    blocks_from_file: Option<String>,
+    dataset: bool,
+    config: Option<String>,
+    overflow_policy: Option<String>,
+    strict_formats: bool,
+    output_format: Option<String>,
+    timing_report: bool,
+    skip_before_deployment: bool,
+    writer_queue_size: Option<usize>,
+    lineage: bool,
+    target_file_mb: Option<u64>,
+    allow_missing_blocks: bool,
+    partition_width: Option<u64>,
+    env_rpc: bool,
+    file_name_template: Option<String>,
+    cache_dir: Option<String>,
+    sort_desc: bool,
+    csv_compression: Option<String>,
+    dry_run_estimate_size: bool,
+    slots_file: Option<String>,
+    verify: bool,
+    include_block_timestamp: bool,
+    fee_percentiles: Option<Vec<f64>>,
+    no_sort: bool,
+    dedup: bool,
+    no_metadata: bool,
+    stats: bool,
+    addresses_file: Option<String>,
+    rpc_batch_size: u64,
+    log_format: Option<String>,
+    request_timeout_seconds: Option<u64>,
+    connect_timeout_seconds: Option<u64>,
+    http_keepalive: Option<u64>,
+    follow: bool,
+    poll_interval: Option<f64>,
+    column_type_override: Option<Vec<String>>,
+    hive_partitions: Option<Vec<String>>,
+    partition_columns: Option<String>,
+    output_prefix: Option<String>,
+    max_log_request_size: Option<u64>,
+    max_response_rows: Option<u64>,
+    start_block: Option<String>,
+    start_block_exclusive: Option<String>,
+    end_block: Option<String>,
+    firehose_endpoint: Option<String>,
+    row_group_mb: Option<u64>,
+    no_dictionary: bool,
+    dictionary_columns: Option<Vec<String>>,
 ) -> PyResult<&PyAny> {
     if let Some(command) = command {
         freeze_command(py, command)
@@ -182,6 +288,59 @@ pub fn _freeze(
             verbose,
             no_verbose,
             event_signature,
+            receipt_request_size,
+            rps_share,
+            startup_jitter,
+            estimate_gas_price,
+            jsonl,
+            blocks_from_file,
+            dataset,
+            config: config.map(std::path::PathBuf::from),
+            overflow_policy,
+            strict_formats,
+            output_format,
+            timing_report,
+            skip_before_deployment,
+            writer_queue_size,
+            lineage,
+            target_file_mb,
+            allow_missing_blocks,
+            partition_width,
+            env_rpc,
+            file_name_template,
+            cache_dir,
+            sort_desc,
+            csv_compression,
+            dry_run_estimate_size,
+            slots_file,
+            verify,
+            include_block_timestamp,
+            fee_percentiles,
+            no_sort,
+            dedup,
+            no_metadata,
+            stats,
+            addresses_file,
+            rpc_batch_size,
+            log_format,
+            request_timeout_seconds,
+            connect_timeout_seconds,
+            http_keepalive,
+            follow,
+            poll_interval,
+            column_type_override,
+            hive_partitions,
+            partition_columns,
+            output_prefix,
+            max_log_request_size,
+            max_response_rows,
+            start_block,
+            start_block_exclusive,
+            end_block,
+            firehose_endpoint,
+            row_group_mb,
+            no_dictionary,
+            dictionary_columns,
         };
 
         pyo3_asyncio::tokio::future_into_py(py, async move {
@@ -199,6 +358,7 @@ pub fn _freeze(
                         ("n_completed".to_string(), result.completed.len().into_py(py)),
                         ("n_skipped".to_string(), result.skipped.len().into_py(py)),
                         ("n_errored".to_string(), result.errored.len().into_py(py)),
+                        ("n_cancelled".to_string(), result.cancelled.len().into_py(py)),
                         // ("paths".to_string(), paths),
                     ]
                     .into_py_dict(py);
@@ -230,6 +390,7 @@ fn freeze_command(py: Python<'_>, command: String) -> PyResult<&PyAny> {
                     ("n_completed".to_string(), result.completed.len().into_py(py)),
                     ("n_skipped".to_string(), result.skipped.len().into_py(py)),
                     ("n_errored".to_string(), result.errored.len().into_py(py)),
+                    ("n_cancelled".to_string(), result.cancelled.len().into_py(py)),
                     // ("paths".to_string(), paths),
                 ]
                 .into_py_dict(py);
@@ -55,6 +55,42 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
         })
         .collect();
 
+    // maps of column name to values, for columns that are only known at runtime
+    let dynamic_col_processing: Vec<_> = field_names_and_types
+        .iter()
+        .filter(|(name, _)| name != "event_cols")
+        .filter(|(_, ty)| quote!(#ty).to_string().contains("IndexMap < String , Vec <"))
+        .map(|(name, ty)| {
+            let ty = quote!(#ty).to_string();
+            if ty.contains("U256") || ty.contains("I256") {
+                quote! {
+                    for (column, values) in self.#name {
+                        if schema.has_column(column.as_str()) {
+                            for u256_type in schema.u256_types.iter() {
+                                cols.push(values.to_u256_series(
+                                    column.clone(),
+                                    u256_type.clone(),
+                                    &schema.binary_type,
+                                    &schema.overflow_policy,
+                                )?);
+                            }
+                        }
+                    }
+                }
+            } else {
+                let macro_name = match ty.contains("Vec < u8 >") {
+                    true => syn::Ident::new("with_series_binary", Span::call_site()),
+                    false => syn::Ident::new("with_series", Span::call_site()),
+                };
+                quote! {
+                    for (column, values) in self.#name {
+                        #macro_name!(cols, column.as_str(), values, schema);
+                    }
+                }
+            }
+        })
+        .collect();
+
     let has_event_cols = !field_names_and_types
         .iter()
         .filter(|(name, _)| name == "event_cols")
@@ -94,6 +130,7 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
                             U256Type::U32 => cols.push(Series::new(full_name, Vec::<u32>::new())),
                             U256Type::U64 => cols.push(Series::new(full_name, Vec::<u64>::new())),
                             U256Type::Decimal128 => cols.push(Series::new(full_name, Vec::<Vec<u8>>::new())),
+                            U256Type::Hex => cols.push(Series::new(full_name, Vec::<String>::new())),
                         }
                     }
                 }
@@ -150,6 +187,7 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
                             chunk_len as usize,
                             &u256_types,
                             &schema.binary_type,
+                            &schema.overflow_policy,
                         );
                         match series_vec {
                             Ok(s) => {
@@ -208,7 +246,10 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
         if let Some(column_type) = map_type_to_column_type(ty) {
             let field_name_str = format!("{}", quote!(#name));
             column_types.push(quote! { (#field_name_str, #column_type) });
-        } else if name != "n_rows" && name != "event_cols" {
+        } else if name != "n_rows" &&
+            name != "event_cols" &&
+            !quote!(#ty).to_string().contains("IndexMap")
+        {
             println!("invalid column type for {name} in table {}", datatype_str);
         }
     }
@@ -234,6 +275,8 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
 
                 #(#field_processing)*
 
+                #(#dynamic_col_processing)*
+
                 if self.chain_id.len() == 0 {
                     with_series!(cols, "chain_id", vec![chain_id; self.n_rows as usize], schema);
                 } else {
@@ -242,7 +285,9 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
 
                 #event_code
 
-                let df = DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)?;
+                let df = tracing::info_span!("build")
+                    .in_scope(|| DataFrame::new(cols).map_err(CollectError::PolarsError));
+                let df = tracing::info_span!("sort").in_scope(|| df.sort_by_schema(schema))?;
                 let mut output = std::collections::HashMap::new();
                 output.insert(datatype, df);
                 Ok(output)
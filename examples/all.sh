@@ -51,10 +51,16 @@ $EXECUTABLE erc721_transfers -b $BLOCKS -o $OUTPUT_DIR/erc721_transfers --contra
 
 $EXECUTABLE eth_calls -b $BLOCKS -o $OUTPUT_DIR/eth_calls --call-data 0x18160ddd --contract 0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2 &
 
+$EXECUTABLE fee_history -b $BLOCKS -o $OUTPUT_DIR/fee_history --inner-request-size 100
+
 $EXECUTABLE logs -b $BLOCKS -o $OUTPUT_DIR/logs
 
+$EXECUTABLE method_id_counts -b $BLOCKS -o $OUTPUT_DIR/method_id_counts
+
 $EXECUTABLE native_transfers -b $BLOCKS -o $OUTPUT_DIR/native_transfers
 
+$EXECUTABLE nft_transfers -b $BLOCKS -o $OUTPUT_DIR/nft_transfers
+
 $EXECUTABLE nonce_diffs -b $BLOCKS -o $OUTPUT_DIR/nonce_diffs
 
 $EXECUTABLE nonces -b $BLOCKS -o $OUTPUT_DIR/nonces --address 0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2 &